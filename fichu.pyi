@@ -0,0 +1,47 @@
+from typing import Literal, Mapping, Optional
+
+LintLevel = Literal["off", "hint", "info", "warning", "error"]
+
+class Diagnostic:
+    """A syntax error or lint violation.
+
+    Lines and columns are zero based, columns count characters like the indices of Python strings.
+    """
+
+    line: int
+    column: int
+    end_line: int
+    end_column: int
+    severity: Literal["error", "warning", "info", "hint"]
+    code: Optional[str]
+    """The name of the lint rule, or "syntax_error"."""
+    message: str
+
+def format(
+    query: str,
+    *,
+    align_predicates: Optional[bool] = None,
+    align_prefixes: Optional[bool] = None,
+    separate_prolouge: Optional[bool] = None,
+    capitalize_keywords: Optional[bool] = None,
+    insert_spaces: Optional[bool] = None,
+    tab_size: Optional[int] = None,
+    where_new_line: Optional[bool] = None,
+    on_syntax_error: Optional[Literal["refuse", "partial"]] = None,
+) -> str:
+    """Formats the query. Settings that are not given keep their default.
+
+    Raises a `ValueError` if the query has syntax errors, unless `on_syntax_error` is "partial".
+    """
+
+def check(query: str, lint: Optional[Mapping[str, LintLevel]] = None) -> list[Diagnostic]:
+    """The syntax errors and lint diagnostics of the query.
+
+    `lint` maps rules and rule categories like "performance" to the level they are reported with.
+    """
+
+def prefixes(query: str) -> dict[str, str]:
+    """The prefixes declared by the query, mapped to their namespace IRI."""
+
+def main() -> None:
+    """Runs the `fichu` command line interface with `sys.argv`."""
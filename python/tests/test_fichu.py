@@ -0,0 +1,67 @@
+import pytest
+
+import fichu
+
+
+def test_format():
+    assert fichu.format("select * {?a <b> ?c}") == "SELECT * {\n  ?a <b> ?c\n}"
+
+
+def test_format_settings():
+    query = "select * {?a <b> ?c}"
+    assert fichu.format(query, tab_size=4, capitalize_keywords=False) == (
+        "select * {\n    ?a <b> ?c\n}"
+    )
+
+
+def test_format_settings_are_keyword_only():
+    with pytest.raises(TypeError):
+        fichu.format("SELECT * {}", True)
+
+
+def test_format_syntax_errors():
+    with pytest.raises(ValueError, match="missing '}'"):
+        fichu.format("select * {")
+    query = "prefix a: <a>\nselect * {"
+    assert fichu.format(query, on_syntax_error="partial") == "PREFIX a: <a>\nselect * {"
+    with pytest.raises(ValueError):
+        fichu.format(query, on_syntax_error="ignore")
+
+
+def test_check():
+    [unused, undeclared] = fichu.check("PREFIX wd: <x>\nSELECT * { ?a wdt:P31 ?b }")
+    assert (unused.line, unused.column, unused.end_column) == (0, 7, 10)
+    assert unused.severity == "warning"
+    assert unused.code == "unused_prefix"
+    assert undeclared.code == "undeclared_prefix"
+
+
+def test_check_syntax_error():
+    [diagnostic] = fichu.check("SELECT * {")
+    assert diagnostic.severity == "error"
+    assert diagnostic.code == "syntax_error"
+
+
+def test_check_columns_count_characters():
+    query = "SELECT * { ?名前 🦀:p ?b }"
+    [diagnostic] = fichu.check(query)
+    assert query[diagnostic.column : diagnostic.end_column] == "🦀:"
+
+
+def test_check_lint_settings():
+    query = "SELECT * { ?s ?p ?o }"
+    assert fichu.check(query) == []
+    [diagnostic] = fichu.check(query, lint={"performance": "error"})
+    assert diagnostic.code == "unbound_triple"
+    assert diagnostic.severity == "error"
+    with pytest.raises(ValueError):
+        fichu.check(query, lint={"performance": "loud"})
+
+
+def test_prefixes():
+    query = "PREFIX wd: <http://www.wikidata.org/entity/>\nPREFIX : <http://example.org/>\nSELECT * {}"
+    assert fichu.prefixes(query) == {
+        "wd": "http://www.wikidata.org/entity/",
+        "": "http://example.org/",
+    }
+    assert fichu.prefixes("SELECT * {}") == {}
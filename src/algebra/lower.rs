@@ -0,0 +1,1295 @@
+use std::collections::HashMap;
+
+use tree_sitter::{Node, Parser};
+
+use crate::{
+    rdf::{literal, resolve, RDF, XSD},
+    results::Term,
+};
+
+use super::{
+    Aggregate, AggregateFunction, AlgebraError, BinaryOperator, Expression, GraphPattern,
+    OrderCondition, PropertyPath, Query, QueryDataset, QueryForm, TermPattern, TriplePattern,
+    UnaryOperator,
+};
+
+type Result<T> = std::result::Result<T, AlgebraError>;
+
+pub(super) fn lower(text: &str, base: Option<&str>) -> Result<Query> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_sparql::language())
+        .expect("The SPARQL grammar is compatible");
+    let tree = parser
+        .parse(text, None)
+        .ok_or_else(|| AlgebraError("The query could not be parsed".to_string()))?;
+    if let Some(error) = first_error(tree.root_node()) {
+        let start = error.start_position();
+        return Err(AlgebraError(format!(
+            "Syntax error at {}:{}",
+            start.row + 1,
+            start.column + 1
+        )));
+    }
+    Lowering {
+        text,
+        base: base.map(str::to_string),
+        prefixes: HashMap::new(),
+        fresh: 0,
+        blank_nodes: HashMap::new(),
+        aggregates: vec![],
+    }
+    .query(tree.root_node())
+}
+
+fn first_error(node: Node) -> Option<Node> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    if !node.has_error() {
+        return None;
+    }
+    node.children(&mut node.walk()).find_map(first_error)
+}
+
+/// The children of `node` without comments.
+fn children(node: Node) -> Vec<Node> {
+    node.children(&mut node.walk())
+        .filter(|child| child.kind() != "comment")
+        .collect()
+}
+
+/// The named children of `node` without comments.
+fn named_children(node: Node) -> Vec<Node> {
+    node.named_children(&mut node.walk())
+        .filter(|child| child.kind() != "comment")
+        .collect()
+}
+
+fn child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    children(node)
+        .into_iter()
+        .find(|child| child.kind() == kind)
+}
+
+/// The triples and paths of a block of triples.
+#[derive(Default)]
+struct Block {
+    triples: Vec<TriplePattern>,
+    paths: Vec<GraphPattern>,
+}
+
+struct Lowering<'a> {
+    text: &'a str,
+    base: Option<String>,
+    prefixes: HashMap<String, String>,
+    /// The number of variables introduced for blank nodes, paths and aggregates.
+    fresh: usize,
+    /// The variables of the blank node labels.
+    blank_nodes: HashMap<String, String>,
+    /// The aggregates of the innermost `SELECT`, bound to variables.
+    aggregates: Vec<(String, Aggregate)>,
+}
+
+impl<'a> Lowering<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.text[node.byte_range()]
+    }
+
+    /// A new variable, see [`super::is_internal`].
+    fn fresh_variable(&mut self, prefix: char) -> String {
+        self.fresh += 1;
+        format!("{}{}", prefix, self.fresh - 1)
+    }
+
+    fn query(&mut self, root: Node) -> Result<Query> {
+        let mut form = None;
+        let mut values = None;
+        for child in named_children(root) {
+            match child.kind() {
+                "Prologue" => self.prologue(child)?,
+                "SelectQuery" | "ConstructQuery" | "AskQuery" | "DescribeQuery" => {
+                    form = Some(child)
+                }
+                "ValuesClause" => values = child_of_kind(child, "DataBlock"),
+                "Update" => {
+                    return Err(AlgebraError(
+                        "Updates can not be translated to the query algebra".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        let form = form.ok_or_else(|| AlgebraError("The query is empty".to_string()))?;
+        let dataset = self.dataset(form)?;
+        let where_clause = child_of_kind(form, "WhereClause");
+        let modifier = child_of_kind(form, "SolutionModifier");
+        let (form, pattern) = match form.kind() {
+            "SelectQuery" => {
+                let pattern = self.where_clause(where_clause)?;
+                let clause = child_of_kind(form, "SelectClause");
+                (
+                    QueryForm::Select,
+                    self.select(clause, pattern, modifier, values)?,
+                )
+            }
+            "AskQuery" => {
+                let pattern = self.where_clause(where_clause)?;
+                (
+                    QueryForm::Ask,
+                    self.select(None, pattern, modifier, values)?,
+                )
+            }
+            "ConstructQuery" => {
+                let (template, pattern) = match child_of_kind(form, "ConstructTemplate") {
+                    Some(template) => {
+                        let template = self.triples(template)?.triples;
+                        // NOTE: Blank nodes of the template are not the ones of the pattern.
+                        self.blank_nodes.clear();
+                        (template, self.where_clause(where_clause)?)
+                    }
+                    // NOTE: `CONSTRUCT WHERE { … }` uses its pattern as template.
+                    None => {
+                        let block = match child_of_kind(form, "TriplesTemplate") {
+                            Some(triples) => self.triples(triples)?,
+                            None => Block::default(),
+                        };
+                        (block.triples.clone(), join_block(block))
+                    }
+                };
+                (
+                    QueryForm::Construct(template),
+                    self.select(None, pattern, modifier, values)?,
+                )
+            }
+            _ => {
+                let pattern = self.where_clause(where_clause)?;
+                let resources = match child_of_kind(form, "*") {
+                    Some(_) => pattern
+                        .variables()
+                        .into_iter()
+                        .map(TermPattern::Variable)
+                        .collect(),
+                    None => named_children(form)
+                        .into_iter()
+                        .filter(|child| matches!(child.kind(), "VAR" | "IRIREF" | "PrefixedName"))
+                        .map(|child| self.term_pattern(child))
+                        .collect::<Result<_>>()?,
+                };
+                (
+                    QueryForm::Describe(resources),
+                    self.select(None, pattern, modifier, values)?,
+                )
+            }
+        };
+        Ok(Query {
+            form,
+            pattern,
+            dataset,
+            base: self.base.clone(),
+        })
+    }
+
+    fn prologue(&mut self, prologue: Node) -> Result<()> {
+        for declaration in named_children(prologue) {
+            let Some(iri) = child_of_kind(declaration, "IRIREF") else {
+                continue;
+            };
+            let iri = self.iri_reference(iri);
+            match declaration.kind() {
+                "BaseDecl" => self.base = Some(iri),
+                _ => {
+                    let prefix = child_of_kind(declaration, "PNAME_NS")
+                        .map(|prefix| self.text(prefix).trim_end_matches(':').to_string())
+                        .unwrap_or_default();
+                    self.prefixes.insert(prefix, iri);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn dataset(&mut self, form: Node) -> Result<Option<QueryDataset>> {
+        let mut dataset: Option<QueryDataset> = None;
+        for clause in children(form)
+            .into_iter()
+            .filter(|child| child.kind() == "DatasetClause")
+        {
+            let dataset = dataset.get_or_insert_with(QueryDataset::default);
+            for graph in named_children(clause) {
+                let Some(iri) = graph.child_by_field_name("source_selector") else {
+                    continue;
+                };
+                let iri = self.iri(iri)?;
+                match graph.kind() {
+                    "NamedGraphClause" => dataset.named.push(iri),
+                    _ => dataset.default.push(iri),
+                }
+            }
+        }
+        Ok(dataset)
+    }
+
+    fn where_clause(&mut self, clause: Option<Node>) -> Result<GraphPattern> {
+        match clause.and_then(|clause| child_of_kind(clause, "GroupGraphPattern")) {
+            Some(pattern) => self.group_graph_pattern(pattern),
+            None => Ok(GraphPattern::Bgp(vec![])),
+        }
+    }
+
+    /// Applies grouping, the select expressions and the solution modifiers to `pattern`.
+    /// Without a select `clause` nothing is projected.
+    fn select(
+        &mut self,
+        clause: Option<Node>,
+        mut pattern: GraphPattern,
+        modifier: Option<Node>,
+        values: Option<Node>,
+    ) -> Result<GraphPattern> {
+        let outer_aggregates = std::mem::take(&mut self.aggregates);
+        let clauses = modifier.map(children).unwrap_or_default();
+        let clause_of_kind = |kind: &str| clauses.iter().find(|child| child.kind() == kind);
+
+        let group_clause = clause_of_kind("GroupClause");
+        let mut group_variables = vec![];
+        for condition in group_clause
+            .map(|clause| named_children(*clause))
+            .unwrap_or_default()
+        {
+            let Some(condition) = named_children(condition).into_iter().next() else {
+                continue;
+            };
+            let (expression, variable) = match condition.kind() {
+                "VAR" => (None, self.variable(condition)),
+                "assignment" => {
+                    let (expression, variable) = self.assignment(condition)?;
+                    (Some(expression), variable)
+                }
+                _ => match self.expression(condition)? {
+                    Expression::Variable(variable) => (None, variable),
+                    expression => (Some(expression), self.fresh_variable('.')),
+                },
+            };
+            if let Some(expression) = expression {
+                pattern = GraphPattern::Extend(Box::new(pattern), variable.clone(), expression);
+            }
+            group_variables.push(variable);
+        }
+
+        let mut having = vec![];
+        if let Some(clause) = clause_of_kind("HavingClause") {
+            for condition in named_children(*clause) {
+                for constraint in named_children(condition) {
+                    having.push(self.expression(constraint)?);
+                }
+            }
+        }
+
+        let mut projection = None;
+        let mut extensions = vec![];
+        let mut is_distinct = false;
+        let mut is_reduced = false;
+        if let Some(clause) = clause {
+            let mut variables = vec![];
+            let mut is_star = false;
+            for child in children(clause) {
+                match child.kind() {
+                    "DISTINCT" => is_distinct = true,
+                    "REDUCED" => is_reduced = true,
+                    "*" => is_star = true,
+                    "VAR" => variables.push(self.variable(child)),
+                    "assignment" => {
+                        let (expression, variable) = self.assignment(child)?;
+                        extensions.push((variable.clone(), expression));
+                        variables.push(variable);
+                    }
+                    _ => {}
+                }
+            }
+            projection = Some((!is_star).then_some(variables));
+        }
+
+        let mut order = vec![];
+        if let Some(clause) = clause_of_kind("OrderClause") {
+            for condition in named_children(*clause) {
+                let descending = condition
+                    .child(0)
+                    .is_some_and(|child| child.kind() == "DESC");
+                for expression in named_children(condition) {
+                    order.push(OrderCondition {
+                        expression: self.expression(expression)?,
+                        descending,
+                    });
+                }
+            }
+        }
+
+        let aggregates = std::mem::replace(&mut self.aggregates, outer_aggregates);
+        if group_clause.is_some() || !aggregates.is_empty() {
+            pattern = GraphPattern::Group {
+                pattern: Box::new(pattern),
+                variables: group_variables,
+                aggregates,
+            };
+        }
+        if let Some(condition) = conjunction(having) {
+            pattern = GraphPattern::Filter(condition, Box::new(pattern));
+        }
+        if let Some(values) = values {
+            pattern = join(pattern, self.values(values)?);
+        }
+        for (variable, expression) in extensions {
+            pattern = GraphPattern::Extend(Box::new(pattern), variable, expression);
+        }
+        if !order.is_empty() {
+            pattern = GraphPattern::OrderBy(Box::new(pattern), order);
+        }
+        if let Some(projection) = projection {
+            let variables = projection.unwrap_or_else(|| pattern.variables());
+            pattern = GraphPattern::Project(Box::new(pattern), variables);
+        }
+        if is_distinct {
+            pattern = GraphPattern::Distinct(Box::new(pattern));
+        } else if is_reduced {
+            pattern = GraphPattern::Reduced(Box::new(pattern));
+        }
+        if let Some(clause) = clause_of_kind("LimitOffsetClauses") {
+            let mut offset = 0;
+            let mut limit = None;
+            for child in named_children(*clause) {
+                let value = child_of_kind(child, "INTEGER")
+                    .and_then(|integer| self.text(integer).parse::<usize>().ok())
+                    .ok_or_else(|| AlgebraError("Invalid LIMIT or OFFSET".to_string()))?;
+                match child.kind() {
+                    "LimitClause" => limit = Some(value),
+                    _ => offset = value,
+                }
+            }
+            pattern = GraphPattern::Slice {
+                pattern: Box::new(pattern),
+                offset,
+                limit,
+            };
+        }
+        Ok(pattern)
+    }
+
+    /// Reads `(expression AS ?variable)`.
+    fn assignment(&mut self, assignment: Node) -> Result<(Expression, String)> {
+        let variable = assignment
+            .child_by_field_name("bound_variable")
+            .map(|variable| self.variable(variable))
+            .unwrap_or_default();
+        let expression = named_children(assignment)
+            .into_iter()
+            .find(|child| Some(*child) != assignment.child_by_field_name("bound_variable"))
+            .ok_or_else(|| AlgebraError("An assignment has no expression".to_string()))?;
+        Ok((self.expression(expression)?, variable))
+    }
+
+    fn group_graph_pattern(&mut self, node: Node) -> Result<GraphPattern> {
+        let Some(sub) = child_of_kind(node, "GroupGraphPatternSub") else {
+            return Ok(GraphPattern::Bgp(vec![]));
+        };
+        let mut pattern = GraphPattern::Bgp(vec![]);
+        let mut filters = vec![];
+        for element in named_children(sub) {
+            pattern = match element.kind() {
+                "SubSelect" => {
+                    let where_pattern = self.where_clause(child_of_kind(element, "WhereClause"))?;
+                    let values = child_of_kind(element, "ValuesClause")
+                        .and_then(|clause| child_of_kind(clause, "DataBlock"));
+                    let select = self.select(
+                        child_of_kind(element, "SelectClause"),
+                        where_pattern,
+                        child_of_kind(element, "SolutionModifier"),
+                        values,
+                    )?;
+                    join(pattern, select)
+                }
+                "TriplesBlock" => {
+                    let block = self.triples(element)?;
+                    join(pattern, join_block(block))
+                }
+                "OptionalGraphPattern" => {
+                    let optional = self.inner_group(element)?;
+                    match optional {
+                        GraphPattern::Filter(expression, optional) => {
+                            GraphPattern::LeftJoin(Box::new(pattern), optional, Some(expression))
+                        }
+                        optional => {
+                            GraphPattern::LeftJoin(Box::new(pattern), Box::new(optional), None)
+                        }
+                    }
+                }
+                "MinusGraphPattern" => {
+                    GraphPattern::Minus(Box::new(pattern), Box::new(self.inner_group(element)?))
+                }
+                "GroupOrUnionGraphPattern" => {
+                    let mut union: Option<GraphPattern> = None;
+                    for group in named_children(element) {
+                        let group = self.group_graph_pattern(group)?;
+                        union = Some(match union {
+                            Some(union) => GraphPattern::Union(Box::new(union), Box::new(group)),
+                            None => group,
+                        });
+                    }
+                    join(pattern, union.unwrap_or(GraphPattern::Bgp(vec![])))
+                }
+                "GraphGraphPattern" => {
+                    let name = named_children(element)
+                        .into_iter()
+                        .find(|child| child.kind() != "GroupGraphPattern")
+                        .ok_or_else(|| AlgebraError("A GRAPH has no name".to_string()))?;
+                    let name = self.term_pattern(name)?;
+                    let graph = GraphPattern::Graph(name, Box::new(self.inner_group(element)?));
+                    join(pattern, graph)
+                }
+                "ServiceGraphPattern" => {
+                    let name = named_children(element)
+                        .into_iter()
+                        .find(|child| child.kind() != "GroupGraphPattern")
+                        .ok_or_else(|| AlgebraError("A SERVICE has no name".to_string()))?;
+                    let service = GraphPattern::Service {
+                        name: self.term_pattern(name)?,
+                        silent: child_of_kind(element, "SILENT").is_some(),
+                        pattern: Box::new(self.inner_group(element)?),
+                    };
+                    join(pattern, service)
+                }
+                "Filter" => {
+                    for constraint in named_children(element) {
+                        filters.push(self.expression(constraint)?);
+                    }
+                    pattern
+                }
+                "Bind" => match child_of_kind(element, "assignment") {
+                    Some(assignment) => {
+                        let (expression, variable) = self.assignment(assignment)?;
+                        GraphPattern::Extend(Box::new(pattern), variable, expression)
+                    }
+                    None => pattern,
+                },
+                "InlineData" => match child_of_kind(element, "DataBlock") {
+                    Some(values) => join(pattern, self.values(values)?),
+                    None => pattern,
+                },
+                _ => pattern,
+            };
+        }
+        Ok(match conjunction(filters) {
+            Some(condition) => GraphPattern::Filter(condition, Box::new(pattern)),
+            None => pattern,
+        })
+    }
+
+    /// The pattern of the group graph pattern in `node`, like `OPTIONAL { … }`.
+    fn inner_group(&mut self, node: Node) -> Result<GraphPattern> {
+        match child_of_kind(node, "GroupGraphPattern") {
+            Some(group) => self.group_graph_pattern(group),
+            None => Ok(GraphPattern::Bgp(vec![])),
+        }
+    }
+
+    fn values(&mut self, block: Node) -> Result<GraphPattern> {
+        let children = children(block);
+        let start = children
+            .iter()
+            .position(|child| child.kind() == "{")
+            .unwrap_or(children.len());
+        let variables: Vec<String> = children[..start]
+            .iter()
+            .filter(|child| child.kind() == "VAR")
+            .map(|variable| self.variable(*variable))
+            .collect();
+        let is_single = children.first().is_some_and(|child| child.kind() == "VAR");
+        let mut rows = vec![];
+        let mut row = vec![];
+        for child in &children[start..] {
+            match child.kind() {
+                "{" | "}" => {}
+                "(" => row = vec![],
+                ")" => rows.push(std::mem::take(&mut row)),
+                "NIL" => rows.push(vec![]),
+                "UNDEF" if is_single => rows.push(vec![None]),
+                "UNDEF" => row.push(None),
+                _ => {
+                    let TermPattern::Term(term) = self.term_pattern(*child)? else {
+                        return Err(AlgebraError("VALUES contain a variable".to_string()));
+                    };
+                    match is_single {
+                        true => rows.push(vec![Some(term)]),
+                        false => row.push(Some(term)),
+                    }
+                }
+            }
+        }
+        if rows.iter().any(|row| row.len() != variables.len()) {
+            return Err(AlgebraError(
+                "A row of VALUES does not bind all variables".to_string(),
+            ));
+        }
+        Ok(GraphPattern::Values { variables, rows })
+    }
+
+    /// The triples of `TriplesBlock`, `TriplesTemplate` and `ConstructTemplate` nodes.
+    fn triples(&mut self, node: Node) -> Result<Block> {
+        let mut block = Block::default();
+        for statement in find_statements(node) {
+            let children = named_children(statement);
+            let Some(subject) = children.first() else {
+                continue;
+            };
+            let subject = self.graph_node(*subject, &mut block)?;
+            if let Some(list) = children
+                .iter()
+                .find(|child| child.kind().starts_with("PropertyList"))
+            {
+                self.property_list(subject, *list, &mut block)?;
+            }
+        }
+        Ok(block)
+    }
+
+    fn property_list(&mut self, subject: TermPattern, list: Node, block: &mut Block) -> Result<()> {
+        let mut predicate = None;
+        for child in children(list) {
+            match child.kind() {
+                ";" => {}
+                "ObjectList" => {
+                    let Some(predicate) = predicate.clone() else {
+                        continue;
+                    };
+                    for object in named_children(child) {
+                        let object = self.graph_node(object, block)?;
+                        self.add_path(subject.clone(), &predicate, object, block);
+                    }
+                }
+                _ => predicate = Some(self.verb(child)?),
+            }
+        }
+        Ok(())
+    }
+
+    fn verb(&mut self, verb: Node) -> Result<Verb> {
+        Ok(match verb.kind() {
+            "VAR" => Verb::Variable(self.variable(verb)),
+            "a" => Verb::Path(PropertyPath::Link(format!("{}type", RDF))),
+            "Path" => Verb::Path(self.path(verb)?),
+            _ => Verb::Path(PropertyPath::Link(self.iri(verb)?)),
+        })
+    }
+
+    /// Adds a triple or a path, sequences of paths are split into triples with new variables.
+    fn add_path(
+        &mut self,
+        subject: TermPattern,
+        verb: &Verb,
+        object: TermPattern,
+        block: &mut Block,
+    ) {
+        let path = match verb {
+            Verb::Variable(variable) => {
+                block.triples.push(TriplePattern {
+                    subject,
+                    predicate: TermPattern::Variable(variable.clone()),
+                    object,
+                });
+                return;
+            }
+            Verb::Path(path) => path,
+        };
+        match path {
+            PropertyPath::Link(iri) => block.triples.push(TriplePattern {
+                subject,
+                predicate: TermPattern::Term(Term::Iri(iri.clone())),
+                object,
+            }),
+            PropertyPath::Inverse(inverse) if matches!(**inverse, PropertyPath::Link(_)) => {
+                self.add_path(object, &Verb::Path((**inverse).clone()), subject, block)
+            }
+            PropertyPath::Sequence(first, second) => {
+                let middle = TermPattern::Variable(self.fresh_variable('?'));
+                self.add_path(
+                    subject,
+                    &Verb::Path((**first).clone()),
+                    middle.clone(),
+                    block,
+                );
+                self.add_path(middle, &Verb::Path((**second).clone()), object, block);
+            }
+            path => block.paths.push(GraphPattern::Path {
+                subject,
+                path: path.clone(),
+                object,
+            }),
+        }
+    }
+
+    fn path(&mut self, node: Node) -> Result<PropertyPath> {
+        let parts = named_children(node);
+        match node.kind() {
+            "Path" | "PathSequence" => {
+                let mut parts = parts.into_iter();
+                let first = parts
+                    .next()
+                    .ok_or_else(|| AlgebraError("A path is empty".to_string()))?;
+                let mut path = self.path(first)?;
+                for part in parts {
+                    let part = self.path(part)?;
+                    path = match node.kind() {
+                        "Path" => PropertyPath::Alternative(Box::new(path), Box::new(part)),
+                        _ => PropertyPath::Sequence(Box::new(path), Box::new(part)),
+                    };
+                }
+                Ok(path)
+            }
+            "PathEltOrInverse" => {
+                let element = parts
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| AlgebraError("A path is empty".to_string()))?;
+                let path = self.path(element)?;
+                Ok(match child_of_kind(node, "^") {
+                    Some(_) => PropertyPath::Inverse(Box::new(path)),
+                    None => path,
+                })
+            }
+            "PathElt" => {
+                let primary = child_of_kind(node, "PathPrimary")
+                    .ok_or_else(|| AlgebraError("A path is empty".to_string()))?;
+                let path = Box::new(self.path(primary)?);
+                Ok(
+                    match child_of_kind(node, "PathMod").map(|modifier| self.text(modifier)) {
+                        Some("*") => PropertyPath::ZeroOrMore(path),
+                        Some("+") => PropertyPath::OneOrMore(path),
+                        Some("?") => PropertyPath::ZeroOrOne(path),
+                        _ => *path,
+                    },
+                )
+            }
+            "PathPrimary" => match parts.first() {
+                Some(set) if set.kind() == "PathNegatedPropertySet" => {
+                    let members = find_all_kind(*set, "PathOneInPropertySet")
+                        .into_iter()
+                        .map(|member| {
+                            let link = self.path(member)?;
+                            Ok(match child_of_kind(member, "^") {
+                                Some(_) => PropertyPath::Inverse(Box::new(link)),
+                                None => link,
+                            })
+                        })
+                        .collect::<Result<_>>()?;
+                    Ok(PropertyPath::NegatedPropertySet(members))
+                }
+                Some(path) if path.kind() == "Path" => self.path(*path),
+                Some(iri) => Ok(PropertyPath::Link(self.iri(*iri)?)),
+                None => Ok(PropertyPath::Link(format!("{}type", RDF))),
+            },
+            "PathOneInPropertySet" => match parts.first() {
+                Some(iri) => Ok(PropertyPath::Link(self.iri(*iri)?)),
+                None => Ok(PropertyPath::Link(format!("{}type", RDF))),
+            },
+            kind => Err(AlgebraError(format!("Unexpected {} in a path", kind))),
+        }
+    }
+
+    /// The term of a graph node, collections and blank node property lists add their triples.
+    fn graph_node(&mut self, node: Node, block: &mut Block) -> Result<TermPattern> {
+        match node.kind() {
+            "collection" => {
+                let items = named_children(node);
+                let nodes: Vec<TermPattern> = items
+                    .iter()
+                    .map(|_| TermPattern::Variable(self.fresh_variable('?')))
+                    .collect();
+                let nil = TermPattern::Term(Term::Iri(format!("{}nil", RDF)));
+                for (index, item) in items.into_iter().enumerate() {
+                    let item = self.graph_node(item, block)?;
+                    let rest = nodes.get(index + 1).unwrap_or(&nil).clone();
+                    for (predicate, object) in [("first", item), ("rest", rest)] {
+                        block.triples.push(TriplePattern {
+                            subject: nodes[index].clone(),
+                            predicate: TermPattern::Term(Term::Iri(format!(
+                                "{}{}",
+                                RDF, predicate
+                            ))),
+                            object,
+                        });
+                    }
+                }
+                Ok(nodes.into_iter().next().unwrap_or(nil))
+            }
+            "BlankNodePropertyListPath" | "BlankNodePropertyList" => {
+                let subject = TermPattern::Variable(self.fresh_variable('?'));
+                if let Some(list) = named_children(node).first() {
+                    self.property_list(subject.clone(), *list, block)?;
+                }
+                Ok(subject)
+            }
+            _ => self.term_pattern(node),
+        }
+    }
+
+    fn term_pattern(&mut self, node: Node) -> Result<TermPattern> {
+        Ok(match node.kind() {
+            "VAR" => TermPattern::Variable(self.variable(node)),
+            "BLANK_NODE_LABEL" => {
+                let label = self.text(node)[2..].to_string();
+                let variable = match self.blank_nodes.get(&label) {
+                    Some(variable) => variable.clone(),
+                    None => {
+                        let variable = self.fresh_variable('?');
+                        self.blank_nodes.insert(label, variable.clone());
+                        variable
+                    }
+                };
+                TermPattern::Variable(variable)
+            }
+            "ANON" => TermPattern::Variable(self.fresh_variable('?')),
+            "NIL" => TermPattern::Term(Term::Iri(format!("{}nil", RDF))),
+            _ => TermPattern::Term(self.term(node)?),
+        })
+    }
+
+    fn term(&self, node: Node) -> Result<Term> {
+        let typed = |datatype: &str| {
+            Ok(Term::Literal {
+                value: self.text(node).to_string(),
+                datatype: Some(format!("{}{}", XSD, datatype)),
+                language: None,
+            })
+        };
+        match node.kind() {
+            "IRIREF" | "PrefixedName" => Ok(Term::Iri(self.iri(node)?)),
+            "INTEGER" => typed("integer"),
+            "DECIMAL" => typed("decimal"),
+            "DOUBLE" => typed("double"),
+            "boolean_literal" => typed("boolean"),
+            "RdfLiteral" => {
+                let value = node
+                    .child_by_field_name("value")
+                    .map(|string| unescape(self.text(string)))
+                    .unwrap_or_default();
+                if let Some(language) = child_of_kind(node, "LANGTAG") {
+                    return Ok(Term::Literal {
+                        value,
+                        datatype: None,
+                        language: Some(self.text(language)[1..].to_string()),
+                    });
+                }
+                match named_children(node)
+                    .into_iter()
+                    .find(|child| matches!(child.kind(), "IRIREF" | "PrefixedName"))
+                {
+                    Some(datatype) => Ok(literal(value, self.iri(datatype)?)),
+                    None => Ok(Term::Literal {
+                        value,
+                        datatype: None,
+                        language: None,
+                    }),
+                }
+            }
+            kind => Err(AlgebraError(format!("Unexpected {} as term", kind))),
+        }
+    }
+
+    fn iri(&self, node: Node) -> Result<String> {
+        match node.kind() {
+            "IRIREF" => Ok(self.iri_reference(node)),
+            "PrefixedName" => {
+                let prefix = child_of_kind(node, "PNAME_NS")
+                    .map(|prefix| self.text(prefix).trim_end_matches(':'))
+                    .unwrap_or_default();
+                let local: String = match child_of_kind(node, "PN_LOCAL") {
+                    Some(local) => {
+                        let mut local_name = String::new();
+                        let mut chars = self.text(local).chars();
+                        while let Some(char) = chars.next() {
+                            match char {
+                                '\\' => local_name.extend(chars.next()),
+                                char => local_name.push(char),
+                            }
+                        }
+                        local_name
+                    }
+                    None => String::new(),
+                };
+                match self.prefixes.get(prefix) {
+                    Some(namespace) => Ok(format!("{}{}", namespace, local)),
+                    None => Err(AlgebraError(format!(
+                        "The prefix \"{}:\" is not declared",
+                        prefix
+                    ))),
+                }
+            }
+            kind => Err(AlgebraError(format!("Expected an IRI, found {}", kind))),
+        }
+    }
+
+    /// The IRI of an `IRIREF`, resolved against the base IRI.
+    fn iri_reference(&self, node: Node) -> String {
+        let text = self.text(node);
+        let iri = text
+            .strip_prefix('<')
+            .and_then(|iri| iri.strip_suffix('>'))
+            .unwrap_or(text);
+        match &self.base {
+            Some(base) => resolve(base, iri),
+            None => iri.to_string(),
+        }
+    }
+
+    fn variable(&self, node: Node) -> String {
+        self.text(node)[1..].to_string()
+    }
+
+    fn expression(&mut self, node: Node) -> Result<Expression> {
+        let children = named_children(node);
+        let argument = |index: usize| {
+            children
+                .get(index)
+                .copied()
+                .ok_or_else(|| AlgebraError(format!("An argument of {} is missing", node.kind())))
+        };
+        match node.kind() {
+            "VAR" => Ok(Expression::Variable(self.variable(node))),
+            "BrackettedExpression" => self.expression(argument(0)?),
+            "binary_expression" => {
+                let operators: Vec<&str> = node
+                    .children_by_field_name("operator", &mut node.walk())
+                    .map(|operator| operator.kind())
+                    .collect();
+                let left = Box::new(self.expression(argument(0)?)?);
+                let right = argument(1)?;
+                let operator = match operators.as_slice() {
+                    ["IN"] => return Ok(Expression::In(left, self.expression_list(right)?)),
+                    ["NOT", "IN"] => {
+                        return Ok(Expression::NotIn(left, self.expression_list(right)?))
+                    }
+                    ["||"] => BinaryOperator::Or,
+                    ["&&"] => BinaryOperator::And,
+                    ["="] => BinaryOperator::Equal,
+                    ["!="] => BinaryOperator::NotEqual,
+                    ["<"] => BinaryOperator::Less,
+                    [">"] => BinaryOperator::Greater,
+                    ["<="] => BinaryOperator::LessOrEqual,
+                    [">="] => BinaryOperator::GreaterOrEqual,
+                    ["+"] => BinaryOperator::Add,
+                    ["-"] => BinaryOperator::Subtract,
+                    ["*"] => BinaryOperator::Multiply,
+                    ["/"] => BinaryOperator::Divide,
+                    operators => {
+                        return Err(AlgebraError(format!(
+                            "Unknown operator {}",
+                            operators.join(" ")
+                        )))
+                    }
+                };
+                Ok(Expression::Binary(
+                    operator,
+                    left,
+                    Box::new(self.expression(right)?),
+                ))
+            }
+            "unary_expression" => {
+                let operator = match node.child(0).map(|operator| operator.kind()) {
+                    Some("!") => UnaryOperator::Not,
+                    Some("+") => UnaryOperator::Plus,
+                    _ => UnaryOperator::Minus,
+                };
+                Ok(Expression::Unary(
+                    operator,
+                    Box::new(self.expression(argument(0)?)?),
+                ))
+            }
+            "BuildInCall" => {
+                let Some(first) = node.child(0) else {
+                    return Err(AlgebraError("A function call is empty".to_string()));
+                };
+                match first.kind() {
+                    "Aggregate" => self.aggregate(first),
+                    "ExistsFunc" | "NotExistsFunc" => {
+                        let pattern = Box::new(self.inner_group(first)?);
+                        Ok(match first.kind() {
+                            "ExistsFunc" => Expression::Exists(pattern),
+                            _ => Expression::NotExists(pattern),
+                        })
+                    }
+                    "RegexExpression" | "SubstringExpression" | "String_replace_expression" => {
+                        let name = first
+                            .child(0)
+                            .map(|keyword| keyword.kind().to_uppercase())
+                            .unwrap_or_default();
+                        let arguments = named_children(first)
+                            .into_iter()
+                            .map(|argument| self.expression(argument))
+                            .collect::<Result<_>>()?;
+                        Ok(Expression::Builtin(name, arguments))
+                    }
+                    keyword => {
+                        let mut arguments = vec![];
+                        for argument in children {
+                            match argument.kind() {
+                                "NIL" => {}
+                                "ExpressionList" => {
+                                    arguments.append(&mut self.expression_list(argument)?)
+                                }
+                                _ => arguments.push(self.expression(argument)?),
+                            }
+                        }
+                        Ok(Expression::Builtin(keyword.to_uppercase(), arguments))
+                    }
+                }
+            }
+            "FunctionCall" => {
+                let iri = node
+                    .child_by_field_name("identifier")
+                    .ok_or_else(|| AlgebraError("A function has no IRI".to_string()))?;
+                let arguments = match child_of_kind(node, "ArgList") {
+                    Some(arguments) => named_children(arguments)
+                        .into_iter()
+                        .filter(|argument| argument.kind() != "NIL")
+                        .map(|argument| self.expression(argument))
+                        .collect::<Result<_>>()?,
+                    None => vec![],
+                };
+                Ok(Expression::Function(self.iri(iri)?, arguments))
+            }
+            _ => Ok(Expression::Term(self.term(node)?)),
+        }
+    }
+
+    fn expression_list(&mut self, list: Node) -> Result<Vec<Expression>> {
+        named_children(list)
+            .into_iter()
+            .filter(|expression| expression.kind() != "NIL")
+            .map(|expression| self.expression(expression))
+            .collect()
+    }
+
+    /// Binds the aggregate to a new variable of the innermost `SELECT` and returns the variable.
+    fn aggregate(&mut self, node: Node) -> Result<Expression> {
+        let children = children(node);
+        let separator = children
+            .iter()
+            .find(|child| child.kind() == "String")
+            .map(|separator| unescape(self.text(*separator)));
+        let function = match children.first().map(|keyword| keyword.kind()) {
+            Some("COUNT") => AggregateFunction::Count,
+            Some("SUM") => AggregateFunction::Sum,
+            Some("MIN") => AggregateFunction::Min,
+            Some("MAX") => AggregateFunction::Max,
+            Some("AVG") => AggregateFunction::Avg,
+            Some("SAMPLE") => AggregateFunction::Sample,
+            _ => AggregateFunction::GroupConcat { separator },
+        };
+        let expression = match children
+            .iter()
+            .find(|child| child.is_named() && child.kind() != "String")
+        {
+            Some(expression) => Some(self.expression(*expression)?),
+            None => None,
+        };
+        let aggregate = Aggregate {
+            function,
+            distinct: children.iter().any(|child| child.kind() == "DISTINCT"),
+            expression,
+        };
+        let variable = match self
+            .aggregates
+            .iter()
+            .find(|(_variable, other)| *other == aggregate)
+        {
+            Some((variable, _aggregate)) => variable.clone(),
+            None => {
+                let variable = self.fresh_variable('.');
+                self.aggregates.push((variable.clone(), aggregate));
+                variable
+            }
+        };
+        Ok(Expression::Variable(variable))
+    }
+}
+
+/// A predicate of a triple: a variable or a path.
+#[derive(Clone)]
+enum Verb {
+    Variable(String),
+    Path(PropertyPath),
+}
+
+/// The `TriplesSameSubject(Path)` statements of a block of triples.
+fn find_statements(node: Node) -> Vec<Node> {
+    match node.kind() {
+        "TriplesSameSubject" | "TriplesSameSubjectPath" => vec![node],
+        _ => named_children(node)
+            .into_iter()
+            .flat_map(find_statements)
+            .collect(),
+    }
+}
+
+fn find_all_kind<'a>(node: Node<'a>, kind: &str) -> Vec<Node<'a>> {
+    match node.kind() == kind {
+        true => vec![node],
+        false => named_children(node)
+            .into_iter()
+            .flat_map(|child| find_all_kind(child, kind))
+            .collect(),
+    }
+}
+
+/// Joins two patterns, the empty group is left out and adjacent basic graph patterns are merged.
+fn join(left: GraphPattern, right: GraphPattern) -> GraphPattern {
+    match (left, right) {
+        (GraphPattern::Bgp(triples), right) if triples.is_empty() => right,
+        (left, GraphPattern::Bgp(triples)) if triples.is_empty() => left,
+        (GraphPattern::Bgp(mut left), GraphPattern::Bgp(mut right)) => {
+            left.append(&mut right);
+            GraphPattern::Bgp(left)
+        }
+        (left, right) => GraphPattern::Join(Box::new(left), Box::new(right)),
+    }
+}
+
+/// The basic graph pattern of the block, joined with its paths.
+fn join_block(block: Block) -> GraphPattern {
+    block
+        .paths
+        .into_iter()
+        .fold(GraphPattern::Bgp(block.triples), join)
+}
+
+fn conjunction(expressions: Vec<Expression>) -> Option<Expression> {
+    expressions.into_iter().reduce(|left, right| {
+        Expression::Binary(BinaryOperator::And, Box::new(left), Box::new(right))
+    })
+}
+
+/// The value of a SPARQL string, with or without its quotes.
+fn unescape(string: &str) -> String {
+    let string = ["\"\"\"", "'''", "\"", "'"]
+        .iter()
+        .find_map(|quote| string.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(string);
+    let mut value = String::new();
+    let mut chars = string.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('t') => value.push('\t'),
+                Some('b') => value.push('\u{8}'),
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('f') => value.push('\u{c}'),
+                Some(kind @ ('u' | 'U')) => {
+                    let length = if kind == 'u' { 4 } else { 8 };
+                    let hex: String = chars.by_ref().take(length).collect();
+                    value.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                }
+                Some(char) => value.push(char),
+                None => value.push('\\'),
+            },
+            char => value.push(char),
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        algebra::{
+            Aggregate, AggregateFunction, BinaryOperator, Expression, GraphPattern, PropertyPath,
+            Query, QueryForm, TermPattern, TriplePattern,
+        },
+        results::Term,
+    };
+
+    fn variable(name: &str) -> TermPattern {
+        TermPattern::Variable(name.to_string())
+    }
+
+    fn iri(iri: &str) -> TermPattern {
+        TermPattern::Term(Term::Iri(iri.to_string()))
+    }
+
+    fn triple(subject: TermPattern, predicate: TermPattern, object: TermPattern) -> TriplePattern {
+        TriplePattern {
+            subject,
+            predicate,
+            object,
+        }
+    }
+
+    #[test]
+    fn basic_graph_patterns() {
+        let query = Query::parse(
+            "PREFIX ex: <http://example.org/> SELECT * WHERE { ?s a ex:C ; ex:p [ ex:q ?o ] . ?s ex:r/ex:t ?x }",
+            None,
+        )
+        .unwrap();
+        assert_eq!(query.form, QueryForm::Select);
+        assert_eq!(
+            query.pattern,
+            GraphPattern::Project(
+                Box::new(GraphPattern::Bgp(vec![
+                    triple(
+                        variable("s"),
+                        iri("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                        iri("http://example.org/C")
+                    ),
+                    triple(variable("?0"), iri("http://example.org/q"), variable("o")),
+                    triple(variable("s"), iri("http://example.org/p"), variable("?0")),
+                    triple(variable("s"), iri("http://example.org/r"), variable("?1")),
+                    triple(variable("?1"), iri("http://example.org/t"), variable("x")),
+                ])),
+                vec!["s".to_string(), "o".to_string(), "x".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn optional_filter_and_paths() {
+        let query = Query::parse(
+            "ASK { ?s <p>* ?o OPTIONAL { ?o <q> ?x FILTER(?x > 1) } }",
+            Some("http://example.org/"),
+        )
+        .unwrap();
+        assert_eq!(
+            query.pattern,
+            GraphPattern::LeftJoin(
+                Box::new(GraphPattern::Path {
+                    subject: variable("s"),
+                    path: PropertyPath::ZeroOrMore(Box::new(PropertyPath::Link(
+                        "http://example.org/p".to_string()
+                    ))),
+                    object: variable("o")
+                }),
+                Box::new(GraphPattern::Bgp(vec![triple(
+                    variable("o"),
+                    iri("http://example.org/q"),
+                    variable("x")
+                )])),
+                Some(Expression::Binary(
+                    BinaryOperator::Greater,
+                    Box::new(Expression::Variable("x".to_string())),
+                    Box::new(Expression::Term(Term::Literal {
+                        value: "1".to_string(),
+                        datatype: Some("http://www.w3.org/2001/XMLSchema#integer".to_string()),
+                        language: None
+                    }))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn aggregates() {
+        let query = Query::parse(
+            "SELECT ?s (COUNT(*) AS ?n) { ?s ?p ?o } GROUP BY ?s HAVING (COUNT(*) > 1) ORDER BY DESC(?n) LIMIT 2",
+            None,
+        )
+        .unwrap();
+        let GraphPattern::Slice {
+            pattern,
+            offset: 0,
+            limit: Some(2),
+        } = query.pattern
+        else {
+            panic!("The query is sliced")
+        };
+        let GraphPattern::Project(pattern, variables) = *pattern else {
+            panic!("The query is projected")
+        };
+        assert_eq!(variables, vec!["s".to_string(), "n".to_string()]);
+        let GraphPattern::OrderBy(pattern, _order) = *pattern else {
+            panic!("The query is ordered")
+        };
+        let GraphPattern::Extend(pattern, variable, Expression::Variable(aggregate)) = *pattern
+        else {
+            panic!("The aggregate is bound")
+        };
+        assert_eq!(variable, "n");
+        let GraphPattern::Filter(_having, pattern) = *pattern else {
+            panic!("The groups are filtered")
+        };
+        let GraphPattern::Group {
+            variables,
+            aggregates,
+            ..
+        } = *pattern
+        else {
+            panic!("The solutions are grouped")
+        };
+        assert_eq!(variables, vec!["s".to_string()]);
+        // NOTE: The same aggregate in the projection and HAVING is computed once.
+        assert_eq!(
+            aggregates,
+            vec![(
+                aggregate,
+                Aggregate {
+                    function: AggregateFunction::Count,
+                    distinct: false,
+                    expression: None
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn values_and_literals() {
+        let query = Query::parse(
+            r#"PREFIX xsd: <http://www.w3.org/2001/XMLSchema#> SELECT * { VALUES (?a ?b) { ("x\n"@en UNDEF) (1.5 "s"^^xsd:string) } }"#,
+            None,
+        )
+        .unwrap();
+        let GraphPattern::Project(pattern, _variables) = query.pattern else {
+            panic!("The query is projected")
+        };
+        assert_eq!(
+            *pattern,
+            GraphPattern::Values {
+                variables: vec!["a".to_string(), "b".to_string()],
+                rows: vec![
+                    vec![
+                        Some(Term::Literal {
+                            value: "x\n".to_string(),
+                            datatype: None,
+                            language: Some("en".to_string())
+                        }),
+                        None
+                    ],
+                    vec![
+                        Some(Term::Literal {
+                            value: "1.5".to_string(),
+                            datatype: Some("http://www.w3.org/2001/XMLSchema#decimal".to_string()),
+                            language: None
+                        }),
+                        Some(Term::Literal {
+                            value: "s".to_string(),
+                            datatype: None,
+                            language: None
+                        })
+                    ]
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Query::parse("SELECT * {", None).unwrap_err().to_string(),
+            "Syntax error at 1:11"
+        );
+        assert_eq!(
+            Query::parse("SELECT * { ?s ex:p ?o }", None)
+                .unwrap_err()
+                .to_string(),
+            "The prefix \"ex:\" is not declared"
+        );
+        assert!(Query::parse("INSERT DATA { <a> <b> <c> }", None).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! The SPARQL 1.1 algebra of queries, translated from their syntax tree.
+//! See <https://www.w3.org/TR/sparql11-query/#sparqlQuery>.
+
+mod lower;
+mod sse;
+
+use std::fmt;
+
+use crate::results::Term;
+
+/// A variable or a fixed term in a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermPattern {
+    Variable(String),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriplePattern {
+    pub subject: TermPattern,
+    pub predicate: TermPattern,
+    pub object: TermPattern,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyPath {
+    Link(String),
+    Inverse(Box<PropertyPath>),
+    Sequence(Box<PropertyPath>, Box<PropertyPath>),
+    Alternative(Box<PropertyPath>, Box<PropertyPath>),
+    ZeroOrMore(Box<PropertyPath>),
+    OneOrMore(Box<PropertyPath>),
+    ZeroOrOne(Box<PropertyPath>),
+    /// `!(a|^b)`, the members are links or inverted links.
+    NegatedPropertySet(Vec<PropertyPath>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphPattern {
+    Bgp(Vec<TriplePattern>),
+    Path {
+        subject: TermPattern,
+        path: PropertyPath,
+        object: TermPattern,
+    },
+    Join(Box<GraphPattern>, Box<GraphPattern>),
+    LeftJoin(Box<GraphPattern>, Box<GraphPattern>, Option<Expression>),
+    Filter(Expression, Box<GraphPattern>),
+    Union(Box<GraphPattern>, Box<GraphPattern>),
+    Minus(Box<GraphPattern>, Box<GraphPattern>),
+    Graph(TermPattern, Box<GraphPattern>),
+    Service {
+        name: TermPattern,
+        silent: bool,
+        pattern: Box<GraphPattern>,
+    },
+    Extend(Box<GraphPattern>, String, Expression),
+    /// Inline data of a `VALUES` clause, `None` is `UNDEF`.
+    Values {
+        variables: Vec<String>,
+        rows: Vec<Vec<Option<Term>>>,
+    },
+    /// Groups the solutions by the `variables` and binds the aggregates of each group.
+    Group {
+        pattern: Box<GraphPattern>,
+        variables: Vec<String>,
+        aggregates: Vec<(String, Aggregate)>,
+    },
+    OrderBy(Box<GraphPattern>, Vec<OrderCondition>),
+    Project(Box<GraphPattern>, Vec<String>),
+    Distinct(Box<GraphPattern>),
+    Reduced(Box<GraphPattern>),
+    Slice {
+        pattern: Box<GraphPattern>,
+        offset: usize,
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderCondition {
+    pub expression: Expression,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Sample,
+    GroupConcat { separator: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub distinct: bool,
+    /// The aggregated expression, `None` for `COUNT(*)`.
+    pub expression: Option<Expression>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl BinaryOperator {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOperator::Or => "||",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::LessOrEqual => "<=",
+            BinaryOperator::GreaterOrEqual => ">=",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    Not,
+    Plus,
+    Minus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Variable(String),
+    Term(Term),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+    In(Box<Expression>, Vec<Expression>),
+    NotIn(Box<Expression>, Vec<Expression>),
+    Exists(Box<GraphPattern>),
+    NotExists(Box<GraphPattern>),
+    /// A builtin function like `STR`, with its name in upper case.
+    Builtin(String, Vec<Expression>),
+    /// A cast like `xsd:integer(?x)` or another function with an IRI.
+    Function(String, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryForm {
+    /// The variables are projected by the pattern.
+    Select,
+    Ask,
+    Construct(Vec<TriplePattern>),
+    Describe(Vec<TermPattern>),
+}
+
+/// The graphs of `FROM` and `FROM NAMED` clauses.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QueryDataset {
+    pub default: Vec<String>,
+    pub named: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub form: QueryForm,
+    pub pattern: GraphPattern,
+    pub dataset: Option<QueryDataset>,
+    /// The base IRI that relative IRIs were resolved against.
+    pub base: Option<String>,
+}
+
+/// A query that can not be translated, because of syntax errors or an unsupported form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgebraError(pub String);
+
+impl fmt::Display for AlgebraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for AlgebraError {}
+
+impl Query {
+    /// Translates the query `text`, relative IRIs are resolved against `base` or a `BASE` declaration.
+    pub fn parse(text: &str, base: Option<&str>) -> Result<Self, AlgebraError> {
+        lower::lower(text, base)
+    }
+}
+
+/// Whether the variable was introduced for a blank node, a path or an aggregate.
+/// These variables are never projected by `SELECT *`.
+pub(crate) fn is_internal(variable: &str) -> bool {
+    variable.starts_with(['?', '.'])
+}
+
+impl GraphPattern {
+    /// The variables that solutions of the pattern can bind, in order of appearance.
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = vec![];
+        self.collect_variables(&mut variables);
+        variables.retain(|variable| !is_internal(variable));
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<String>) {
+        let mut add = |variable: &String| {
+            if !variables.contains(variable) {
+                variables.push(variable.clone());
+            }
+        };
+        match self {
+            GraphPattern::Bgp(triples) => {
+                for triple in triples {
+                    for term in [&triple.subject, &triple.predicate, &triple.object] {
+                        if let TermPattern::Variable(variable) = term {
+                            add(variable);
+                        }
+                    }
+                }
+            }
+            GraphPattern::Path {
+                subject, object, ..
+            } => {
+                for term in [subject, object] {
+                    if let TermPattern::Variable(variable) = term {
+                        add(variable);
+                    }
+                }
+            }
+            GraphPattern::Join(left, right)
+            | GraphPattern::LeftJoin(left, right, _)
+            | GraphPattern::Union(left, right) => {
+                left.collect_variables(variables);
+                right.collect_variables(variables);
+            }
+            GraphPattern::Graph(name, pattern) => {
+                if let TermPattern::Variable(variable) = name {
+                    add(variable);
+                }
+                pattern.collect_variables(variables);
+            }
+            GraphPattern::Extend(pattern, variable, _) => {
+                pattern.collect_variables(variables);
+                if !variables.contains(variable) {
+                    variables.push(variable.clone());
+                }
+            }
+            GraphPattern::Values {
+                variables: bound, ..
+            } => bound.iter().for_each(add),
+            GraphPattern::Group {
+                variables: keys,
+                aggregates,
+                ..
+            } => {
+                keys.iter().for_each(&mut add);
+                aggregates
+                    .iter()
+                    .for_each(|(variable, _aggregate)| add(variable));
+            }
+            GraphPattern::Project(_pattern, projected) => projected.iter().for_each(add),
+            GraphPattern::Minus(pattern, _)
+            | GraphPattern::Filter(_, pattern)
+            | GraphPattern::Service { pattern, .. }
+            | GraphPattern::OrderBy(pattern, _)
+            | GraphPattern::Distinct(pattern)
+            | GraphPattern::Reduced(pattern)
+            | GraphPattern::Slice { pattern, .. } => pattern.collect_variables(variables),
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//! Writes the algebra as SSE, the S-expressions of Apache Jena.
+//! See <https://jena.apache.org/documentation/notes/sse.html>.
+
+use crate::{rdf::XSD, results::Term};
+
+use super::{
+    Aggregate, AggregateFunction, Expression, GraphPattern, OrderCondition, PropertyPath, Query,
+    TermPattern, TriplePattern, UnaryOperator,
+};
+
+impl Query {
+    /// The algebra of the query as SSE, IRIs are abbreviated by the `prefixes`.
+    /// Only the pattern is written, the templates of `CONSTRUCT` and `DESCRIBE` are not part of the algebra.
+    pub fn to_sse(&self, prefixes: &[(String, String)]) -> String {
+        let writer = Writer { prefixes };
+        let mut sse = match prefixes.is_empty() {
+            true => writer.pattern(&self.pattern, 0),
+            false => {
+                let declarations: Vec<String> = prefixes
+                    .iter()
+                    .map(|(prefix, namespace)| format!("({}: <{}>)", prefix, namespace))
+                    .collect();
+                list(
+                    format!("prefix ({})", declarations.join(" ")),
+                    vec![writer.pattern(&self.pattern, 2)],
+                    0,
+                )
+            }
+        };
+        sse.push('\n');
+        sse
+    }
+}
+
+/// `(head child…)` with every child on its own line, indented below the head.
+fn list(head: String, children: Vec<String>, indent: usize) -> String {
+    let mut sse = format!("({}", head);
+    for child in children {
+        sse.push('\n');
+        sse.push_str(&" ".repeat(indent + 2));
+        sse.push_str(&child);
+    }
+    sse.push(')');
+    sse
+}
+
+fn variable(variable: &str) -> String {
+    // NOTE: Internal variables start with `?` or `.`, so they are written as `??0` and `?.0` like in Jena.
+    format!("?{}", variable)
+}
+
+fn variables(variables: &[String]) -> String {
+    let variables: Vec<String> = variables.iter().map(|name| variable(name)).collect();
+    format!("({})", variables.join(" "))
+}
+
+/// The name of a builtin in Jena, e.g. `sameTerm` for `SAMETERM`.
+fn builtin_name(name: &str) -> String {
+    match name {
+        "SAMETERM" => "sameTerm".to_string(),
+        "LANGMATCHES" => "langMatches".to_string(),
+        "ISIRI" => "isIRI".to_string(),
+        "ISURI" => "isURI".to_string(),
+        "ISBLANK" => "isBlank".to_string(),
+        "ISLITERAL" => "isLiteral".to_string(),
+        "ISNUMERIC" => "isNumeric".to_string(),
+        name => name.to_lowercase(),
+    }
+}
+
+/// Whether the lexical form of a numeric or boolean literal can be written without quotes.
+fn is_bare(value: &str, datatype: &str) -> bool {
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|char| char.is_ascii_digit());
+    match datatype.strip_prefix(XSD) {
+        Some("integer") => digits(unsigned),
+        Some("decimal") => unsigned.split_once('.').is_some_and(|(integer, fraction)| {
+            (integer.is_empty() || digits(integer)) && digits(fraction)
+        }),
+        Some("double") => unsigned
+            .split_once(['e', 'E'])
+            .is_some_and(|(mantissa, exponent)| {
+                let mantissa = match mantissa.split_once('.') {
+                    Some((integer, fraction)) => {
+                        (integer.is_empty() || digits(integer))
+                            && (fraction.is_empty() || digits(fraction))
+                            && mantissa != "."
+                    }
+                    None => digits(mantissa),
+                };
+                mantissa && digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))
+            }),
+        Some("boolean") => matches!(value, "true" | "false"),
+        _ => false,
+    }
+}
+
+struct Writer<'a> {
+    prefixes: &'a [(String, String)],
+}
+
+impl Writer<'_> {
+    fn pattern(&self, pattern: &GraphPattern, indent: usize) -> String {
+        let child = |pattern: &GraphPattern| self.pattern(pattern, indent + 2);
+        match pattern {
+            GraphPattern::Bgp(triples) => match triples.as_slice() {
+                [] => "(table unit)".to_string(),
+                [triple] => format!("(bgp {})", self.triple(triple)),
+                triples => list(
+                    "bgp".to_string(),
+                    triples.iter().map(|triple| self.triple(triple)).collect(),
+                    indent,
+                ),
+            },
+            GraphPattern::Path {
+                subject,
+                path,
+                object,
+            } => format!(
+                "(path {} {} {})",
+                self.term_pattern(subject),
+                self.path(path),
+                self.term_pattern(object)
+            ),
+            GraphPattern::Join(left, right) => {
+                list("join".to_string(), vec![child(left), child(right)], indent)
+            }
+            GraphPattern::LeftJoin(left, right, expression) => {
+                let mut children = vec![child(left), child(right)];
+                children.extend(
+                    expression
+                        .iter()
+                        .map(|expression| self.expression(expression, indent + 2)),
+                );
+                list("leftjoin".to_string(), children, indent)
+            }
+            GraphPattern::Filter(expression, pattern) => list(
+                format!("filter {}", self.expression(expression, indent)),
+                vec![child(pattern)],
+                indent,
+            ),
+            GraphPattern::Union(left, right) => {
+                list("union".to_string(), vec![child(left), child(right)], indent)
+            }
+            GraphPattern::Minus(left, right) => {
+                list("minus".to_string(), vec![child(left), child(right)], indent)
+            }
+            GraphPattern::Graph(name, pattern) => list(
+                format!("graph {}", self.term_pattern(name)),
+                vec![child(pattern)],
+                indent,
+            ),
+            GraphPattern::Service {
+                name,
+                silent,
+                pattern,
+            } => list(
+                format!(
+                    "service {}{}",
+                    if *silent { "silent " } else { "" },
+                    self.term_pattern(name)
+                ),
+                vec![child(pattern)],
+                indent,
+            ),
+            GraphPattern::Extend(pattern, name, expression) => list(
+                format!(
+                    "extend (({} {}))",
+                    variable(name),
+                    self.expression(expression, indent)
+                ),
+                vec![child(pattern)],
+                indent,
+            ),
+            GraphPattern::Values {
+                variables: names,
+                rows,
+            } => {
+                if names.is_empty() && rows.len() == 1 {
+                    return "(table unit)".to_string();
+                }
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        let bindings: Vec<String> = names
+                            .iter()
+                            .zip(row)
+                            .filter_map(|(name, term)| {
+                                let term = term.as_ref()?;
+                                Some(format!(" [{} {}]", variable(name), self.term(term)))
+                            })
+                            .collect();
+                        format!("(row{})", bindings.concat())
+                    })
+                    .collect();
+                list(
+                    format!(
+                        "table (vars{})",
+                        names
+                            .iter()
+                            .map(|name| format!(" {}", variable(name)))
+                            .collect::<String>()
+                    ),
+                    rows,
+                    indent,
+                )
+            }
+            GraphPattern::Group {
+                pattern,
+                variables: keys,
+                aggregates,
+            } => {
+                let aggregates: Vec<String> = aggregates
+                    .iter()
+                    .map(|(name, aggregate)| {
+                        format!("({} {})", variable(name), self.aggregate(aggregate, indent))
+                    })
+                    .collect();
+                list(
+                    format!("group {} ({})", variables(keys), aggregates.join(" ")),
+                    vec![child(pattern)],
+                    indent,
+                )
+            }
+            GraphPattern::OrderBy(pattern, conditions) => {
+                let conditions: Vec<String> = conditions
+                    .iter()
+                    .map(|condition| self.order_condition(condition, indent))
+                    .collect();
+                list(
+                    format!("order ({})", conditions.join(" ")),
+                    vec![child(pattern)],
+                    indent,
+                )
+            }
+            GraphPattern::Project(pattern, projected) => list(
+                format!("project {}", variables(projected)),
+                vec![child(pattern)],
+                indent,
+            ),
+            GraphPattern::Distinct(pattern) => {
+                list("distinct".to_string(), vec![child(pattern)], indent)
+            }
+            GraphPattern::Reduced(pattern) => {
+                list("reduced".to_string(), vec![child(pattern)], indent)
+            }
+            GraphPattern::Slice {
+                pattern,
+                offset,
+                limit,
+            } => list(
+                format!(
+                    "slice {} {}",
+                    match offset {
+                        0 => "_".to_string(),
+                        offset => offset.to_string(),
+                    },
+                    limit.map_or("_".to_string(), |limit| limit.to_string())
+                ),
+                vec![child(pattern)],
+                indent,
+            ),
+        }
+    }
+
+    fn triple(&self, triple: &TriplePattern) -> String {
+        format!(
+            "(triple {} {} {})",
+            self.term_pattern(&triple.subject),
+            self.term_pattern(&triple.predicate),
+            self.term_pattern(&triple.object)
+        )
+    }
+
+    fn term_pattern(&self, term: &TermPattern) -> String {
+        match term {
+            TermPattern::Variable(name) => variable(name),
+            TermPattern::Term(term) => self.term(term),
+        }
+    }
+
+    fn term(&self, term: &Term) -> String {
+        match term {
+            Term::Literal {
+                value,
+                datatype: Some(datatype),
+                language: None,
+            } if is_bare(value, datatype) => value.clone(),
+            term => term.abbreviated(self.prefixes),
+        }
+    }
+
+    fn iri(&self, iri: &str) -> String {
+        self.term(&Term::Iri(iri.to_string()))
+    }
+
+    fn path(&self, path: &PropertyPath) -> String {
+        match path {
+            PropertyPath::Link(iri) => self.iri(iri),
+            PropertyPath::Inverse(path) => format!("(reverse {})", self.path(path)),
+            PropertyPath::Sequence(left, right) => {
+                format!("(seq {} {})", self.path(left), self.path(right))
+            }
+            PropertyPath::Alternative(left, right) => {
+                format!("(alt {} {})", self.path(left), self.path(right))
+            }
+            PropertyPath::ZeroOrMore(path) => format!("(path* {})", self.path(path)),
+            PropertyPath::OneOrMore(path) => format!("(path+ {})", self.path(path)),
+            PropertyPath::ZeroOrOne(path) => format!("(path? {})", self.path(path)),
+            PropertyPath::NegatedPropertySet(members) => {
+                let members: Vec<String> = members.iter().map(|member| self.path(member)).collect();
+                format!("(notoneof {})", members.join(" "))
+            }
+        }
+    }
+
+    /// The expression on one line, except for the patterns of `EXISTS`.
+    fn expression(&self, expression: &Expression, indent: usize) -> String {
+        let call = |head: String, arguments: &[Expression]| {
+            let arguments: String = arguments
+                .iter()
+                .map(|argument| format!(" {}", self.expression(argument, indent)))
+                .collect();
+            format!("({}{})", head, arguments)
+        };
+        match expression {
+            Expression::Variable(name) => variable(name),
+            Expression::Term(term) => self.term(term),
+            Expression::Binary(operator, left, right) => format!(
+                "({} {} {})",
+                operator.symbol(),
+                self.expression(left, indent),
+                self.expression(right, indent)
+            ),
+            Expression::Unary(operator, argument) => format!(
+                "({} {})",
+                match operator {
+                    UnaryOperator::Not => "!",
+                    UnaryOperator::Plus => "+",
+                    UnaryOperator::Minus => "-",
+                },
+                self.expression(argument, indent)
+            ),
+            Expression::In(argument, list) | Expression::NotIn(argument, list) => {
+                let head = match expression {
+                    Expression::In(..) => "in",
+                    _ => "notin",
+                };
+                call(
+                    format!("{} {}", head, self.expression(argument, indent)),
+                    list,
+                )
+            }
+            Expression::Exists(pattern) => list(
+                "exists".to_string(),
+                vec![self.pattern(pattern, indent + 2)],
+                indent,
+            ),
+            Expression::NotExists(pattern) => list(
+                "notexists".to_string(),
+                vec![self.pattern(pattern, indent + 2)],
+                indent,
+            ),
+            Expression::Builtin(name, arguments) => call(builtin_name(name), arguments),
+            Expression::Function(iri, arguments) => call(self.iri(iri), arguments),
+        }
+    }
+
+    fn aggregate(&self, aggregate: &Aggregate, indent: usize) -> String {
+        let mut sse = match &aggregate.function {
+            AggregateFunction::Count => "(count",
+            AggregateFunction::Sum => "(sum",
+            AggregateFunction::Min => "(min",
+            AggregateFunction::Max => "(max",
+            AggregateFunction::Avg => "(avg",
+            AggregateFunction::Sample => "(sample",
+            AggregateFunction::GroupConcat { .. } => "(group_concat",
+        }
+        .to_string();
+        if aggregate.distinct {
+            sse.push_str(" distinct");
+        }
+        if let AggregateFunction::GroupConcat {
+            separator: Some(separator),
+        } = &aggregate.function
+        {
+            let separator = Term::Literal {
+                value: separator.clone(),
+                datatype: None,
+                language: None,
+            };
+            sse.push_str(&format!(" (separator {})", separator));
+        }
+        if let Some(expression) = &aggregate.expression {
+            sse.push(' ');
+            sse.push_str(&self.expression(expression, indent));
+        }
+        sse.push(')');
+        sse
+    }
+
+    fn order_condition(&self, condition: &OrderCondition, indent: usize) -> String {
+        let expression = self.expression(&condition.expression, indent);
+        match condition.descending {
+            true => format!("(desc {})", expression),
+            false => expression,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{algebra::Query, analysis::query_prefixes};
+
+    fn sse(query: &str) -> String {
+        Query::parse(query, None)
+            .unwrap()
+            .to_sse(&query_prefixes(query))
+    }
+
+    #[test]
+    fn optional_filter() {
+        assert_eq!(
+            sse(indoc! {"
+                PREFIX ex: <http://example.org/>
+                SELECT ?s ?name {
+                  ?s a ex:Person ; ex:age ?age .
+                  OPTIONAL { ?s ex:name ?name FILTER (lang(?name) = \"en\") }
+                  FILTER (?age >= 18)
+                }
+            "}),
+            indoc! {r#"
+                (prefix ((ex: <http://example.org/>))
+                  (project (?s ?name)
+                    (filter (>= ?age 18)
+                      (leftjoin
+                        (bgp
+                          (triple ?s <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> ex:Person)
+                          (triple ?s ex:age ?age))
+                        (bgp (triple ?s ex:name ?name))
+                        (= (lang ?name) "en")))))
+            "#}
+        );
+    }
+
+    #[test]
+    fn modifiers_and_aggregates() {
+        assert_eq!(
+            sse(indoc! {"
+                SELECT DISTINCT ?s (COUNT(DISTINCT ?o) AS ?n) {
+                  ?s <http://p>+/^<http://q> [] .
+                  MINUS { ?s <http://r> 1.5 }
+                }
+                GROUP BY ?s HAVING (COUNT(DISTINCT ?o) > 1)
+                ORDER BY DESC(?n)
+                LIMIT 10
+            "}),
+            indoc! {"
+                (slice _ 10
+                  (distinct
+                    (project (?s ?n)
+                      (order ((desc ?n))
+                        (extend ((?n ?.2))
+                          (filter (> ?.2 1)
+                            (group (?s) ((?.2 (count distinct ?o)))
+                              (minus
+                                (join
+                                  (bgp (triple ??0 <http://q> ??1))
+                                  (path ?s (path+ <http://p>) ??1))
+                                (bgp (triple ?s <http://r> 1.5))))))))))
+            "}
+        );
+    }
+
+    #[test]
+    fn values_and_exists() {
+        assert_eq!(
+            sse(indoc! {r#"
+                SELECT * {
+                  VALUES (?x ?y) { (1 "a"@en) (UNDEF true) }
+                  FILTER NOT EXISTS { ?x ?p ?y }
+                  SERVICE SILENT <http://example.org/sparql> { ?x ?p ?z }
+                }
+            "#}),
+            indoc! {r#"
+                (project (?x ?y ?p ?z)
+                  (filter (notexists
+                    (bgp (triple ?x ?p ?y)))
+                    (join
+                      (table (vars ?x ?y)
+                        (row [?x 1] [?y "a"@en])
+                        (row [?y true]))
+                      (service silent <http://example.org/sparql>
+                        (bgp (triple ?x ?p ?z))))))
+            "#}
+        );
+    }
+}
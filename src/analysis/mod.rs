@@ -5,9 +5,9 @@ use std::collections::HashSet;
 use log::{error, info};
 pub use state::*;
 
-use tree_sitter::{Node, Query, QueryCursor};
+use tree_sitter::{Node, Parser, Query, QueryCursor};
 
-use crate::lsp::textdocument::{Position, Range};
+use crate::lsp::textdocument::Range;
 
 fn collect_all_unique_captures(node: Node, query_str: &str, text: &String) -> Vec<String> {
     match Query::new(&tree_sitter_sparql::language(), query_str) {
@@ -33,7 +33,50 @@ fn collect_all_unique_captures(node: Node, query_str: &str, text: &String) -> Ve
     }
 }
 
-pub fn get_all_variables(analyis_state: &AnalysisState, uri: &String) -> Vec<String> {
+/// Returns `node` and all of its descendants with the given kind, in document order.
+pub(crate) fn find_all<'a>(node: Node<'a>, kind: &str) -> Vec<Node<'a>> {
+    let mut nodes = vec![];
+    if node.kind() == kind {
+        nodes.push(node);
+    }
+    for child in node.children(&mut node.walk()) {
+        nodes.append(&mut find_all(child, kind));
+    }
+    nodes
+}
+
+/// Like [`find_all`], but does not descend into descendants of the `boundaries` kinds.
+pub(crate) fn find_all_until<'a>(node: Node<'a>, kind: &str, boundaries: &[&str]) -> Vec<Node<'a>> {
+    let mut nodes = vec![];
+    if node.kind() == kind {
+        nodes.push(node);
+    }
+    for child in node.children(&mut node.walk()) {
+        if !boundaries.contains(&child.kind()) {
+            nodes.append(&mut find_all_until(child, kind, boundaries));
+        }
+    }
+    nodes
+}
+
+/// Returns the names of all variables in `node`, without the leading `?` or `$`.
+pub(crate) fn variables_in(node: Node, text: &str) -> HashSet<String> {
+    find_all(node, "VAR")
+        .into_iter()
+        .map(|var| variable_name(var, text))
+        .collect()
+}
+
+/// Returns the name of a `VAR` node, without the leading `?` or `$`.
+pub(crate) fn variable_name(var: Node, text: &str) -> String {
+    var.utf8_text(text.as_bytes())
+        .ok()
+        .and_then(|var| var.get(1..))
+        .unwrap_or("")
+        .to_string()
+}
+
+pub fn get_all_variables(analyis_state: &AnalysisState, uri: &str) -> Vec<String> {
     match analyis_state.get_state(uri) {
         Some((document, Some(tree))) => {
             collect_all_unique_captures(tree.root_node(), "(VAR) @variable", &document.text)
@@ -49,25 +92,7 @@ pub fn get_all_variables(analyis_state: &AnalysisState, uri: &String) -> Vec<Str
     }
 }
 
-pub fn get_kind_at_position(
-    analyis_state: &AnalysisState,
-    uri: &String,
-    position: &Position,
-) -> Option<&'static str> {
-    match analyis_state.get_tree(uri) {
-        Some(tree) => {
-            let point = position.to_point();
-            Some(
-                tree.root_node()
-                    .descendant_for_point_range(point, point)?
-                    .kind(),
-            )
-        }
-        None => None,
-    }
-}
-
-pub fn get_declared_namspaces(analyis_state: &AnalysisState, uri: &String) -> Vec<(String, Range)> {
+pub fn get_declared_namspaces(analyis_state: &AnalysisState, uri: &str) -> Vec<(String, Range)> {
     match analyis_state.get_state(uri) {
         Some((document, Some(tree))) => {
             match Query::new(
@@ -82,7 +107,7 @@ pub fn get_declared_namspaces(analyis_state: &AnalysisState, uri: &String) -> Ve
                             node.utf8_text(document.text.as_bytes())
                                 .unwrap()
                                 .to_string(),
-                            Range::from_node(node),
+                            document.range(node),
                         )
                     })
                     .collect(),
@@ -113,7 +138,73 @@ pub fn get_declared_namspaces(analyis_state: &AnalysisState, uri: &String) -> Ve
     }
 }
 
-pub fn get_used_namspaces(analyis_state: &AnalysisState, uri: &String) -> Vec<(String, Range)> {
+/// The prefix declarations of `node` as pairs of prefix and namespace IRI, in document order.
+/// The prefix has no trailing `:`, the IRI no angle brackets.
+pub(crate) fn prefix_declarations(node: Node, text: &str) -> Vec<(String, String)> {
+    find_all(node, "PrefixDecl")
+        .into_iter()
+        .filter_map(|prefix_declaration| {
+            let prefix = prefix_declaration
+                .child(1)?
+                .utf8_text(text.as_bytes())
+                .ok()?;
+            let iri = prefix_declaration
+                .child(2)?
+                .utf8_text(text.as_bytes())
+                .ok()?;
+            Some((
+                prefix.strip_suffix(':')?.to_string(),
+                iri.strip_prefix('<')?.strip_suffix('>')?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// The prefix declarations of the query `text`, see [`prefix_declarations`].
+pub(crate) fn query_prefixes(text: &str) -> Vec<(String, String)> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_sparql::language())
+        .expect("The SPARQL grammar is compatible");
+    match parser.parse(text, None) {
+        Some(tree) => prefix_declarations(tree.root_node(), text),
+        None => vec![],
+    }
+}
+
+/// Whether `text` is an update instead of a query.
+pub(crate) fn is_update(text: &str) -> bool {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_sparql::language())
+        .expect("The SPARQL grammar is compatible");
+    parser
+        .parse(text, None)
+        .is_some_and(|tree| !find_all(tree.root_node(), "Update").is_empty())
+}
+
+/// The prefixed name of `iri` with the longest matching namespace of the `prefixes`.
+pub(crate) fn abbreviate(iri: &str, prefixes: &[(String, String)]) -> Option<String> {
+    prefixes
+        .iter()
+        .filter_map(|(prefix, namespace)| {
+            let local_name = iri.strip_prefix(namespace.as_str())?;
+            is_local_name(local_name).then_some((namespace.len(), prefix, local_name))
+        })
+        .max_by_key(|(length, _prefix, _local_name)| *length)
+        .map(|(_length, prefix, local_name)| format!("{}:{}", prefix, local_name))
+}
+
+/// Whether `name` can be written as local part of a prefixed name without escapes.
+/// This is stricter than `PN_LOCAL`, e.g. `%` is never accepted.
+fn is_local_name(name: &str) -> bool {
+    name.chars()
+        .all(|char| char.is_alphanumeric() || matches!(char, '_' | '-' | '.'))
+        && !name.starts_with(['-', '.'])
+        && !name.ends_with('.')
+}
+
+pub fn get_used_namspaces(analyis_state: &AnalysisState, uri: &str) -> Vec<(String, Range)> {
     match analyis_state.get_state(uri) {
         Some((document, Some(tree))) => {
             match Query::new(
@@ -128,7 +219,7 @@ pub fn get_used_namspaces(analyis_state: &AnalysisState, uri: &String) -> Vec<(S
                             node.utf8_text(document.text.as_bytes())
                                 .unwrap()
                                 .to_string(),
-                            Range::from_node(node),
+                            document.range(node),
                         )
                     })
                     .collect(),
@@ -161,7 +252,7 @@ pub fn get_used_namspaces(analyis_state: &AnalysisState, uri: &String) -> Vec<(S
 
 pub(crate) fn get_unused_prefixes(
     analysis_state: &AnalysisState,
-    uri: &String,
+    uri: &str,
 ) -> impl Iterator<Item = (String, Range)> {
     let declared_namespaces = get_declared_namspaces(analysis_state, uri);
     let declared_namespaces_set: HashSet<String> = declared_namespaces
@@ -189,7 +280,7 @@ pub(crate) fn get_unused_prefixes(
 
 pub(crate) fn get_undeclared_prefixes(
     analysis_state: &AnalysisState,
-    uri: &String,
+    uri: &str,
 ) -> impl Iterator<Item = (String, Range)> {
     let declared_namespaces = get_declared_namspaces(analysis_state, uri);
     let declared_namespaces_set: HashSet<String> = declared_namespaces
@@ -221,12 +312,32 @@ mod tests {
 
     use crate::{
         analysis::{
-            get_declared_namspaces, get_undeclared_prefixes, get_unused_prefixes,
+            abbreviate, get_declared_namspaces, get_undeclared_prefixes, get_unused_prefixes,
             get_used_namspaces, AnalysisState,
         },
-        lsp::textdocument::TextDocumentItem,
+        lsp::{textdocument::TextDocumentItem, TextDocumentContentChangeEvent},
     };
 
+    #[test]
+    fn snapshots_keep_their_version() {
+        let mut state = AnalysisState::new();
+        state.add_document(TextDocumentItem::new("uri", "SELECT * {}"));
+        let snapshot = state.clone();
+        state.change_document(
+            "uri".to_string(),
+            1,
+            vec![TextDocumentContentChangeEvent {
+                text: "SELECT ?a {}".to_string(),
+            }],
+        );
+        let (document, tree) = snapshot.get_state("uri").unwrap();
+        assert_eq!(document.text, "SELECT * {}");
+        assert_eq!(tree.as_ref().unwrap().root_node().end_byte(), 11);
+        assert_eq!(snapshot.get_version("uri"), Some(0));
+        assert_eq!(state.get_state("uri").unwrap().0.text, "SELECT ?a {}");
+        assert_eq!(state.get_version("uri"), Some(1));
+    }
+
     #[test]
     fn declared_namespaces() {
         let mut state = AnalysisState::new();
@@ -240,7 +351,7 @@ mod tests {
                  SELECT * {}"
             ),
         ));
-        let declared_namesapces = get_declared_namspaces(&state, &"uri".to_string());
+        let declared_namesapces = get_declared_namspaces(&state, "uri");
         assert_eq!(
             declared_namesapces
                 .iter()
@@ -250,6 +361,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prefix_declarations() {
+        let text = indoc!(
+            "PREFIX wd: <http://www.wikidata.org/entity/>
+             PREFIX : <http://example.org/>
+             PREFIX broken <iri>
+             SELECT * {}"
+        );
+        let mut state = AnalysisState::new();
+        state.add_document(TextDocumentItem::new("uri", text));
+        let (_document, tree) = state.get_state("uri").unwrap();
+        assert_eq!(
+            super::prefix_declarations(tree.as_ref().unwrap().root_node(), text),
+            vec![
+                (
+                    "wd".to_string(),
+                    "http://www.wikidata.org/entity/".to_string()
+                ),
+                ("".to_string(), "http://example.org/".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn used_namespaces() {
         let mut state = AnalysisState::new();
@@ -257,7 +391,7 @@ mod tests {
             "uri",
             indoc!("SELECT * {?a wdt:P32 ?b. ?a wd:p32 ?b. ?a wdt:P31 ?b}"),
         ));
-        let declared_namesapces = get_used_namspaces(&state, &"uri".to_string());
+        let declared_namesapces = get_used_namspaces(&state, "uri");
         assert_eq!(
             declared_namesapces
                 .iter()
@@ -274,7 +408,7 @@ mod tests {
             "uri",
             indoc!("SELECT * {x:y y:p x:x}"),
         ));
-        let declared_namesapces: Vec<String> = get_undeclared_prefixes(&state, &"uri".to_string())
+        let declared_namesapces: Vec<String> = get_undeclared_prefixes(&state, "uri")
             .map(|(namespace, _range)| namespace)
             .collect();
         assert_eq!(declared_namesapces, vec!["x:", "y:", "x:"]);
@@ -291,9 +425,40 @@ mod tests {
                  SELECT * {}"
             ),
         ));
-        let declared_namesapces: Vec<String> = get_unused_prefixes(&state, &"uri".to_string())
+        let declared_namesapces: Vec<String> = get_unused_prefixes(&state, "uri")
             .map(|(namespace, _range)| namespace)
             .collect();
         assert_eq!(declared_namesapces, vec!["wdt:", "wdt:"]);
     }
+
+    #[test]
+    fn abbreviate_iris() {
+        let prefixes: Vec<(String, String)> = [
+            ("ex", "http://example.org/"),
+            ("exa", "http://example.org/a/"),
+            ("", "http://example.org/b#"),
+        ]
+        .iter()
+        .map(|(prefix, namespace)| (prefix.to_string(), namespace.to_string()))
+        .collect();
+        assert_eq!(
+            abbreviate("http://example.org/x", &prefixes),
+            Some("ex:x".to_string())
+        );
+        assert_eq!(
+            abbreviate("http://example.org/a/x", &prefixes),
+            Some("exa:x".to_string())
+        );
+        assert_eq!(
+            abbreviate("http://example.org/b#c", &prefixes),
+            Some(":c".to_string())
+        );
+        assert_eq!(
+            abbreviate("http://example.org/", &prefixes),
+            Some("ex:".to_string())
+        );
+        assert_eq!(abbreviate("http://example.org/a?b=c", &prefixes), None);
+        assert_eq!(abbreviate("http://example.org/x.", &prefixes), None);
+        assert_eq!(abbreviate("http://other.org/x", &prefixes), None);
+    }
 }
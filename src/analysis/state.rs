@@ -1,48 +1,56 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
-use log::{error, info};
+use log::error;
 
 use tree_sitter::{Parser, Tree};
 
-use crate::lsp::textdocument::TextDocumentItem;
+use crate::lsp::{textdocument::TextDocumentItem, PositionEncodingKind};
 
+/// The open documents and their syntax trees.
+/// The documents are reference counted, so a clone is a cheap snapshot that can be sent to other threads.
+#[derive(Clone)]
 pub struct AnalysisState {
-    documents: HashMap<String, (TextDocumentItem, Option<Tree>)>,
-    parser: Parser,
+    documents: HashMap<String, Arc<(TextDocumentItem, Option<Tree>)>>,
+    /// The position encoding negotiated with the client, used by every added document.
+    pub(crate) position_encoding: PositionEncodingKind,
 }
 
 impl AnalysisState {
     pub fn new() -> Self {
-        let mut parser = Parser::new();
-        match parser.set_language(&tree_sitter_sparql::language()) {
-            Ok(()) => {
-                info!("Successfully initialized Parser")
-            }
-            Err(err) => error!("Error while initializing parser: {}", err),
-        };
         Self {
             documents: HashMap::new(),
-            parser,
+            position_encoding: PositionEncodingKind::default(),
         }
     }
 
-    pub(crate) fn add_document(&mut self, text_document: TextDocumentItem) {
-        let tree = self.parser.parse(&text_document.text, None);
-
+    pub(crate) fn add_document(&mut self, mut text_document: TextDocumentItem) {
+        text_document.position_encoding = self.position_encoding;
+        let tree = parse(&text_document.text);
         let uri = text_document.uri.clone();
-        self.documents.insert(uri.clone(), (text_document, tree));
+        self.documents.insert(uri, Arc::new((text_document, tree)));
     }
 
+    /// Applies the changes of version `version`, older or repeated versions are ignored.
     pub(crate) fn change_document(
         &mut self,
         uri: String,
+        version: u32,
         content_changes: Vec<crate::lsp::TextDocumentContentChangeEvent>,
     ) {
         match self.documents.get_mut(&uri) {
-            Some((text_document, old_tree)) => {
-                text_document.apply_changes(content_changes);
-                let tree = self.parser.parse(&text_document.text, None);
-                *old_tree = tree;
+            Some(document) if version <= document.0.version() => {
+                error!(
+                    "Ignored stale version {} of document {}, current version is {}",
+                    version,
+                    uri,
+                    document.0.version()
+                );
+            }
+            Some(document) => {
+                // NOTE: Snapshots that still refer to the old version keep their own copy.
+                let (text_document, tree) = Arc::make_mut(document);
+                text_document.apply_changes(content_changes, version);
+                *tree = parse(&text_document.text);
             }
             None => {
                 error!("Recived changes for unknown document: {}", uri);
@@ -50,15 +58,33 @@ impl AnalysisState {
         }
     }
 
+    pub(crate) fn remove_document(&mut self, uri: &str) {
+        if self.documents.remove(uri).is_none() {
+            error!("Closed unknown document: {}", uri);
+        }
+    }
+
     // pub(crate) fn documents(&self) -> impl Iterator<Item = &String> {
     //     self.documents.keys()
     // }
 
-    pub(crate) fn get_state(&self, uri: &String) -> Option<&(TextDocumentItem, Option<Tree>)> {
-        self.documents.get(uri)
+    pub(crate) fn get_state(&self, uri: &str) -> Option<&(TextDocumentItem, Option<Tree>)> {
+        self.documents.get(uri).map(Arc::as_ref)
     }
 
-    pub(crate) fn get_tree(&self, uri: &str) -> Option<&Tree> {
-        self.documents.get(uri)?.1.as_ref()
+    /// The version of the document, `None` if no such document is open.
+    pub(crate) fn get_version(&self, uri: &str) -> Option<u32> {
+        self.documents.get(uri).map(|document| document.0.version())
+    }
+}
+
+fn parse(text: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    match parser.set_language(&tree_sitter_sparql::language()) {
+        Ok(()) => parser.parse(text, None),
+        Err(err) => {
+            error!("Error while initializing parser: {}", err);
+            None
+        }
     }
 }
@@ -0,0 +1,27 @@
+use camino::Utf8PathBuf;
+
+use crate::{algebra::Query, analysis::query_prefixes};
+
+use super::eval::read;
+
+/// Runs `fichu algebra` and returns the process exit code.
+/// `0`: the algebra was printed, `2`: the file could not be read or translated.
+pub fn run(path: Utf8PathBuf) -> i32 {
+    let (text, base) = match read(&path) {
+        Ok(read) => read,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+    };
+    match Query::parse(&text, Some(&base)) {
+        Ok(query) => {
+            print!("{}", query.to_sse(&query_prefixes(&text)));
+            0
+        }
+        Err(error) => {
+            eprintln!("{} can not be translated: {}", path, error);
+            2
+        }
+    }
+}
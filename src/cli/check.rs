@@ -0,0 +1,304 @@
+use std::fs;
+
+use crate::{
+    lsp::PositionEncodingKind, server::check_with_encoding, Diagnostic, DiagnosticSeverity,
+    LintSettings,
+};
+use camino::Utf8PathBuf;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable diagnostics with a source snippet
+    Human,
+    /// A JSON array of diagnostics
+    Json,
+    /// SARIF 2.1.0, e.g. for GitHub code scanning
+    Sarif,
+}
+
+/// The lowest severity that makes `fichu check` fail.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum FailLevel {
+    Error,
+    Warning,
+    Info,
+    Hint,
+    Never,
+}
+
+impl FailLevel {
+    fn is_reached_by(&self, severity: DiagnosticSeverity) -> bool {
+        let threshold = match self {
+            FailLevel::Error => DiagnosticSeverity::Error,
+            FailLevel::Warning => DiagnosticSeverity::Warning,
+            FailLevel::Info => DiagnosticSeverity::Information,
+            FailLevel::Hint => DiagnosticSeverity::Hint,
+            FailLevel::Never => return false,
+        };
+        // NOTE: Lower values are more severe.
+        severity as u8 <= threshold as u8
+    }
+}
+
+/// The diagnostics of a single checked file.
+pub struct FileReport {
+    path: Utf8PathBuf,
+    text: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Runs `fichu check` and returns the process exit code.
+/// `0`: no diagnostic reached the fail level, `1`: at least one did, `2`: a file could not be read.
+pub fn run(
+    paths: Vec<Utf8PathBuf>,
+    format: OutputFormat,
+    fail_on: FailLevel,
+    settings: &LintSettings,
+) -> i32 {
+    let mut exit_code = 0;
+    let mut reports = vec![];
+    for path in paths {
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                // NOTE: The columns are converted to characters from bytes.
+                let diagnostics = check_with_encoding(&text, settings, PositionEncodingKind::Utf8);
+                reports.push(FileReport {
+                    path,
+                    text,
+                    diagnostics,
+                });
+            }
+            Err(error) => {
+                eprintln!("Could not read {}: {}", path, error);
+                exit_code = 2;
+            }
+        }
+    }
+    let output = match format {
+        OutputFormat::Human => render_human(&reports),
+        OutputFormat::Json => render_json(&reports),
+        OutputFormat::Sarif => render_sarif(&reports),
+    };
+    print!("{}", output);
+    if exit_code == 0
+        && reports
+            .iter()
+            .flat_map(|report| &report.diagnostics)
+            .any(|diagnostic| fail_on.is_reached_by(diagnostic.severity))
+    {
+        exit_code = 1;
+    }
+    exit_code
+}
+
+fn severity_name(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::Error => "error",
+        DiagnosticSeverity::Warning => "warning",
+        DiagnosticSeverity::Information => "info",
+        DiagnosticSeverity::Hint => "hint",
+    }
+}
+
+/// Converts a byte column into a (1-based) character column.
+fn char_column(line: &str, byte_column: u32) -> usize {
+    let mut byte_column = (byte_column as usize).min(line.len());
+    while !line.is_char_boundary(byte_column) {
+        byte_column -= 1;
+    }
+    line[..byte_column].chars().count() + 1
+}
+
+fn render_human(reports: &[FileReport]) -> String {
+    let mut output = String::new();
+    let mut count = 0;
+    for report in reports {
+        let lines: Vec<&str> = report.text.lines().collect();
+        for diagnostic in &report.diagnostics {
+            count += 1;
+            let start = &diagnostic.range.start;
+            let end = &diagnostic.range.end;
+            let line = lines.get(start.line as usize).copied().unwrap_or("");
+            let column = char_column(line, start.character);
+            output += &format!(
+                "{}:{}:{}: {}[{}] {}\n",
+                report.path,
+                start.line + 1,
+                column,
+                severity_name(diagnostic.severity),
+                diagnostic.code.as_deref().unwrap_or(&diagnostic.source),
+                diagnostic.message
+            );
+            let end_column = match end.line == start.line {
+                true => char_column(line, end.character),
+                false => line.chars().count() + 1,
+            };
+            let line_number = (start.line + 1).to_string();
+            let gutter = " ".repeat(line_number.len());
+            output += &format!("{} |\n", gutter);
+            output += &format!("{} | {}\n", line_number, line);
+            output += &format!(
+                "{} | {}{}\n\n",
+                gutter,
+                " ".repeat(column - 1),
+                "^".repeat(end_column.saturating_sub(column).max(1))
+            );
+        }
+    }
+    if count > 0 {
+        output += &format!(
+            "Found {} problem{}\n",
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+    }
+    output
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    path: &'a str,
+    #[serde(flatten)]
+    diagnostic: &'a Diagnostic,
+    level: &'static str,
+}
+
+fn render_json(reports: &[FileReport]) -> String {
+    let diagnostics: Vec<JsonDiagnostic> = reports
+        .iter()
+        .flat_map(|report| {
+            report.diagnostics.iter().map(|diagnostic| JsonDiagnostic {
+                path: report.path.as_str(),
+                diagnostic,
+                level: severity_name(diagnostic.severity),
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&diagnostics).expect("Diagnostics should be serializable") + "\n"
+}
+
+fn render_sarif(reports: &[FileReport]) -> String {
+    let mut rules: Vec<&str> = vec![];
+    let mut results: Vec<serde_json::Value> = vec![];
+    for report in reports {
+        let lines: Vec<&str> = report.text.lines().collect();
+        let line = |number: u32| lines.get(number as usize).copied().unwrap_or("");
+        for diagnostic in &report.diagnostics {
+            let rule = diagnostic.code.as_deref().unwrap_or(&diagnostic.source);
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+            let start = &diagnostic.range.start;
+            let end = &diagnostic.range.end;
+            results.push(json!({
+                "ruleId": rule,
+                "level": match diagnostic.severity {
+                    DiagnosticSeverity::Error => "error",
+                    DiagnosticSeverity::Warning => "warning",
+                    DiagnosticSeverity::Information | DiagnosticSeverity::Hint => "note",
+                },
+                "message": { "text": diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": report.path.as_str() },
+                        "region": {
+                            "startLine": start.line + 1,
+                            "startColumn": char_column(line(start.line), start.character),
+                            "endLine": end.line + 1,
+                            "endColumn": char_column(line(end.line), end.character),
+                        }
+                    }
+                }]
+            }));
+        }
+    }
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules.iter().map(|rule| json!({ "id": rule })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }]
+    });
+    serde_json::to_string_pretty(&sarif).expect("SARIF should be serializable") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lsp::PositionEncodingKind, server::check_with_encoding, DiagnosticSeverity, LintSettings,
+    };
+    use indoc::indoc;
+
+    use super::{render_human, render_sarif, FailLevel, FileReport};
+
+    fn report(text: &str) -> FileReport {
+        FileReport {
+            path: "query.rq".into(),
+            text: text.to_string(),
+            diagnostics: check_with_encoding(
+                text,
+                &LintSettings::default(),
+                PositionEncodingKind::Utf8,
+            ),
+        }
+    }
+
+    #[test]
+    fn human() {
+        let output = render_human(&[report("PREFIX wd: <>\nSELECT * {}")]);
+        assert_eq!(
+            output,
+            indoc!(
+                "query.rq:1:8: warning[unused_prefix] 'wd:' is declared here, but was never used
+                   |
+                 1 | PREFIX wd: <>
+                   |        ^^^
+
+                 Found 1 problem
+                 "
+            )
+        );
+        assert_eq!(render_human(&[report("SELECT * {}")]), "");
+    }
+
+    #[test]
+    fn sarif() {
+        let output: serde_json::Value =
+            serde_json::from_str(&render_sarif(&[report("SELECT * {?a wd:Q5 ?b}")])).unwrap();
+        let result = &output["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "undeclared_prefix");
+        assert_eq!(result["level"], "warning");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            14
+        );
+        assert_eq!(
+            output["runs"][0]["tool"]["driver"]["rules"][0]["id"],
+            "undeclared_prefix"
+        );
+        let output: serde_json::Value =
+            serde_json::from_str(&render_sarif(&[report("SELECT * {?名前 🦀:p ?b}")])).unwrap();
+        let region = &output["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], 15);
+        assert_eq!(region["endColumn"], 17);
+    }
+
+    #[test]
+    fn fail_level() {
+        assert!(FailLevel::Warning.is_reached_by(DiagnosticSeverity::Error));
+        assert!(FailLevel::Warning.is_reached_by(DiagnosticSeverity::Warning));
+        assert!(!FailLevel::Error.is_reached_by(DiagnosticSeverity::Warning));
+        assert!(!FailLevel::Never.is_reached_by(DiagnosticSeverity::Error));
+    }
+}
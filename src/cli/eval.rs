@@ -0,0 +1,164 @@
+use std::fs;
+
+use crate::{
+    algebra::Query,
+    analysis::query_prefixes,
+    eval::{evaluate, QueryAnswer},
+    rdf::{file_iri, Dataset, RdfFormat, Term},
+};
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::results::ResultsOutput;
+
+/// Runs `fichu eval` and returns the process exit code.
+/// `0`: the results were printed, `1`: the query could not be evaluated,
+/// `2`: a file could not be read or has syntax errors.
+pub fn run(path: Utf8PathBuf, data: Vec<Utf8PathBuf>, format: ResultsOutput) -> i32 {
+    let mut dataset = Dataset::new();
+    for path in &data {
+        if let Err(error) = load(&mut dataset, path, None) {
+            eprintln!("{}", error);
+            return 2;
+        }
+    }
+    let (text, base) = match read(&path) {
+        Ok(read) => read,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+    };
+    let query = match Query::parse(&text, Some(&base)) {
+        Ok(query) => query,
+        Err(error) => {
+            eprintln!("{} can not be evaluated: {}", path, error);
+            return 2;
+        }
+    };
+    match evaluate(&query, &dataset) {
+        Ok(answer) => {
+            print!("{}", render(answer, &text, format));
+            0
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+/// Reads a file and returns its text with its `file:` IRI, the base of relative IRIs.
+pub(super) fn read(path: &Utf8Path) -> Result<(String, String), String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let absolute =
+        fs::canonicalize(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    Ok((text, file_iri(&absolute.to_string_lossy())))
+}
+
+/// Loads an RDF file into the default graph or the named graph `graph`.
+/// The format is guessed from the extension.
+pub(super) fn load(
+    dataset: &mut Dataset,
+    path: &Utf8Path,
+    graph: Option<Term>,
+) -> Result<(), String> {
+    let format = path
+        .extension()
+        .and_then(RdfFormat::from_extension)
+        .ok_or_else(|| format!("The RDF format of {} is unknown", path))?;
+    let (text, base) = read(path)?;
+    match graph {
+        Some(graph) => dataset.load_graph(graph, &text, format, &base),
+        None => dataset.load(&text, format, &base),
+    }
+    .map_err(|error| format!("{}: {}", path, error))
+}
+
+/// Renders results in the output format, triples are always written as N-Triples.
+fn render(answer: QueryAnswer, query: &str, format: ResultsOutput) -> String {
+    match answer {
+        QueryAnswer::Results(results) => match format {
+            ResultsOutput::Table => results.table(&query_prefixes(query)),
+            format => results.serialize(format.format()),
+        },
+        QueryAnswer::Graph(triples) => triples
+            .iter()
+            .map(|triple| format!("{}\n", triple))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use camino::Utf8PathBuf;
+    use indoc::indoc;
+
+    use crate::{
+        algebra::Query,
+        cli::tests::file,
+        eval::evaluate,
+        rdf::{Dataset, Term},
+    };
+
+    use super::{load, render, ResultsOutput};
+
+    #[test]
+    fn evaluate_files() {
+        let turtle = file(
+            "data.ttl",
+            "@prefix ex: <http://example.org/> .\nex:a ex:p <b> .",
+        );
+        let quads = file(
+            "data.nq",
+            "<http://example.org/c> <http://example.org/p> \"c\" <http://example.org/g> .",
+        );
+        let mut dataset = Dataset::new();
+        load(&mut dataset, &turtle, None).unwrap();
+        load(&mut dataset, &quads, None).unwrap();
+        let query = "PREFIX ex: <http://example.org/>\nSELECT ?s ?o { { ?s ex:p ?o } UNION { GRAPH ?g { ?s ex:p ?o } } }";
+        let answer = evaluate(&Query::parse(query, None).unwrap(), &dataset).unwrap();
+        let base = fs::canonicalize(turtle.parent().unwrap()).unwrap();
+        assert_eq!(
+            render(answer, query, ResultsOutput::Csv),
+            format!(
+                "s,o\r\nhttp://example.org/a,file://{}/b\r\nhttp://example.org/c,c\r\n",
+                base.display()
+            )
+        );
+        let query = "PREFIX ex: <http://example.org/>\nSELECT ?s ?o { GRAPH ex:g { ?s ex:p ?o } }";
+        let answer = evaluate(&Query::parse(query, None).unwrap(), &dataset).unwrap();
+        assert_eq!(
+            render(answer, query, ResultsOutput::Table),
+            indoc! {r#"
+                | ?s   | ?o  |
+                |------|-----|
+                | ex:c | "c" |
+            "#}
+        );
+        fs::remove_file(turtle).unwrap();
+        fs::remove_file(quads).unwrap();
+    }
+
+    #[test]
+    fn construct() {
+        let mut dataset = Dataset::new();
+        let named = file("graph.nt", "<http://a> <http://p> _:x .");
+        load(
+            &mut dataset,
+            &named,
+            Some(Term::Iri("http://g".to_string())),
+        )
+        .unwrap();
+        let query = "CONSTRUCT { ?s ?p ?o } WHERE { GRAPH <http://g> { ?s ?p ?o } }";
+        let answer = evaluate(&Query::parse(query, None).unwrap(), &dataset).unwrap();
+        assert_eq!(
+            render(answer, query, ResultsOutput::Json),
+            "<http://a> <http://p> _:b0 .\n"
+        );
+        assert!(load(&mut dataset, &Utf8PathBuf::from("data.unknown"), None).is_err());
+        fs::remove_file(named).unwrap();
+    }
+}
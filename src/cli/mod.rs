@@ -0,0 +1,319 @@
+mod algebra;
+mod check;
+mod eval;
+mod results;
+mod run;
+mod testsuite;
+
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{BufRead, BufReader, Seek},
+    path::PathBuf,
+    process::exit,
+    sync::mpsc::channel,
+};
+
+use crate::{format, results::ResultsFormat, EndpointSettings, FormatSettings, Server, Settings};
+use camino::Utf8PathBuf;
+use check::{FailLevel, OutputFormat};
+use log::LevelFilter;
+use log4rs::{
+    append::file::FileAppender,
+    config::{Appender, Root},
+    encode::pattern::PatternEncoder,
+    Config,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use results::ResultsOutput;
+
+use clap::{Parser, Subcommand};
+
+/// fichu: An SPARQL language server and formatter
+#[derive(Debug, Parser)]
+#[command(version, about, long_about= None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the language server
+    Server,
+    /// Run the formatter on a given file
+    Format { path: Utf8PathBuf },
+    /// Check the given files for syntax errors and lint violations
+    #[command(alias = "lint")]
+    Check {
+        #[arg(required = true)]
+        paths: Vec<Utf8PathBuf>,
+        /// The output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+        format: OutputFormat,
+        /// The lowest severity that causes a non-zero exit code
+        #[arg(long, value_enum, default_value_t = FailLevel::Error)]
+        fail_on: FailLevel,
+    },
+    /// Execute a query on a SPARQL endpoint and print the results
+    Run {
+        path: Utf8PathBuf,
+        /// The URL of the endpoint, defaults to the one in fichu.toml
+        #[arg(long)]
+        endpoint: Option<String>,
+        /// The output format
+        #[arg(long, value_enum, default_value_t = ResultsOutput::Table)]
+        format: ResultsOutput,
+    },
+    /// Evaluate a query on RDF files and print the results
+    Eval {
+        path: Utf8PathBuf,
+        /// Turtle, N-Triples, N-Quads or TriG files to load, the format is guessed from the extension
+        #[arg(long)]
+        data: Vec<Utf8PathBuf>,
+        /// The output format, triples are printed as N-Triples
+        #[arg(long, value_enum, default_value_t = ResultsOutput::Table)]
+        format: ResultsOutput,
+    },
+    /// Print the SPARQL algebra of a query as S-expressions
+    Algebra { path: Utf8PathBuf },
+    /// Work with query results
+    Results {
+        #[clap(subcommand)]
+        command: ResultsCommand,
+    },
+    /// Run the query evaluation tests of W3C test suite manifests
+    #[command(hide = true)]
+    Testsuite {
+        manifest: Utf8PathBuf,
+        /// A file with the tests that are expected to fail, one per line,
+        /// relative to the directory of the manifest
+        #[arg(long)]
+        known_failures: Option<Utf8PathBuf>,
+    },
+    /// Watch the logs
+    Logs,
+}
+
+#[derive(Debug, Subcommand)]
+enum ResultsCommand {
+    /// Convert query results between formats and print them
+    Convert {
+        path: Utf8PathBuf,
+        /// The format to convert to
+        #[arg(long, value_enum)]
+        to: ResultsOutput,
+        /// The format of the results: json, xml, csv or tsv, by default guessed from the extension
+        #[arg(long)]
+        from: Option<ResultsFormat>,
+        /// A query whose prefixes abbreviate the IRIs of tables
+        #[arg(long)]
+        query: Option<Utf8PathBuf>,
+    },
+}
+
+fn get_logfile_path() -> PathBuf {
+    let mut app_dir = dirs_next::data_dir().expect("Failed to find data directory");
+    app_dir.push("fichu");
+    if !app_dir.exists() {
+        std::fs::create_dir_all(&app_dir).expect("Failed to create app directory");
+    }
+    app_dir.join("fichu.log")
+}
+
+fn configure_logging() {
+    let logfile_path = get_logfile_path();
+    let logfile = FileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new("{l} - {m}{n}")))
+        .build(logfile_path)
+        .expect("Failed to create logfile");
+
+    let config = Config::builder()
+        .appender(Appender::builder().build("file", Box::new(logfile)))
+        .build(Root::builder().appender("file").build(LevelFilter::Info))
+        .unwrap();
+
+    log4rs::init_config(config).expect("Failed to configure logger");
+}
+
+/// Formats the file in place and returns the exit code.
+/// Files with syntax errors are left untouched, unless the settings allow to format them partially.
+fn format_file(path: &Utf8PathBuf, settings: &FormatSettings) -> i32 {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            return 2;
+        }
+    };
+    match format(&text, settings) {
+        Ok(formatted_text) => match fs::write(path, formatted_text) {
+            Ok(()) => {
+                println!("Sucessfully formatted {path}");
+                0
+            }
+            Err(error) => {
+                eprintln!("Could not write {}: {}", path, error);
+                2
+            }
+        },
+        Err(error) => {
+            eprintln!("{} was not formatted: {}", path, error);
+            1
+        }
+    }
+}
+
+/// Runs the `fichu` command line interface with the given arguments, starting with the binary name.
+pub fn run<T: Into<OsString> + Clone>(args: impl IntoIterator<Item = T>) {
+    configure_logging();
+
+    let cli = Cli::parse_from(args);
+    match cli.command {
+        Command::Server => {
+            // Start server and listen to stdio
+            let mut server = Server::new();
+            server.listen_stdio();
+        }
+        Command::Format { path } => {
+            let settings = Settings::load().expect("could not load Settings");
+            exit(format_file(&path, &settings.format));
+        }
+        Command::Check {
+            paths,
+            format,
+            fail_on,
+        } => {
+            let settings = Settings::load().expect("could not load Settings");
+            exit(check::run(paths, format, fail_on, &settings.lint));
+        }
+        Command::Run {
+            path,
+            endpoint,
+            format,
+        } => {
+            let settings = Settings::load().expect("could not load Settings");
+            let endpoint = match (endpoint, settings.endpoint) {
+                (Some(url), Some(settings)) => EndpointSettings { url, ..settings },
+                (Some(url), None) => EndpointSettings::new(&url),
+                (None, Some(settings)) => settings,
+                (None, None) => {
+                    eprintln!("No endpoint given, pass --endpoint or configure one in fichu.toml");
+                    exit(2);
+                }
+            };
+            exit(run::run(path, &endpoint, format));
+        }
+        Command::Eval { path, data, format } => exit(eval::run(path, data, format)),
+        Command::Algebra { path } => exit(algebra::run(path)),
+        Command::Results {
+            command:
+                ResultsCommand::Convert {
+                    path,
+                    to,
+                    from,
+                    query,
+                },
+        } => exit(results::convert(path, from, to, query)),
+        Command::Testsuite {
+            manifest,
+            known_failures,
+        } => exit(testsuite::run(manifest, known_failures)),
+        Command::Logs => {
+            let logfile_path = get_logfile_path();
+            // Open the file and seek to the end (to mimic `tail -f` behavior)
+            let mut file = File::open(&logfile_path).expect("Could not open file");
+            let mut pos = std::fs::metadata(&logfile_path)
+                .expect("could not read file metatdaa")
+                .len();
+
+            let (tx, rx) = channel();
+
+            // Create a file watcher
+            let mut watcher: RecommendedWatcher =
+                Watcher::new(tx, notify::Config::default()).expect("Could not create watcher");
+
+            // Start watching the file
+            watcher
+                .watch(logfile_path.as_ref(), RecursiveMode::NonRecursive)
+                .expect("Could not watch file");
+
+            for res in rx {
+                match res {
+                    Ok(_event) => {
+                        // ignore any event that didn't change the pos
+                        if file.metadata().unwrap().len() == pos {
+                            continue;
+                        }
+
+                        // read from pos to end of file
+                        file.seek(std::io::SeekFrom::Start(pos)).unwrap();
+
+                        // update post to end of file
+                        pos = file.metadata().unwrap().len();
+
+                        let reader = BufReader::new(&file);
+                        for line in reader.lines() {
+                            println!("{}", line.unwrap());
+                        }
+                    }
+                    Err(error) => println!("{error:?}"),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use camino::Utf8PathBuf;
+
+    use crate::{FormatSettings, SyntaxErrorPolicy};
+
+    use super::format_file;
+
+    /// Writes `text` to a temporary file, the `name` has to be unique among the tests.
+    pub(super) fn file(name: &str, text: &str) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fichu-{}-{}", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn format_in_place() {
+        let path = file(
+            "valid.rq",
+            "select * {?a ?b ?c}\n# a long comment at the end",
+        );
+        assert_eq!(format_file(&path, &FormatSettings::default()), 0);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "SELECT * {\n  ?a ?b ?c\n}\n# a long comment at the end"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn syntax_errors_leave_the_file_untouched() {
+        let path = file("invalid.rq", "prefix a: <a>\nselect * {?a ?b");
+        assert_eq!(format_file(&path, &FormatSettings::default()), 1);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "prefix a: <a>\nselect * {?a ?b"
+        );
+        let settings = FormatSettings::builder()
+            .on_syntax_error(SyntaxErrorPolicy::Partial)
+            .build();
+        assert_eq!(format_file(&path, &settings), 0);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "PREFIX a: <a>\nselect * {?a ?b"
+        );
+        fs::remove_file(&path).unwrap();
+        assert_eq!(format_file(&path, &settings), 2);
+    }
+}
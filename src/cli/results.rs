@@ -0,0 +1,156 @@
+use std::fs;
+
+use crate::{
+    analysis::query_prefixes,
+    results::{QueryResults, ResultsFormat},
+};
+use camino::Utf8PathBuf;
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ResultsOutput {
+    /// An aligned table of the solutions
+    Table,
+    /// SPARQL 1.1 Query Results JSON
+    Json,
+    /// SPARQL Query Results XML
+    Xml,
+    /// SPARQL 1.1 Query Results CSV
+    Csv,
+    /// SPARQL 1.1 Query Results TSV
+    Tsv,
+}
+
+impl ResultsOutput {
+    /// The format that is written, tables are rendered from JSON.
+    pub(super) fn format(&self) -> ResultsFormat {
+        match self {
+            ResultsOutput::Table | ResultsOutput::Json => ResultsFormat::Json,
+            ResultsOutput::Xml => ResultsFormat::Xml,
+            ResultsOutput::Csv => ResultsFormat::Csv,
+            ResultsOutput::Tsv => ResultsFormat::Tsv,
+        }
+    }
+}
+
+/// Runs `fichu results convert` and returns the process exit code.
+/// `0`: the results were converted, `1`: they are invalid,
+/// `2`: a file could not be read or the format is unknown.
+pub fn convert(
+    path: Utf8PathBuf,
+    from: Option<ResultsFormat>,
+    to: ResultsOutput,
+    query: Option<Utf8PathBuf>,
+) -> i32 {
+    match converted(&path, from, to, query.as_ref()) {
+        Ok(output) => {
+            print!("{}", output);
+            0
+        }
+        Err((exit_code, error)) => {
+            eprintln!("{}", error);
+            exit_code
+        }
+    }
+}
+
+/// The results in `path` rendered in the output format,
+/// or the exit code of [`convert`] with the error message.
+fn converted(
+    path: &Utf8PathBuf,
+    from: Option<ResultsFormat>,
+    to: ResultsOutput,
+    query: Option<&Utf8PathBuf>,
+) -> Result<String, (i32, String)> {
+    let from = from
+        .or_else(|| path.extension().and_then(ResultsFormat::from_extension))
+        .ok_or_else(|| {
+            (
+                2,
+                format!("The format of {} is unknown, pass it with --from", path),
+            )
+        })?;
+    let read = |path: &Utf8PathBuf| {
+        fs::read_to_string(path).map_err(|error| (2, format!("Could not read {}: {}", path, error)))
+    };
+    let text = read(path)?;
+    let prefixes = match query {
+        Some(query) => query_prefixes(&read(query)?),
+        None => vec![],
+    };
+    let results =
+        QueryResults::parse(&text, from).map_err(|error| (1, format!("{}: {}", path, error)))?;
+    Ok(render(&results, to, &prefixes))
+}
+
+/// The results in the output format, tables abbreviate IRIs with the `prefixes`.
+fn render(results: &QueryResults, output: ResultsOutput, prefixes: &[(String, String)]) -> String {
+    match output {
+        ResultsOutput::Table => results.table(prefixes),
+        output => results.serialize(output.format()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use camino::Utf8PathBuf;
+    use indoc::indoc;
+
+    use crate::{cli::tests::file, results::ResultsFormat};
+
+    use super::{converted, ResultsOutput};
+
+    #[test]
+    fn convert_files() {
+        let results = file(
+            "results.srj",
+            r#"{"head":{"vars":["s"]},"results":{"bindings":[{"s":{"type":"uri","value":"http://a"}}]}}"#,
+        );
+        let query = file("query.rq", "PREFIX ex: <http://> SELECT * {}");
+        assert_eq!(
+            converted(&results, None, ResultsOutput::Csv, None),
+            Ok("s\r\nhttp://a\r\n".to_string())
+        );
+        assert_eq!(
+            converted(&results, None, ResultsOutput::Table, Some(&query)),
+            Ok(indoc! {"
+                | ?s   |
+                |------|
+                | ex:a |
+            "}
+            .to_string())
+        );
+        // The format is given explicitly.
+        assert!(matches!(
+            converted(
+                &results,
+                Some(ResultsFormat::Xml),
+                ResultsOutput::Json,
+                None
+            ),
+            Err((1, _))
+        ));
+        let unknown = file("results.txt", "");
+        assert_eq!(
+            converted(&unknown, None, ResultsOutput::Json, None),
+            Err((
+                2,
+                format!("The format of {} is unknown, pass it with --from", unknown)
+            ))
+        );
+        assert!(matches!(
+            converted(
+                &results,
+                None,
+                ResultsOutput::Table,
+                Some(&Utf8PathBuf::from("/does/not/exist.rq"))
+            ),
+            Err((2, _))
+        ));
+        for path in [results, query, unknown] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::{fs, time::Duration};
+
+use crate::{
+    analysis::{is_update, query_prefixes},
+    endpoint::execute_query,
+    results::{QueryResults, ResultsFormat},
+    EndpointSettings,
+};
+use camino::Utf8PathBuf;
+
+use super::results::ResultsOutput;
+
+/// Runs `fichu run` and returns the process exit code.
+/// `0`: the results were printed, `1`: the endpoint failed,
+/// `2`: the query could not be read or is an update.
+pub fn run(path: Utf8PathBuf, endpoint: &EndpointSettings, format: ResultsOutput) -> i32 {
+    let query = match fs::read_to_string(&path) {
+        Ok(query) => query,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            return 2;
+        }
+    };
+    if is_update(&query) {
+        eprintln!("{} is an update, only queries can be executed", path);
+        return 2;
+    }
+    match execute(&query, endpoint, format) {
+        Ok((output, duration)) => {
+            print!("{}", output);
+            eprintln!(
+                "Executed on {} in {} ms",
+                endpoint.url,
+                duration.as_millis()
+            );
+            0
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+/// Executes `query` and renders the results, returns them with the time the endpoint took.
+fn execute(
+    query: &str,
+    endpoint: &EndpointSettings,
+    format: ResultsOutput,
+) -> Result<(String, Duration), String> {
+    let response = execute_query(
+        &endpoint.url,
+        query,
+        format.format(),
+        Duration::from_secs(endpoint.timeout),
+    )
+    .map_err(|error| error.to_string())?;
+    let output = match format {
+        ResultsOutput::Table => QueryResults::parse(&response.body, ResultsFormat::Json)
+            .map_err(|error| error.to_string())?
+            .table(&query_prefixes(query)),
+        _ => response.body,
+    };
+    Ok((output, response.duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{endpoint::mock::serve_once, EndpointSettings};
+
+    use super::{execute, ResultsOutput};
+
+    #[test]
+    fn table() {
+        let (url, handle) = serve_once(
+            200,
+            "application/sparql-results+json",
+            r#"{"head":{"vars":["s","n"]},"results":{"bindings":[{"s":{"type":"uri","value":"http://a"},"n":{"type":"literal","value":"42","datatype":"http://www.w3.org/2001/XMLSchema#integer"}},{"s":{"type":"uri","value":"http://example.org/b"}}]}}"#,
+        );
+        let (output, _duration) = execute(
+            "SELECT * { ?s ?p ?n }",
+            &EndpointSettings::new(&url),
+            ResultsOutput::Table,
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            indoc! {r#"
+                | ?s                     | ?n                                               |
+                |------------------------|--------------------------------------------------|
+                | <http://a>             | "42"^^<http://www.w3.org/2001/XMLSchema#integer> |
+                | <http://example.org/b> |                                                  |
+            "#}
+        );
+        assert_eq!(
+            handle.join().unwrap().accept,
+            "application/sparql-results+json"
+        );
+    }
+
+    #[test]
+    fn raw_results() {
+        let (url, handle) = serve_once(200, "text/tab-separated-values", "?s\n<http://a>\n");
+        let (output, _duration) = execute(
+            "SELECT ?s {}",
+            &EndpointSettings::new(&url),
+            ResultsOutput::Tsv,
+        )
+        .unwrap();
+        assert_eq!(output, "?s\n<http://a>\n");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn endpoint_errors() {
+        let (url, handle) = serve_once(500, "text/plain", "Timeout");
+        let error =
+            execute("SELECT", &EndpointSettings::new(&url), ResultsOutput::Table).unwrap_err();
+        assert_eq!(error, "The endpoint answered with status 500: Timeout");
+        handle.join().unwrap();
+    }
+}
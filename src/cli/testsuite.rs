@@ -0,0 +1,585 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use crate::{
+    algebra::{GraphPattern, Query},
+    eval::{canonical_literal, evaluate, QueryAnswer},
+    rdf::{file_path, Dataset, Graph, Term, Triple, RDF},
+    results::{QueryResults, ResultsFormat},
+};
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::eval::{load, read};
+
+// https://w3c.github.io/rdf-tests/sparql/sparql11/
+const MF: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#";
+const QT: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-query#";
+const RS: &str = "http://www.w3.org/2001/sw/DataAccess/tests/result-set#";
+const DAWGT: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-dawg#";
+
+/// The outcome of the tests of a manifest and the manifests it includes.
+#[derive(Debug, Default)]
+struct Report {
+    /// The IRIs of the passed tests.
+    passed: Vec<String>,
+    failed: Vec<Failure>,
+}
+
+#[derive(Debug)]
+struct Failure {
+    /// The IRI of the test.
+    test: String,
+    name: String,
+    reason: String,
+}
+
+/// Runs `fichu testsuite` and returns the process exit code.
+/// `0`: all tests passed or are known failures, `1`: some tests failed or passed unexpectedly,
+/// `2`: a manifest or the known failures could not be read.
+pub fn run(path: Utf8PathBuf, known_failures: Option<Utf8PathBuf>) -> i32 {
+    let known_failures = match known_failures.as_deref().map(read_known_failures) {
+        Some(Ok(known_failures)) => known_failures,
+        Some(Err(error)) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+        None => HashSet::new(),
+    };
+    let mut report = Report::default();
+    let result = run_manifest(&path, &mut report).and_then(|()| read(&path));
+    let base = match result {
+        Ok((_text, base)) => base,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+    };
+    // NOTE: Tests are listed relative to the directory of the manifest,
+    // so that the list works in every checkout of the test suite.
+    let directory = &base[..=base.rfind('/').unwrap_or(0)];
+    let relative = |test: &str| test.strip_prefix(directory).unwrap_or(test).to_string();
+    let mut unexpected = 0;
+    for failure in &report.failed {
+        let test = relative(&failure.test);
+        match known_failures.contains(&test) {
+            true => println!("KNOWN {} ({}): {}", test, failure.name, failure.reason),
+            false => {
+                unexpected += 1;
+                println!("FAIL {} ({}): {}", test, failure.name, failure.reason);
+            }
+        }
+    }
+    for test in report.passed.iter().map(|test| relative(test)) {
+        if known_failures.contains(&test) {
+            unexpected += 1;
+            println!("PASS {}: remove it from the known failures", test);
+        }
+    }
+    println!(
+        "passed {} of {}",
+        report.passed.len(),
+        report.passed.len() + report.failed.len()
+    );
+    match unexpected {
+        0 => 0,
+        _ => 1,
+    }
+}
+
+/// The tests that are expected to fail, one per line. Empty lines and `#` comments are skipped.
+fn read_known_failures(path: &Utf8Path) -> Result<HashSet<String>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn iri(iri: &str) -> Term {
+    Term::Iri(iri.to_string())
+}
+
+fn object<'a>(graph: &'a Graph, subject: &Term, predicate: &str) -> Option<&'a Term> {
+    graph
+        .matching(Some(subject), Some(&iri(predicate)), None)
+        .next()
+        .map(|triple| &triple.object)
+}
+
+fn objects<'a>(graph: &'a Graph, subject: &Term, predicate: &str) -> Vec<&'a Term> {
+    graph
+        .matching(Some(subject), Some(&iri(predicate)), None)
+        .map(|triple| &triple.object)
+        .collect()
+}
+
+/// The members of an RDF collection.
+/// A cyclic collection ends before the first node that repeats.
+fn list<'a>(graph: &'a Graph, mut head: &'a Term) -> Vec<&'a Term> {
+    let mut members = vec![];
+    let mut visited = HashSet::new();
+    while let Some(member) = object(graph, head, &format!("{}first", RDF)) {
+        if !visited.insert(head) {
+            break;
+        }
+        members.push(member);
+        match object(graph, head, &format!("{}rest", RDF)) {
+            Some(rest) => head = rest,
+            None => break,
+        }
+    }
+    members
+}
+
+/// The path of a `file:` IRI in a manifest.
+fn path_of(term: &Term) -> Result<Utf8PathBuf, String> {
+    match term {
+        Term::Iri(iri) => file_path(iri)
+            .map(Utf8PathBuf::from)
+            .ok_or_else(|| format!("{} is not a local file", iri)),
+        term => Err(format!("{} is not a file", term)),
+    }
+}
+
+fn run_manifest(path: &Utf8Path, report: &mut Report) -> Result<(), String> {
+    let mut manifest = Dataset::new();
+    load(&mut manifest, path, None)?;
+    let graph = manifest.default_graph();
+    let manifests: Vec<&Term> = graph
+        .matching(
+            None,
+            Some(&iri(&format!("{}type", RDF))),
+            Some(&iri(&format!("{}Manifest", MF))),
+        )
+        .map(|triple| &triple.subject)
+        .collect();
+    for manifest in manifests {
+        for include in objects(graph, manifest, &format!("{}include", MF)) {
+            for included in list(graph, include) {
+                run_manifest(&path_of(included)?, report)?;
+            }
+        }
+        for entries in objects(graph, manifest, &format!("{}entries", MF)) {
+            for test in list(graph, entries) {
+                let kind = object(graph, test, &format!("{}type", RDF));
+                let is_supported = [
+                    format!("{}QueryEvaluationTest", MF),
+                    format!("{}CSVResultFormatTest", MF),
+                ]
+                .iter()
+                .any(|supported| kind == Some(&iri(supported)));
+                let is_withdrawn = object(graph, test, &format!("{}approval", DAWGT))
+                    == Some(&iri(&format!("{}Withdrawn", DAWGT)));
+                if !is_supported || is_withdrawn {
+                    continue;
+                }
+                let id = match test {
+                    Term::Iri(iri) => iri.clone(),
+                    test => test.to_string(),
+                };
+                let name = match object(graph, test, &format!("{}name", MF)) {
+                    Some(Term::Literal { value, .. }) => value.clone(),
+                    _ => String::new(),
+                };
+                match run_test(graph, test) {
+                    Ok(()) => report.passed.push(id),
+                    Err(reason) => report.failed.push(Failure {
+                        test: id,
+                        name,
+                        reason,
+                    }),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_test(manifest: &Graph, test: &Term) -> Result<(), String> {
+    let action =
+        object(manifest, test, &format!("{}action", MF)).ok_or("The test has no action")?;
+    let query_file =
+        object(manifest, action, &format!("{}query", QT)).ok_or("The test has no query")?;
+    let mut dataset = Dataset::new();
+    for data in objects(manifest, action, &format!("{}data", QT)) {
+        load(&mut dataset, &path_of(data)?, None)?;
+    }
+    for data in objects(manifest, action, &format!("{}graphData", QT)) {
+        load(&mut dataset, &path_of(data)?, Some(data.clone()))?;
+    }
+    let (text, base) = read(&path_of(query_file)?)?;
+    let query = Query::parse(&text, Some(&base)).map_err(|error| error.to_string())?;
+    // NOTE: The graphs of `FROM` clauses are files next to the query.
+    if let Some(clauses) = &query.dataset {
+        for name in clauses.default.iter().chain(&clauses.named) {
+            let name = iri(name);
+            if dataset.graph(&name).is_none() {
+                load(&mut dataset, &path_of(&name)?, Some(name.clone()))?;
+            }
+        }
+    }
+    let answer = evaluate(&query, &dataset).map_err(|error| error.to_string())?;
+    let result =
+        path_of(object(manifest, test, &format!("{}result", MF)).ok_or("The test has no result")?)?;
+    let expected = expected_answer(&result)?;
+    let answer = match (answer, &expected) {
+        // NOTE: CSV loses datatypes, the results are compared after a round trip.
+        (QueryAnswer::Results(results), _) if result.extension() == Some("csv") => {
+            QueryAnswer::Results(
+                QueryResults::parse(&results.serialize(ResultsFormat::Csv), ResultsFormat::Csv)
+                    .map_err(|error| error.to_string())?,
+            )
+        }
+        (answer, _) => answer,
+    };
+    match compare(&answer, &expected, is_ordered(&query.pattern)) {
+        true => Ok(()),
+        false => Err(format!(
+            "expected {}, got {}",
+            describe(&expected),
+            describe(&answer)
+        )),
+    }
+}
+
+/// Reads the expected results or graph of a test.
+fn expected_answer(path: &Utf8Path) -> Result<QueryAnswer, String> {
+    if let Some(format) = match path.extension() {
+        Some("srx") => Some(ResultsFormat::Xml),
+        Some("srj") => Some(ResultsFormat::Json),
+        Some(extension) => ResultsFormat::from_extension(extension),
+        None => None,
+    } {
+        let (text, _base) = read(path)?;
+        return QueryResults::parse(&text, format)
+            .map(QueryAnswer::Results)
+            .map_err(|error| format!("{}: {}", path, error));
+    }
+    let mut dataset = Dataset::new();
+    load(&mut dataset, path, None)?;
+    let graph = dataset.default_graph();
+    let result_sets: Vec<&Term> = graph
+        .matching(
+            None,
+            Some(&iri(&format!("{}type", RDF))),
+            Some(&iri(&format!("{}ResultSet", RS))),
+        )
+        .map(|triple| &triple.subject)
+        .collect();
+    Ok(match result_sets.first() {
+        Some(result_set) => QueryAnswer::Results(result_set_results(graph, result_set)),
+        None => QueryAnswer::Graph(graph.iter().cloned().collect()),
+    })
+}
+
+/// Decodes results written as RDF with the result set vocabulary.
+fn result_set_results(graph: &Graph, result_set: &Term) -> QueryResults {
+    if let Some(Term::Literal { value, .. }) = object(graph, result_set, &format!("{}boolean", RS))
+    {
+        return QueryResults::Boolean(value == "true");
+    }
+    let variables: Vec<String> = objects(graph, result_set, &format!("{}resultVariable", RS))
+        .into_iter()
+        .filter_map(|variable| match variable {
+            Term::Literal { value, .. } => Some(value.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut solutions: Vec<(Option<String>, Vec<Option<Term>>)> = vec![];
+    for solution in objects(graph, result_set, &format!("{}solution", RS)) {
+        let mut values = vec![None; variables.len()];
+        for binding in objects(graph, solution, &format!("{}binding", RS)) {
+            let variable = object(graph, binding, &format!("{}variable", RS));
+            let index = variables.iter().position(
+                |name| matches!(variable, Some(Term::Literal { value, .. }) if value == name),
+            );
+            if let Some(index) = index {
+                values[index] = object(graph, binding, &format!("{}value", RS)).cloned();
+            }
+        }
+        let index = match object(graph, solution, &format!("{}index", RS)) {
+            Some(Term::Literal { value, .. }) => Some(format!("{:0>20}", value)),
+            _ => None,
+        };
+        solutions.push((index, values));
+    }
+    solutions.sort_by(|left, right| left.0.cmp(&right.0));
+    QueryResults::Solutions {
+        variables,
+        solutions: solutions
+            .into_iter()
+            .map(|(_index, values)| values)
+            .collect(),
+    }
+}
+
+/// Whether the order of the solutions is defined by an `ORDER BY`.
+fn is_ordered(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::OrderBy(..) => true,
+        GraphPattern::Project(pattern, _)
+        | GraphPattern::Distinct(pattern)
+        | GraphPattern::Reduced(pattern)
+        | GraphPattern::Slice { pattern, .. }
+        | GraphPattern::Extend(pattern, ..) => is_ordered(pattern),
+        _ => false,
+    }
+}
+
+fn describe(answer: &QueryAnswer) -> String {
+    match answer {
+        QueryAnswer::Results(QueryResults::Boolean(value)) => value.to_string(),
+        QueryAnswer::Results(QueryResults::Solutions { solutions, .. }) => {
+            format!("{} solutions", solutions.len())
+        }
+        QueryAnswer::Graph(triples) => format!("{} triples", triples.len()),
+    }
+}
+
+type Row = Vec<Option<Term>>;
+
+/// Compares answers, blank nodes may have other labels and literals other lexical forms.
+fn compare(actual: &QueryAnswer, expected: &QueryAnswer, ordered: bool) -> bool {
+    match (actual, expected) {
+        (
+            QueryAnswer::Results(QueryResults::Boolean(actual)),
+            QueryAnswer::Results(QueryResults::Boolean(expected)),
+        ) => actual == expected,
+        (
+            QueryAnswer::Results(QueryResults::Solutions {
+                variables,
+                solutions,
+            }),
+            QueryAnswer::Results(QueryResults::Solutions {
+                variables: expected_variables,
+                solutions: expected_solutions,
+            }),
+        ) => {
+            if variables.len() != expected_variables.len() {
+                return false;
+            }
+            let Some(indices) = expected_variables
+                .iter()
+                .map(|variable| variables.iter().position(|other| other == variable))
+                .collect::<Option<Vec<usize>>>()
+            else {
+                return false;
+            };
+            let rows: Vec<Row> = solutions
+                .iter()
+                .map(|solution| {
+                    indices
+                        .iter()
+                        .map(|index| solution[*index].clone())
+                        .collect()
+                })
+                .collect();
+            isomorphic(&rows, expected_solutions, ordered)
+        }
+        (QueryAnswer::Graph(actual), QueryAnswer::Graph(expected)) => {
+            let rows = |triples: &[Triple]| -> Vec<Row> {
+                triples
+                    .iter()
+                    .map(|triple| {
+                        vec![
+                            Some(triple.subject.clone()),
+                            Some(triple.predicate.clone()),
+                            Some(triple.object.clone()),
+                        ]
+                    })
+                    .collect()
+            };
+            isomorphic(&rows(actual), &rows(expected), false)
+        }
+        _ => false,
+    }
+}
+
+fn isomorphic(actual: &[Row], expected: &[Row], ordered: bool) -> bool {
+    let mut used = vec![false; expected.len()];
+    actual.len() == expected.len() && search(actual, expected, ordered, &mut used, &HashMap::new())
+}
+
+/// Matches the rows in order, with a consistent mapping of blank nodes.
+fn search(
+    actual: &[Row],
+    expected: &[Row],
+    ordered: bool,
+    used: &mut [bool],
+    blank_nodes: &HashMap<String, String>,
+) -> bool {
+    let Some((row, rest)) = actual.split_first() else {
+        return true;
+    };
+    let index = expected.len() - actual.len();
+    let candidates: Vec<usize> = match ordered {
+        true => vec![index],
+        false => (0..expected.len()).filter(|index| !used[*index]).collect(),
+    };
+    for candidate in candidates {
+        if let Some(extended) = match_row(row, &expected[candidate], blank_nodes) {
+            used[candidate] = true;
+            if search(rest, expected, ordered, used, &extended) {
+                return true;
+            }
+            used[candidate] = false;
+        }
+    }
+    false
+}
+
+fn match_row(
+    actual: &Row,
+    expected: &Row,
+    blank_nodes: &HashMap<String, String>,
+) -> Option<HashMap<String, String>> {
+    let mut blank_nodes = blank_nodes.clone();
+    for (actual, expected) in actual.iter().zip(expected) {
+        match (actual, expected) {
+            (None, None) => {}
+            (Some(Term::BlankNode(actual)), Some(Term::BlankNode(expected))) => {
+                match blank_nodes.get(actual) {
+                    Some(mapped) if mapped == expected => {}
+                    Some(_mapped) => return None,
+                    None if blank_nodes.values().any(|mapped| mapped == expected) => return None,
+                    None => {
+                        blank_nodes.insert(actual.clone(), expected.clone());
+                    }
+                }
+            }
+            (Some(actual), Some(expected))
+                if canonical_literal(actual) == canonical_literal(expected) => {}
+            _ => return None,
+        }
+    }
+    Some(blank_nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use camino::Utf8PathBuf;
+    use indoc::indoc;
+
+    use crate::rdf::{Dataset, RdfFormat, Term};
+
+    use super::{list, run, run_manifest, Report};
+
+    #[test]
+    fn manifests() {
+        let directory = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fichu-testsuite-{}", std::process::id()));
+        fs::create_dir_all(directory.join("sub")).unwrap();
+        let files = [
+            (
+                "manifest.ttl",
+                indoc! {r#"
+                    @prefix mf: <http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#> .
+                    @prefix qt: <http://www.w3.org/2001/sw/DataAccess/tests/test-query#> .
+                    <> a mf:Manifest ;
+                      mf:include ( <sub/manifest.ttl> ) ;
+                      mf:entries ( <#select> <#wrong> <#syntax> ) .
+                    <#select> a mf:QueryEvaluationTest ; mf:name "select" ;
+                      mf:action [ qt:query <select.rq> ; qt:data <data.ttl> ] ;
+                      mf:result <select.srj> .
+                    <#wrong> a mf:QueryEvaluationTest ; mf:name "wrong" ;
+                      mf:action [ qt:query <ask.rq> ; qt:data <data.ttl> ] ;
+                      mf:result <select.srj> .
+                    <#syntax> a mf:PositiveSyntaxTest11 ; mf:action <ask.rq> .
+                "#},
+            ),
+            (
+                "sub/manifest.ttl",
+                indoc! {r#"
+                    @prefix mf: <http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#> .
+                    @prefix qt: <http://www.w3.org/2001/sw/DataAccess/tests/test-query#> .
+                    <> a mf:Manifest ; mf:entries ( <#construct> <#csv> ) .
+                    <#construct> a mf:QueryEvaluationTest ; mf:name "construct" ;
+                      mf:action [ qt:query <construct.rq> ; qt:data <../data.ttl> ] ;
+                      mf:result <construct.ttl> .
+                    <#csv> a mf:CSVResultFormatTest ; mf:name "csv" ;
+                      mf:action [ qt:query <../select.rq> ; qt:data <../data.ttl> ] ;
+                      mf:result <select.csv> .
+                "#},
+            ),
+            (
+                "data.ttl",
+                "<http://a> <http://p> 1, 2.0 .\n<http://b> <http://p> _:x .",
+            ),
+            (
+                "select.rq",
+                "SELECT ?o { ?s <http://p> ?o FILTER(isLiteral(?o)) } ORDER BY ?o",
+            ),
+            ("ask.rq", "ASK { ?s ?p ?o }"),
+            (
+                "select.srj",
+                r#"{"head":{"vars":["o"]},"results":{"bindings":[
+                    {"o":{"type":"literal","value":"1","datatype":"http://www.w3.org/2001/XMLSchema#integer"}},
+                    {"o":{"type":"literal","value":"2.00","datatype":"http://www.w3.org/2001/XMLSchema#decimal"}}
+                ]}}"#,
+            ),
+            (
+                "sub/construct.rq",
+                "CONSTRUCT { ?o <http://q> [] } WHERE { ?s <http://p> ?o FILTER(isBlank(?o)) }",
+            ),
+            ("sub/construct.ttl", "_:a <http://q> _:b ."),
+            ("sub/select.csv", "o\r\n1\r\n2.0\r\n"),
+        ];
+        for (name, text) in files {
+            fs::write(directory.join(name), text).unwrap();
+        }
+        let manifest = directory.join("manifest.ttl");
+        let mut report = Report::default();
+        run_manifest(&manifest, &mut report).unwrap();
+        assert_eq!(report.passed.len(), 3);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "wrong");
+        assert_eq!(report.failed[0].reason, "expected 2 solutions, got true");
+
+        let known_failures = directory.join("known-failures.txt");
+        assert_eq!(run(manifest.clone(), Some(known_failures.clone())), 2);
+        fs::write(&known_failures, "# Known failures\nmanifest.ttl#wrong\n").unwrap();
+        assert_eq!(run(manifest.clone(), Some(known_failures.clone())), 0);
+        assert_eq!(run(manifest.clone(), None), 1);
+        // Tests that pass are removed from the list.
+        fs::write(
+            &known_failures,
+            "manifest.ttl#wrong\nsub/manifest.ttl#csv\n",
+        )
+        .unwrap();
+        assert_eq!(run(manifest, Some(known_failures)), 1);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn cyclic_list() {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(
+                indoc! {r#"
+                    @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+                    <http://a> rdf:first "a" ; rdf:rest <http://b> .
+                    <http://b> rdf:first "b" ; rdf:rest <http://a> .
+                "#},
+                RdfFormat::Turtle,
+                "",
+            )
+            .unwrap();
+        let head = Term::Iri("http://a".to_string());
+        let members = list(dataset.default_graph(), &head);
+        assert_eq!(
+            members
+                .iter()
+                .map(|member| member.to_string())
+                .collect::<Vec<_>>(),
+            vec![r#""a""#, r#""b""#]
+        );
+    }
+}
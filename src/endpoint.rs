@@ -0,0 +1,209 @@
+//! Sends queries to SPARQL endpoints over the SPARQL 1.1 Protocol.
+
+use std::{fmt, time::Duration};
+
+use crate::results::ResultsFormat;
+
+/// The answer of an endpoint to a query.
+#[derive(Debug)]
+pub struct QueryResponse {
+    /// The media type of the body, without parameters.
+    pub content_type: String,
+    pub body: String,
+    /// How long it took until the whole body was received.
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+pub enum EndpointError {
+    /// The endpoint answered with an error status, e.g. because the query is invalid.
+    Status { status: u16, body: String },
+    /// The endpoint could not be reached or the connection broke.
+    Transport(String),
+    /// Queries can not be sent from this build.
+    #[cfg(target_family = "wasm")]
+    Unsupported,
+}
+
+impl fmt::Display for EndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointError::Status { status, body } => {
+                write!(f, "The endpoint answered with status {}", status)?;
+                match body.trim() {
+                    "" => Ok(()),
+                    body => write!(f, ": {}", body),
+                }
+            }
+            EndpointError::Transport(error) => write!(f, "Could not reach the endpoint: {}", error),
+            #[cfg(target_family = "wasm")]
+            EndpointError::Unsupported => write!(f, "Queries can not be executed in this build"),
+        }
+    }
+}
+
+impl std::error::Error for EndpointError {}
+
+/// Posts `query` to `endpoint` and requests the results in `format`.
+#[cfg(not(target_family = "wasm"))]
+pub fn execute_query(
+    endpoint: &str,
+    query: &str,
+    format: ResultsFormat,
+    timeout: Duration,
+) -> Result<QueryResponse, EndpointError> {
+    use std::{io::Read, time::Instant};
+
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let start = Instant::now();
+    let response = agent
+        .post(endpoint)
+        .set("Content-Type", "application/sparql-query")
+        .set("Accept", format.media_type())
+        .send_string(query)
+        .map_err(|error| match error {
+            ureq::Error::Status(status, response) => EndpointError::Status {
+                status,
+                body: response.into_string().unwrap_or_default(),
+            },
+            error => EndpointError::Transport(error.to_string()),
+        })?;
+    let content_type = response.content_type().to_string();
+    // NOTE: `into_string` stops at 10 MB, results can be larger.
+    let mut body = String::new();
+    response
+        .into_reader()
+        .read_to_string(&mut body)
+        .map_err(|error| EndpointError::Transport(error.to_string()))?;
+    Ok(QueryResponse {
+        content_type,
+        body,
+        duration: start.elapsed(),
+    })
+}
+
+/// There are no blocking requests in the browser.
+#[cfg(target_family = "wasm")]
+pub fn execute_query(
+    _endpoint: &str,
+    _query: &str,
+    _format: ResultsFormat,
+    _timeout: Duration,
+) -> Result<QueryResponse, EndpointError> {
+    Err(EndpointError::Unsupported)
+}
+
+/// A local endpoint for tests.
+#[cfg(all(test, not(target_family = "wasm")))]
+pub(crate) mod mock {
+    use std::{thread, time::Duration};
+
+    use tiny_http::{Header, Response, Server};
+
+    /// A request the mock endpoint received.
+    #[derive(Debug)]
+    pub(crate) struct Received {
+        pub(crate) content_type: String,
+        pub(crate) accept: String,
+        pub(crate) body: String,
+    }
+
+    /// Starts an endpoint that answers a single request with `status` and `body`.
+    /// Returns its URL and a handle that yields the request.
+    pub(crate) fn serve_once(
+        status: u16,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<Received>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sparql", server.server_addr());
+        let handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let header = |name: &str| {
+                request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
+                    .map_or(String::new(), |header| header.value.to_string())
+            };
+            let mut received = Received {
+                content_type: header("Content-Type"),
+                accept: header("Accept"),
+                body: String::new(),
+            };
+            request
+                .as_reader()
+                .read_to_string(&mut received.body)
+                .unwrap();
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(Header::from_bytes("Content-Type", content_type).unwrap());
+            request.respond(response).unwrap();
+            received
+        });
+        (url, handle)
+    }
+
+    /// Starts an endpoint that answers `count` requests with `body`, each after `delay`.
+    /// Returns its URL.
+    pub(crate) fn serve_delayed(
+        count: usize,
+        delay: Duration,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sparql", server.server_addr());
+        thread::spawn(move || {
+            for request in server.incoming_requests().take(count) {
+                thread::spawn(move || {
+                    thread::sleep(delay);
+                    let response = Response::from_string(body)
+                        .with_header(Header::from_bytes("Content-Type", content_type).unwrap());
+                    request.respond(response).unwrap();
+                });
+            }
+        });
+        url
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use std::time::Duration;
+
+    use crate::results::ResultsFormat;
+
+    use super::{execute_query, mock::serve_once, EndpointError};
+
+    #[test]
+    fn post_query() {
+        let (url, handle) = serve_once(200, "text/csv; charset=utf-8", "s\r\nhttp://a\r\n");
+        let response = execute_query(
+            &url,
+            "SELECT * { ?s ?p ?o }",
+            ResultsFormat::Csv,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(response.content_type, "text/csv");
+        assert_eq!(response.body, "s\r\nhttp://a\r\n");
+        let received = handle.join().unwrap();
+        assert_eq!(received.content_type, "application/sparql-query");
+        assert_eq!(received.accept, "text/csv");
+        assert_eq!(received.body, "SELECT * { ?s ?p ?o }");
+    }
+
+    #[test]
+    fn error_status() {
+        let (url, handle) = serve_once(400, "text/plain", "Parse error");
+        let error =
+            execute_query(&url, "SELECT", ResultsFormat::Json, Duration::from_secs(5)).unwrap_err();
+        handle.join().unwrap();
+        assert!(matches!(error, EndpointError::Status { status: 400, .. }));
+        assert_eq!(
+            error.to_string(),
+            "The endpoint answered with status 400: Parse error"
+        );
+    }
+}
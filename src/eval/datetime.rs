@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+
+use crate::results::Term;
+
+/// The value of an `xsd:dateTime` or `xsd:date` literal.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct DateTime<'a> {
+    pub(super) year: i64,
+    pub(super) month: u32,
+    pub(super) day: u32,
+    pub(super) hours: u32,
+    pub(super) minutes: u32,
+    /// The lexical form of the seconds, like `05.5`.
+    pub(super) seconds: &'a str,
+    /// The lexical form of the timezone, like `Z` or `-05:00`, empty without timezone.
+    pub(super) timezone: &'a str,
+}
+
+impl<'a> DateTime<'a> {
+    /// Parses the lexical form of an `xsd:dateTime`, or of an `xsd:date` when `time` is false.
+    pub(super) fn parse(lexical: &'a str, time: bool) -> Option<Self> {
+        let (negative, rest) = match lexical.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, lexical),
+        };
+        let (year, rest) = rest.split_once('-')?;
+        let (month, rest) = rest.split_at_checked(2)?;
+        let rest = rest.strip_prefix('-')?;
+        let (day, rest) = rest.split_at_checked(2)?;
+        let (hours, minutes, seconds, timezone) = match time {
+            true => {
+                let rest = rest.strip_prefix('T')?;
+                let (hours, rest) = rest.split_at_checked(2)?;
+                let (minutes, rest) = rest.strip_prefix(':')?.split_at_checked(2)?;
+                let rest = rest.strip_prefix(':')?;
+                let end = rest
+                    .find(|char: char| !char.is_ascii_digit() && char != '.')
+                    .unwrap_or(rest.len());
+                let (seconds, timezone) = rest.split_at(end);
+                (
+                    hours.parse().ok()?,
+                    minutes.parse().ok()?,
+                    seconds,
+                    timezone,
+                )
+            }
+            false => (0, 0, "00", rest),
+        };
+        if year.len() < 4
+            || !year.chars().all(|char| char.is_ascii_digit())
+            || seconds.len() < 2
+            || seconds.parse::<f64>().ok()? >= 60.0
+            || !(timezone.is_empty() || timezone == "Z" || timezone_minutes(timezone).is_some())
+        {
+            return None;
+        }
+        let datetime = DateTime {
+            year: year.parse::<i64>().ok()? * if negative { -1 } else { 1 },
+            month: month.parse().ok()?,
+            day: day.parse().ok()?,
+            hours,
+            minutes,
+            seconds,
+            timezone,
+        };
+        (matches!(datetime.month, 1..=12)
+            && matches!(datetime.day, 1..=31)
+            && (datetime.hours < 24 || datetime.hours == 24 && datetime.minutes == 0)
+            && datetime.minutes < 60)
+            .then_some(datetime)
+    }
+
+    /// The offset of the timezone in minutes.
+    pub(super) fn offset(&self) -> Option<i64> {
+        match self.timezone {
+            "" => None,
+            "Z" => Some(0),
+            timezone => timezone_minutes(timezone),
+        }
+    }
+
+    /// The seconds since 1970 in UTC, assuming UTC without timezone.
+    fn instant(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = self.seconds.parse::<f64>().unwrap_or_default();
+        (days * 86400 + self.hours as i64 * 3600 + self.minutes as i64 * 60
+            - self.offset().unwrap_or_default() * 60) as f64
+            + seconds
+    }
+
+    /// Compares the instants, `None` if only one has a timezone.
+    pub(super) fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.offset().is_some() != other.offset().is_some() {
+            return None;
+        }
+        self.instant().partial_cmp(&other.instant())
+    }
+
+    /// The timezone as `xsd:dayTimeDuration`, like `-PT5H`.
+    pub(super) fn timezone_duration(&self) -> Option<String> {
+        let offset = self.offset()?;
+        let sign = if offset < 0 { "-" } else { "" };
+        let (hours, minutes) = (offset.abs() / 60, offset.abs() % 60);
+        Some(match (hours, minutes) {
+            (0, 0) => "PT0S".to_string(),
+            (hours, 0) => format!("{}PT{}H", sign, hours),
+            (0, minutes) => format!("{}PT{}M", sign, minutes),
+            (hours, minutes) => format!("{}PT{}H{}M", sign, hours, minutes),
+        })
+    }
+}
+
+fn timezone_minutes(timezone: &str) -> Option<i64> {
+    let sign = match timezone.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = timezone[1..].split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (i64, i64) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
+
+// https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The current time as `xsd:dateTime` literal in UTC.
+pub(super) fn now() -> Term {
+    #[cfg(not(target_family = "wasm"))]
+    let milliseconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default();
+    #[cfg(target_family = "wasm")]
+    let milliseconds = js_sys::Date::now() as i64;
+    let seconds = milliseconds.div_euclid(1000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    crate::rdf::typed(
+        &format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            milliseconds.rem_euclid(1000)
+        ),
+        "dateTime",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::{civil_from_days, days_from_civil, DateTime};
+
+    #[test]
+    fn parse() {
+        let datetime = DateTime::parse("2011-01-10T14:45:13.815-05:00", true).unwrap();
+        assert_eq!((datetime.year, datetime.month, datetime.day), (2011, 1, 10));
+        assert_eq!((datetime.hours, datetime.minutes), (14, 45));
+        assert_eq!(datetime.seconds, "13.815");
+        assert_eq!(datetime.timezone_duration().unwrap(), "-PT5H");
+        assert_eq!(DateTime::parse("2011-01-10", false).unwrap().day, 10);
+        assert_eq!(DateTime::parse("2011-13-10T00:00:00", true), None);
+        assert_eq!(DateTime::parse("2011-01-10T00:00", true), None);
+    }
+
+    #[test]
+    fn compare() {
+        let utc = DateTime::parse("2002-04-02T17:00:00Z", true).unwrap();
+        let offset = DateTime::parse("2002-04-02T12:00:00-05:00", true).unwrap();
+        let local = DateTime::parse("2002-04-02T17:00:00", true).unwrap();
+        assert_eq!(utc.compare(&offset), Some(Ordering::Equal));
+        assert_eq!(utc.compare(&local), None);
+        for days in [-800_000, -1, 0, 59, 11_016, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -0,0 +1,809 @@
+use std::cmp::Ordering;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    algebra::{
+        BinaryOperator, Expression, GraphPattern, TermPattern, TriplePattern, UnaryOperator,
+    },
+    rdf::{literal, resolve, typed, Graph, RDF, XSD},
+    results::Term,
+};
+
+use super::{datetime::DateTime, numeric::Numeric, Evaluator, Solution};
+
+/// The lexical form and the language of a simple literal, `xsd:string` or language-tagged string.
+pub(super) fn string(term: &Term) -> Option<(&str, Option<&str>)> {
+    match term {
+        Term::Literal {
+            value,
+            datatype: None,
+            language,
+        } => Some((value, language.as_deref())),
+        Term::Literal {
+            value,
+            datatype: Some(datatype),
+            language: None,
+        } if datatype.strip_prefix(XSD) == Some("string") => Some((value, None)),
+        _ => None,
+    }
+}
+
+fn simple(value: impl Into<String>) -> Term {
+    Term::Literal {
+        value: value.into(),
+        datatype: None,
+        language: None,
+    }
+}
+
+/// A string with the language of another string.
+fn with_language(value: impl Into<String>, language: Option<&str>) -> Term {
+    Term::Literal {
+        value: value.into(),
+        datatype: None,
+        language: language.map(str::to_string),
+    }
+}
+
+fn boolean(value: bool) -> Term {
+    typed(if value { "true" } else { "false" }, "boolean")
+}
+
+/// The local name of the datatype of a literal with an XML schema datatype.
+fn xsd_datatype(term: &Term) -> Option<&str> {
+    match term {
+        Term::Literal {
+            datatype: Some(datatype),
+            ..
+        } => datatype.strip_prefix(XSD),
+        _ => None,
+    }
+}
+
+fn boolean_value(term: &Term) -> Option<bool> {
+    match (xsd_datatype(term)?, term) {
+        ("boolean", Term::Literal { value, .. }) => match value.as_str() {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn datetime(term: &Term) -> Option<DateTime<'_>> {
+    match (xsd_datatype(term)?, term) {
+        ("dateTime", Term::Literal { value, .. }) => DateTime::parse(value, true),
+        ("date", Term::Literal { value, .. }) => DateTime::parse(value, false),
+        _ => None,
+    }
+}
+
+/// The effective boolean value of a term, `None` if it has none.
+pub(super) fn effective_boolean_value(term: &Term) -> Option<bool> {
+    if xsd_datatype(term) == Some("boolean") {
+        return Some(boolean_value(term).unwrap_or(false));
+    }
+    if Numeric::is_numeric_type(term) {
+        return Some(Numeric::from_term(term).is_some_and(|numeric| !numeric.is_zero_or_nan()));
+    }
+    match string(term)? {
+        (value, None) => Some(!value.is_empty()),
+        (_value, Some(_language)) => None,
+    }
+}
+
+/// Compares two terms with `<`, `None` if they are not comparable.
+pub(super) fn compare(left: &Term, right: &Term) -> Option<Ordering> {
+    if let (Some(left), Some(right)) = (Numeric::from_term(left), Numeric::from_term(right)) {
+        return left.compare(right);
+    }
+    if let (Some((left, None)), Some((right, None))) = (string(left), string(right)) {
+        return Some(left.cmp(right));
+    }
+    if let (Some(left), Some(right)) = (boolean_value(left), boolean_value(right)) {
+        return Some(left.cmp(&right));
+    }
+    match (datetime(left), datetime(right)) {
+        (Some(left_value), Some(right_value)) if xsd_datatype(left) == xsd_datatype(right) => {
+            left_value.compare(&right_value)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the datatype of the literal is known, terms of other datatypes can not be compared.
+fn is_known_literal(term: &Term) -> bool {
+    string(term).is_some()
+        || Numeric::is_numeric_type(term)
+        || matches!(xsd_datatype(term), Some("boolean" | "dateTime" | "date"))
+}
+
+/// Compares two terms with `=`, `None` for literals of unknown datatypes.
+fn equal(left: &Term, right: &Term) -> Option<bool> {
+    if left == right {
+        return Some(true);
+    }
+    if Numeric::is_numeric_type(left) && Numeric::is_numeric_type(right) {
+        let (left, right) = (Numeric::from_term(left)?, Numeric::from_term(right)?);
+        return Some(left.compare(right) == Some(Ordering::Equal));
+    }
+    match (string(left), string(right)) {
+        (Some((left, None)), Some((right, None))) => return Some(left == right),
+        (Some((left, Some(left_language))), Some((right, Some(right_language)))) => {
+            return Some(left == right && left_language.eq_ignore_ascii_case(right_language))
+        }
+        _ => {}
+    }
+    if let (Some(left), Some(right)) = (boolean_value(left), boolean_value(right)) {
+        return Some(left == right);
+    }
+    if let (Some(left), Some(right)) = (datetime(left), datetime(right)) {
+        return Some(left.compare(&right)? == Ordering::Equal);
+    }
+    match (left, right) {
+        (Term::Literal { .. }, Term::Literal { .. })
+            if !is_known_literal(left) || !is_known_literal(right) =>
+        {
+            None
+        }
+        _ => Some(false),
+    }
+}
+
+/// Whether the second argument of a string function can be used with the first.
+fn compatible_strings<'a>(
+    left: &'a Term,
+    right: &'a Term,
+) -> Option<(&'a str, Option<&'a str>, &'a str)> {
+    let (left, left_language) = string(left)?;
+    let (right, right_language) = string(right)?;
+    (right_language.is_none() || right_language == left_language).then_some((
+        left,
+        left_language,
+        right,
+    ))
+}
+
+impl Evaluator<'_> {
+    /// The effective boolean value of the expression, `None` for errors.
+    pub(super) fn condition(
+        &self,
+        expression: &Expression,
+        solution: &Solution,
+        graph: &Graph,
+    ) -> Option<bool> {
+        effective_boolean_value(&self.expression(expression, solution, graph)?)
+    }
+
+    /// The value of the expression, `None` for errors and unbound variables.
+    pub(super) fn expression(
+        &self,
+        expression: &Expression,
+        solution: &Solution,
+        graph: &Graph,
+    ) -> Option<Term> {
+        match expression {
+            Expression::Variable(variable) => solution.get(variable).cloned(),
+            Expression::Term(term) => Some(term.clone()),
+            Expression::Binary(
+                operator @ (BinaryOperator::Or | BinaryOperator::And),
+                left,
+                right,
+            ) => {
+                let left = self.condition(left, solution, graph);
+                let right = self.condition(right, solution, graph);
+                // NOTE: Errors are ignored if the other operand decides the result.
+                let decisive = *operator == BinaryOperator::Or;
+                match (left, right) {
+                    (Some(value), _) | (_, Some(value)) if value == decisive => {
+                        Some(boolean(decisive))
+                    }
+                    (Some(_), Some(_)) => Some(boolean(!decisive)),
+                    _ => None,
+                }
+            }
+            Expression::Binary(operator, left, right) => {
+                let left = self.expression(left, solution, graph)?;
+                let right = self.expression(right, solution, graph)?;
+                binary(*operator, &left, &right)
+            }
+            Expression::Unary(UnaryOperator::Not, operand) => {
+                Some(boolean(!self.condition(operand, solution, graph)?))
+            }
+            Expression::Unary(operator, operand) => {
+                let numeric = Numeric::from_term(&self.expression(operand, solution, graph)?)?;
+                match operator {
+                    UnaryOperator::Minus => numeric.negate().map(Numeric::to_term),
+                    _ => Some(numeric.to_term()),
+                }
+            }
+            Expression::In(value, list) | Expression::NotIn(value, list) => {
+                let is_in = matches!(expression, Expression::In(..));
+                let value = self.expression(value, solution, graph)?;
+                let mut is_error = false;
+                for item in list {
+                    match self
+                        .expression(item, solution, graph)
+                        .and_then(|item| equal(&value, &item))
+                    {
+                        Some(true) => return Some(boolean(is_in)),
+                        Some(false) => {}
+                        None => is_error = true,
+                    }
+                }
+                (!is_error).then(|| boolean(!is_in))
+            }
+            Expression::Exists(pattern) => Some(boolean(self.exists(pattern, solution, graph))),
+            Expression::NotExists(pattern) => Some(boolean(!self.exists(pattern, solution, graph))),
+            Expression::Builtin(name, arguments) => self.builtin(name, arguments, solution, graph),
+            Expression::Function(iri, arguments) => {
+                let arguments: Vec<Term> = arguments
+                    .iter()
+                    .map(|argument| self.expression(argument, solution, graph))
+                    .collect::<Option<_>>()?;
+                cast(iri, &arguments)
+            }
+        }
+    }
+
+    /// Whether the pattern has a solution, with the values of `solution` substituted.
+    fn exists(&self, pattern: &GraphPattern, solution: &Solution, graph: &Graph) -> bool {
+        let pattern = substitute(pattern, solution);
+        self.pattern_from(&pattern, graph, vec![solution.clone()])
+            .is_ok_and(|solutions| !solutions.is_empty())
+    }
+
+    fn builtin(
+        &self,
+        name: &str,
+        arguments: &[Expression],
+        solution: &Solution,
+        graph: &Graph,
+    ) -> Option<Term> {
+        match (name, arguments) {
+            ("BOUND", [argument]) => {
+                return Some(boolean(
+                    self.expression(argument, solution, graph).is_some(),
+                ))
+            }
+            ("IF", [condition, then, otherwise]) => {
+                return match self.condition(condition, solution, graph)? {
+                    true => self.expression(then, solution, graph),
+                    false => self.expression(otherwise, solution, graph),
+                }
+            }
+            ("COALESCE", arguments) => {
+                return arguments
+                    .iter()
+                    .find_map(|argument| self.expression(argument, solution, graph))
+            }
+            _ => {}
+        }
+        let arguments: Vec<Term> = arguments
+            .iter()
+            .map(|argument| self.expression(argument, solution, graph))
+            .collect::<Option<_>>()?;
+        match (name, arguments.as_slice()) {
+            ("STR", [Term::Iri(iri)]) => Some(simple(iri.as_str())),
+            ("STR", [Term::Literal { value, .. }]) => Some(simple(value.as_str())),
+            ("LANG", [Term::Literal { language, .. }]) => {
+                Some(simple(language.clone().unwrap_or_default()))
+            }
+            ("LANGMATCHES", [tag, range]) => {
+                let (Some((tag, None)), Some((range, None))) = (string(tag), string(range)) else {
+                    return None;
+                };
+                let (tag, range) = (tag.to_lowercase(), range.to_lowercase());
+                Some(boolean(match range.as_str() {
+                    "*" => !tag.is_empty(),
+                    range => tag == range || tag.starts_with(&format!("{}-", range)),
+                }))
+            }
+            (
+                "DATATYPE",
+                [Term::Literal {
+                    datatype, language, ..
+                }],
+            ) => Some(Term::Iri(match (datatype, language) {
+                (_, Some(_)) => format!("{}langString", RDF),
+                (Some(datatype), None) => datatype.clone(),
+                (None, None) => format!("{}string", XSD),
+            })),
+            ("IRI" | "URI", [Term::Iri(iri)]) => Some(Term::Iri(iri.clone())),
+            ("IRI" | "URI", [term]) => match string(term)? {
+                (value, None) => Some(Term::Iri(match self.base {
+                    Some(base) => resolve(base, value),
+                    None => value.to_string(),
+                })),
+                _ => None,
+            },
+            ("BNODE", []) => Some(self.blank_node()),
+            ("BNODE", [term]) => {
+                let (label, None) = string(term)? else {
+                    return None;
+                };
+                // NOTE: The same label gives the same blank node within a solution.
+                let key = (solution.clone(), label.to_string());
+                let mut labels = self.labels.borrow_mut();
+                Some(
+                    labels
+                        .entry(key)
+                        .or_insert_with(|| self.blank_node())
+                        .clone(),
+                )
+            }
+            ("RAND", []) => {
+                let mut bytes = [0; 8];
+                getrandom::getrandom(&mut bytes).ok()?;
+                let random = (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64;
+                Some(Numeric::Double(random).to_term())
+            }
+            ("ABS" | "CEIL" | "FLOOR" | "ROUND", [term]) => {
+                let numeric = Numeric::from_term(term)?;
+                match name {
+                    "ABS" => numeric.abs(),
+                    "CEIL" => Some(numeric.ceil()),
+                    "FLOOR" => Some(numeric.floor()),
+                    _ => Some(numeric.round()),
+                }
+                .map(Numeric::to_term)
+            }
+            ("CONCAT", arguments) => {
+                let mut value = String::new();
+                let mut languages = vec![];
+                for argument in arguments {
+                    let (string, language) = string(argument)?;
+                    value.push_str(string);
+                    languages.push(language);
+                }
+                let language = match languages.split_first() {
+                    Some((first, rest)) if rest.iter().all(|language| language == first) => *first,
+                    _ => None,
+                };
+                Some(with_language(value, language))
+            }
+            ("SUBSTR", [term, start, rest @ ..]) if rest.len() <= 1 => {
+                let (value, language) = string(term)?;
+                let start = Numeric::from_term(start)?.round().to_f64();
+                let end = match rest.first() {
+                    Some(length) => start + Numeric::from_term(length)?.round().to_f64(),
+                    None => f64::INFINITY,
+                };
+                // NOTE: Positions start at 1, like in XPath `fn:substring`.
+                let substring: String = value
+                    .chars()
+                    .enumerate()
+                    .filter(|(index, _char)| {
+                        let position = (*index + 1) as f64;
+                        position >= start && position < end
+                    })
+                    .map(|(_index, char)| char)
+                    .collect();
+                Some(with_language(substring, language))
+            }
+            ("STRLEN", [term]) => {
+                Some(Numeric::integer(string(term)?.0.chars().count() as i128).to_term())
+            }
+            ("REPLACE", [term, pattern, replacement, flags @ ..]) if flags.len() <= 1 => {
+                let (value, language) = string(term)?;
+                let regex = regex(pattern, flags.first())?;
+                let (replacement, None) = string(replacement)? else {
+                    return None;
+                };
+                let replaced = regex.replace_all(value, replacement_template(replacement));
+                Some(with_language(replaced, language))
+            }
+            ("REGEX", [term, pattern, flags @ ..]) if flags.len() <= 1 => {
+                let (value, _language) = string(term)?;
+                Some(boolean(regex(pattern, flags.first())?.is_match(value)))
+            }
+            ("UCASE" | "LCASE", [term]) => {
+                let (value, language) = string(term)?;
+                Some(with_language(
+                    match name {
+                        "UCASE" => value.to_uppercase(),
+                        _ => value.to_lowercase(),
+                    },
+                    language,
+                ))
+            }
+            ("ENCODE_FOR_URI", [term]) => {
+                let (value, _language) = string(term)?;
+                let mut encoded = String::new();
+                for byte in value.bytes() {
+                    match byte {
+                        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                            encoded.push(byte as char)
+                        }
+                        byte => encoded.push_str(&format!("%{:02X}", byte)),
+                    }
+                }
+                Some(simple(encoded))
+            }
+            ("CONTAINS" | "STRSTARTS" | "STRENDS", [left, right]) => {
+                let (left, _language, right) = compatible_strings(left, right)?;
+                Some(boolean(match name {
+                    "CONTAINS" => left.contains(right),
+                    "STRSTARTS" => left.starts_with(right),
+                    _ => left.ends_with(right),
+                }))
+            }
+            ("STRBEFORE" | "STRAFTER", [left, right]) => {
+                let (left, language, right) = compatible_strings(left, right)?;
+                Some(match left.find(right) {
+                    Some(index) => with_language(
+                        match name {
+                            "STRBEFORE" => &left[..index],
+                            _ => &left[index + right.len()..],
+                        },
+                        language,
+                    ),
+                    None => simple(""),
+                })
+            }
+            ("YEAR" | "MONTH" | "DAY" | "HOURS" | "MINUTES", [term]) => {
+                let datetime = datetime(term)?;
+                let value = match name {
+                    "YEAR" => datetime.year as i128,
+                    "MONTH" => datetime.month as i128,
+                    "DAY" => datetime.day as i128,
+                    "HOURS" => datetime.hours as i128,
+                    _ => datetime.minutes as i128,
+                };
+                Some(Numeric::integer(value).to_term())
+            }
+            ("SECONDS", [term]) => {
+                let datetime = datetime(term)?;
+                Numeric::parse(datetime.seconds, "decimal")?
+                    .to_decimal()
+                    .map(Numeric::to_term)
+            }
+            ("TIMEZONE", [term]) => Some(typed(
+                &datetime(term)?.timezone_duration()?,
+                "dayTimeDuration",
+            )),
+            ("TZ", [term]) => Some(simple(datetime(term)?.timezone)),
+            ("NOW", []) => Some(self.now.clone()),
+            ("UUID", []) => Some(Term::Iri(format!("urn:uuid:{}", uuid()?))),
+            ("STRUUID", []) => Some(simple(uuid()?)),
+            // NOTE: Hash functions are not supported, they raise errors.
+            ("MD5" | "SHA1" | "SHA256" | "SHA384" | "SHA512", _) => None,
+            ("STRLANG", [term, language]) => {
+                let (Some((value, None)), Some((language, None))) =
+                    (string(term), string(language))
+                else {
+                    return None;
+                };
+                (!language.is_empty()).then(|| with_language(value, Some(language)))
+            }
+            ("STRDT", [term, Term::Iri(datatype)]) => match string(term)? {
+                (value, None) => Some(literal(value.to_string(), datatype.clone())),
+                _ => None,
+            },
+            ("SAMETERM", [left, right]) => Some(boolean(left == right)),
+            ("ISIRI" | "ISURI", [term]) => Some(boolean(matches!(term, Term::Iri(_)))),
+            ("ISBLANK", [term]) => Some(boolean(matches!(term, Term::BlankNode(_)))),
+            ("ISLITERAL", [term]) => Some(boolean(matches!(term, Term::Literal { .. }))),
+            ("ISNUMERIC", [term]) => Some(boolean(Numeric::from_term(term).is_some())),
+            _ => None,
+        }
+    }
+}
+
+fn binary(operator: BinaryOperator, left: &Term, right: &Term) -> Option<Term> {
+    let comparison = |accept: fn(Ordering) -> bool| {
+        if let (Some(left), Some(right)) = (Numeric::from_term(left), Numeric::from_term(right)) {
+            // NOTE: Comparisons with NaN are false.
+            return Some(boolean(left.compare(right).is_some_and(accept)));
+        }
+        compare(left, right).map(|ordering| boolean(accept(ordering)))
+    };
+    match operator {
+        BinaryOperator::Equal => equal(left, right).map(boolean),
+        BinaryOperator::NotEqual => equal(left, right).map(|equal| boolean(!equal)),
+        BinaryOperator::Less => comparison(Ordering::is_lt),
+        BinaryOperator::Greater => comparison(Ordering::is_gt),
+        BinaryOperator::LessOrEqual => comparison(Ordering::is_le),
+        BinaryOperator::GreaterOrEqual => comparison(Ordering::is_ge),
+        BinaryOperator::Or | BinaryOperator::And => None,
+        BinaryOperator::Add
+        | BinaryOperator::Subtract
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide => {
+            let (left, right) = (Numeric::from_term(left)?, Numeric::from_term(right)?);
+            match operator {
+                BinaryOperator::Add => left.add(right),
+                BinaryOperator::Subtract => left.subtract(right),
+                BinaryOperator::Multiply => left.multiply(right),
+                _ => left.divide(right),
+            }
+            .map(Numeric::to_term)
+        }
+    }
+}
+
+/// The regular expression of `REGEX` and `REPLACE` with XPath flags.
+fn regex(pattern: &Term, flags: Option<&Term>) -> Option<Regex> {
+    let (pattern, None) = string(pattern)? else {
+        return None;
+    };
+    let flags = match flags.map(string) {
+        Some(Some((flags, None))) => flags,
+        Some(_) => return None,
+        None => "",
+    };
+    let mut builder = match flags.contains('q') {
+        true => RegexBuilder::new(&regex::escape(pattern)),
+        false => RegexBuilder::new(pattern),
+    };
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            's' => builder.dot_matches_new_line(true),
+            'm' => builder.multi_line(true),
+            'x' => builder.ignore_whitespace(true),
+            'q' => &mut builder,
+            _ => return None,
+        };
+    }
+    builder.build().ok()
+}
+
+/// Translates an XPath replacement like `\$$1` to the syntax of the regex crate.
+fn replacement_template(replacement: &str) -> String {
+    let mut template = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('$') => template.push_str("$$"),
+                Some(char) => template.push(char),
+                None => {}
+            },
+            '$' => {
+                let mut group = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    group.push(digit);
+                }
+                template.push_str(&format!("${{{}}}", group));
+            }
+            char => template.push(char),
+        }
+    }
+    template
+}
+
+fn uuid() -> Option<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).ok()?;
+    // NOTE: A version 4 UUID of the RFC 4122 variant.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Casts a term with a constructor function of XML schema, like `xsd:integer(?x)`.
+fn cast(iri: &str, arguments: &[Term]) -> Option<Term> {
+    let [term] = arguments else {
+        return None;
+    };
+    let datatype = iri.strip_prefix(XSD)?;
+    let lexical = match term {
+        Term::Iri(iri) if datatype == "string" => return Some(simple(iri.as_str())),
+        Term::Literal {
+            value,
+            language: None,
+            ..
+        } => value.as_str(),
+        _ => return None,
+    };
+    let is_string = string(term).is_some();
+    let numeric = Numeric::from_term(term);
+    let boolean_value = boolean_value(term);
+    match datatype {
+        "string" => Some(simple(lexical)),
+        "boolean" => match (numeric, boolean_value) {
+            (Some(numeric), _) => Some(boolean(!numeric.is_zero_or_nan())),
+            (_, Some(value)) => Some(boolean(value)),
+            _ if is_string => match lexical.trim() {
+                "true" | "1" => Some(boolean(true)),
+                "false" | "0" => Some(boolean(false)),
+                _ => None,
+            },
+            _ => None,
+        },
+        "integer" | "decimal" | "float" | "double" => {
+            let numeric = match (numeric, boolean_value) {
+                (Some(numeric), _) => numeric,
+                (_, Some(value)) => Numeric::integer(value as i128),
+                _ if is_string => match datatype {
+                    "integer" | "decimal" => Numeric::parse(lexical, datatype)?,
+                    _ => Numeric::parse(lexical, "double")?,
+                },
+                _ => return None,
+            };
+            match datatype {
+                "integer" => numeric.to_integer().map(Numeric::integer),
+                "decimal" => numeric.to_decimal(),
+                "float" => Some(Numeric::Float(numeric.to_f64() as f32)),
+                _ => Some(Numeric::Double(numeric.to_f64())),
+            }
+            .map(Numeric::to_term)
+        }
+        "dateTime" if is_string || datetime(term).is_some() => {
+            let lexical = lexical.trim();
+            DateTime::parse(lexical, true).map(|_datetime| typed(lexical, "dateTime"))
+        }
+        _ => None,
+    }
+}
+
+fn substitute_term(term: &TermPattern, solution: &Solution) -> TermPattern {
+    match term {
+        TermPattern::Variable(variable) => match solution.get(variable) {
+            Some(value) => TermPattern::Term(value.clone()),
+            None => term.clone(),
+        },
+        term => term.clone(),
+    }
+}
+
+/// Replaces the variables bound by the solution with their values, for `EXISTS`.
+/// Subqueries keep their own variables.
+fn substitute(pattern: &GraphPattern, solution: &Solution) -> GraphPattern {
+    let boxed = |pattern: &GraphPattern| Box::new(substitute(pattern, solution));
+    match pattern {
+        GraphPattern::Bgp(triples) => GraphPattern::Bgp(
+            triples
+                .iter()
+                .map(|triple| TriplePattern {
+                    subject: substitute_term(&triple.subject, solution),
+                    predicate: substitute_term(&triple.predicate, solution),
+                    object: substitute_term(&triple.object, solution),
+                })
+                .collect(),
+        ),
+        GraphPattern::Path {
+            subject,
+            path,
+            object,
+        } => GraphPattern::Path {
+            subject: substitute_term(subject, solution),
+            path: path.clone(),
+            object: substitute_term(object, solution),
+        },
+        GraphPattern::Join(left, right) => GraphPattern::Join(boxed(left), boxed(right)),
+        GraphPattern::LeftJoin(left, right, expression) => GraphPattern::LeftJoin(
+            boxed(left),
+            boxed(right),
+            expression
+                .as_ref()
+                .map(|expression| substitute_expression(expression, solution)),
+        ),
+        GraphPattern::Filter(expression, pattern) => {
+            GraphPattern::Filter(substitute_expression(expression, solution), boxed(pattern))
+        }
+        GraphPattern::Union(left, right) => GraphPattern::Union(boxed(left), boxed(right)),
+        GraphPattern::Minus(left, right) => GraphPattern::Minus(boxed(left), boxed(right)),
+        GraphPattern::Graph(name, pattern) => {
+            GraphPattern::Graph(substitute_term(name, solution), boxed(pattern))
+        }
+        GraphPattern::Extend(pattern, variable, expression) => GraphPattern::Extend(
+            boxed(pattern),
+            variable.clone(),
+            substitute_expression(expression, solution),
+        ),
+        pattern => pattern.clone(),
+    }
+}
+
+fn substitute_expression(expression: &Expression, solution: &Solution) -> Expression {
+    let all = |expressions: &[Expression]| {
+        expressions
+            .iter()
+            .map(|expression| substitute_expression(expression, solution))
+            .collect()
+    };
+    let boxed = |expression: &Expression| Box::new(substitute_expression(expression, solution));
+    match expression {
+        Expression::Variable(variable) => match solution.get(variable) {
+            Some(value) => Expression::Term(value.clone()),
+            None => expression.clone(),
+        },
+        Expression::Term(_) => expression.clone(),
+        Expression::Binary(operator, left, right) => {
+            Expression::Binary(*operator, boxed(left), boxed(right))
+        }
+        Expression::Unary(operator, operand) => Expression::Unary(*operator, boxed(operand)),
+        Expression::In(value, list) => Expression::In(boxed(value), all(list)),
+        Expression::NotIn(value, list) => Expression::NotIn(boxed(value), all(list)),
+        Expression::Exists(pattern) => Expression::Exists(Box::new(substitute(pattern, solution))),
+        Expression::NotExists(pattern) => {
+            Expression::NotExists(Box::new(substitute(pattern, solution)))
+        }
+        Expression::Builtin(name, arguments) => Expression::Builtin(name.clone(), all(arguments)),
+        Expression::Function(iri, arguments) => Expression::Function(iri.clone(), all(arguments)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cast, equal, replacement_template};
+    use crate::{rdf::typed, results::Term};
+
+    fn simple(value: &str) -> Term {
+        Term::Literal {
+            value: value.to_string(),
+            datatype: None,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(
+            equal(&typed("1", "integer"), &typed("1.0", "double")),
+            Some(true)
+        );
+        assert_eq!(equal(&simple("a"), &typed("1", "integer")), Some(false));
+        assert_eq!(
+            equal(
+                &Term::Literal {
+                    value: "a".to_string(),
+                    datatype: None,
+                    language: Some("en".to_string())
+                },
+                &Term::Literal {
+                    value: "a".to_string(),
+                    datatype: None,
+                    language: Some("EN".to_string())
+                }
+            ),
+            Some(true)
+        );
+        let unknown = Term::Literal {
+            value: "a".to_string(),
+            datatype: Some("http://example.org/type".to_string()),
+            language: None,
+        };
+        assert_eq!(equal(&unknown, &simple("b")), None);
+        assert_eq!(equal(&unknown, &unknown), Some(true));
+    }
+
+    #[test]
+    fn casts() {
+        let xsd = |name: &str| format!("http://www.w3.org/2001/XMLSchema#{}", name);
+        assert_eq!(
+            cast(&xsd("integer"), &[simple(" 12 ")]),
+            Some(typed("12", "integer"))
+        );
+        assert_eq!(
+            cast(&xsd("integer"), &[typed("2.7", "decimal")]),
+            Some(typed("2", "integer"))
+        );
+        assert_eq!(
+            cast(&xsd("boolean"), &[typed("0", "integer")]),
+            Some(typed("false", "boolean"))
+        );
+        assert_eq!(
+            cast(&xsd("string"), &[Term::Iri("http://a".to_string())]),
+            Some(simple("http://a"))
+        );
+        assert_eq!(cast(&xsd("integer"), &[simple("a")]), None);
+    }
+
+    #[test]
+    fn replacements() {
+        assert_eq!(replacement_template("$1-\\$0"), "${1}-$$0");
+    }
+}
@@ -0,0 +1,954 @@
+//! Evaluates queries on an in-memory dataset.
+//! See <https://www.w3.org/TR/sparql11-query/#sparqlAlgebraEval>.
+
+mod datetime;
+mod expression;
+mod numeric;
+
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use crate::{
+    algebra::{
+        is_internal, Aggregate, AggregateFunction, GraphPattern, PropertyPath, Query, QueryForm,
+        TermPattern, TriplePattern,
+    },
+    rdf::{Dataset, Graph, Triple},
+    results::{QueryResults, Term},
+};
+
+use numeric::Numeric;
+
+/// The answer of a query, results for `SELECT` and `ASK`, triples for `CONSTRUCT` and `DESCRIBE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryAnswer {
+    Results(QueryResults),
+    Graph(Vec<Triple>),
+}
+
+/// A query that could not be evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError(pub String);
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+type Result<T> = std::result::Result<T, EvalError>;
+
+/// The values bound to the variables by a solution.
+type Solution = BTreeMap<String, Term>;
+
+/// The term with a canonical lexical form if it is a numeric literal, to compare results.
+pub(crate) fn canonical_literal(term: &Term) -> Term {
+    if let Some(numeric) = Numeric::from_term(term) {
+        if let (Term::Literal { value, .. }, Term::Literal { datatype, .. }) =
+            (numeric.to_term(), term)
+        {
+            return Term::Literal {
+                value,
+                datatype: datatype.clone(),
+                language: None,
+            };
+        }
+    }
+    match expression::string(term) {
+        Some((value, language)) => Term::Literal {
+            value: value.to_string(),
+            datatype: None,
+            language: language.map(str::to_lowercase),
+        },
+        None => term.clone(),
+    }
+}
+
+/// Evaluates the query on the dataset.
+/// The graphs of `FROM` and `FROM NAMED` clauses are looked up in the named graphs of the dataset.
+pub fn evaluate(query: &Query, dataset: &Dataset) -> Result<QueryAnswer> {
+    let view;
+    let dataset = match &query.dataset {
+        Some(clauses) => {
+            let mut merged = Dataset::new();
+            for name in &clauses.default {
+                if let Some(graph) = dataset.graph(&Term::Iri(name.clone())) {
+                    for triple in graph.iter() {
+                        merged.insert(triple.clone(), None);
+                    }
+                }
+            }
+            for name in &clauses.named {
+                let name = Term::Iri(name.clone());
+                if let Some(graph) = dataset.graph(&name) {
+                    for triple in graph.iter() {
+                        merged.insert(triple.clone(), Some(name.clone()));
+                    }
+                }
+            }
+            view = merged;
+            &view
+        }
+        None => dataset,
+    };
+    let evaluator = Evaluator {
+        dataset,
+        base: query.base.as_deref(),
+        blank_nodes: Cell::new(0),
+        now: datetime::now(),
+        labels: RefCell::default(),
+    };
+    let graph = dataset.default_graph();
+    let solutions = evaluator.pattern(&query.pattern, graph)?;
+    Ok(match &query.form {
+        QueryForm::Select => {
+            let variables = query.pattern.variables();
+            let solutions = solutions
+                .into_iter()
+                .map(|solution| {
+                    variables
+                        .iter()
+                        .map(|variable| solution.get(variable).cloned())
+                        .collect()
+                })
+                .collect();
+            QueryAnswer::Results(QueryResults::Solutions {
+                variables,
+                solutions,
+            })
+        }
+        QueryForm::Ask => QueryAnswer::Results(QueryResults::Boolean(!solutions.is_empty())),
+        QueryForm::Construct(template) => {
+            QueryAnswer::Graph(evaluator.construct(template, &solutions))
+        }
+        QueryForm::Describe(resources) => {
+            QueryAnswer::Graph(evaluator.describe(resources, &solutions, graph))
+        }
+    })
+}
+
+struct Evaluator<'a> {
+    dataset: &'a Dataset,
+    base: Option<&'a str>,
+    /// The number of blank nodes created by the query.
+    blank_nodes: Cell<usize>,
+    /// The value of `NOW()`, the same for the whole query.
+    now: Term,
+    /// The blank nodes of `BNODE(label)` calls, by solution and label.
+    labels: RefCell<HashMap<(Solution, String), Term>>,
+}
+
+/// Whether the solutions bind the same values to their shared variables.
+fn compatible(left: &Solution, right: &Solution) -> bool {
+    let (smaller, larger) = match left.len() <= right.len() {
+        true => (left, right),
+        false => (right, left),
+    };
+    smaller
+        .iter()
+        .all(|(variable, value)| larger.get(variable).is_none_or(|other| other == value))
+}
+
+fn merge(left: &Solution, right: &Solution) -> Solution {
+    let mut merged = left.clone();
+    merged.extend(
+        right
+            .iter()
+            .map(|(variable, value)| (variable.clone(), value.clone())),
+    );
+    merged
+}
+
+/// Binds the pattern term to `value`, `false` if it is bound to another term.
+fn bind(solution: &mut Solution, pattern: &TermPattern, value: &Term) -> bool {
+    match pattern {
+        TermPattern::Term(term) => term == value,
+        TermPattern::Variable(variable) => match solution.get(variable) {
+            Some(bound) => bound == value,
+            None => {
+                solution.insert(variable.clone(), value.clone());
+                true
+            }
+        },
+    }
+}
+
+/// The term of a pattern term in the solution, `None` for unbound variables.
+fn instantiate<'a>(pattern: &'a TermPattern, solution: &'a Solution) -> Option<&'a Term> {
+    match pattern {
+        TermPattern::Term(term) => Some(term),
+        TermPattern::Variable(variable) => solution.get(variable),
+    }
+}
+
+impl Evaluator<'_> {
+    /// A new blank node, distinct from the blank nodes of the dataset.
+    fn blank_node(&self) -> Term {
+        let id = self.blank_nodes.get();
+        self.blank_nodes.set(id + 1);
+        Term::BlankNode(format!("q{}", id))
+    }
+
+    fn pattern(&self, pattern: &GraphPattern, graph: &Graph) -> Result<Vec<Solution>> {
+        self.pattern_from(pattern, graph, vec![Solution::new()])
+    }
+
+    /// Evaluates the pattern and joins its solutions with the `seeds`.
+    /// Triple patterns and paths are evaluated with the values of each seed.
+    fn pattern_from(
+        &self,
+        pattern: &GraphPattern,
+        graph: &Graph,
+        seeds: Vec<Solution>,
+    ) -> Result<Vec<Solution>> {
+        match pattern {
+            GraphPattern::Bgp(triples) => Ok(triples.iter().fold(seeds, |solutions, triple| {
+                solutions
+                    .iter()
+                    .flat_map(|solution| self.triple(triple, graph, solution))
+                    .collect()
+            })),
+            GraphPattern::Path {
+                subject,
+                path,
+                object,
+            } => Ok(seeds
+                .iter()
+                .flat_map(|solution| self.path(subject, path, object, graph, solution))
+                .collect()),
+            GraphPattern::Join(left, right) => {
+                let solutions = self.pattern_from(left, graph, seeds)?;
+                self.pattern_from(right, graph, solutions)
+            }
+            _ => {
+                let solutions = self.pattern_at(pattern, graph)?;
+                if seeds.len() == 1 && seeds[0].is_empty() {
+                    return Ok(solutions);
+                }
+                Ok(join(&seeds, &solutions))
+            }
+        }
+    }
+
+    /// Evaluates the patterns that are not evaluated from seeds.
+    fn pattern_at(&self, pattern: &GraphPattern, graph: &Graph) -> Result<Vec<Solution>> {
+        match pattern {
+            GraphPattern::Bgp(_) | GraphPattern::Path { .. } | GraphPattern::Join(..) => {
+                self.pattern(pattern, graph)
+            }
+            GraphPattern::LeftJoin(left, right, expression) => {
+                let left = self.pattern(left, graph)?;
+                let right = self.pattern(right, graph)?;
+                let mut solutions = vec![];
+                for left in left {
+                    let mut is_extended = false;
+                    for right in right.iter().filter(|right| compatible(&left, right)) {
+                        let merged = merge(&left, right);
+                        if expression.as_ref().is_none_or(|expression| {
+                            self.condition(expression, &merged, graph) == Some(true)
+                        }) {
+                            solutions.push(merged);
+                            is_extended = true;
+                        }
+                    }
+                    if !is_extended {
+                        solutions.push(left);
+                    }
+                }
+                Ok(solutions)
+            }
+            GraphPattern::Filter(expression, pattern) => Ok(self
+                .pattern(pattern, graph)?
+                .into_iter()
+                .filter(|solution| self.condition(expression, solution, graph) == Some(true))
+                .collect()),
+            GraphPattern::Union(left, right) => {
+                let mut solutions = self.pattern(left, graph)?;
+                solutions.append(&mut self.pattern(right, graph)?);
+                Ok(solutions)
+            }
+            GraphPattern::Minus(left, right) => {
+                let right = self.pattern(right, graph)?;
+                Ok(self
+                    .pattern(left, graph)?
+                    .into_iter()
+                    .filter(|left| {
+                        !right.iter().any(|right| {
+                            compatible(left, right)
+                                && right.keys().any(|variable| left.contains_key(variable))
+                        })
+                    })
+                    .collect())
+            }
+            GraphPattern::Graph(name, pattern) => {
+                let mut solutions = vec![];
+                for (graph_name, graph) in self.dataset.named_graphs() {
+                    let mut binding = Solution::new();
+                    if bind(&mut binding, name, graph_name) {
+                        solutions.append(&mut self.pattern_from(pattern, graph, vec![binding])?);
+                    }
+                }
+                Ok(solutions)
+            }
+            GraphPattern::Service { name, silent, .. } => match silent {
+                true => Ok(vec![Solution::new()]),
+                false => Err(EvalError(format!(
+                    "Federated queries are not supported, the SERVICE {} is not queried",
+                    match name {
+                        TermPattern::Variable(variable) => format!("?{}", variable),
+                        TermPattern::Term(term) => term.to_string(),
+                    }
+                ))),
+            },
+            GraphPattern::Extend(pattern, variable, expression) => Ok(self
+                .pattern(pattern, graph)?
+                .into_iter()
+                .map(|mut solution| {
+                    if !solution.contains_key(variable) {
+                        if let Some(value) = self.expression(expression, &solution, graph) {
+                            solution.insert(variable.clone(), value);
+                        }
+                    }
+                    solution
+                })
+                .collect()),
+            GraphPattern::Values { variables, rows } => Ok(rows
+                .iter()
+                .map(|row| {
+                    variables
+                        .iter()
+                        .zip(row)
+                        .filter_map(|(variable, value)| Some((variable.clone(), value.clone()?)))
+                        .collect()
+                })
+                .collect()),
+            GraphPattern::Group {
+                pattern,
+                variables,
+                aggregates,
+            } => {
+                let solutions = self.pattern(pattern, graph)?;
+                Ok(self.group(solutions, variables, aggregates, graph))
+            }
+            GraphPattern::OrderBy(pattern, conditions) => {
+                let mut solutions = self.pattern(pattern, graph)?;
+                let keys: Vec<Vec<Option<Term>>> = solutions
+                    .iter()
+                    .map(|solution| {
+                        conditions
+                            .iter()
+                            .map(|condition| {
+                                self.expression(&condition.expression, solution, graph)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let mut order: Vec<usize> = (0..solutions.len()).collect();
+                order.sort_by(|left, right| {
+                    conditions
+                        .iter()
+                        .enumerate()
+                        .map(|(index, condition)| {
+                            let ordering = order_terms(
+                                keys[*left][index].as_ref(),
+                                keys[*right][index].as_ref(),
+                            );
+                            match condition.descending {
+                                true => ordering.reverse(),
+                                false => ordering,
+                            }
+                        })
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                });
+                let mut sorted: Vec<Option<Solution>> = solutions.drain(..).map(Some).collect();
+                Ok(order
+                    .into_iter()
+                    .filter_map(|index| sorted[index].take())
+                    .collect())
+            }
+            GraphPattern::Project(pattern, variables) => Ok(self
+                .pattern(pattern, graph)?
+                .into_iter()
+                .map(|mut solution| {
+                    solution.retain(|variable, _value| variables.contains(variable));
+                    solution
+                })
+                .collect()),
+            GraphPattern::Distinct(pattern) | GraphPattern::Reduced(pattern) => {
+                let mut seen = HashSet::new();
+                Ok(self
+                    .pattern(pattern, graph)?
+                    .into_iter()
+                    .filter(|solution| seen.insert(solution.clone()))
+                    .collect())
+            }
+            GraphPattern::Slice {
+                pattern,
+                offset,
+                limit,
+            } => Ok(self
+                .pattern(pattern, graph)?
+                .into_iter()
+                .skip(*offset)
+                .take(limit.unwrap_or(usize::MAX))
+                .collect()),
+        }
+    }
+
+    fn triple(&self, triple: &TriplePattern, graph: &Graph, solution: &Solution) -> Vec<Solution> {
+        let subject = instantiate(&triple.subject, solution);
+        let predicate = instantiate(&triple.predicate, solution);
+        let object = instantiate(&triple.object, solution);
+        graph
+            .matching(subject, predicate, object)
+            .filter_map(|matched| {
+                let mut solution = solution.clone();
+                (bind(&mut solution, &triple.subject, &matched.subject)
+                    && bind(&mut solution, &triple.predicate, &matched.predicate)
+                    && bind(&mut solution, &triple.object, &matched.object))
+                .then_some(solution)
+            })
+            .collect()
+    }
+
+    fn path(
+        &self,
+        subject: &TermPattern,
+        path: &PropertyPath,
+        object: &TermPattern,
+        graph: &Graph,
+        solution: &Solution,
+    ) -> Vec<Solution> {
+        let bound_subject = instantiate(subject, solution).cloned();
+        let bound_object = instantiate(object, solution).cloned();
+        let pairs: Vec<(Term, Term)> = match (bound_subject, bound_object) {
+            (Some(start), _) => path_targets(&start, path, graph)
+                .into_iter()
+                .map(|end| (start.clone(), end))
+                .collect(),
+            (None, Some(end)) => path_targets(&end, &inverse(path), graph)
+                .into_iter()
+                .map(|start| (start, end.clone()))
+                .collect(),
+            (None, None) => graph
+                .nodes()
+                .into_iter()
+                .flat_map(|start| {
+                    path_targets(start, path, graph)
+                        .into_iter()
+                        .map(|end| (start.clone(), end))
+                })
+                .collect(),
+        };
+        pairs
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let mut solution = solution.clone();
+                (bind(&mut solution, subject, &start) && bind(&mut solution, object, &end))
+                    .then_some(solution)
+            })
+            .collect()
+    }
+
+    fn group(
+        &self,
+        solutions: Vec<Solution>,
+        variables: &[String],
+        aggregates: &[(String, Aggregate)],
+        graph: &Graph,
+    ) -> Vec<Solution> {
+        let mut groups: Vec<(Solution, Vec<Solution>)> = vec![];
+        let mut indices: HashMap<Solution, usize> = HashMap::new();
+        for solution in solutions {
+            let key: Solution = variables
+                .iter()
+                .filter_map(|variable| Some((variable.clone(), solution.get(variable)?.clone())))
+                .collect();
+            match indices.get(&key) {
+                Some(index) => groups[*index].1.push(solution),
+                None => {
+                    indices.insert(key.clone(), groups.len());
+                    groups.push((key, vec![solution]));
+                }
+            }
+        }
+        // NOTE: Without `GROUP BY` the aggregates are computed over one group, even if it is empty.
+        if groups.is_empty() && variables.is_empty() {
+            groups.push((Solution::new(), vec![]));
+        }
+        groups
+            .into_iter()
+            .map(|(mut key, members)| {
+                for (variable, aggregate) in aggregates {
+                    if let Some(value) = self.aggregate(aggregate, &members, graph) {
+                        key.insert(variable.clone(), value);
+                    }
+                }
+                key
+            })
+            .collect()
+    }
+
+    fn aggregate(
+        &self,
+        aggregate: &Aggregate,
+        members: &[Solution],
+        graph: &Graph,
+    ) -> Option<Term> {
+        let Some(expression) = &aggregate.expression else {
+            let count = match aggregate.distinct {
+                true => members.iter().collect::<HashSet<_>>().len(),
+                false => members.len(),
+            };
+            return Some(Numeric::integer(count as i128).to_term());
+        };
+        let mut values: Vec<Option<Term>> = members
+            .iter()
+            .map(|solution| self.expression(expression, solution, graph))
+            .collect();
+        if aggregate.distinct {
+            let mut seen = HashSet::new();
+            values.retain(|value| seen.insert(value.clone()));
+        }
+        match &aggregate.function {
+            AggregateFunction::Count => {
+                Some(Numeric::integer(values.iter().flatten().count() as i128).to_term())
+            }
+            AggregateFunction::Sum | AggregateFunction::Avg => {
+                let mut sum = Numeric::integer(0);
+                for value in &values {
+                    sum = sum.add(Numeric::from_term(value.as_ref()?)?)?;
+                }
+                match aggregate.function {
+                    AggregateFunction::Avg if !values.is_empty() => sum
+                        .divide(Numeric::integer(values.len() as i128))
+                        .map(Numeric::to_term),
+                    _ => Some(sum.to_term()),
+                }
+            }
+            AggregateFunction::Min | AggregateFunction::Max => {
+                let values = values.into_iter().flatten();
+                match aggregate.function {
+                    AggregateFunction::Min => {
+                        values.min_by(|left, right| order_terms(Some(left), Some(right)))
+                    }
+                    _ => values.max_by(|left, right| order_terms(Some(left), Some(right))),
+                }
+            }
+            AggregateFunction::Sample => values.into_iter().flatten().next(),
+            AggregateFunction::GroupConcat { separator } => {
+                let mut strings = vec![];
+                let mut languages = HashSet::new();
+                for value in values {
+                    let (string, language) = expression::string(value.as_ref()?)?;
+                    strings.push(string.to_string());
+                    languages.insert(language.map(str::to_string));
+                }
+                // NOTE: The language is kept if all values have the same.
+                let language = match languages.len() {
+                    1 => languages.into_iter().next().flatten(),
+                    _ => None,
+                };
+                Some(Term::Literal {
+                    value: strings.join(separator.as_deref().unwrap_or(" ")),
+                    datatype: None,
+                    language,
+                })
+            }
+        }
+    }
+
+    fn construct(&self, template: &[TriplePattern], solutions: &[Solution]) -> Vec<Triple> {
+        let mut triples = vec![];
+        let mut seen = HashSet::new();
+        for solution in solutions {
+            // NOTE: Each solution gets its own blank nodes for the blank nodes of the template.
+            let mut blank_nodes = HashMap::new();
+            let mut term = |pattern: &TermPattern| match pattern {
+                TermPattern::Variable(variable) if is_internal(variable) => Some(
+                    blank_nodes
+                        .entry(variable.clone())
+                        .or_insert_with(|| self.blank_node())
+                        .clone(),
+                ),
+                pattern => instantiate(pattern, solution).cloned(),
+            };
+            for pattern in template {
+                let (Some(subject), Some(predicate), Some(object)) = (
+                    term(&pattern.subject),
+                    term(&pattern.predicate),
+                    term(&pattern.object),
+                ) else {
+                    continue;
+                };
+                let triple = Triple {
+                    subject,
+                    predicate,
+                    object,
+                };
+                let is_valid = !matches!(triple.subject, Term::Literal { .. })
+                    && matches!(triple.predicate, Term::Iri(_));
+                if is_valid && seen.insert(triple.clone()) {
+                    triples.push(triple);
+                }
+            }
+        }
+        triples
+    }
+
+    /// The triples about the resources and their blank nodes, their concise bounded description.
+    fn describe(
+        &self,
+        resources: &[TermPattern],
+        solutions: &[Solution],
+        graph: &Graph,
+    ) -> Vec<Triple> {
+        let mut queue: VecDeque<Term> = VecDeque::new();
+        for resource in resources {
+            match resource {
+                TermPattern::Term(term) => queue.push_back(term.clone()),
+                TermPattern::Variable(variable) => queue.extend(
+                    solutions
+                        .iter()
+                        .filter_map(|solution| solution.get(variable).cloned()),
+                ),
+            }
+        }
+        let mut described = HashSet::new();
+        let mut triples = vec![];
+        while let Some(resource) = queue.pop_front() {
+            if matches!(resource, Term::Literal { .. }) || !described.insert(resource.clone()) {
+                continue;
+            }
+            for triple in graph.matching(Some(&resource), None, None) {
+                if let Term::BlankNode(_) = triple.object {
+                    queue.push_back(triple.object.clone());
+                }
+                triples.push(triple.clone());
+            }
+        }
+        triples
+    }
+}
+
+fn join(left: &[Solution], right: &[Solution]) -> Vec<Solution> {
+    left.iter()
+        .flat_map(|left| {
+            right
+                .iter()
+                .filter(|right| compatible(left, right))
+                .map(|right| merge(left, right))
+        })
+        .collect()
+}
+
+fn inverse(path: &PropertyPath) -> PropertyPath {
+    match path {
+        PropertyPath::Inverse(path) => (**path).clone(),
+        path => PropertyPath::Inverse(Box::new(path.clone())),
+    }
+}
+
+/// The ends of the paths from `start`, with duplicates for alternatives and sequences.
+fn path_targets(start: &Term, path: &PropertyPath, graph: &Graph) -> Vec<Term> {
+    match path {
+        PropertyPath::Link(iri) => graph
+            .matching(Some(start), Some(&Term::Iri(iri.clone())), None)
+            .map(|triple| triple.object.clone())
+            .collect(),
+        PropertyPath::Inverse(path) => match &**path {
+            PropertyPath::Link(iri) => graph
+                .matching(None, Some(&Term::Iri(iri.clone())), Some(start))
+                .map(|triple| triple.subject.clone())
+                .collect(),
+            PropertyPath::Inverse(path) => path_targets(start, path, graph),
+            PropertyPath::Sequence(first, second) => path_targets(
+                start,
+                &PropertyPath::Sequence(Box::new(inverse(second)), Box::new(inverse(first))),
+                graph,
+            ),
+            PropertyPath::Alternative(first, second) => {
+                let mut targets = path_targets(start, &inverse(first), graph);
+                targets.append(&mut path_targets(start, &inverse(second), graph));
+                targets
+            }
+            PropertyPath::ZeroOrMore(path) => path_targets(
+                start,
+                &PropertyPath::ZeroOrMore(Box::new(inverse(path))),
+                graph,
+            ),
+            PropertyPath::OneOrMore(path) => path_targets(
+                start,
+                &PropertyPath::OneOrMore(Box::new(inverse(path))),
+                graph,
+            ),
+            PropertyPath::ZeroOrOne(path) => path_targets(
+                start,
+                &PropertyPath::ZeroOrOne(Box::new(inverse(path))),
+                graph,
+            ),
+            PropertyPath::NegatedPropertySet(members) => path_targets(
+                start,
+                &PropertyPath::NegatedPropertySet(members.iter().map(inverse).collect()),
+                graph,
+            ),
+        },
+        PropertyPath::Sequence(first, second) => path_targets(start, first, graph)
+            .iter()
+            .flat_map(|middle| path_targets(middle, second, graph))
+            .collect(),
+        PropertyPath::Alternative(first, second) => {
+            let mut targets = path_targets(start, first, graph);
+            targets.append(&mut path_targets(start, second, graph));
+            targets
+        }
+        PropertyPath::ZeroOrOne(path) => {
+            let mut targets = vec![start.clone()];
+            for target in path_targets(start, path, graph) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            targets
+        }
+        PropertyPath::ZeroOrMore(path) => reachable(vec![start.clone()], path, graph),
+        PropertyPath::OneOrMore(path) => reachable(path_targets(start, path, graph), path, graph),
+        PropertyPath::NegatedPropertySet(members) => {
+            let (inverted, forward): (Vec<&PropertyPath>, Vec<&PropertyPath>) = members
+                .iter()
+                .partition(|member| matches!(member, PropertyPath::Inverse(_)));
+            let excluded = |members: &[&PropertyPath], predicate: &Term| {
+                members.iter().any(|member| {
+                    let link = match member {
+                        PropertyPath::Inverse(link) => link,
+                        link => *link,
+                    };
+                    matches!((link, predicate), (PropertyPath::Link(iri), Term::Iri(other)) if iri == other)
+                })
+            };
+            let mut targets = vec![];
+            // NOTE: Only inverted members match triples in reverse, `!(^p)` has no forward triples.
+            if !forward.is_empty() || inverted.is_empty() {
+                targets.extend(
+                    graph
+                        .matching(Some(start), None, None)
+                        .filter(|triple| !excluded(&forward, &triple.predicate))
+                        .map(|triple| triple.object.clone()),
+                );
+            }
+            if !inverted.is_empty() {
+                targets.extend(
+                    graph
+                        .matching(None, None, Some(start))
+                        .filter(|triple| !excluded(&inverted, &triple.predicate))
+                        .map(|triple| triple.subject.clone()),
+                );
+            }
+            targets
+        }
+    }
+}
+
+/// The distinct nodes reachable from the `starts` with any number of steps along the path.
+fn reachable(starts: Vec<Term>, path: &PropertyPath, graph: &Graph) -> Vec<Term> {
+    let mut seen: HashSet<Term> = HashSet::new();
+    let mut reached = vec![];
+    let mut queue: VecDeque<Term> = starts.into();
+    while let Some(node) = queue.pop_front() {
+        if seen.insert(node.clone()) {
+            queue.extend(path_targets(&node, path, graph));
+            reached.push(node);
+        }
+    }
+    reached
+}
+
+/// The order of `ORDER BY`: unbound, blank nodes, IRIs and then literals.
+fn order_terms(left: Option<&Term>, right: Option<&Term>) -> Ordering {
+    let rank = |term: Option<&Term>| match term {
+        None => 0,
+        Some(Term::BlankNode(_)) => 1,
+        Some(Term::Iri(_)) => 2,
+        Some(Term::Literal { .. }) => 3,
+    };
+    match (left, right) {
+        (Some(Term::BlankNode(left)), Some(Term::BlankNode(right)))
+        | (Some(Term::Iri(left)), Some(Term::Iri(right))) => left.cmp(right),
+        (Some(left @ Term::Literal { .. }), Some(right @ Term::Literal { .. })) => {
+            expression::compare(left, right).unwrap_or_else(|| {
+                // NOTE: Literals without an order are ordered by their lexical forms.
+                let key = |term: &Term| match term {
+                    Term::Literal {
+                        value,
+                        datatype,
+                        language,
+                    } => (value.clone(), datatype.clone(), language.clone()),
+                    _ => Default::default(),
+                };
+                key(left).cmp(&key(right))
+            })
+        }
+        (left, right) => rank(left).cmp(&rank(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{evaluate, QueryAnswer};
+    use crate::{
+        algebra::Query,
+        rdf::{Dataset, RdfFormat},
+        results::{QueryResults, ResultsFormat},
+    };
+
+    const DATA: &str = indoc! {r#"
+        @prefix ex: <http://example.org/> .
+        ex:alice a ex:Person ; ex:name "Alice" ; ex:age 30 ; ex:knows ex:bob .
+        ex:bob a ex:Person ; ex:name "Bob"@en ; ex:age 25 ; ex:knows ex:carol .
+        ex:carol a ex:Person ; ex:name "Carol" .
+        ex:g1 { ex:alice ex:likes ex:tea }
+    "#};
+
+    fn dataset() -> Dataset {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(DATA, RdfFormat::TriG, "http://example.org/")
+            .unwrap();
+        dataset
+    }
+
+    /// The solutions as CSV, to compare them at a glance.
+    fn csv(query: &str) -> String {
+        let query =
+            Query::parse(&format!("PREFIX ex: <http://example.org/> {}", query), None).unwrap();
+        match evaluate(&query, &dataset()).unwrap() {
+            QueryAnswer::Results(results) => results.serialize(ResultsFormat::Csv),
+            QueryAnswer::Graph(triples) => triples
+                .iter()
+                .map(|triple| format!("{}\n", triple))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn graph_patterns() {
+        assert_eq!(
+            csv("SELECT ?name ?age { ?p a ex:Person ; ex:name ?name OPTIONAL { ?p ex:age ?age } } ORDER BY ?name"),
+            "name,age\r\nAlice,30\r\nBob,25\r\nCarol,\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?p { { ?p ex:age 30 } UNION { ?p ex:knows ex:carol } }"),
+            "p\r\nhttp://example.org/alice\r\nhttp://example.org/bob\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?p { ?p a ex:Person MINUS { ?p ex:age ?age } }"),
+            "p\r\nhttp://example.org/carol\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?x { ex:alice ex:knows+ ?x }"),
+            "x\r\nhttp://example.org/bob\r\nhttp://example.org/carol\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?g ?o { GRAPH ?g { ?s ex:likes ?o } }"),
+            "g,o\r\nhttp://example.org/g1,http://example.org/tea\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?p { ?p a ex:Person FILTER NOT EXISTS { ?p ex:knows ?q } }"),
+            "p\r\nhttp://example.org/carol\r\n"
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(
+            csv(indoc! {r#"
+                SELECT ?p ?next ?label {
+                  ?p ex:age ?age
+                  BIND (?age + 1 AS ?next)
+                  BIND (CONCAT(UCASE(STR(?p)), "-", STRAFTER(STR(?p), "org/")) AS ?label)
+                  FILTER (?age > 26 && REGEX(STR(?p), "ALI", "i"))
+                }
+            "#}),
+            "p,next,label\r\nhttp://example.org/alice,31,HTTP://EXAMPLE.ORG/ALICE-alice\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?x { VALUES ?x { 1 2.5 \"a\" } FILTER (isNUMERIC(?x) && ?x IN (2.5, 3)) }"),
+            "x\r\n2.5\r\n"
+        );
+        assert_eq!(
+            csv(
+                r#"SELECT (<http://www.w3.org/2001/XMLSchema#integer>("12") * 2 AS ?x) (COALESCE(?unbound, LANG("a"@en)) AS ?y) {}"#
+            ),
+            "x,y\r\n24,en\r\n"
+        );
+    }
+
+    #[test]
+    fn aggregates_and_modifiers() {
+        assert_eq!(
+            csv("SELECT (COUNT(*) AS ?n) (SUM(?age) AS ?sum) (AVG(?age) AS ?avg) (MAX(?age) AS ?max) { ?p ex:age ?age }"),
+            "n,sum,avg,max\r\n2,55,27.5,30\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?type (GROUP_CONCAT(?name; SEPARATOR=\"|\") AS ?names) { ?p a ?type ; ex:name ?name } GROUP BY ?type"),
+            "type,names\r\nhttp://example.org/Person,Alice|Bob|Carol\r\n"
+        );
+        assert_eq!(
+            csv("SELECT (COUNT(*) AS ?n) { ?p ex:missing ?o }"),
+            "n\r\n0\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?p { ?p ex:name ?name } ORDER BY DESC(?name) LIMIT 1 OFFSET 1"),
+            "p\r\nhttp://example.org/bob\r\n"
+        );
+        assert_eq!(
+            csv("SELECT DISTINCT ?type { ?p a ?type }"),
+            "type\r\nhttp://example.org/Person\r\n"
+        );
+        assert_eq!(
+            csv("SELECT ?p ?n { ?p a ex:Person { SELECT ?p (COUNT(?q) AS ?n) { ?p ex:knows ?q } GROUP BY ?p } } ORDER BY ?p"),
+            "p,n\r\nhttp://example.org/alice,1\r\nhttp://example.org/bob,1\r\n"
+        );
+    }
+
+    #[test]
+    fn forms() {
+        assert_eq!(
+            csv("ASK { ex:alice ex:knows ex:bob }"),
+            "_askResult\r\ntrue\r\n"
+        );
+        assert_eq!(
+            csv("CONSTRUCT { ?o ex:knownBy ?s } WHERE { ?s ex:knows ?o }"),
+            "<http://example.org/bob> <http://example.org/knownBy> <http://example.org/alice> .\n\
+             <http://example.org/carol> <http://example.org/knownBy> <http://example.org/bob> .\n"
+        );
+        assert_eq!(
+            csv("DESCRIBE ex:carol"),
+            "<http://example.org/carol> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Person> .\n\
+             <http://example.org/carol> <http://example.org/name> \"Carol\" .\n"
+        );
+        let query = Query::parse("SELECT * { SERVICE <http://a> { ?s ?p ?o } }", None).unwrap();
+        assert!(evaluate(&query, &dataset()).is_err());
+        let query =
+            Query::parse("SELECT * FROM <http://example.org/g1> { ?s ?p ?o }", None).unwrap();
+        assert_eq!(
+            evaluate(&query, &dataset()).unwrap(),
+            QueryAnswer::Results(
+                QueryResults::parse(
+                    "s,p,o\r\nhttp://example.org/alice,http://example.org/likes,http://example.org/tea\r\n",
+                    ResultsFormat::Csv
+                )
+                .unwrap()
+            )
+        );
+    }
+}
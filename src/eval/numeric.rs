@@ -0,0 +1,496 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    rdf::{typed, XSD},
+    results::Term,
+};
+
+/// The datatypes derived from `xsd:integer`, their values are integers.
+const INTEGER_TYPES: [&str; 13] = [
+    "integer",
+    "nonPositiveInteger",
+    "negativeInteger",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "nonNegativeInteger",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+    "positiveInteger",
+];
+
+/// The number of fractional digits of quotients of decimals.
+const DIVISION_SCALE: u32 = 18;
+
+/// An `xsd:decimal`, `mantissa / 10^scale` without trailing zeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    fn new(mantissa: i128, scale: u32) -> Self {
+        let mut decimal = Decimal { mantissa, scale };
+        while decimal.scale > 0 && decimal.mantissa % 10 == 0 {
+            decimal.mantissa /= 10;
+            decimal.scale -= 1;
+        }
+        decimal
+    }
+
+    fn integer(value: i128) -> Self {
+        Decimal::new(value, 0)
+    }
+
+    fn parse(lexical: &str) -> Option<Self> {
+        let (negative, digits) = match lexical.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, lexical.strip_prefix('+').unwrap_or(lexical)),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !(integer.chars().chain(fraction.chars())).all(|char| char.is_ascii_digit())
+        {
+            return None;
+        }
+        let mut mantissa: i128 = 0;
+        for digit in integer.chars().chain(fraction.chars()) {
+            mantissa = mantissa
+                .checked_mul(10)?
+                .checked_add(digit.to_digit(10)? as i128)?;
+        }
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Some(Decimal::new(mantissa, fraction.len() as u32))
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        Decimal::parse(&format!("{}", value))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// The mantissas of both decimals with the same scale.
+    fn align(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        Some((
+            self.mantissa
+                .checked_mul(10i128.checked_pow(scale - self.scale)?)?,
+            other
+                .mantissa
+                .checked_mul(10i128.checked_pow(scale - other.scale)?)?,
+            scale,
+        ))
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        let (left, right, scale) = self.align(other)?;
+        Some(Decimal::new(left.checked_add(right)?, scale))
+    }
+
+    fn subtract(self, other: Self) -> Option<Self> {
+        let (left, right, scale) = self.align(other)?;
+        Some(Decimal::new(left.checked_sub(right)?, scale))
+    }
+
+    fn multiply(self, other: Self) -> Option<Self> {
+        Some(Decimal::new(
+            self.mantissa.checked_mul(other.mantissa)?,
+            self.scale + other.scale,
+        ))
+    }
+
+    fn divide(self, other: Self) -> Option<Self> {
+        if other.mantissa == 0 {
+            return None;
+        }
+        // NOTE: Large operands leave less room for fractional digits.
+        (0..=DIVISION_SCALE).rev().find_map(|scale| {
+            let shift = (scale + other.scale).checked_sub(self.scale)?;
+            let numerator = self.mantissa.checked_mul(10i128.checked_pow(shift)?)?;
+            Some(Decimal::new(numerator / other.mantissa, scale))
+        })
+    }
+
+    fn compare(self, other: Self) -> Ordering {
+        match self.align(other) {
+            Some((left, right, _scale)) => left.cmp(&right),
+            None => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+
+    /// The integer part, rounded towards zero.
+    fn truncate(self) -> i128 {
+        self.mantissa / 10i128.pow(self.scale)
+    }
+
+    fn floor(self) -> Self {
+        let integer = self.truncate();
+        match self.mantissa < 0 && Decimal::integer(integer) != self {
+            true => Decimal::integer(integer - 1),
+            false => Decimal::integer(integer),
+        }
+    }
+
+    fn ceil(self) -> Self {
+        let integer = self.truncate();
+        match self.mantissa > 0 && Decimal::integer(integer) != self {
+            true => Decimal::integer(integer + 1),
+            false => Decimal::integer(integer),
+        }
+    }
+
+    /// Rounds halves towards positive infinity, like `fn:round`.
+    fn round(self) -> Self {
+        match self.add(Decimal::new(5, 1)) {
+            Some(decimal) => decimal.floor(),
+            None => self,
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Writes the canonical form, with at least one digit after the point.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        match fraction.is_empty() {
+            true => write!(f, "{}{}.0", sign, integer),
+            false => write!(f, "{}{}.{}", sign, integer, fraction),
+        }
+    }
+}
+
+/// The value of a numeric literal. The variants are ordered by type promotion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Numeric {
+    Integer(i128),
+    Decimal(Decimal),
+    Float(f32),
+    Double(f64),
+}
+
+impl Numeric {
+    /// The value of a numeric literal, `None` for other terms and invalid lexical forms.
+    pub(super) fn from_term(term: &Term) -> Option<Self> {
+        let Term::Literal {
+            value,
+            datatype: Some(datatype),
+            language: None,
+        } = term
+        else {
+            return None;
+        };
+        let datatype = datatype.strip_prefix(XSD)?;
+        if INTEGER_TYPES.contains(&datatype) {
+            let digits = value.strip_prefix(['+', '-']).unwrap_or(value.as_str());
+            if digits.is_empty() || !digits.chars().all(|char| char.is_ascii_digit()) {
+                return None;
+            }
+            return value
+                .strip_prefix('+')
+                .unwrap_or(value)
+                .parse()
+                .ok()
+                .map(Numeric::Integer);
+        }
+        match datatype {
+            "decimal" => Decimal::parse(value).map(Numeric::Decimal),
+            "float" => parse_floating(value).map(|value| Numeric::Float(value as f32)),
+            "double" => parse_floating(value).map(Numeric::Double),
+            _ => None,
+        }
+    }
+
+    /// Whether the term is a literal with a numeric datatype, even with an invalid lexical form.
+    pub(super) fn is_numeric_type(term: &Term) -> bool {
+        match term {
+            Term::Literal {
+                datatype: Some(datatype),
+                ..
+            } => datatype.strip_prefix(XSD).is_some_and(|datatype| {
+                INTEGER_TYPES.contains(&datatype)
+                    || matches!(datatype, "decimal" | "float" | "double")
+            }),
+            _ => false,
+        }
+    }
+
+    pub(super) fn to_term(self) -> Term {
+        match self {
+            Numeric::Integer(value) => typed(&value.to_string(), "integer"),
+            Numeric::Decimal(value) => typed(&value.to_string(), "decimal"),
+            Numeric::Float(value) => typed(&format_floating(value as f64, true), "float"),
+            Numeric::Double(value) => typed(&format_floating(value, false), "double"),
+        }
+    }
+
+    pub(super) fn integer(value: i128) -> Self {
+        Numeric::Integer(value)
+    }
+
+    pub(super) fn to_f64(self) -> f64 {
+        match self {
+            Numeric::Integer(value) => value as f64,
+            Numeric::Decimal(value) => value.to_f64(),
+            Numeric::Float(value) => value as f64,
+            Numeric::Double(value) => value,
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Numeric::Integer(_) => 0,
+            Numeric::Decimal(_) => 1,
+            Numeric::Float(_) => 2,
+            Numeric::Double(_) => 3,
+        }
+    }
+
+    /// The value promoted to the type of the given rank.
+    fn promote(self, rank: u8) -> Self {
+        match (self, rank) {
+            (Numeric::Integer(value), 1) => Numeric::Decimal(Decimal::integer(value)),
+            (numeric, 2) if numeric.rank() < 2 => Numeric::Float(numeric.to_f64() as f32),
+            (numeric, 3) if numeric.rank() < 3 => Numeric::Double(numeric.to_f64()),
+            (numeric, _) => numeric,
+        }
+    }
+
+    fn promoted(self, other: Self) -> (Self, Self) {
+        let rank = self.rank().max(other.rank());
+        (self.promote(rank), other.promote(rank))
+    }
+
+    pub(super) fn add(self, other: Self) -> Option<Self> {
+        match self.promoted(other) {
+            (Numeric::Integer(left), Numeric::Integer(right)) => {
+                left.checked_add(right).map(Numeric::Integer)
+            }
+            (Numeric::Decimal(left), Numeric::Decimal(right)) => {
+                left.add(right).map(Numeric::Decimal)
+            }
+            (Numeric::Float(left), Numeric::Float(right)) => Some(Numeric::Float(left + right)),
+            (left, right) => Some(Numeric::Double(left.to_f64() + right.to_f64())),
+        }
+    }
+
+    pub(super) fn subtract(self, other: Self) -> Option<Self> {
+        match self.promoted(other) {
+            (Numeric::Integer(left), Numeric::Integer(right)) => {
+                left.checked_sub(right).map(Numeric::Integer)
+            }
+            (Numeric::Decimal(left), Numeric::Decimal(right)) => {
+                left.subtract(right).map(Numeric::Decimal)
+            }
+            (Numeric::Float(left), Numeric::Float(right)) => Some(Numeric::Float(left - right)),
+            (left, right) => Some(Numeric::Double(left.to_f64() - right.to_f64())),
+        }
+    }
+
+    pub(super) fn multiply(self, other: Self) -> Option<Self> {
+        match self.promoted(other) {
+            (Numeric::Integer(left), Numeric::Integer(right)) => {
+                left.checked_mul(right).map(Numeric::Integer)
+            }
+            (Numeric::Decimal(left), Numeric::Decimal(right)) => {
+                left.multiply(right).map(Numeric::Decimal)
+            }
+            (Numeric::Float(left), Numeric::Float(right)) => Some(Numeric::Float(left * right)),
+            (left, right) => Some(Numeric::Double(left.to_f64() * right.to_f64())),
+        }
+    }
+
+    /// Divides the numbers, the quotient of integers is a decimal.
+    pub(super) fn divide(self, other: Self) -> Option<Self> {
+        match self.promoted(other) {
+            (Numeric::Integer(left), Numeric::Integer(right)) => Decimal::integer(left)
+                .divide(Decimal::integer(right))
+                .map(Numeric::Decimal),
+            (Numeric::Decimal(left), Numeric::Decimal(right)) => {
+                left.divide(right).map(Numeric::Decimal)
+            }
+            (Numeric::Float(left), Numeric::Float(right)) => Some(Numeric::Float(left / right)),
+            (left, right) => Some(Numeric::Double(left.to_f64() / right.to_f64())),
+        }
+    }
+
+    pub(super) fn negate(self) -> Option<Self> {
+        match self {
+            Numeric::Integer(value) => value.checked_neg().map(Numeric::Integer),
+            Numeric::Decimal(value) => Decimal::integer(0).subtract(value).map(Numeric::Decimal),
+            Numeric::Float(value) => Some(Numeric::Float(-value)),
+            Numeric::Double(value) => Some(Numeric::Double(-value)),
+        }
+    }
+
+    /// Compares the numbers, `None` if one is `NaN`.
+    pub(super) fn compare(self, other: Self) -> Option<Ordering> {
+        match self.promoted(other) {
+            (Numeric::Integer(left), Numeric::Integer(right)) => Some(left.cmp(&right)),
+            (Numeric::Decimal(left), Numeric::Decimal(right)) => Some(left.compare(right)),
+            (left, right) => left.to_f64().partial_cmp(&right.to_f64()),
+        }
+    }
+
+    pub(super) fn is_zero_or_nan(self) -> bool {
+        match self {
+            Numeric::Integer(value) => value == 0,
+            Numeric::Decimal(value) => value.mantissa == 0,
+            numeric => numeric.to_f64() == 0.0 || numeric.to_f64().is_nan(),
+        }
+    }
+
+    pub(super) fn abs(self) -> Option<Self> {
+        match self.compare(Numeric::Integer(0)) {
+            Some(Ordering::Less) => self.negate(),
+            _ => Some(self),
+        }
+    }
+
+    pub(super) fn floor(self) -> Self {
+        match self {
+            Numeric::Decimal(value) => Numeric::Decimal(value.floor()),
+            Numeric::Float(value) => Numeric::Float(value.floor()),
+            Numeric::Double(value) => Numeric::Double(value.floor()),
+            integer => integer,
+        }
+    }
+
+    pub(super) fn ceil(self) -> Self {
+        match self {
+            Numeric::Decimal(value) => Numeric::Decimal(value.ceil()),
+            Numeric::Float(value) => Numeric::Float(value.ceil()),
+            Numeric::Double(value) => Numeric::Double(value.ceil()),
+            integer => integer,
+        }
+    }
+
+    pub(super) fn round(self) -> Self {
+        match self {
+            Numeric::Decimal(value) => Numeric::Decimal(value.round()),
+            Numeric::Float(value) => Numeric::Float((value + 0.5).floor()),
+            Numeric::Double(value) => Numeric::Double((value + 0.5).floor()),
+            integer => integer,
+        }
+    }
+
+    /// The value as integer, rounded towards zero. `None` for infinite and `NaN` values.
+    pub(super) fn to_integer(self) -> Option<i128> {
+        match self {
+            Numeric::Integer(value) => Some(value),
+            Numeric::Decimal(value) => Some(value.truncate()),
+            numeric => {
+                let value = numeric.to_f64();
+                (value.is_finite() && value.abs() < i128::MAX as f64).then(|| value.trunc() as i128)
+            }
+        }
+    }
+
+    pub(super) fn to_decimal(self) -> Option<Self> {
+        match self {
+            Numeric::Integer(value) => Some(Numeric::Decimal(Decimal::integer(value))),
+            Numeric::Decimal(value) => Some(Numeric::Decimal(value)),
+            numeric => Decimal::from_f64(numeric.to_f64()).map(Numeric::Decimal),
+        }
+    }
+
+    /// Parses the lexical form of an integer, decimal or double for casts from strings.
+    pub(super) fn parse(lexical: &str, datatype: &str) -> Option<Self> {
+        Numeric::from_term(&typed(lexical.trim(), datatype))
+    }
+}
+
+/// Parses the lexical form of an `xsd:double` or `xsd:float`.
+fn parse_floating(lexical: &str) -> Option<f64> {
+    match lexical {
+        "INF" | "+INF" => Some(f64::INFINITY),
+        "-INF" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ if lexical
+            .chars()
+            .all(|char| char.is_ascii_digit() || matches!(char, '+' | '-' | '.' | 'e' | 'E')) =>
+        {
+            lexical.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// The canonical form of a double or float, like `1.5E0`.
+fn format_floating(value: f64, is_float: bool) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+    let formatted = match is_float {
+        true => format!("{:E}", value as f32),
+        false => format!("{:E}", value),
+    };
+    match formatted.split_once('E') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{}.0E{}", mantissa, exponent)
+        }
+        _ => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::{Decimal, Numeric};
+    use crate::rdf::typed;
+
+    fn numeric(value: &str, datatype: &str) -> Numeric {
+        Numeric::from_term(&typed(value, datatype)).unwrap()
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(Decimal::parse("+01.50").unwrap().to_string(), "1.5");
+        assert_eq!(Decimal::parse("-.5").unwrap().to_string(), "-0.5");
+        assert_eq!(Decimal::parse("3").unwrap().to_string(), "3.0");
+        assert_eq!(Decimal::parse("1e3"), None);
+        let third = Decimal::integer(1).divide(Decimal::integer(3)).unwrap();
+        assert_eq!(third.to_string(), "0.333333333333333333");
+        assert_eq!(Decimal::parse("-2.5").unwrap().round().to_string(), "-2.0");
+        assert_eq!(Decimal::parse("2.5").unwrap().round().to_string(), "3.0");
+        assert_eq!(Decimal::parse("-2.5").unwrap().floor().to_string(), "-3.0");
+    }
+
+    #[test]
+    fn promotion() {
+        let sum = numeric("1", "integer")
+            .add(numeric("0.5", "decimal"))
+            .unwrap();
+        assert_eq!(sum.to_term(), typed("1.5", "decimal"));
+        let product = numeric("2", "int").multiply(numeric("1.5e0", "double"));
+        assert_eq!(product.unwrap().to_term(), typed("3.0E0", "double"));
+        let quotient = numeric("3", "integer").divide(numeric("2", "integer"));
+        assert_eq!(quotient.unwrap().to_term(), typed("1.5", "decimal"));
+        assert_eq!(
+            numeric("1", "integer").divide(numeric("0", "integer")),
+            None
+        );
+        assert_eq!(
+            numeric("1", "integer").compare(numeric("1.0", "decimal")),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(Numeric::from_term(&typed("a", "integer")), None);
+        assert_eq!(numeric("-INF", "double").to_term(), typed("-INF", "double"));
+    }
+}
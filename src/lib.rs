@@ -1,14 +1,44 @@
+//! A formatter, linter and language server for SPARQL.
+//!
+//! ```
+//! let formatted = fichu::format("select * {?s ?p ?o}", &fichu::FormatSettings::default()).unwrap();
+//! assert_eq!(formatted, "SELECT * {\n  ?s ?p ?o\n}");
+//! ```
+
+mod algebra;
 mod analysis;
+// NOTE: The entry point of the binary and the Python module, not part of the library API.
+#[cfg(not(target_family = "wasm"))]
+#[doc(hidden)]
+pub mod cli;
+mod endpoint;
+mod eval;
 mod lsp;
+#[cfg(feature = "python")]
+mod python;
+mod rdf;
+mod results;
 mod rpc;
 mod server;
+mod wasm;
 
-use server::Server;
+pub use lsp::{
+    textdocument::{Position, Range},
+    Diagnostic, DiagnosticSeverity,
+};
+pub use server::{
+    check,
+    configuration::{
+        EndpointSettings, FormatSettings, FormatSettingsBuilder, LintLevel, LintSettings, Settings,
+        SettingsBuilder, SyntaxErrorPolicy,
+    },
+    format, format_raw, FormatError, Server,
+};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub fn init_language_server() -> Server {
     #[cfg(target_arch = "wasm32")]
     wasm_logger::init(wasm_logger::Config::default());
-    return Server::new();
+    Server::new()
 }
@@ -1,29 +1,52 @@
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
+use super::{Markupkind, SemanticTokensLegend};
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerCapabilities {
-    text_document_sync: TextDocumentSyncKind,
+    position_encoding: PositionEncodingKind,
+    text_document_sync: TextDocumentSyncOptions,
     hover_provider: bool,
     completion_provider: CompletionOptions,
     document_formatting_provider: DocumentFormattingOptions,
-    diagnostic_provider: DiagnosticOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diagnostic_provider: Option<DiagnosticOptions>,
+    document_symbol_provider: bool,
+    folding_range_provider: bool,
+    selection_range_provider: bool,
+    semantic_tokens_provider: SemanticTokensOptions,
+    signature_help_provider: SignatureHelpOptions,
+    execute_command_provider: ExecuteCommandOptions,
+    code_action_provider: CodeActionOptions,
 }
 
 impl ServerCapabilities {
-    pub fn new() -> Self {
+    /// The capabilities of the server, adapted to what the client supports.
+    pub fn new(client_capabilities: &ClientCapabilities) -> Self {
         Self {
-            text_document_sync: TextDocumentSyncKind::Full,
+            position_encoding: client_capabilities.position_encoding(),
+            text_document_sync: TextDocumentSyncOptions::new(),
             hover_provider: true,
             completion_provider: CompletionOptions::new(),
             document_formatting_provider: DocumentFormattingOptions {},
-            diagnostic_provider: DiagnosticOptions::new(),
+            diagnostic_provider: client_capabilities
+                .pull_diagnostics()
+                .then(DiagnosticOptions::new),
+            document_symbol_provider: true,
+            folding_range_provider: true,
+            selection_range_provider: true,
+            semantic_tokens_provider: SemanticTokensOptions::new(),
+            signature_help_provider: SignatureHelpOptions::new(),
+            execute_command_provider: ExecuteCommandOptions::new(),
+            code_action_provider: CodeActionOptions::new(),
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 struct DiagnosticOptions {
     identifier: String,
     inter_file_dependencies: bool,
@@ -40,6 +63,45 @@ impl DiagnosticOptions {
     }
 }
 
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#positionEncodingKind
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum PositionEncodingKind {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-16")]
+    #[default]
+    Utf16,
+    /// Counts unicode code points, like the indices of Python strings.
+    #[serde(rename = "utf-32")]
+    Utf32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TextDocumentSyncOptions {
+    open_close: bool,
+    change: TextDocumentSyncKind,
+    save: SaveOptions,
+}
+
+impl TextDocumentSyncOptions {
+    fn new() -> Self {
+        Self {
+            open_close: true,
+            change: TextDocumentSyncKind::Full,
+            save: SaveOptions {
+                include_text: false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SaveOptions {
+    include_text: bool,
+}
+
 #[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq)]
 #[repr(u8)]
 pub enum TextDocumentSyncKind {
@@ -64,24 +126,214 @@ impl CompletionOptions {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SignatureHelpOptions {
+    trigger_characters: Vec<String>,
+}
+
+impl SignatureHelpOptions {
+    fn new() -> Self {
+        Self {
+            trigger_characters: vec!["(".to_string(), ",".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ExecuteCommandOptions {
+    commands: Vec<String>,
+}
+
+impl ExecuteCommandOptions {
+    fn new() -> Self {
+        Self {
+            commands: vec![
+                "fichu.executeQuery".to_string(),
+                "fichu.showAlgebra".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CodeActionOptions {
+    code_action_kinds: Vec<String>,
+}
+
+impl CodeActionOptions {
+    fn new() -> Self {
+        Self {
+            code_action_kinds: vec![
+                "refactor.rewrite".to_string(),
+                "refactor.extract".to_string(),
+            ],
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct DocumentFormattingOptions {
     // WARNING: This could also inherit WorkDoneProgressOptions (not implemented yet).
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SemanticTokensOptions {
+    legend: SemanticTokensLegend,
+    range: bool,
+    full: SemanticTokensFullOptions,
+}
+
+impl SemanticTokensOptions {
+    fn new() -> Self {
+        Self {
+            legend: SemanticTokensLegend::new(),
+            range: true,
+            full: SemanticTokensFullOptions { delta: true },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SemanticTokensFullOptions {
+    delta: bool,
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#clientCapabilities
+// WARNING: Only the capabilities the server makes use of are parsed.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClientCapabilities {
+    workspace: WorkspaceClientCapabilities,
+    text_document: TextDocumentClientCapabilities,
+    general: GeneralClientCapabilities,
+}
+
+impl ClientCapabilities {
+    /// UTF-8 if the client supports it, else the mandatory UTF-16.
+    pub fn position_encoding(&self) -> PositionEncodingKind {
+        match self
+            .general
+            .position_encodings
+            .iter()
+            .any(|encoding| encoding == "utf-8")
+        {
+            true => PositionEncodingKind::Utf8,
+            false => PositionEncodingKind::Utf16,
+        }
+    }
+
+    pub fn snippet_support(&self) -> bool {
+        self.text_document
+            .completion
+            .completion_item
+            .snippet_support
+    }
+
+    /// Clients that don't state the supported formats get markdown.
+    pub fn markdown_support(&self) -> bool {
+        self.text_document
+            .hover
+            .content_format
+            .as_ref()
+            .is_none_or(|formats| formats.contains(&Markupkind::Markdown))
+    }
+
+    /// Whether the client requests diagnostics, otherwise they are published.
+    pub fn pull_diagnostics(&self) -> bool {
+        self.text_document.diagnostic.is_some()
+    }
+
+    pub fn configuration_registration(&self) -> bool {
+        self.workspace.did_change_configuration.dynamic_registration
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct WorkspaceClientCapabilities {
+    did_change_configuration: DynamicRegistrationCapabilities,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct TextDocumentClientCapabilities {
+    completion: CompletionClientCapabilities,
+    hover: HoverClientCapabilities,
+    diagnostic: Option<DynamicRegistrationCapabilities>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct CompletionClientCapabilities {
+    completion_item: CompletionItemCapabilities,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct CompletionItemCapabilities {
+    snippet_support: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct HoverClientCapabilities {
+    content_format: Option<Vec<Markupkind>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct GeneralClientCapabilities {
+    /// Not an enum, clients may support encodings the server does not know.
+    position_encodings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct DynamicRegistrationCapabilities {
+    dynamic_registration: bool,
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ServerCapabilities;
+    use super::{ClientCapabilities, PositionEncodingKind, ServerCapabilities};
 
     #[test]
     fn serialize() {
-        let server_capabilities = ServerCapabilities::new();
+        let server_capabilities = ServerCapabilities::new(&ClientCapabilities::default());
 
         let serialized = serde_json::to_string(&server_capabilities).unwrap();
 
         assert_eq!(
             serialized,
-            "{\"textDocumentSync\":1,\"hoverProvider\":true,\"completionProvider\":{\"triggerCharacters\":[\"?\"]},\"documentFormattingProvider\":{},\"diagnosticProvider\":{\"identifier\":\"sparql-ls\",\"inter_file_dependencies\":false,\"workspace_diagnostics\":false}}"
+            "{\"positionEncoding\":\"utf-16\",\"textDocumentSync\":{\"openClose\":true,\"change\":1,\"save\":{\"includeText\":false}},\"hoverProvider\":true,\"completionProvider\":{\"triggerCharacters\":[\"?\"]},\"documentFormattingProvider\":{},\"documentSymbolProvider\":true,\"foldingRangeProvider\":true,\"selectionRangeProvider\":true,\"semanticTokensProvider\":{\"legend\":{\"tokenTypes\":[\"keyword\",\"function\",\"variable\",\"namespace\",\"property\",\"iri\",\"string\",\"number\",\"type\",\"decorator\",\"blankNode\",\"comment\"],\"tokenModifiers\":[\"projected\",\"local\"]},\"range\":true,\"full\":{\"delta\":true}},\"signatureHelpProvider\":{\"triggerCharacters\":[\"(\",\",\"]},\"executeCommandProvider\":{\"commands\":[\"fichu.executeQuery\",\"fichu.showAlgebra\"]},\"codeActionProvider\":{\"codeActionKinds\":[\"refactor.rewrite\",\"refactor.extract\"]}}"
         );
     }
+
+    #[test]
+    fn negotiate() {
+        let client_capabilities: ClientCapabilities = serde_json::from_str(
+            r#"{"general":{"positionEncodings":["utf-32","utf-8","utf-16"]},"textDocument":{"completion":{"completionItem":{"snippetSupport":true}},"hover":{"contentFormat":["plaintext"]},"diagnostic":{"dynamicRegistration":false}},"workspace":{"didChangeConfiguration":{"dynamicRegistration":true}},"window":{"workDoneProgress":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            client_capabilities.position_encoding(),
+            PositionEncodingKind::Utf8
+        );
+        assert!(client_capabilities.snippet_support());
+        assert!(!client_capabilities.markdown_support());
+        assert!(client_capabilities.pull_diagnostics());
+        assert!(client_capabilities.configuration_registration());
+        let serialized =
+            serde_json::to_string(&ServerCapabilities::new(&client_capabilities)).unwrap();
+        assert!(serialized.starts_with(r#"{"positionEncoding":"utf-8","#));
+        assert!(serialized.contains(r#""diagnosticProvider":{"identifier":"sparql-ls","interFileDependencies":false,"workspaceDiagnostics":false}"#));
+
+        let defaults = ClientCapabilities::default();
+        assert_eq!(defaults.position_encoding(), PositionEncodingKind::Utf16);
+        assert!(!defaults.snippet_support());
+        assert!(defaults.markdown_support());
+        assert!(!defaults.pull_diagnostics());
+    }
 }
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::{BaseMessage, RequestId, RequestMessage};
+
+/// A request of the server to register a capability dynamically.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct RegistrationRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: RegistrationParams,
+}
+
+impl RegistrationRequest {
+    pub(crate) fn new(id: RequestId, registrations: Vec<Registration>) -> Self {
+        Self {
+            base: RequestMessage {
+                base: BaseMessage::new("client/registerCapability".to_string()),
+                id,
+            },
+            params: RegistrationParams { registrations },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct RegistrationParams {
+    registrations: Vec<Registration>,
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#registration
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Registration {
+    /// The id to unregister the capability with, the method is used.
+    pub id: String,
+    pub method: String,
+}
+
+impl Registration {
+    pub(crate) fn new(method: &str) -> Self {
+        Self {
+            id: method.to_string(),
+            method: method.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rpc::RequestId;
+
+    use super::{Registration, RegistrationRequest};
+
+    #[test]
+    fn serialize() {
+        let request = RegistrationRequest::new(
+            RequestId::Integer(1),
+            vec![Registration::new("workspace/didChangeConfiguration")],
+        );
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"jsonrpc":"2.0","method":"client/registerCapability","id":1,"params":{"registrations":[{"id":"workspace/didChangeConfiguration","method":"workspace/didChangeConfiguration"}]}}"#
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    lsp::capabilities::ServerCapabilities,
-    rpc::{RequestMessage, ResponseMessage},
+    lsp::capabilities::{ClientCapabilities, ServerCapabilities},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
 };
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -14,8 +14,11 @@ pub struct ClientInfo {
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct InitializeParams {
-    // WARNING: This is not to Spec! It's optional
-    pub client_info: ClientInfo,
+    pub client_info: Option<ClientInfo>,
+    #[serde(default)]
+    pub capabilities: ClientCapabilities,
+    /// The settings of the client, in the structure of the settings file.
+    pub initialization_options: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -46,17 +49,14 @@ pub struct InitializeResonse {
 }
 
 impl InitializeResonse {
-    pub fn new(id: u32) -> Self {
+    pub fn new(id: RequestId, capabilities: ServerCapabilities) -> Self {
         InitializeResonse {
-            base: ResponseMessage {
-                jsonrpc: "2.0".to_string(),
-                id,
-            },
+            base: ResponseMessage::new(id),
             result: InitializeResult {
-                capabilities: ServerCapabilities::new(),
+                capabilities,
                 server_info: Some(ServerInfo {
-                    name: "lsping".to_string(),
-                    version: Some("0.0.0.1".to_string()),
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: Some(env!("CARGO_PKG_VERSION").to_string()),
                 }),
             },
         }
@@ -66,11 +66,14 @@ impl InitializeResonse {
 #[cfg(test)]
 mod tests {
     use crate::{
-        lsp::ClientInfo,
-        rpc::{BaseMessage, RequestMessage},
+        lsp::{
+            capabilities::{ClientCapabilities, ServerCapabilities},
+            ClientInfo,
+        },
+        rpc::{BaseMessage, RequestId, RequestMessage},
     };
 
-    use super::{InitializeParams, InitializeRequest};
+    use super::{InitializeParams, InitializeRequest, InitializeResonse};
 
     #[test]
     fn deserialize() {
@@ -84,15 +87,41 @@ mod tests {
                         jsonrpc: "2.0".to_string(),
                         method: "initialize".to_string()
                     },
-                    id: 1,
+                    id: RequestId::Integer(1),
                 },
                 params: InitializeParams {
-                    client_info: ClientInfo {
+                    client_info: Some(ClientInfo {
                         name: "dings".to_string(),
                         version: Some("42.1".to_string())
-                    }
+                    }),
+                    capabilities: ClientCapabilities::default(),
+                    initialization_options: None,
                 }
             }
         );
     }
+
+    #[test]
+    fn deserialize_minimal() {
+        let message = br#"{"jsonrpc":"2.0","id":"init","method":"initialize","params":{"processId":null,"capabilities":{},"initializationOptions":{"format":{"tab_size":4}}}}"#;
+        let init_request: InitializeRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(init_request.params.client_info, None);
+        assert_eq!(
+            init_request.params.initialization_options,
+            Some(serde_json::json!({"format": {"tab_size": 4}}))
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let response = InitializeResonse::new(
+            RequestId::Integer(1),
+            ServerCapabilities::new(&ClientCapabilities::default()),
+        );
+        let serialized = serde_json::to_string(&response).unwrap();
+        assert!(serialized.ends_with(&format!(
+            r#""serverInfo":{{"name":"fichu","version":"{}"}}}}}}"#,
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
 }
@@ -1,20 +1,42 @@
+mod client_registercapability;
 mod initialize;
 mod shutdown;
+mod textdocument_codeaction;
 mod textdocument_completion;
 mod textdocument_diagnostic;
 mod textdocument_didchange;
+mod textdocument_didclose;
 mod textdocument_didopen;
+mod textdocument_didsave;
+mod textdocument_documentsymbol;
+mod textdocument_foldingrange;
 mod textdocument_formatting;
 mod textdocument_hover;
 mod textdocument_publishdiagnostics;
+mod textdocument_selectionrange;
+mod textdocument_semantictokens;
+mod textdocument_signaturehelp;
 mod utils;
+mod workspace_didchangeconfiguration;
+mod workspace_executecommand;
 
+pub use client_registercapability::*;
 pub use initialize::*;
 pub use shutdown::*;
+pub use textdocument_codeaction::*;
 pub use textdocument_completion::*;
 pub use textdocument_diagnostic::*;
 pub use textdocument_didchange::*;
+pub use textdocument_didclose::*;
 pub use textdocument_didopen::*;
+pub use textdocument_didsave::*;
+pub use textdocument_documentsymbol::*;
+pub use textdocument_foldingrange::*;
 pub use textdocument_formatting::*;
 pub use textdocument_hover::*;
 pub use textdocument_publishdiagnostics::*;
+pub use textdocument_selectionrange::*;
+pub use textdocument_semantictokens::*;
+pub use textdocument_signaturehelp::*;
+pub use workspace_didchangeconfiguration::*;
+pub use workspace_executecommand::*;
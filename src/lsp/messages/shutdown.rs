@@ -1,4 +1,4 @@
-use crate::rpc::ResponseMessage;
+use crate::rpc::{RequestId, ResponseMessage};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -9,12 +9,9 @@ pub struct ShutdownResponse {
 }
 
 impl ShutdownResponse {
-    pub fn new(id: u32) -> Self {
+    pub fn new(id: RequestId) -> Self {
         Self {
-            base: ResponseMessage {
-                jsonrpc: "2.0".to_string(),
-                id,
-            },
+            base: ResponseMessage::new(id),
             result: None,
         }
     }
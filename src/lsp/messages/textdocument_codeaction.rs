@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsp::textdocument::{Range, TextDocumentIdentifier, TextEdit},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CodeActionRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: CodeActionParams,
+}
+
+impl CodeActionRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+
+    pub(crate) fn get_range(&self) -> &Range {
+        &self.params.range
+    }
+
+    /// The requested kinds of code actions, `None` if all kinds are requested.
+    pub(crate) fn get_only(&self) -> Option<&Vec<String>> {
+        self.params.context.only.as_ref()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CodeActionParams {
+    text_document: TextDocumentIdentifier,
+    range: Range,
+    context: CodeActionContext,
+}
+
+// WARNING: The diagnostics and the trigger kind of the context are ignored.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct CodeActionContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    only: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CodeActionResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: Vec<CodeAction>,
+}
+
+impl CodeActionResponse {
+    pub(crate) fn new(id: RequestId, code_actions: Vec<CodeAction>) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: code_actions,
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#codeAction
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CodeAction {
+    pub title: String,
+    /// A hierarchical kind like `refactor.rewrite`.
+    pub kind: String,
+    pub edit: WorkspaceEdit,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceEdit {
+    /// The edits of each document, by URI.
+    pub changes: HashMap<String, Vec<TextEdit>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        lsp::textdocument::{Position, Range, TextEdit},
+        rpc::RequestId,
+    };
+
+    use super::{CodeAction, CodeActionRequest, CodeActionResponse, WorkspaceEdit};
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/codeAction","id":5,"params":{"textDocument":{"uri":"file:///dings"},"range":{"start":{"line":1,"character":2},"end":{"line":1,"character":4}},"context":{"diagnostics":[],"only":["refactor"],"triggerKind":1}}}"#;
+        let request: CodeActionRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(5));
+        assert_eq!(request.get_document_uri(), "file:///dings");
+        assert_eq!(request.get_range().end, Position::new(1, 4));
+        assert_eq!(request.get_only(), Some(&vec!["refactor".to_string()]));
+
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/codeAction","id":5,"params":{"textDocument":{"uri":"file:///dings"},"range":{"start":{"line":1,"character":2},"end":{"line":1,"character":2}},"context":{"diagnostics":[]}}}"#;
+        let request: CodeActionRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_only(), None);
+    }
+
+    #[test]
+    fn serialize() {
+        let response = CodeActionResponse::new(
+            RequestId::Integer(5),
+            vec![CodeAction {
+                title: "Wrap in OPTIONAL".to_string(),
+                kind: "refactor.rewrite".to_string(),
+                edit: WorkspaceEdit {
+                    changes: HashMap::from([(
+                        "file:///dings".to_string(),
+                        vec![TextEdit::new(Range::new(0, 1, 0, 2), "x".to_string())],
+                    )]),
+                },
+            }],
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":5,"result":[{"title":"Wrap in OPTIONAL","kind":"refactor.rewrite","edit":{"changes":{"file:///dings":[{"range":{"start":{"line":0,"character":1},"end":{"line":0,"character":2}},"newText":"x"}]}}}]}"#
+        );
+    }
+}
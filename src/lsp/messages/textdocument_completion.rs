@@ -3,7 +3,7 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
     lsp::textdocument::Position,
-    rpc::{RequestMessage, ResponseMessage},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
 };
 
 use super::utils::TextDocumentPositionParams;
@@ -24,12 +24,12 @@ impl CompletionRequest {
         &self.params.base.text_document.uri
     }
 
-    pub(crate) fn get_id(&self) -> u32 {
-        self.base.id
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
     }
 
     pub(crate) fn get_completion_context(&self) -> &CompletionContext {
-        return &self.params.context;
+        &self.params.context
     }
 }
 
@@ -62,64 +62,73 @@ pub struct CompletionResponse {
     result: CompletionResult,
 }
 
+const KEYWORD_SNIPPETS: &[(&str, &str, &str)] = &[
+    ("SELECT", "SELECT ${1:*} WHERE {\n  $0\n}", "Select query"),
+    (
+        "PREFIX",
+        "PREFIX ${1:namespace}: <${0:iri}>",
+        "Declare a namespace",
+    ),
+    ("FILTER", "FILTER ( $0 )", "Filter the results"),
+    (
+        "ORDER BY",
+        "ORDER BY ${1|ASC,DESC|} ( $0 )",
+        "Sort the results",
+    ),
+];
+
 impl CompletionResponse {
-    pub fn new(id: u32) -> Self {
+    pub fn new(id: RequestId, snippet_support: bool) -> Self {
         CompletionResponse {
             base: ResponseMessage::new(id),
             result: CompletionResult {
-                items: vec![
-                    CompletionItem {
-                        label: "SELECT".to_string(),
-                        insert_text: "SELECT ${1:*} WHERE {\n  $0\n}".to_string(),
-                        kind: CompletionItemKind::Snippet,
-                        detail: "Select query".to_string(),
-                        insert_text_format: InsertTextFormat::Snippet,
-                    },
-                    CompletionItem {
-                        label: "PREFIX".to_string(),
-                        insert_text: "PREFIX ${1:namespace}: <${0:iri}>".to_string(),
-                        kind: CompletionItemKind::Snippet,
-                        detail: "Declare a namespace".to_string(),
-                        insert_text_format: InsertTextFormat::Snippet,
-                    },
-                    CompletionItem {
-                        label: "FILTER".to_string(),
-                        insert_text: "FILTER ( $0 )".to_string(),
-                        kind: CompletionItemKind::Snippet,
-                        detail: "Filter the results".to_string(),
-                        insert_text_format: InsertTextFormat::Snippet,
-                    },
-                    CompletionItem {
-                        label: "ORDER BY".to_string(),
-                        insert_text: "ORDER BY ${1|ASC,DESC|} ( $0 )".to_string(),
-                        kind: CompletionItemKind::Snippet,
-                        detail: "Sort the results".to_string(),
-                        insert_text_format: InsertTextFormat::Snippet,
-                    },
-                ],
+                items: CompletionItem::keywords(snippet_support),
             },
         }
     }
 
-    pub(crate) fn from_variables(id: u32, variables: Vec<String>) -> Self {
+    pub(crate) fn from_variables(id: RequestId, variables: Vec<String>) -> Self {
         CompletionResponse {
             base: ResponseMessage::new(id),
             result: CompletionResult {
-                items: variables
-                    .iter()
-                    .map(|variable| CompletionItem {
-                        label: variable.to_owned(),
-                        insert_text: variable.to_owned(),
-                        kind: CompletionItemKind::Variable,
-                        detail: "".to_string(),
-                        insert_text_format: InsertTextFormat::Snippet,
-                    })
-                    .collect(),
+                items: CompletionItem::variables(variables),
             },
         }
     }
 }
 
+/// The text a snippet inserts: without tabstops and with the first option of each choice.
+fn snippet_text(snippet: &str) -> String {
+    let mut text = String::new();
+    let mut chars = snippet.chars().peekable();
+    let mut open_placeholders = 0;
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => text.extend(chars.next()),
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+                match chars.next() {
+                    Some(':') => open_placeholders += 1,
+                    Some('|') => {
+                        let choices: String =
+                            chars.by_ref().take_while(|char| *char != '|').collect();
+                        text += choices.split(',').next().unwrap_or_default();
+                        chars.next();
+                    }
+                    _ => {}
+                }
+            }
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+            }
+            '}' if open_placeholders > 0 => open_placeholders -= 1,
+            char => text.push(char),
+        }
+    }
+    text
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct CompletionResult {
     items: Vec<CompletionItem>,
@@ -127,7 +136,7 @@ struct CompletionResult {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct CompletionItem {
+pub struct CompletionItem {
     label: String,
     kind: CompletionItemKind,
     detail: String,
@@ -135,6 +144,42 @@ struct CompletionItem {
     insert_text_format: InsertTextFormat,
 }
 
+impl CompletionItem {
+    /// The keyword snippets, as plain text if the client does not support snippets.
+    pub(crate) fn keywords(snippet_support: bool) -> Vec<Self> {
+        KEYWORD_SNIPPETS
+            .iter()
+            .map(|(label, snippet, detail)| CompletionItem {
+                label: label.to_string(),
+                insert_text: match snippet_support {
+                    true => snippet.to_string(),
+                    false => snippet_text(snippet),
+                },
+                kind: CompletionItemKind::Snippet,
+                detail: detail.to_string(),
+                insert_text_format: match snippet_support {
+                    true => InsertTextFormat::Snippet,
+                    false => InsertTextFormat::PlainText,
+                },
+            })
+            .collect()
+    }
+
+    pub(crate) fn variables(variables: Vec<String>) -> Vec<Self> {
+        variables
+            .into_iter()
+            .map(|variable| CompletionItem {
+                label: variable.clone(),
+                insert_text: variable,
+                kind: CompletionItemKind::Variable,
+                detail: "".to_string(),
+                // `$` starts variables as well as tabstops.
+                insert_text_format: InsertTextFormat::PlainText,
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq)]
 #[repr(u8)]
 enum CompletionItemKind {
@@ -180,10 +225,10 @@ mod tests {
             textdocument::{Position, TextDocumentIdentifier},
             CompletionContext, CompletionParams,
         },
-        rpc::{BaseMessage, RequestMessage},
+        rpc::{BaseMessage, RequestId, RequestMessage},
     };
 
-    use super::{CompletionRequest, CompletionResponse};
+    use super::{snippet_text, CompletionRequest, CompletionResponse};
 
     #[test]
     fn deserialize() {
@@ -198,7 +243,7 @@ mod tests {
                         jsonrpc: "2.0".to_string(),
                         method: "textDocument/completion".to_string()
                     },
-                    id: 4
+                    id: RequestId::Integer(4)
                 },
                 params: CompletionParams {
                     base: TextDocumentPositionParams {
@@ -218,9 +263,30 @@ mod tests {
 
     #[test]
     fn serialize() {
-        let completion_response = CompletionResponse::new(1337);
+        let completion_response = CompletionResponse::new(RequestId::Integer(1337), true);
         let expected_message = r#"{"jsonrpc":"2.0","id":1337,"result":{"items":[{"label":"SELECT","kind":15,"detail":"Select query","insertText":"SELECT ${1:*} WHERE {\n  $0\n}","insertTextFormat":2},{"label":"PREFIX","kind":15,"detail":"Declare a namespace","insertText":"PREFIX ${1:namespace}: <${0:iri}>","insertTextFormat":2},{"label":"FILTER","kind":15,"detail":"Filter the results","insertText":"FILTER ( $0 )","insertTextFormat":2},{"label":"ORDER BY","kind":15,"detail":"Sort the results","insertText":"ORDER BY ${1|ASC,DESC|} ( $0 )","insertTextFormat":2}]}}"#;
         let actual_message = serde_json::to_string(&completion_response).unwrap();
         assert_eq!(actual_message, expected_message);
     }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            snippet_text("SELECT ${1:*} WHERE {\n  $0\n}"),
+            "SELECT * WHERE {\n  \n}"
+        );
+        assert_eq!(
+            snippet_text("PREFIX ${1:namespace}: <${0:iri}>"),
+            "PREFIX namespace: <iri>"
+        );
+        assert_eq!(
+            snippet_text("ORDER BY ${1|ASC,DESC|} ( $0 )"),
+            "ORDER BY ASC (  )"
+        );
+        assert_eq!(snippet_text("\\$1 ${2:a${3:b}c}"), "$1 abc");
+        let completion_response = CompletionResponse::new(RequestId::Integer(1), false);
+        assert!(serde_json::to_string(&completion_response)
+            .unwrap()
+            .contains(r#""insertText":"FILTER (  )","insertTextFormat":1"#));
+    }
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     lsp::textdocument::TextDocumentIdentifier,
-    rpc::{RequestMessage, ResponseMessage},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
 };
 
 use super::Diagnostic;
@@ -18,6 +18,8 @@ pub struct DiagnosticRequest {
 #[serde(rename_all = "camelCase")]
 pub struct DocumentDiagnosticParams {
     pub text_document: TextDocumentIdentifier,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_result_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -28,26 +30,33 @@ pub struct DiagnosticResponse {
 }
 
 impl DiagnosticResponse {
-    pub fn new(id: u32, items: Vec<Diagnostic>) -> Self {
+    pub fn new(id: RequestId, result_id: Option<String>, items: Vec<Diagnostic>) -> Self {
         Self {
             base: ResponseMessage::new(id),
-            result: DocumentDiagnosticReport {
-                kind: DocumentDiagnosticReportKind::Full,
-                items,
-            },
+            result: DocumentDiagnosticReport::Full { result_id, items },
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct DocumentDiagnosticReport {
-    kind: DocumentDiagnosticReportKind,
-    pub items: Vec<Diagnostic>,
+    /// The diagnostics did not change since the report with `result_id`.
+    pub fn unchanged(id: RequestId, result_id: String) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: DocumentDiagnosticReport::Unchanged { result_id },
+        }
+    }
 }
 
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#documentDiagnosticReport
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
-pub enum DocumentDiagnosticReportKind {
-    Full,
-    Unchanged,
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DocumentDiagnosticReport {
+    Full {
+        #[serde(rename = "resultId", skip_serializing_if = "Option::is_none")]
+        result_id: Option<String>,
+        items: Vec<Diagnostic>,
+    },
+    Unchanged {
+        #[serde(rename = "resultId")]
+        result_id: String,
+    },
 }
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{lsp::textdocument::TextDocumentIdentifier, rpc::BaseMessage};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DidCloseTextDocumentNotification {
+    #[serde(flatten)]
+    base: BaseMessage,
+    pub params: DidCloseTextDocumentParams,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidCloseTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{lsp::textdocument::TextDocumentIdentifier, rpc::BaseMessage};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DidSaveTextDocumentNotification {
+    #[serde(flatten)]
+    base: BaseMessage,
+    pub params: DidSaveTextDocumentParams,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidSaveTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+    // WARNING: The optional text is ignored, the server does not request it.
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::{
+    lsp::textdocument::{Range, TextDocumentIdentifier},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DocumentSymbolRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: DocumentSymbolParams,
+}
+
+impl DocumentSymbolRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DocumentSymbolParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DocumentSymbolResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: Vec<DocumentSymbol>,
+}
+
+impl DocumentSymbolResponse {
+    pub(crate) fn new(id: RequestId, symbols: Vec<DocumentSymbol>) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: symbols,
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#documentSymbol
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    pub range: Range,
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children: Vec<DocumentSymbol>,
+}
+
+#[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum SymbolKind {
+    File = 1,
+    Module = 2,
+    Namespace = 3,
+    Package = 4,
+    Class = 5,
+    Method = 6,
+    Property = 7,
+    Field = 8,
+    Constructor = 9,
+    Enum = 10,
+    Interface = 11,
+    Function = 12,
+    Variable = 13,
+    Constant = 14,
+    String = 15,
+    Number = 16,
+    Boolean = 17,
+    Array = 18,
+    Object = 19,
+    Key = 20,
+    Null = 21,
+    EnumMember = 22,
+    Struct = 23,
+    Event = 24,
+    Operator = 25,
+    TypeParameter = 26,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lsp::textdocument::Range, rpc::RequestId};
+
+    use super::{DocumentSymbol, DocumentSymbolRequest, DocumentSymbolResponse, SymbolKind};
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/documentSymbol","id":3,"params":{"textDocument":{"uri":"file:///dings"}}}"#;
+        let request: DocumentSymbolRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(3));
+        assert_eq!(request.get_document_uri(), "file:///dings");
+    }
+
+    #[test]
+    fn serialize() {
+        let response = DocumentSymbolResponse::new(
+            RequestId::Integer(3),
+            vec![DocumentSymbol {
+                name: "wd:".to_string(),
+                detail: None,
+                kind: SymbolKind::Namespace,
+                range: Range::new(0, 0, 0, 10),
+                selection_range: Range::new(0, 7, 0, 10),
+                children: vec![],
+            }],
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"name":"wd:","kind":3,"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":10}},"selectionRange":{"start":{"line":0,"character":7},"end":{"line":0,"character":10}}}]}"#
+        );
+    }
+}
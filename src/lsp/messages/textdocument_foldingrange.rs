@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsp::textdocument::TextDocumentIdentifier,
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FoldingRangeRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: FoldingRangeParams,
+}
+
+impl FoldingRangeRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FoldingRangeParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FoldingRangeResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: Vec<FoldingRange>,
+}
+
+impl FoldingRangeResponse {
+    pub(crate) fn new(id: RequestId, folding_ranges: Vec<FoldingRange>) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: folding_ranges,
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#foldingRange
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FoldingRange {
+    pub start_line: u32,
+    pub start_character: u32,
+    pub end_line: u32,
+    pub end_character: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<FoldingRangeKind>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FoldingRangeKind {
+    Comment,
+    Imports,
+    Region,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FoldingRange, FoldingRangeKind, FoldingRangeRequest, FoldingRangeResponse};
+    use crate::rpc::RequestId;
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/foldingRange","id":5,"params":{"textDocument":{"uri":"file:///dings"}}}"#;
+        let request: FoldingRangeRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(5));
+        assert_eq!(request.get_document_uri(), "file:///dings");
+    }
+
+    #[test]
+    fn serialize() {
+        let response = FoldingRangeResponse::new(
+            RequestId::Integer(5),
+            vec![FoldingRange {
+                start_line: 0,
+                start_character: 0,
+                end_line: 2,
+                end_character: 10,
+                kind: Some(FoldingRangeKind::Imports),
+            }],
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":5,"result":[{"startLine":0,"startCharacter":0,"endLine":2,"endCharacter":10,"kind":"imports"}]}"#
+        );
+    }
+}
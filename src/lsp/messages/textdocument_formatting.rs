@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     lsp::textdocument::{TextDocumentIdentifier, TextEdit},
-    rpc::{RequestMessage, ResponseMessage},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
 };
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -12,8 +12,8 @@ pub struct FormattingRequest {
     params: DocumentFormattingParams,
 }
 impl FormattingRequest {
-    pub(crate) fn get_id(&self) -> u32 {
-        self.base.id
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
     }
 
     pub fn get_document_uri(&self) -> &String {
@@ -47,7 +47,7 @@ pub struct FormattingResponse {
     result: Vec<TextEdit>,
 }
 impl FormattingResponse {
-    pub(crate) fn new(id: u32, text_edits: Vec<TextEdit>) -> Self {
+    pub(crate) fn new(id: RequestId, text_edits: Vec<TextEdit>) -> Self {
         Self {
             base: ResponseMessage::new(id),
             result: text_edits,
@@ -63,7 +63,7 @@ mod tests {
             textdocument::{Range, TextDocumentIdentifier, TextEdit},
             FormattingResponse,
         },
-        rpc::{BaseMessage, RequestMessage},
+        rpc::{BaseMessage, RequestId, RequestMessage},
     };
 
     use super::FormattingRequest;
@@ -81,7 +81,7 @@ mod tests {
                         jsonrpc: "2.0".to_string(),
                         method: "textDocument/formatting".to_string()
                     },
-                    id: 2
+                    id: RequestId::Integer(2)
                 },
                 params: DocumentFormattingParams {
                     text_document: TextDocumentIdentifier {
@@ -99,7 +99,7 @@ mod tests {
     #[test]
     fn serialize() {
         let text_edits = vec![TextEdit::new(Range::new(0, 1, 2, 3), "dings".to_string())];
-        let formatting_response = FormattingResponse::new(42, text_edits);
+        let formatting_response = FormattingResponse::new(RequestId::Integer(42), text_edits);
         let expected_message = r#"{"jsonrpc":"2.0","id":42,"result":[{"range":{"start":{"line":0,"character":1},"end":{"line":2,"character":3}},"newText":"dings"}]}"#;
         assert_eq!(
             serde_json::to_string(&formatting_response).unwrap(),
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     lsp::textdocument::Position,
-    rpc::{RequestMessage, ResponseMessage},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
 };
 
 use super::utils::TextDocumentPositionParams;
@@ -23,8 +23,8 @@ impl HoverRequest {
         &self.params.text_document_position.text_document.uri
     }
 
-    pub(crate) fn get_id(&self) -> u32 {
-        self.base.id
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
     }
 }
 
@@ -38,19 +38,27 @@ struct HoverParams {
 pub struct HoverResponse {
     #[serde(flatten)]
     base: ResponseMessage,
-    result: HoverResult,
+    result: Option<HoverResult>,
 }
 
 impl HoverResponse {
-    pub fn new(id: u32, content: String) -> Self {
+    pub fn new(id: RequestId, content: String, kind: Markupkind) -> Self {
         HoverResponse {
             base: ResponseMessage::new(id),
-            result: HoverResult {
+            result: Some(HoverResult {
                 contents: HoverResultContents::MarkupContent(MarkupContent::Content {
-                    kind: Markupkind::Markdown,
+                    kind,
                     value: content,
                 }),
-            },
+            }),
+        }
+    }
+
+    /// A response for positions without documentation.
+    pub fn empty(id: RequestId) -> Self {
+        HoverResponse {
+            base: ResponseMessage::new(id),
+            result: None,
         }
     }
 }
@@ -81,7 +89,7 @@ enum MarkupContent {
     Content { kind: Markupkind, value: String },
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum Markupkind {
     Plaintext,
@@ -95,10 +103,10 @@ mod tests {
             messages::textdocument_hover::{HoverParams, Position, TextDocumentPositionParams},
             textdocument::TextDocumentIdentifier,
         },
-        rpc::{BaseMessage, RequestMessage},
+        rpc::{BaseMessage, RequestId, RequestMessage},
     };
 
-    use super::{HoverRequest, HoverResponse};
+    use super::{HoverRequest, HoverResponse, Markupkind};
 
     #[test]
     fn deserialize() {
@@ -113,7 +121,7 @@ mod tests {
                         jsonrpc: "2.0".to_string(),
                         method: "textDocument/hover".to_string()
                     },
-                    id: 2
+                    id: RequestId::Integer(2)
                 },
                 params: HoverParams {
                     text_document_position: TextDocumentPositionParams {
@@ -129,11 +137,19 @@ mod tests {
 
     #[test]
     fn serialize() {
-        let hover_response = HoverResponse::new(42, "hover content".to_string());
+        let hover_response = HoverResponse::new(
+            RequestId::Integer(42),
+            "hover content".to_string(),
+            Markupkind::Markdown,
+        );
         let expected_message = r#"{"jsonrpc":"2.0","id":42,"result":{"contents":{"kind":"markdown","value":"hover content"}}}"#;
         assert_eq!(
             serde_json::to_string(&hover_response).unwrap(),
             expected_message
         );
+        assert_eq!(
+            serde_json::to_string(&HoverResponse::empty(RequestId::Integer(42))).unwrap(),
+            r#"{"jsonrpc":"2.0","id":42,"result":null}"#
+        );
     }
 }
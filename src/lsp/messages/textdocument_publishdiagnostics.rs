@@ -13,15 +13,20 @@ pub struct PublishDiagnosticsNotification {
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct PublishDiagnosticsPrarams {
     pub uri: String,
+    /// The version of the document the diagnostics were computed for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
     pub diagnostics: Vec<Diagnostic>,
 }
 
 // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnostic
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Diagnostic {
     pub range: Range,
     pub severity: DiagnosticSeverity,
-    // code: u32 | String,
+    // WARNING: This is not to spec, the code could also be a number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
     // codeDescription: CodeDescription
     pub source: String,
     pub message: String,
@@ -31,7 +36,7 @@ pub struct Diagnostic {
 }
 
 // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnosticSeverity
-#[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq)]
+#[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy)]
 #[repr(u8)]
 pub enum DiagnosticSeverity {
     Error = 1,
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsp::textdocument::{Position, Range, TextDocumentIdentifier},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SelectionRangeRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: SelectionRangeParams,
+}
+
+impl SelectionRangeRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+
+    pub(crate) fn get_positions(&self) -> &Vec<Position> {
+        &self.params.positions
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SelectionRangeParams {
+    text_document: TextDocumentIdentifier,
+    positions: Vec<Position>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SelectionRangeResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: Vec<SelectionRange>,
+}
+
+impl SelectionRangeResponse {
+    pub(crate) fn new(id: RequestId, selection_ranges: Vec<SelectionRange>) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: selection_ranges,
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#selectionRange
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SelectionRange {
+    pub range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<SelectionRange>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lsp::textdocument::{Position, Range},
+        rpc::RequestId,
+    };
+
+    use super::{SelectionRange, SelectionRangeRequest, SelectionRangeResponse};
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/selectionRange","id":6,"params":{"textDocument":{"uri":"file:///dings"},"positions":[{"line":1,"character":2}]}}"#;
+        let request: SelectionRangeRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(6));
+        assert_eq!(request.get_positions(), &vec![Position::new(1, 2)]);
+    }
+
+    #[test]
+    fn serialize() {
+        let response = SelectionRangeResponse::new(
+            RequestId::Integer(6),
+            vec![SelectionRange {
+                range: Range::new(0, 1, 0, 2),
+                parent: Some(Box::new(SelectionRange {
+                    range: Range::new(0, 0, 0, 3),
+                    parent: None,
+                })),
+            }],
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":6,"result":[{"range":{"start":{"line":0,"character":1},"end":{"line":0,"character":2}},"parent":{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":3}}}}]}"#
+        );
+    }
+}
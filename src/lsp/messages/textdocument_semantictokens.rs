@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsp::textdocument::{Range, TextDocumentIdentifier},
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#semanticTokenTypes
+/// The token types the server reports.
+/// The position in `SemanticTokenType::ALL` is the index that is used in the token data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SemanticTokenType {
+    Keyword,
+    /// Builtin functions, aggregates and custom function calls.
+    Function,
+    Variable,
+    /// The prefix of a prefixed name.
+    Namespace,
+    /// The local part of a prefixed name.
+    Property,
+    /// Full IRIs, i.e. `<...>`.
+    Iri,
+    String,
+    Number,
+    /// The datatype of a literal.
+    Type,
+    /// The language tag of a literal.
+    Decorator,
+    BlankNode,
+    Comment,
+}
+
+impl SemanticTokenType {
+    pub const ALL: [SemanticTokenType; 12] = [
+        SemanticTokenType::Keyword,
+        SemanticTokenType::Function,
+        SemanticTokenType::Variable,
+        SemanticTokenType::Namespace,
+        SemanticTokenType::Property,
+        SemanticTokenType::Iri,
+        SemanticTokenType::String,
+        SemanticTokenType::Number,
+        SemanticTokenType::Type,
+        SemanticTokenType::Decorator,
+        SemanticTokenType::BlankNode,
+        SemanticTokenType::Comment,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SemanticTokenType::Keyword => "keyword",
+            SemanticTokenType::Function => "function",
+            SemanticTokenType::Variable => "variable",
+            SemanticTokenType::Namespace => "namespace",
+            SemanticTokenType::Property => "property",
+            SemanticTokenType::Iri => "iri",
+            SemanticTokenType::String => "string",
+            SemanticTokenType::Number => "number",
+            SemanticTokenType::Type => "type",
+            SemanticTokenType::Decorator => "decorator",
+            SemanticTokenType::BlankNode => "blankNode",
+            SemanticTokenType::Comment => "comment",
+        }
+    }
+}
+
+/// The token modifiers the server reports.
+/// Each modifier is a bit in the modifier set of a token, in the order of `SemanticTokenModifier::ALL`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SemanticTokenModifier {
+    /// A variable that is projected by the enclosing (sub-)select.
+    Projected,
+    /// A variable that is only used inside the query.
+    Local,
+}
+
+impl SemanticTokenModifier {
+    pub const ALL: [SemanticTokenModifier; 2] = [
+        SemanticTokenModifier::Projected,
+        SemanticTokenModifier::Local,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SemanticTokenModifier::Projected => "projected",
+            SemanticTokenModifier::Local => "local",
+        }
+    }
+
+    pub fn bit(&self) -> u32 {
+        1 << *self as u32
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensLegend {
+    token_types: Vec<String>,
+    token_modifiers: Vec<String>,
+}
+
+impl SemanticTokensLegend {
+    pub fn new() -> Self {
+        Self {
+            token_types: SemanticTokenType::ALL
+                .iter()
+                .map(|token_type| token_type.name().to_string())
+                .collect(),
+            token_modifiers: SemanticTokenModifier::ALL
+                .iter()
+                .map(|modifier| modifier.name().to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SemanticTokensFullRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: SemanticTokensParams,
+}
+
+impl SemanticTokensFullRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SemanticTokensParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SemanticTokensRangeRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: SemanticTokensRangeParams,
+}
+
+impl SemanticTokensRangeRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+
+    pub(crate) fn get_range(&self) -> &Range {
+        &self.params.range
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SemanticTokensRangeParams {
+    text_document: TextDocumentIdentifier,
+    range: Range,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SemanticTokensDeltaRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: SemanticTokensDeltaParams,
+}
+
+impl SemanticTokensDeltaRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.text_document.uri
+    }
+
+    pub(crate) fn get_previous_result_id(&self) -> &String {
+        &self.params.previous_result_id
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SemanticTokensDeltaParams {
+    text_document: TextDocumentIdentifier,
+    previous_result_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SemanticTokensResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: SemanticTokens,
+}
+
+impl SemanticTokensResponse {
+    pub(crate) fn new(id: RequestId, semantic_tokens: SemanticTokens) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: semantic_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SemanticTokensDeltaResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: SemanticTokensDeltaResult,
+}
+
+impl SemanticTokensDeltaResponse {
+    pub(crate) fn new(id: RequestId, result: SemanticTokensDeltaResult) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result,
+        }
+    }
+}
+
+/// A delta request is answered with the full tokens, if the previous result is not known anymore.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SemanticTokensDeltaResult {
+    Full(SemanticTokens),
+    Delta(SemanticTokensDelta),
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#semanticTokens
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokens {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    /// Five integers per token: delta line, delta start character, length, type and modifiers.
+    pub data: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDelta {
+    pub result_id: String,
+    pub edits: Vec<SemanticTokensEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensEdit {
+    pub start: u32,
+    pub delete_count: u32,
+    pub data: Vec<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SemanticTokens, SemanticTokensDelta, SemanticTokensDeltaRequest,
+        SemanticTokensDeltaResponse, SemanticTokensDeltaResult, SemanticTokensEdit,
+        SemanticTokensLegend, SemanticTokensRangeRequest, SemanticTokensResponse,
+    };
+    use crate::rpc::RequestId;
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/semanticTokens/range","id":3,"params":{"textDocument":{"uri":"file:///dings"},"range":{"start":{"line":0,"character":0},"end":{"line":2,"character":4}}}}"#;
+        let request: SemanticTokensRangeRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(3));
+        assert_eq!(request.get_document_uri(), "file:///dings");
+        assert_eq!(request.get_range().end.line, 2);
+
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/semanticTokens/full/delta","id":4,"params":{"textDocument":{"uri":"file:///dings"},"previousResultId":"7"}}"#;
+        let request: SemanticTokensDeltaRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_previous_result_id(), "7");
+    }
+
+    #[test]
+    fn serialize() {
+        let response = SemanticTokensResponse::new(
+            RequestId::Integer(3),
+            SemanticTokens {
+                result_id: Some("1".to_string()),
+                data: vec![0, 0, 6, 0, 0],
+            },
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":3,"result":{"resultId":"1","data":[0,0,6,0,0]}}"#
+        );
+        let response = SemanticTokensDeltaResponse::new(
+            RequestId::Integer(4),
+            SemanticTokensDeltaResult::Delta(SemanticTokensDelta {
+                result_id: "2".to_string(),
+                edits: vec![SemanticTokensEdit {
+                    start: 5,
+                    delete_count: 5,
+                    data: vec![],
+                }],
+            }),
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":4,"result":{"resultId":"2","edits":[{"start":5,"deleteCount":5,"data":[]}]}}"#
+        );
+    }
+
+    #[test]
+    fn legend() {
+        let legend = serde_json::to_value(SemanticTokensLegend::new()).unwrap();
+        assert_eq!(legend["tokenTypes"][0], "keyword");
+        assert_eq!(legend["tokenModifiers"][1], "local");
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lsp::textdocument::Position,
+    rpc::{RequestId, RequestMessage, ResponseMessage},
+};
+
+use super::utils::TextDocumentPositionParams;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignatureHelpRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: SignatureHelpParams,
+}
+
+impl SignatureHelpRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_document_uri(&self) -> &String {
+        &self.params.base.text_document.uri
+    }
+
+    pub(crate) fn get_position(&self) -> &Position {
+        &self.params.base.position
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SignatureHelpParams {
+    // WARNING: The optional SignatureHelpContext is ignored.
+    #[serde(flatten)]
+    base: TextDocumentPositionParams,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignatureHelpResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: Option<SignatureHelp>,
+}
+
+impl SignatureHelpResponse {
+    pub(crate) fn new(id: RequestId, signature_help: Option<SignatureHelp>) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result: signature_help,
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#signatureHelp
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInformation>,
+    pub active_signature: u32,
+    pub active_parameter: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignatureInformation {
+    pub label: String,
+    pub documentation: String,
+    pub parameters: Vec<ParameterInformation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ParameterInformation {
+    /// The start and end offset of the parameter in the signature label, in UTF-16 code units.
+    pub label: [u32; 2],
+    pub documentation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lsp::textdocument::Position, rpc::RequestId};
+
+    use super::{
+        ParameterInformation, SignatureHelp, SignatureHelpRequest, SignatureHelpResponse,
+        SignatureInformation,
+    };
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"textDocument/signatureHelp","id":8,"params":{"textDocument":{"uri":"file:///dings"},"position":{"line":1,"character":12},"context":{"triggerKind":2,"triggerCharacter":"(","isRetrigger":false}}}"#;
+        let request: SignatureHelpRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(8));
+        assert_eq!(request.get_document_uri(), "file:///dings");
+        assert_eq!(request.get_position(), &Position::new(1, 12));
+    }
+
+    #[test]
+    fn serialize() {
+        let response = SignatureHelpResponse::new(
+            RequestId::Integer(8),
+            Some(SignatureHelp {
+                signatures: vec![SignatureInformation {
+                    label: "STR(literal ltrl)".to_string(),
+                    documentation: "Lexical form".to_string(),
+                    parameters: vec![ParameterInformation {
+                        label: [4, 16],
+                        documentation: "A literal".to_string(),
+                    }],
+                }],
+                active_signature: 0,
+                active_parameter: 0,
+            }),
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":8,"result":{"signatures":[{"label":"STR(literal ltrl)","documentation":"Lexical form","parameters":[{"label":[4,16],"documentation":"A literal"}]}],"activeSignature":0,"activeParameter":0}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&SignatureHelpResponse::new(RequestId::Integer(8), None))
+                .unwrap(),
+            r#"{"jsonrpc":"2.0","id":8,"result":null}"#
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::BaseMessage;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DidChangeConfigurationNotification {
+    #[serde(flatten)]
+    base: BaseMessage,
+    pub params: DidChangeConfigurationParams,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DidChangeConfigurationParams {
+    /// The settings of the client, in the structure of the settings file.
+    pub settings: serde_json::Value,
+}
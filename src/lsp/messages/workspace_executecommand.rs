@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::{RequestId, RequestMessage, ResponseMessage};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ExecuteCommandRequest {
+    #[serde(flatten)]
+    base: RequestMessage,
+    params: ExecuteCommandParams,
+}
+
+impl ExecuteCommandRequest {
+    pub(crate) fn get_id(&self) -> RequestId {
+        self.base.id.clone()
+    }
+
+    pub(crate) fn get_command(&self) -> &str {
+        &self.params.command
+    }
+
+    pub(crate) fn get_arguments(&self) -> &[serde_json::Value] {
+        &self.params.arguments
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ExecuteCommandParams {
+    command: String,
+    #[serde(default)]
+    arguments: Vec<serde_json::Value>,
+}
+
+/// The answer to a command, its result depends on the command.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ExecuteCommandResponse {
+    #[serde(flatten)]
+    base: ResponseMessage,
+    result: serde_json::Value,
+}
+
+impl ExecuteCommandResponse {
+    pub(crate) fn new(id: RequestId, result: serde_json::Value) -> Self {
+        Self {
+            base: ResponseMessage::new(id),
+            result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::rpc::RequestId;
+
+    use super::{ExecuteCommandRequest, ExecuteCommandResponse};
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"jsonrpc":"2.0","method":"workspace/executeCommand","id":3,"params":{"command":"fichu.executeQuery","arguments":[{"uri":"file:///a.rq"}]}}"#;
+        let request = serde_json::from_slice::<ExecuteCommandRequest>(message).unwrap();
+        assert_eq!(request.get_id(), RequestId::Integer(3));
+        assert_eq!(request.get_command(), "fichu.executeQuery");
+        assert_eq!(request.get_arguments(), [json!({"uri": "file:///a.rq"})]);
+
+        let message = br#"{"jsonrpc":"2.0","method":"workspace/executeCommand","id":4,"params":{"command":"fichu.executeQuery"}}"#;
+        let request = serde_json::from_slice::<ExecuteCommandRequest>(message).unwrap();
+        assert!(request.get_arguments().is_empty());
+    }
+
+    #[test]
+    fn serialize() {
+        let response = ExecuteCommandResponse::new(RequestId::Integer(3), json!({"a": 1}));
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":3,"result":{"a":1}}"#
+        );
+    }
+}
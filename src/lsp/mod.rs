@@ -2,4 +2,5 @@ mod capabilities;
 pub mod messages;
 pub mod textdocument;
 
+pub use capabilities::{ClientCapabilities, PositionEncodingKind, ServerCapabilities};
 pub use messages::*;
@@ -1,19 +1,25 @@
-use std::fmt;
+use std::{fmt, sync::OnceLock};
 
 use log::error;
 use serde::{Deserialize, Serialize};
 
 use tree_sitter::{Node, Point};
 
-use super::TextDocumentContentChangeEvent;
+use super::{PositionEncodingKind, TextDocumentContentChangeEvent};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TextDocumentItem {
     pub uri: String,
     language_id: String,
     version: u32,
     pub text: String,
+    /// The encoding of the `character` of the positions exchanged with the client.
+    #[serde(skip)]
+    pub(crate) position_encoding: PositionEncodingKind,
+    /// The byte offset of the start of each line, computed on first use.
+    #[serde(skip)]
+    line_starts: OnceLock<Vec<usize>>,
 }
 
 impl TextDocumentItem {
@@ -23,12 +29,22 @@ impl TextDocumentItem {
             text: text.to_string(),
             language_id: "sparql".to_string(),
             version: 0,
+            position_encoding: PositionEncodingKind::default(),
+            line_starts: OnceLock::new(),
         }
     }
+
+    pub(crate) fn version(&self) -> u32 {
+        self.version
+    }
+
     pub(crate) fn apply_changes(
         &mut self,
         mut content_canges: Vec<TextDocumentContentChangeEvent>,
+        version: u32,
     ) {
+        self.version = version;
+        self.line_starts = OnceLock::new();
         match content_canges.first_mut() {
             Some(change) => self.text = std::mem::take(&mut change.text),
             None => {
@@ -41,13 +57,107 @@ impl TextDocumentItem {
     }
 
     pub fn get_full_range(&self) -> Range {
-        let lines = self.text.lines().collect::<Vec<&str>>();
-        return Range::new(
-            0,
-            0,
-            lines.len() as u32,
-            lines.last().unwrap_or(&"").len() as u32,
-        );
+        let last_row = self.line_starts().len() - 1;
+        Range {
+            start: Position::new(0, 0),
+            end: self.position(Point::new(last_row, self.line(last_row).len())),
+        }
+    }
+
+    fn line_starts(&self) -> &[usize] {
+        self.line_starts.get_or_init(|| {
+            std::iter::once(0)
+                .chain(self.text.match_indices('\n').map(|(offset, _)| offset + 1))
+                .collect()
+        })
+    }
+
+    /// The text of line `row` without the line break, empty for rows after the last line.
+    fn line(&self, row: usize) -> &str {
+        let line_starts = self.line_starts();
+        match line_starts.get(row) {
+            Some(&start) => {
+                let end = line_starts
+                    .get(row + 1)
+                    .map_or(self.text.len(), |next_start| next_start - 1);
+                self.text[start..end].trim_end_matches('\r')
+            }
+            None => "",
+        }
+    }
+
+    /// Converts a tree-sitter point (with a byte column) to a position in the client's encoding.
+    pub(crate) fn position(&self, point: Point) -> Position {
+        let line = self.line(point.row);
+        let mut column = point.column.min(line.len());
+        while !line.is_char_boundary(column) {
+            column -= 1;
+        }
+        let character = match self.position_encoding {
+            PositionEncodingKind::Utf8 => column,
+            PositionEncodingKind::Utf16 => line[..column].encode_utf16().count(),
+            PositionEncodingKind::Utf32 => line[..column].chars().count(),
+        };
+        Position::new(point.row as u32, character as u32)
+    }
+
+    /// Converts a position in the client's encoding to a tree-sitter point.
+    /// Characters after the end of the line map to the end of the line.
+    pub(crate) fn point(&self, position: &Position) -> Point {
+        let line = self.line(position.line as usize);
+        let character = position.character as usize;
+        let column = match self.position_encoding {
+            PositionEncodingKind::Utf8 => {
+                let mut column = character.min(line.len());
+                while !line.is_char_boundary(column) {
+                    column -= 1;
+                }
+                column
+            }
+            PositionEncodingKind::Utf16 => {
+                let mut code_units = 0;
+                line.char_indices()
+                    .find(|(_offset, char)| {
+                        code_units += char.len_utf16();
+                        code_units > character
+                    })
+                    .map_or(line.len(), |(offset, _char)| offset)
+            }
+            PositionEncodingKind::Utf32 => line
+                .char_indices()
+                .nth(character)
+                .map_or(line.len(), |(offset, _char)| offset),
+        };
+        Point::new(position.line as usize, column)
+    }
+
+    /// The byte offset of a position in the client's encoding.
+    /// Positions after the last line map to the end of the text.
+    pub(crate) fn offset(&self, position: &Position) -> usize {
+        let point = self.point(position);
+        self.line_starts()
+            .get(point.row)
+            .map_or(self.text.len(), |start| start + point.column)
+    }
+
+    /// Converts a byte offset to a tree-sitter point.
+    pub(crate) fn point_at(&self, offset: usize) -> Point {
+        let line_starts = self.line_starts();
+        let row = line_starts.partition_point(|&start| start <= offset) - 1;
+        Point::new(row, offset - line_starts[row])
+    }
+
+    /// The range of `node` in the client's encoding.
+    pub(crate) fn range(&self, node: Node) -> Range {
+        self.range_between(node, node)
+    }
+
+    /// The range from the start of `first` to the end of `last`, in the client's encoding.
+    pub(crate) fn range_between(&self, first: Node, last: Node) -> Range {
+        Range {
+            start: self.position(first.start_position()),
+            end: self.position(last.end_position()),
+        }
     }
 }
 
@@ -56,7 +166,7 @@ impl TextDocumentItem {
 pub struct VersionedTextDocumentIdentifier {
     #[serde(flatten)]
     pub base: TextDocumentIdentifier,
-    version: u32,
+    pub version: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -68,21 +178,14 @@ type Uri = String;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Position {
-    line: u32,
-    character: u32,
+    pub line: u32,
+    pub character: u32,
 }
 
 impl Position {
     pub fn new(line: u32, character: u32) -> Self {
         Self { line, character }
     }
-
-    pub(crate) fn to_point(&self) -> Point {
-        Point {
-            row: self.line as usize,
-            column: self.character as usize,
-        }
-    }
 }
 
 impl fmt::Display for Position {
@@ -93,8 +196,8 @@ impl fmt::Display for Position {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Range {
-    start: Position,
-    end: Position,
+    pub start: Position,
+    pub end: Position,
 }
 
 impl Range {
@@ -104,21 +207,6 @@ impl Range {
             end: Position::new(end_line, end_character),
         }
     }
-
-    pub(crate) fn from_node(node: Node) -> Range {
-        Self {
-            start: Position::new(
-                node.start_position().row as u32,
-                node.start_position().column as u32,
-            ),
-            end: Position::new(
-                node.end_position().row as u32,
-                node.end_position().column as u32,
-            ),
-        }
-    }
-
-    // pub from_node(node: N
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -136,36 +224,114 @@ impl TextEdit {
 
 #[cfg(test)]
 mod tests {
-    use crate::lsp::TextDocumentContentChangeEvent;
+    use tree_sitter::Point;
 
-    use super::TextDocumentItem;
+    use crate::lsp::{PositionEncodingKind, TextDocumentContentChangeEvent};
+
+    use super::{Position, Range, TextDocumentItem};
+
+    fn document(text: &str, position_encoding: PositionEncodingKind) -> TextDocumentItem {
+        let mut document = TextDocumentItem::new("file:///dings", text);
+        document.position_encoding = position_encoding;
+        document
+    }
 
     #[test]
     fn full_changes() {
         let changes: Vec<TextDocumentContentChangeEvent> = vec![TextDocumentContentChangeEvent {
             text: "goodbye world".to_string(),
         }];
-        let mut document: TextDocumentItem = TextDocumentItem {
-            uri: "file:///dings".to_string(),
-            language_id: "foo".to_string(),
-            version: 1,
-            text: "hello world".to_string(),
-        };
+        let mut document = TextDocumentItem::new("file:///dings", "hello world");
 
-        document.apply_changes(changes);
+        document.apply_changes(changes, 2);
         assert_eq!(document.text, "goodbye world");
+        assert_eq!(document.version(), 2);
     }
 
     #[test]
     fn no_changes() {
         let changes: Vec<TextDocumentContentChangeEvent> = vec![];
-        let mut document: TextDocumentItem = TextDocumentItem {
-            uri: "file:///dings".to_string(),
-            language_id: "foo".to_string(),
-            version: 1,
-            text: "hello world".to_string(),
-        };
-        document.apply_changes(changes);
+        let mut document = TextDocumentItem::new("file:///dings", "hello world");
+        document.apply_changes(changes, 2);
         assert_eq!(document.text, "hello world");
     }
+
+    #[test]
+    fn utf16_positions() {
+        let document = document(
+            "# αβγ\r\nSELECT ?名前 {\n  ?a ?b \"🦀\" }",
+            PositionEncodingKind::Utf16,
+        );
+        assert_eq!(document.position(Point::new(0, 4)), Position::new(0, 3));
+        assert_eq!(document.position(Point::new(1, 14)), Position::new(1, 10));
+        assert_eq!(document.position(Point::new(2, 13)), Position::new(2, 11));
+        assert_eq!(document.point(&Position::new(0, 3)), Point::new(0, 4));
+        assert_eq!(document.point(&Position::new(1, 10)), Point::new(1, 14));
+        assert_eq!(document.point(&Position::new(2, 11)), Point::new(2, 13));
+    }
+
+    #[test]
+    fn utf8_positions() {
+        let document = document(
+            "# αβγ\r\nSELECT ?名前 {\n  ?a ?b \"🦀\" }",
+            PositionEncodingKind::Utf8,
+        );
+        assert_eq!(document.position(Point::new(1, 14)), Position::new(1, 14));
+        assert_eq!(document.point(&Position::new(2, 13)), Point::new(2, 13));
+    }
+
+    #[test]
+    fn utf32_positions() {
+        let document = document(
+            "# αβγ\r\nSELECT ?名前 {\n  ?a ?b \"🦀\" }",
+            PositionEncodingKind::Utf32,
+        );
+        assert_eq!(document.position(Point::new(1, 14)), Position::new(1, 10));
+        assert_eq!(document.position(Point::new(2, 13)), Position::new(2, 10));
+        assert_eq!(document.point(&Position::new(2, 10)), Point::new(2, 13));
+        assert_eq!(document.point(&Position::new(2, 42)), Point::new(2, 16));
+    }
+
+    #[test]
+    fn positions_inside_characters_and_behind_lines() {
+        let utf16 = document("?名前\r\n?🦀", PositionEncodingKind::Utf16);
+        // The second half of a surrogate pair maps to the start of the character.
+        assert_eq!(utf16.point(&Position::new(1, 2)), Point::new(1, 1));
+        assert_eq!(utf16.point(&Position::new(0, 42)), Point::new(0, 7));
+        assert_eq!(utf16.point(&Position::new(5, 0)), Point::new(5, 0));
+        assert_eq!(utf16.position(Point::new(0, 100)), Position::new(0, 3));
+        let utf8 = document("?名前\r\n?🦀", PositionEncodingKind::Utf8);
+        assert_eq!(utf8.point(&Position::new(0, 3)), Point::new(0, 1));
+        assert_eq!(utf8.point(&Position::new(0, 42)), Point::new(0, 7));
+    }
+
+    #[test]
+    fn offsets() {
+        let document = document("?名前\r\n?🦀", PositionEncodingKind::Utf16);
+        assert_eq!(document.offset(&Position::new(0, 2)), 4);
+        assert_eq!(document.offset(&Position::new(1, 3)), 14);
+        assert_eq!(document.offset(&Position::new(1, 42)), 14);
+        assert_eq!(document.offset(&Position::new(7, 0)), 14);
+    }
+
+    #[test]
+    fn full_range() {
+        let document = document("SELECT *\n{ ?a ?b \"ωω\" }", PositionEncodingKind::Utf16);
+        assert_eq!(document.get_full_range(), Range::new(0, 0, 1, 14));
+        let document = TextDocumentItem::new("file:///dings", "SELECT * {}\n");
+        assert_eq!(document.get_full_range(), Range::new(0, 0, 1, 0));
+    }
+
+    #[test]
+    fn line_index_is_rebuilt_after_changes() {
+        let mut document = document("αβγ", PositionEncodingKind::Utf16);
+        assert_eq!(document.position(Point::new(0, 6)), Position::new(0, 3));
+        document.apply_changes(
+            vec![TextDocumentContentChangeEvent {
+                text: "a\nαβγ".to_string(),
+            }],
+            1,
+        );
+        assert_eq!(document.position(Point::new(1, 6)), Position::new(1, 3));
+    }
 }
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    analysis::query_prefixes,
+    cli,
+    lsp::{DiagnosticSeverity, PositionEncodingKind},
+    server::{
+        check_with_encoding,
+        configuration::{FormatSettings, LintLevel, LintSettings},
+    },
+};
+
+/// A syntax error or lint violation.
+/// Lines and columns are zero based, columns count characters like the indices of Python strings.
+#[pyclass(frozen, get_all, module = "fichu")]
+struct Diagnostic {
+    line: u32,
+    column: u32,
+    end_line: u32,
+    end_column: u32,
+    /// `"error"`, `"warning"`, `"info"` or `"hint"`.
+    severity: &'static str,
+    /// The name of the lint rule, or `"syntax_error"`.
+    code: Option<String>,
+    message: String,
+}
+
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic({}:{}-{}:{}, {}, {:?}, {:?})",
+            self.line,
+            self.column,
+            self.end_line,
+            self.end_column,
+            self.severity,
+            self.code.as_deref().unwrap_or(""),
+            self.message
+        )
+    }
+}
+
+impl From<crate::Diagnostic> for Diagnostic {
+    fn from(diagnostic: crate::Diagnostic) -> Self {
+        Self {
+            line: diagnostic.range.start.line,
+            column: diagnostic.range.start.character,
+            end_line: diagnostic.range.end.line,
+            end_column: diagnostic.range.end.character,
+            severity: match diagnostic.severity {
+                DiagnosticSeverity::Error => "error",
+                DiagnosticSeverity::Warning => "warning",
+                DiagnosticSeverity::Information => "info",
+                DiagnosticSeverity::Hint => "hint",
+            },
+            code: diagnostic.code,
+            message: diagnostic.message,
+        }
+    }
+}
+
+/// Reads a setting that is given by its name, like `"warning"`.
+fn from_str<T: DeserializeOwned>(value: &str) -> PyResult<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| PyValueError::new_err(format!("Unknown setting \"{}\"", value)))
+}
+
+/// Formats the query. Settings that are not given keep their default.
+/// Raises a `ValueError` if the query has syntax errors, unless `on_syntax_error` is `"partial"`.
+#[pyfunction]
+#[pyo3(signature = (
+    query,
+    *,
+    align_predicates = None,
+    align_prefixes = None,
+    separate_prolouge = None,
+    capitalize_keywords = None,
+    insert_spaces = None,
+    tab_size = None,
+    where_new_line = None,
+    on_syntax_error = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn format(
+    query: &str,
+    align_predicates: Option<bool>,
+    align_prefixes: Option<bool>,
+    separate_prolouge: Option<bool>,
+    capitalize_keywords: Option<bool>,
+    insert_spaces: Option<bool>,
+    tab_size: Option<u8>,
+    where_new_line: Option<bool>,
+    on_syntax_error: Option<String>,
+) -> PyResult<String> {
+    let default = FormatSettings::default();
+    let settings = FormatSettings {
+        align_predicates: align_predicates.unwrap_or(default.align_predicates),
+        align_prefixes: align_prefixes.unwrap_or(default.align_prefixes),
+        separate_prolouge: separate_prolouge.unwrap_or(default.separate_prolouge),
+        capitalize_keywords: capitalize_keywords.unwrap_or(default.capitalize_keywords),
+        insert_spaces: insert_spaces.unwrap_or(default.insert_spaces),
+        tab_size: tab_size.unwrap_or(default.tab_size),
+        where_new_line: where_new_line.unwrap_or(default.where_new_line),
+        on_syntax_error: match on_syntax_error {
+            Some(policy) => from_str(&policy)?,
+            None => default.on_syntax_error,
+        },
+    };
+    crate::format(query, &settings).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+/// The syntax errors and lint diagnostics of the query.
+/// `lint` maps rules and rule categories like `"performance"` to `"off"`, `"hint"`, `"info"`,
+/// `"warning"` or `"error"`.
+#[pyfunction]
+#[pyo3(signature = (query, lint = None))]
+fn check(query: &str, lint: Option<HashMap<String, String>>) -> PyResult<Vec<Diagnostic>> {
+    let rules = lint
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(rule, level)| Ok((rule, from_str::<LintLevel>(&level)?)))
+        .collect::<PyResult<HashMap<String, LintLevel>>>()?;
+    Ok(check_with_encoding(
+        query,
+        &LintSettings::new(rules),
+        PositionEncodingKind::Utf32,
+    )
+    .into_iter()
+    .map(Diagnostic::from)
+    .collect())
+}
+
+/// The prefixes declared by the query, mapped to their namespace IRI.
+#[pyfunction]
+fn prefixes(query: &str) -> HashMap<String, String> {
+    query_prefixes(query).into_iter().collect()
+}
+
+/// Runs the `fichu` command line interface with `sys.argv`.
+/// This is the entry point of the `fichu` script the Python package installs.
+#[pyfunction]
+fn main(py: Python<'_>) -> PyResult<()> {
+    let args: Vec<String> = py.import("sys")?.getattr("argv")?.extract()?;
+    // NOTE: Python handles SIGINT itself, but it never gets control back while the CLI runs.
+    let signal = py.import("signal")?;
+    signal.call_method1(
+        "signal",
+        (signal.getattr("SIGINT")?, signal.getattr("SIG_DFL")?),
+    )?;
+    cli::run(args);
+    Ok(())
+}
+
+/// Format and check SPARQL queries.
+#[pymodule]
+fn fichu(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Diagnostic>()?;
+    module.add_function(wrap_pyfunction!(format, module)?)?;
+    module.add_function(wrap_pyfunction!(check, module)?)?;
+    module.add_function(wrap_pyfunction!(prefixes, module)?)?;
+    module.add_function(wrap_pyfunction!(main, module)?)?;
+    Ok(())
+}
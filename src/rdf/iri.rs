@@ -0,0 +1,234 @@
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2
+
+/// The components of an IRI reference, without their delimiters.
+struct Components<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+fn split(reference: &str) -> Components<'_> {
+    let (rest, fragment) = match reference.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (reference, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (scheme, rest) = match rest.split_once(':') {
+        Some((scheme, rest))
+            if scheme.starts_with(|char: char| char.is_ascii_alphabetic())
+                && scheme.chars().all(|char| {
+                    char.is_ascii_alphanumeric() || matches!(char, '+' | '-' | '.')
+                }) =>
+        {
+            (Some(scheme), rest)
+        }
+        _ => (None, rest),
+    };
+    let (authority, path) = match rest.strip_prefix("//") {
+        Some(rest) => match rest.find('/') {
+            Some(end) => (Some(&rest[..end]), &rest[end..]),
+            None => (Some(rest), ""),
+        },
+        None => (None, rest),
+    };
+    Components {
+        scheme,
+        authority,
+        path,
+        query,
+        fragment,
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::new();
+    let pop = |output: &mut String| output.truncate(output.rfind('/').unwrap_or(0));
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../") {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("./") {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = &input[3..];
+            pop(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            pop(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let end = input[1..].find('/').map_or(input.len(), |end| end + 1);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+/// Resolves the IRI `reference` against `base`.
+/// References with a scheme are returned with their dot segments removed.
+pub fn resolve(base: &str, reference: &str) -> String {
+    let reference = split(reference);
+    let base = split(base);
+    let (scheme, authority, path, query) = if reference.scheme.is_some() {
+        (
+            reference.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.authority.is_some() {
+        (
+            base.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.scheme,
+            base.authority,
+            base.path.to_string(),
+            reference.query.or(base.query),
+        )
+    } else if reference.path.starts_with('/') {
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else {
+        let merged = match (base.authority.is_some(), base.path.is_empty()) {
+            (true, true) => format!("/{}", reference.path),
+            _ => match base.path.rfind('/') {
+                Some(end) => format!("{}{}", &base.path[..=end], reference.path),
+                None => reference.path.to_string(),
+            },
+        };
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(&merged),
+            reference.query,
+        )
+    };
+    let mut iri = String::new();
+    if let Some(scheme) = scheme {
+        iri.push_str(scheme);
+        iri.push(':');
+    }
+    if let Some(authority) = authority {
+        iri.push_str("//");
+        iri.push_str(authority);
+    }
+    iri.push_str(&path);
+    if let Some(query) = query {
+        iri.push('?');
+        iri.push_str(query);
+    }
+    if let Some(fragment) = reference.fragment {
+        iri.push('#');
+        iri.push_str(fragment);
+    }
+    iri
+}
+
+/// The `file:` IRI of an absolute path.
+pub fn file_iri(path: &str) -> String {
+    let mut iri = String::from("file://");
+    if !path.starts_with('/') {
+        iri.push('/');
+    }
+    for char in path.chars() {
+        match char {
+            '\\' => iri.push('/'),
+            ' ' | '%' | '#' | '?' | '<' | '>' | '"' => {
+                iri.push_str(&format!("%{:02X}", char as u32))
+            }
+            char => iri.push(char),
+        }
+    }
+    iri
+}
+
+/// The path of a `file:` IRI, if it is one.
+pub fn file_path(iri: &str) -> Option<String> {
+    let path = iri.strip_prefix("file://")?;
+    let path = path.split(['#', '?']).next().unwrap_or(path);
+    let bytes = path.as_bytes();
+    let mut decoded = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], path.get(index + 1..index + 3)) {
+            (b'%', Some(hex)) if u8::from_str_radix(hex, 16).is_ok() => {
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_iri, file_path, resolve};
+
+    #[test]
+    fn resolve_references() {
+        // https://www.rfc-editor.org/rfc/rfc3986#section-5.4
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            ("./../g", "http://a/b/g"),
+            ("g/../h", "http://a/b/c/h"),
+        ] {
+            assert_eq!(resolve(base, reference), expected, "{}", reference);
+        }
+        assert_eq!(
+            resolve("file:///tests/manifest.ttl", "data.ttl"),
+            "file:///tests/data.ttl"
+        );
+    }
+
+    #[test]
+    fn file_iris() {
+        assert_eq!(file_iri("/a b/c.ttl"), "file:///a%20b/c.ttl");
+        assert_eq!(file_path("file:///a%20b/c.ttl").unwrap(), "/a b/c.ttl");
+        assert_eq!(file_path("http://a"), None);
+    }
+}
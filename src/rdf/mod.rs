@@ -0,0 +1,439 @@
+//! RDF datasets in memory, read from Turtle, TriG, N-Triples and N-Quads files.
+
+mod iri;
+mod turtle;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+pub use iri::{file_iri, file_path, resolve};
+pub(crate) use turtle::{literal, typed};
+
+pub use crate::results::Term;
+
+pub(crate) const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub(crate) const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// The syntaxes RDF files are read in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RdfFormat {
+    Turtle,
+    NTriples,
+    NQuads,
+    TriG,
+}
+
+impl RdfFormat {
+    /// The format of a file extension, like `ttl` or `nq`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "ttl" | "turtle" => Some(RdfFormat::Turtle),
+            "nt" => Some(RdfFormat::NTriples),
+            "nq" => Some(RdfFormat::NQuads),
+            "trig" => Some(RdfFormat::TriG),
+            _ => None,
+        }
+    }
+}
+
+/// An RDF file that could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdfError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid RDF in line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RdfError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Triple {
+    pub subject: Term,
+    pub predicate: Term,
+    pub object: Term,
+}
+
+impl fmt::Display for Triple {
+    /// Writes the triple as N-Triples statement.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} .", self.subject, self.predicate, self.object)
+    }
+}
+
+/// A set of triples, indexed by their terms.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    triples: Vec<Triple>,
+    contained: HashSet<Triple>,
+    subjects: HashMap<Term, Vec<usize>>,
+    predicates: HashMap<Term, Vec<usize>>,
+    objects: HashMap<Term, Vec<usize>>,
+}
+
+impl Graph {
+    /// Adds the triple, returns whether it was new.
+    pub fn insert(&mut self, triple: Triple) -> bool {
+        if self.contained.contains(&triple) {
+            return false;
+        }
+        let index = self.triples.len();
+        for (terms, term) in [
+            (&mut self.subjects, &triple.subject),
+            (&mut self.predicates, &triple.predicate),
+            (&mut self.objects, &triple.object),
+        ] {
+            terms.entry(term.clone()).or_default().push(index);
+        }
+        self.contained.insert(triple.clone());
+        self.triples.push(triple);
+        true
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.triples.len()
+    }
+
+    /// The triples in the order they were inserted.
+    pub fn iter(&self) -> impl Iterator<Item = &Triple> {
+        self.triples.iter()
+    }
+
+    /// The triples with the given terms, `None` matches any term.
+    pub fn matching<'a>(
+        &'a self,
+        subject: Option<&Term>,
+        predicate: Option<&Term>,
+        object: Option<&Term>,
+    ) -> Box<dyn Iterator<Item = &'a Triple> + 'a> {
+        let candidates = [
+            (subject, &self.subjects),
+            (predicate, &self.predicates),
+            (object, &self.objects),
+        ]
+        .into_iter()
+        .filter_map(|(term, index)| term.map(|term| index.get(term).map_or(&[][..], Vec::as_slice)))
+        .min_by_key(|indices| indices.len());
+        let (subject, predicate, object) = (subject.cloned(), predicate.cloned(), object.cloned());
+        let matches = move |triple: &&Triple| {
+            subject
+                .as_ref()
+                .is_none_or(|subject| triple.subject == *subject)
+                && predicate
+                    .as_ref()
+                    .is_none_or(|predicate| triple.predicate == *predicate)
+                && object
+                    .as_ref()
+                    .is_none_or(|object| triple.object == *object)
+        };
+        match candidates {
+            Some(indices) => Box::new(
+                indices
+                    .iter()
+                    .map(|index| &self.triples[*index])
+                    .filter(matches),
+            ),
+            None => Box::new(self.triples.iter()),
+        }
+    }
+
+    /// The subjects and objects of all triples, without duplicates.
+    pub fn nodes(&self) -> Vec<&Term> {
+        let mut seen = HashSet::new();
+        self.triples
+            .iter()
+            .flat_map(|triple| [&triple.subject, &triple.object])
+            .filter(|term| seen.insert(*term))
+            .collect()
+    }
+}
+
+/// A default graph and named graphs.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    default: Graph,
+    named: Vec<(Term, Graph)>,
+    /// The number of blank nodes read, each file gets its own.
+    blank_nodes: usize,
+}
+
+impl Dataset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the RDF `text` into the dataset. Relative IRIs are resolved against `base`.
+    /// Turtle and N-Triples are read into the default graph.
+    pub fn load(&mut self, text: &str, format: RdfFormat, base: &str) -> Result<(), RdfError> {
+        for (triple, graph) in turtle::parse(text, format, base, &mut self.blank_nodes)? {
+            self.insert(triple, graph);
+        }
+        Ok(())
+    }
+
+    /// Reads the RDF `text` into the named graph `name`, the graphs of quads are ignored.
+    pub fn load_graph(
+        &mut self,
+        name: Term,
+        text: &str,
+        format: RdfFormat,
+        base: &str,
+    ) -> Result<(), RdfError> {
+        let quads = turtle::parse(text, format, base, &mut self.blank_nodes)?;
+        let graph = self.graph_mut(name);
+        for (triple, _graph) in quads {
+            graph.insert(triple);
+        }
+        Ok(())
+    }
+
+    /// Adds a triple to the default graph or a named graph.
+    pub fn insert(&mut self, triple: Triple, graph: Option<Term>) -> bool {
+        match graph {
+            Some(name) => self.graph_mut(name).insert(triple),
+            None => self.default.insert(triple),
+        }
+    }
+
+    fn graph_mut(&mut self, name: Term) -> &mut Graph {
+        let index = match self.named.iter().position(|(other, _graph)| *other == name) {
+            Some(index) => index,
+            None => {
+                self.named.push((name, Graph::default()));
+                self.named.len() - 1
+            }
+        };
+        &mut self.named[index].1
+    }
+
+    pub fn default_graph(&self) -> &Graph {
+        &self.default
+    }
+
+    /// The named graph `name`, if it exists.
+    pub fn graph(&self, name: &Term) -> Option<&Graph> {
+        self.named
+            .iter()
+            .find(|(other, _graph)| other == name)
+            .map(|(_name, graph)| graph)
+    }
+
+    /// The named graphs in the order they were created.
+    pub fn named_graphs(&self) -> impl Iterator<Item = (&Term, &Graph)> {
+        self.named.iter().map(|(name, graph)| (name, graph))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{Dataset, RdfFormat, Term, Triple};
+
+    fn iri(iri: &str) -> Term {
+        Term::Iri(iri.to_string())
+    }
+
+    fn literal(value: &str, datatype: Option<&str>, language: Option<&str>) -> Term {
+        Term::Literal {
+            value: value.to_string(),
+            datatype: datatype.map(str::to_string),
+            language: language.map(str::to_string),
+        }
+    }
+
+    fn triples(dataset: &Dataset) -> Vec<String> {
+        dataset
+            .default_graph()
+            .iter()
+            .map(Triple::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn turtle() {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(
+                indoc! {r#"
+                    @prefix ex: <http://example.org/> .
+                    PREFIX : <http://example.org/default#>
+                    @base <http://example.org/base/> .
+                    # A comment
+                    ex:a a ex:Class ; ex:p "x"@en, 'y', """multi
+                    line""" ;
+                        ex:q 1, -2.5, 3e2, true ;
+                        ex:r <relative>, :local, ex:with.dot, ex:esc\-aped .
+                    _:x ex:p [ ex:q ex:b ] , [] .
+                    ex:list ex:items ( 1 ex:c ) .
+                    ex:a ex:p "x"^^<http://www.w3.org/2001/XMLSchema#string> .
+                "#},
+                RdfFormat::Turtle,
+                "http://example.org/doc",
+            )
+            .unwrap();
+        assert_eq!(
+            triples(&dataset),
+            vec![
+                "<http://example.org/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/Class> .",
+                "<http://example.org/a> <http://example.org/p> \"x\"@en .",
+                "<http://example.org/a> <http://example.org/p> \"y\" .",
+                "<http://example.org/a> <http://example.org/p> \"multi\\nline\" .",
+                "<http://example.org/a> <http://example.org/q> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+                "<http://example.org/a> <http://example.org/q> \"-2.5\"^^<http://www.w3.org/2001/XMLSchema#decimal> .",
+                "<http://example.org/a> <http://example.org/q> \"3e2\"^^<http://www.w3.org/2001/XMLSchema#double> .",
+                "<http://example.org/a> <http://example.org/q> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .",
+                "<http://example.org/a> <http://example.org/r> <http://example.org/base/relative> .",
+                "<http://example.org/a> <http://example.org/r> <http://example.org/default#local> .",
+                "<http://example.org/a> <http://example.org/r> <http://example.org/with.dot> .",
+                "<http://example.org/a> <http://example.org/r> <http://example.org/esc-aped> .",
+                "_:b1 <http://example.org/q> <http://example.org/b> .",
+                "_:b0 <http://example.org/p> _:b1 .",
+                "_:b0 <http://example.org/p> _:b2 .",
+                "_:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+                "_:b3 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:b4 .",
+                "_:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://example.org/c> .",
+                "_:b4 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .",
+                "<http://example.org/list> <http://example.org/items> _:b3 .",
+                // NOTE: `xsd:string` literals are simple literals, unlike the language-tagged one.
+                "<http://example.org/a> <http://example.org/p> \"x\" .",
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_nodes_of_files_differ() {
+        let mut dataset = Dataset::new();
+        for _ in 0..2 {
+            dataset
+                .load("_:a <http://p> _:a .", RdfFormat::NTriples, "")
+                .unwrap();
+        }
+        assert_eq!(
+            triples(&dataset),
+            vec!["_:b0 <http://p> _:b0 .", "_:b1 <http://p> _:b1 ."]
+        );
+    }
+
+    #[test]
+    fn quads() {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(
+                indoc! {r#"
+                    <http://s> <http://p> "o" .
+                    <http://s> <http://p> "o" <http://g> .
+                    _:s <http://p> "aä" _:g .
+                "#},
+                RdfFormat::NQuads,
+                "",
+            )
+            .unwrap();
+        assert_eq!(dataset.default_graph().len(), 1);
+        let graph = dataset.graph(&iri("http://g")).unwrap();
+        assert_eq!(graph.len(), 1);
+        let (name, graph) = dataset.named_graphs().nth(1).unwrap();
+        assert_eq!(name, &Term::BlankNode("b1".to_string()));
+        assert_eq!(
+            graph.iter().next().unwrap().object,
+            literal("aä", None, None)
+        );
+    }
+
+    #[test]
+    fn trig() {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(
+                indoc! {r#"
+                    @prefix ex: <http://example.org/> .
+                    ex:s ex:p ex:o .
+                    { ex:s ex:p ex:default }
+                    ex:g { ex:s ex:p ex:o . ex:s ex:q 1 }
+                    GRAPH ex:h { ex:s ex:p ex:o }
+                "#},
+                RdfFormat::TriG,
+                "",
+            )
+            .unwrap();
+        assert_eq!(dataset.default_graph().len(), 2);
+        assert_eq!(
+            dataset.graph(&iri("http://example.org/g")).unwrap().len(),
+            2
+        );
+        assert_eq!(
+            dataset.graph(&iri("http://example.org/h")).unwrap().len(),
+            1
+        );
+        dataset
+            .load_graph(
+                iri("http://example.org/h"),
+                "<http://s> <http://p> <http://o> .",
+                RdfFormat::Turtle,
+                "",
+            )
+            .unwrap();
+        assert_eq!(
+            dataset.graph(&iri("http://example.org/h")).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn matching() {
+        let mut dataset = Dataset::new();
+        dataset
+            .load(
+                "<http://a> <http://p> <http://b>, <http://c> . <http://b> <http://p> <http://c> .",
+                RdfFormat::Turtle,
+                "",
+            )
+            .unwrap();
+        let graph = dataset.default_graph();
+        assert_eq!(
+            graph.matching(Some(&iri("http://a")), None, None).count(),
+            2
+        );
+        assert_eq!(
+            graph
+                .matching(None, Some(&iri("http://p")), Some(&iri("http://c")))
+                .count(),
+            2
+        );
+        assert_eq!(graph.matching(None, None, None).count(), 3);
+        assert_eq!(
+            graph.matching(Some(&iri("http://x")), None, None).count(),
+            0
+        );
+        assert_eq!(graph.nodes().len(), 3);
+    }
+
+    #[test]
+    fn errors() {
+        for (text, line) in [
+            ("<http://a> <http://b> .", 1),
+            ("ex:a <http://b> <http://c> .", 1),
+            ("<http://a>\n<http://b> \"open .", 2),
+            ("<http://a> <http://b> <http://c>", 1),
+        ] {
+            let error = Dataset::new()
+                .load(text, RdfFormat::Turtle, "")
+                .unwrap_err();
+            assert_eq!(error.line, line, "{}", text);
+        }
+        assert_eq!(
+            Dataset::new()
+                .load("ex:a ex:b ex:c .", RdfFormat::Turtle, "")
+                .unwrap_err()
+                .to_string(),
+            "Invalid RDF in line 1: the prefix \"ex:\" is not declared"
+        );
+    }
+}
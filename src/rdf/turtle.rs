@@ -0,0 +1,626 @@
+use std::collections::HashMap;
+
+use super::{iri::resolve, RdfError, RdfFormat, Term, Triple, RDF, XSD};
+
+/// A triple and the name of its graph, `None` for the default graph.
+pub(super) type Quad = (Triple, Option<Term>);
+
+// https://www.w3.org/TR/turtle/ and https://www.w3.org/TR/trig/
+// NOTE: N-Triples and N-Quads are read with the same rules, the only difference
+// is the optional graph name after the object of an N-Quads statement.
+pub(super) fn parse(
+    text: &str,
+    format: RdfFormat,
+    base: &str,
+    blank_nodes: &mut usize,
+) -> Result<Vec<Quad>, RdfError> {
+    let mut reader = Reader {
+        text,
+        position: 0,
+        format,
+        base: base.to_string(),
+        prefixes: HashMap::new(),
+        labels: HashMap::new(),
+        blank_nodes,
+        graph: None,
+        quads: vec![],
+    };
+    reader.document()?;
+    Ok(reader.quads)
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    position: usize,
+    format: RdfFormat,
+    base: String,
+    prefixes: HashMap<String, String>,
+    /// The blank nodes of the labels in the document.
+    labels: HashMap<String, String>,
+    /// The number of blank nodes in the dataset, new ones are numbered after them.
+    blank_nodes: &'a mut usize,
+    graph: Option<Term>,
+    quads: Vec<Quad>,
+}
+
+impl<'a> Reader<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, RdfError> {
+        Err(RdfError {
+            line: self.text[..self.position].matches('\n').count() + 1,
+            message: message.into(),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += char.len_utf8();
+        Some(char)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest().starts_with(token) {
+            true => {
+                self.position += token.len();
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), RdfError> {
+        self.skip_whitespace();
+        match self.eat(token) {
+            true => Ok(()),
+            false => self.error(format!("expected \"{}\"", token)),
+        }
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            match trimmed.starts_with('#') {
+                true => self.position += trimmed.find('\n').unwrap_or(trimmed.len()),
+                false => break,
+            }
+        }
+    }
+
+    /// Eats a case-insensitive keyword like `PREFIX` that is followed by whitespace.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let rest = self.rest();
+        let matches = rest
+            .get(..keyword.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(keyword))
+            && rest[keyword.len()..].starts_with(|char: char| char.is_whitespace() || char == '<');
+        if matches {
+            self.position += keyword.len();
+        }
+        matches
+    }
+
+    fn emit(&mut self, subject: Term, predicate: Term, object: Term) {
+        self.quads.push((
+            Triple {
+                subject,
+                predicate,
+                object,
+            },
+            self.graph.clone(),
+        ));
+    }
+
+    fn fresh_blank_node(&mut self) -> Term {
+        let id = format!("b{}", self.blank_nodes);
+        *self.blank_nodes += 1;
+        Term::BlankNode(id)
+    }
+
+    fn document(&mut self) -> Result<(), RdfError> {
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                return Ok(());
+            }
+            match self.format {
+                RdfFormat::NQuads => self.quad()?,
+                _ => self.statement()?,
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<(), RdfError> {
+        if self.eat("@prefix") {
+            self.prefix()?;
+            return self.expect(".");
+        }
+        if self.eat("@base") {
+            self.base()?;
+            return self.expect(".");
+        }
+        if self.keyword("PREFIX") {
+            return self.prefix();
+        }
+        if self.keyword("BASE") {
+            return self.base();
+        }
+        if self.format == RdfFormat::TriG {
+            if self.keyword("GRAPH") {
+                self.skip_whitespace();
+                let name = self.term()?;
+                return self.wrapped_graph(Some(name));
+            }
+            if self.peek() == Some('{') {
+                return self.wrapped_graph(None);
+            }
+        }
+        let subject = match self.peek() {
+            Some('[') if !self.is_anonymous() => {
+                let subject = self.blank_node_property_list()?;
+                self.skip_whitespace();
+                if matches!(self.peek(), Some('.')) {
+                    self.bump();
+                    return Ok(());
+                }
+                subject
+            }
+            _ => self.term()?,
+        };
+        self.skip_whitespace();
+        if self.format == RdfFormat::TriG && self.peek() == Some('{') {
+            return self.wrapped_graph(Some(subject));
+        }
+        self.predicate_object_list(subject)?;
+        self.expect(".")
+    }
+
+    fn prefix(&mut self) -> Result<(), RdfError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while let Some(char) = self.peek() {
+            if char == ':' || char.is_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+        let prefix = self.text[start..self.position].to_string();
+        self.expect(":")?;
+        self.skip_whitespace();
+        let namespace = self.iri_reference()?;
+        self.prefixes.insert(prefix, namespace);
+        Ok(())
+    }
+
+    fn base(&mut self) -> Result<(), RdfError> {
+        self.skip_whitespace();
+        self.base = self.iri_reference()?;
+        Ok(())
+    }
+
+    /// Reads `{ triples }` into the graph `name`.
+    fn wrapped_graph(&mut self, name: Option<Term>) -> Result<(), RdfError> {
+        self.expect("{")?;
+        let outer = std::mem::replace(&mut self.graph, name);
+        loop {
+            self.skip_whitespace();
+            if self.eat("}") {
+                break;
+            }
+            let subject = match self.peek() {
+                Some('[') if !self.is_anonymous() => {
+                    let subject = self.blank_node_property_list()?;
+                    self.skip_whitespace();
+                    subject
+                }
+                None => return self.error("expected \"}\""),
+                _ => self.term()?,
+            };
+            self.skip_whitespace();
+            if !matches!(self.peek(), Some('.' | '}')) {
+                self.predicate_object_list(subject)?;
+            }
+            self.skip_whitespace();
+            if !self.eat(".") && self.peek() != Some('}') {
+                return self.error("expected \".\" or \"}\"");
+            }
+        }
+        self.graph = outer;
+        Ok(())
+    }
+
+    /// Reads an N-Quads statement, a triple with an optional graph name.
+    fn quad(&mut self) -> Result<(), RdfError> {
+        let subject = self.term()?;
+        self.skip_whitespace();
+        let predicate = self.term()?;
+        self.skip_whitespace();
+        let object = self.term()?;
+        self.skip_whitespace();
+        self.graph = match self.peek() {
+            Some('.') => None,
+            _ => Some(self.term()?),
+        };
+        self.emit(subject, predicate, object);
+        self.expect(".")
+    }
+
+    fn predicate_object_list(&mut self, subject: Term) -> Result<(), RdfError> {
+        loop {
+            self.skip_whitespace();
+            let predicate = self.verb()?;
+            loop {
+                self.skip_whitespace();
+                let object = self.object()?;
+                self.emit(subject.clone(), predicate.clone(), object);
+                self.skip_whitespace();
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            let mut has_semicolon = false;
+            loop {
+                self.skip_whitespace();
+                match self.eat(";") {
+                    true => has_semicolon = true,
+                    false => break,
+                }
+            }
+            if !has_semicolon || matches!(self.peek(), Some('.' | ']' | '}') | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn verb(&mut self) -> Result<Term, RdfError> {
+        let rest = self.rest();
+        if rest.starts_with('a')
+            && !rest[1..].starts_with(|char: char| is_name_char(char) || char == ':')
+        {
+            self.bump();
+            return Ok(Term::Iri(format!("{}type", RDF)));
+        }
+        self.iri().map(Term::Iri)
+    }
+
+    fn iri(&mut self) -> Result<String, RdfError> {
+        match self.peek() {
+            Some('<') => self.iri_reference(),
+            _ => self.prefixed_name(),
+        }
+    }
+
+    fn object(&mut self) -> Result<Term, RdfError> {
+        match self.peek() {
+            Some('[') if !self.is_anonymous() => self.blank_node_property_list(),
+            Some('(') => self.collection(),
+            _ => self.term(),
+        }
+    }
+
+    /// Whether the next token is `[]`, possibly with whitespace inside.
+    fn is_anonymous(&self) -> bool {
+        self.rest()
+            .strip_prefix('[')
+            .is_some_and(|rest| rest.trim_start().starts_with(']'))
+    }
+
+    fn blank_node_property_list(&mut self) -> Result<Term, RdfError> {
+        self.expect("[")?;
+        let node = self.fresh_blank_node();
+        self.predicate_object_list(node.clone())?;
+        self.expect("]")?;
+        Ok(node)
+    }
+
+    fn collection(&mut self) -> Result<Term, RdfError> {
+        self.expect("(")?;
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.eat(")") {
+                break;
+            }
+            if self.peek().is_none() {
+                return self.error("expected \")\"");
+            }
+            items.push(self.object()?);
+        }
+        let nodes: Vec<Term> = items.iter().map(|_| self.fresh_blank_node()).collect();
+        let nil = Term::Iri(format!("{}nil", RDF));
+        for (index, item) in items.into_iter().enumerate() {
+            let rest = nodes.get(index + 1).unwrap_or(&nil).clone();
+            self.emit(
+                nodes[index].clone(),
+                Term::Iri(format!("{}first", RDF)),
+                item,
+            );
+            self.emit(
+                nodes[index].clone(),
+                Term::Iri(format!("{}rest", RDF)),
+                rest,
+            );
+        }
+        Ok(nodes.into_iter().next().unwrap_or(nil))
+    }
+
+    /// Reads an IRI, blank node or literal.
+    fn term(&mut self) -> Result<Term, RdfError> {
+        let rest = self.rest();
+        match self.peek() {
+            Some('<') => self.iri_reference().map(Term::Iri),
+            Some('"' | '\'') => self.literal(),
+            Some('[') if self.is_anonymous() => {
+                self.position += rest.find(']').expect("anonymous nodes are closed") + 1;
+                Ok(self.fresh_blank_node())
+            }
+            Some('_') if rest.starts_with("_:") => {
+                self.position += 2;
+                let label = self.name(true);
+                if label.is_empty() {
+                    return self.error("expected a blank node label");
+                }
+                let node = match self.labels.get(&label) {
+                    Some(id) => Term::BlankNode(id.clone()),
+                    None => {
+                        let node = self.fresh_blank_node();
+                        if let Term::BlankNode(id) = &node {
+                            self.labels.insert(label, id.clone());
+                        }
+                        node
+                    }
+                };
+                Ok(node)
+            }
+            Some('0'..='9' | '+' | '-' | '.') => self.number(),
+            Some(_)
+                if ["true", "false"].iter().any(|boolean| {
+                    rest.starts_with(boolean)
+                        && !rest[boolean.len()..]
+                            .starts_with(|char: char| is_name_char(char) || char == ':')
+                }) =>
+            {
+                let value = if rest.starts_with("true") {
+                    "true"
+                } else {
+                    "false"
+                };
+                self.position += value.len();
+                Ok(typed(value, "boolean"))
+            }
+            Some(_) => self.prefixed_name().map(Term::Iri),
+            None => self.error("expected a term"),
+        }
+    }
+
+    fn iri_reference(&mut self) -> Result<String, RdfError> {
+        if !self.eat("<") {
+            return self.error("expected an IRI");
+        }
+        let mut iri = String::new();
+        loop {
+            match self.bump() {
+                Some('>') => break,
+                Some('\\') => match self.bump() {
+                    Some(kind @ ('u' | 'U')) => iri.push(self.code_point(kind)?),
+                    _ => return self.error("invalid escape sequence in IRI"),
+                },
+                Some(char) if char.is_whitespace() || matches!(char, '<' | '"') => {
+                    return self.error("invalid character in IRI")
+                }
+                Some(char) => iri.push(char),
+                None => return self.error("expected \">\""),
+            }
+        }
+        Ok(match self.base.is_empty() {
+            true => iri,
+            false => resolve(&self.base, &iri),
+        })
+    }
+
+    /// Reads the hex digits of a `\u` or `\U` escape sequence.
+    fn code_point(&mut self, kind: char) -> Result<char, RdfError> {
+        let length = if kind == 'u' { 4 } else { 8 };
+        let hex = self.rest().get(..length).unwrap_or_default();
+        match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+            Some(char) if hex.len() == length => {
+                self.position += length;
+                Ok(char)
+            }
+            _ => self.error("invalid escape sequence"),
+        }
+    }
+
+    /// Reads a name of a prefix, a local name or a blank node label.
+    /// Local names can contain escapes and colons, no name ends with a dot.
+    fn name(&mut self, is_label: bool) -> String {
+        let mut name = String::new();
+        loop {
+            let rest = self.rest();
+            match self.peek() {
+                Some('\\') if !is_label && rest.len() > 1 => {
+                    self.bump();
+                    name.push(self.bump().expect("the escaped character exists"));
+                }
+                Some(':') if !is_label => {
+                    self.bump();
+                    name.push(':');
+                }
+                Some('.')
+                    if rest[1..].starts_with(|char: char| {
+                        is_name_char(char) || (!is_label && matches!(char, ':' | '%' | '\\'))
+                    }) =>
+                {
+                    self.bump();
+                    name.push('.');
+                }
+                Some('%') if !is_label => {
+                    self.bump();
+                    name.push('%');
+                }
+                Some(char) if is_name_char(char) => {
+                    self.bump();
+                    name.push(char);
+                }
+                _ => return name,
+            }
+        }
+    }
+
+    fn prefixed_name(&mut self) -> Result<String, RdfError> {
+        let start = self.position;
+        while let Some(char) = self.peek() {
+            match char {
+                ':' => break,
+                '.' if self.rest()[1..].starts_with(is_name_char) => {}
+                char if is_name_char(char) => {}
+                _ => {
+                    self.position = start;
+                    return self.error("expected a term");
+                }
+            }
+            self.bump();
+        }
+        let prefix = self.text[start..self.position].to_string();
+        if !self.eat(":") {
+            return self.error("expected a term");
+        }
+        let local = self.name(false);
+        match self.prefixes.get(&prefix) {
+            Some(namespace) => Ok(format!("{}{}", namespace, local)),
+            None => {
+                self.position = start;
+                self.error(format!("the prefix \"{}:\" is not declared", prefix))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Term, RdfError> {
+        let start = self.position;
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.bump();
+        }
+        let digits = |reader: &mut Self| {
+            let mut count = 0;
+            while reader.peek().is_some_and(|char| char.is_ascii_digit()) {
+                reader.bump();
+                count += 1;
+            }
+            count
+        };
+        let mut count = digits(self);
+        let mut datatype = "integer";
+        let rest = self.rest();
+        if rest.starts_with('.') && rest[1..].starts_with(|char: char| char.is_ascii_digit()) {
+            self.bump();
+            count += digits(self);
+            datatype = "decimal";
+        }
+        let rest = self.rest();
+        if count > 0 && rest.starts_with(['e', 'E']) {
+            let exponent = rest[1..].strip_prefix(['+', '-']).unwrap_or(&rest[1..]);
+            if exponent.starts_with(|char: char| char.is_ascii_digit()) {
+                self.position += rest.len() - exponent.len();
+                digits(self);
+                datatype = "double";
+            }
+        }
+        if count == 0 {
+            self.position = start;
+            return self.error("expected a term");
+        }
+        Ok(typed(&self.text[start..self.position], datatype))
+    }
+
+    fn literal(&mut self) -> Result<Term, RdfError> {
+        let quote = self.bump().expect("literals start with a quote");
+        let long: String = [quote; 3].iter().collect();
+        let is_long = self.rest().starts_with(&long[1..]);
+        if is_long {
+            self.position += 2;
+        }
+        let mut value = String::new();
+        loop {
+            if is_long && self.eat(&long) {
+                break;
+            }
+            match self.bump() {
+                Some(char) if char == quote && !is_long => break,
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('f') => '\u{c}',
+                        Some(char @ ('"' | '\'' | '\\')) => char,
+                        Some(kind @ ('u' | 'U')) => self.code_point(kind)?,
+                        _ => return self.error("invalid escape sequence in string"),
+                    };
+                    value.push(escaped);
+                }
+                Some('\n' | '\r') if !is_long => return self.error("unterminated string"),
+                Some(char) => value.push(char),
+                None => return self.error("unterminated string"),
+            }
+        }
+        if self.eat("@") {
+            let start = self.position;
+            while self
+                .peek()
+                .is_some_and(|char| char.is_ascii_alphanumeric() || char == '-')
+            {
+                self.bump();
+            }
+            let language = &self.text[start..self.position];
+            if language.is_empty() {
+                return self.error("expected a language tag");
+            }
+            return Ok(Term::Literal {
+                value,
+                datatype: None,
+                language: Some(language.to_string()),
+            });
+        }
+        if self.eat("^^") {
+            let datatype = self.iri()?;
+            return Ok(literal(value, datatype));
+        }
+        Ok(Term::Literal {
+            value,
+            datatype: None,
+            language: None,
+        })
+    }
+}
+
+fn is_name_char(char: char) -> bool {
+    char.is_alphanumeric() || matches!(char, '_' | '-' | '\u{B7}')
+}
+
+/// A literal with a datatype of XML schema, like `integer`.
+pub(crate) fn typed(value: &str, datatype: &str) -> Term {
+    Term::Literal {
+        value: value.to_string(),
+        datatype: Some(format!("{}{}", XSD, datatype)),
+        language: None,
+    }
+}
+
+/// A typed literal, `xsd:string` literals are simple literals.
+pub(crate) fn literal(value: String, datatype: String) -> Term {
+    Term::Literal {
+        value,
+        datatype: (datatype != format!("{}string", XSD)).then_some(datatype),
+        language: None,
+    }
+}
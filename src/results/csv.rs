@@ -0,0 +1,155 @@
+use csv::{ReaderBuilder, Terminator, WriterBuilder};
+
+use super::{
+    tsv::{ask_result, ASK_VARIABLE},
+    QueryResults, ResultsError, Term,
+};
+
+// https://www.w3.org/TR/sparql11-results-csv-tsv/#csv
+pub(super) fn parse(text: &str) -> Result<QueryResults, ResultsError> {
+    let invalid = |error: csv::Error| ResultsError(error.to_string());
+    let mut reader = ReaderBuilder::new().from_reader(text.as_bytes());
+    let variables: Vec<String> = reader
+        .headers()
+        .map_err(invalid)?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let solutions = reader
+        .records()
+        .map(|record| {
+            Ok(record
+                .map_err(invalid)?
+                .iter()
+                .map(|value| (!value.is_empty()).then(|| term(value)))
+                .collect())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ask_result(variables, solutions))
+}
+
+/// Guesses the kind of a term from its value.
+fn term(value: &str) -> Term {
+    if let Some(id) = value.strip_prefix("_:") {
+        return Term::BlankNode(id.to_string());
+    }
+    let has_scheme = value.split_once(':').is_some_and(|(scheme, _rest)| {
+        scheme.starts_with(|char: char| char.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || matches!(char, '+' | '-' | '.'))
+    });
+    match has_scheme && !value.contains(char::is_whitespace) {
+        true => Term::Iri(value.to_string()),
+        false => Term::Literal {
+            value: value.to_string(),
+            datatype: None,
+            language: None,
+        },
+    }
+}
+
+pub(super) fn serialize(results: &QueryResults) -> String {
+    let mut writer = WriterBuilder::new()
+        .terminator(Terminator::CRLF)
+        .from_writer(vec![]);
+    let mut write = |record: Vec<&str>| {
+        writer
+            .write_record(record)
+            .expect("Writing to memory does not fail")
+    };
+    match results {
+        QueryResults::Boolean(boolean) => {
+            write(vec![ASK_VARIABLE]);
+            write(vec![if *boolean { "true" } else { "false" }]);
+        }
+        QueryResults::Solutions {
+            variables,
+            solutions,
+        } => {
+            write(variables.iter().map(String::as_str).collect());
+            for solution in solutions {
+                let values: Vec<String> = solution
+                    .iter()
+                    .map(|term| match term {
+                        Some(Term::Iri(iri)) => iri.clone(),
+                        Some(Term::BlankNode(id)) => format!("_:{}", id),
+                        Some(Term::Literal { value, .. }) => value.clone(),
+                        None => String::new(),
+                    })
+                    .collect();
+                write(values.iter().map(String::as_str).collect());
+            }
+        }
+    }
+    String::from_utf8(
+        writer
+            .into_inner()
+            .expect("Writing to memory does not fail"),
+    )
+    .expect("The values are UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::results::{QueryResults, Term};
+
+    use super::{parse, serialize};
+
+    #[test]
+    fn round_trip() {
+        let text = "s,label\r\nhttp://example.org/a,\"Hello, \"\"World\"\"\"\r\n_:b,\r\nurn:x,mailto me\r\n";
+        let results = parse(text).unwrap();
+        assert_eq!(
+            results,
+            QueryResults::Solutions {
+                variables: vec!["s".to_string(), "label".to_string()],
+                solutions: vec![
+                    vec![
+                        Some(Term::Iri("http://example.org/a".to_string())),
+                        Some(Term::Literal {
+                            value: "Hello, \"World\"".to_string(),
+                            datatype: None,
+                            language: None
+                        })
+                    ],
+                    vec![Some(Term::BlankNode("b".to_string())), None],
+                    vec![
+                        Some(Term::Iri("urn:x".to_string())),
+                        Some(Term::Literal {
+                            value: "mailto me".to_string(),
+                            datatype: None,
+                            language: None
+                        })
+                    ]
+                ]
+            }
+        );
+        assert_eq!(serialize(&results), text);
+    }
+
+    #[test]
+    fn multi_line_values() {
+        let results = QueryResults::Solutions {
+            variables: vec!["o".to_string()],
+            solutions: vec![vec![Some(Term::Literal {
+                value: "a\nb".to_string(),
+                datatype: Some("http://www.w3.org/2001/XMLSchema#string".to_string()),
+                language: None,
+            })]],
+        };
+        assert_eq!(serialize(&results), "o\r\n\"a\nb\"\r\n");
+    }
+
+    #[test]
+    fn ask_results() {
+        assert_eq!(
+            serialize(&QueryResults::Boolean(true)),
+            "_askResult\r\ntrue\r\n"
+        );
+        assert_eq!(
+            parse("_askResult\r\nfalse\r\n").unwrap(),
+            QueryResults::Boolean(false)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{QueryResults, ResultsError, Term};
+
+// https://www.w3.org/TR/sparql11-results-json/
+#[derive(Deserialize)]
+struct Document {
+    #[serde(default)]
+    head: Head,
+    results: Option<Results>,
+    boolean: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+struct Head {
+    #[serde(default)]
+    vars: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Results {
+    bindings: Vec<HashMap<String, Binding>>,
+}
+
+#[derive(Deserialize)]
+struct Binding {
+    #[serde(rename = "type")]
+    kind: String,
+    value: String,
+    datatype: Option<String>,
+    #[serde(rename = "xml:lang")]
+    language: Option<String>,
+}
+
+impl TryFrom<Binding> for Term {
+    type Error = ResultsError;
+
+    fn try_from(binding: Binding) -> Result<Self, Self::Error> {
+        match binding.kind.as_str() {
+            "uri" => Ok(Term::Iri(binding.value)),
+            "bnode" => Ok(Term::BlankNode(binding.value)),
+            // NOTE: "typed-literal" is not in the recommendation, but some endpoints still send it.
+            "literal" | "typed-literal" => Ok(Term::Literal {
+                value: binding.value,
+                datatype: binding.datatype,
+                language: binding.language,
+            }),
+            kind => Err(ResultsError(format!("unknown term type \"{}\"", kind))),
+        }
+    }
+}
+
+pub(super) fn parse(text: &str) -> Result<QueryResults, ResultsError> {
+    let document: Document =
+        serde_json::from_str(text).map_err(|error| ResultsError(error.to_string()))?;
+    match (document.boolean, document.results) {
+        (Some(boolean), _) => Ok(QueryResults::Boolean(boolean)),
+        (None, Some(results)) => {
+            let variables = document.head.vars;
+            let solutions = results
+                .bindings
+                .into_iter()
+                .map(|mut bindings| {
+                    variables
+                        .iter()
+                        .map(|variable| bindings.remove(variable).map(Term::try_from).transpose())
+                        .collect()
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(QueryResults::Solutions {
+                variables,
+                solutions,
+            })
+        }
+        (None, None) => Err(ResultsError(
+            "neither \"results\" nor \"boolean\" is present".to_string(),
+        )),
+    }
+}
+
+pub(super) fn serialize(results: &QueryResults) -> String {
+    let document = match results {
+        QueryResults::Boolean(boolean) => json!({"head": {}, "boolean": boolean}),
+        QueryResults::Solutions {
+            variables,
+            solutions,
+        } => {
+            let bindings: Vec<Map<String, Value>> = solutions
+                .iter()
+                .map(|solution| {
+                    variables
+                        .iter()
+                        .zip(solution)
+                        .filter_map(|(variable, term)| {
+                            Some((variable.clone(), binding(term.as_ref()?)))
+                        })
+                        .collect()
+                })
+                .collect();
+            json!({"head": {"vars": variables}, "results": {"bindings": bindings}})
+        }
+    };
+    serde_json::to_string_pretty(&document).expect("Results are serializable")
+}
+
+fn binding(term: &Term) -> Value {
+    match term {
+        Term::Iri(iri) => json!({"type": "uri", "value": iri}),
+        Term::BlankNode(id) => json!({"type": "bnode", "value": id}),
+        Term::Literal {
+            value,
+            datatype,
+            language,
+        } => {
+            let mut binding = json!({"type": "literal", "value": value});
+            if let Some(language) = language {
+                binding["xml:lang"] = json!(language);
+            } else if let Some(datatype) = datatype {
+                binding["datatype"] = json!(datatype);
+            }
+            binding
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::results::{QueryResults, Term};
+
+    use super::{parse, serialize};
+
+    #[test]
+    fn parse_solutions() {
+        let text = r#"{
+            "head": {"vars": ["s", "o"]},
+            "results": {"bindings": [
+                {"s": {"type": "uri", "value": "http://a"}, "o": {"type": "literal", "value": "x", "xml:lang": "en"}},
+                {"s": {"type": "bnode", "value": "b1"}}
+            ]}
+        }"#;
+        assert_eq!(
+            parse(text).unwrap(),
+            QueryResults::Solutions {
+                variables: vec!["s".to_string(), "o".to_string()],
+                solutions: vec![
+                    vec![
+                        Some(Term::Iri("http://a".to_string())),
+                        Some(Term::Literal {
+                            value: "x".to_string(),
+                            datatype: None,
+                            language: Some("en".to_string())
+                        })
+                    ],
+                    vec![Some(Term::BlankNode("b1".to_string())), None]
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn parse_boolean() {
+        assert_eq!(
+            parse(r#"{"head": {}, "boolean": true}"#).unwrap(),
+            QueryResults::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn invalid_results() {
+        assert!(parse(r#"{"head": {}}"#).is_err());
+        assert!(parse("not json").is_err());
+        assert!(parse(
+            r#"{"head": {"vars": ["s"]}, "results": {"bindings": [{"s": {"type": "thing", "value": "x"}}]}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn serialize_solutions() {
+        let results = QueryResults::Solutions {
+            variables: vec!["s".to_string(), "o".to_string()],
+            solutions: vec![vec![
+                Some(Term::BlankNode("b".to_string())),
+                Some(Term::Literal {
+                    value: "1".to_string(),
+                    datatype: Some("http://www.w3.org/2001/XMLSchema#integer".to_string()),
+                    language: None,
+                }),
+            ]],
+        };
+        let serialized: serde_json::Value = serde_json::from_str(&serialize(&results)).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "head": {"vars": ["s", "o"]},
+                "results": {"bindings": [{
+                    "s": {"type": "bnode", "value": "b"},
+                    "o": {"type": "literal", "value": "1", "datatype": "http://www.w3.org/2001/XMLSchema#integer"}
+                }]}
+            })
+        );
+        assert_eq!(
+            parse(&serialize(&QueryResults::Boolean(false))).unwrap(),
+            QueryResults::Boolean(false)
+        );
+    }
+}
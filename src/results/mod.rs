@@ -0,0 +1,270 @@
+//! The results of SPARQL queries, as endpoints return them.
+//! They are read and written in all formats of SPARQL 1.1 and rendered as tables.
+
+mod csv;
+mod json;
+mod table;
+mod tsv;
+mod xml;
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// The formats of the SPARQL 1.1 Query Results.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultsFormat {
+    #[default]
+    Json,
+    Xml,
+    Csv,
+    Tsv,
+}
+
+impl ResultsFormat {
+    /// The media type that is requested from endpoints.
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            ResultsFormat::Json => "application/sparql-results+json",
+            ResultsFormat::Xml => "application/sparql-results+xml",
+            ResultsFormat::Csv => "text/csv",
+            ResultsFormat::Tsv => "text/tab-separated-values",
+        }
+    }
+
+    /// The format of a file extension, like `srj` or `csv`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "srj" | "json" => Some(ResultsFormat::Json),
+            "srx" | "xml" => Some(ResultsFormat::Xml),
+            "csv" => Some(ResultsFormat::Csv),
+            "tsv" => Some(ResultsFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ResultsFormat {
+    type Err = ResultsError;
+
+    /// Reads `json`, `xml`, `csv` or `tsv`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "json" => Ok(ResultsFormat::Json),
+            "xml" => Ok(ResultsFormat::Xml),
+            "csv" => Ok(ResultsFormat::Csv),
+            "tsv" => Ok(ResultsFormat::Tsv),
+            name => Err(ResultsError(format!(
+                "unknown format \"{}\", expected json, xml, csv or tsv",
+                name
+            ))),
+        }
+    }
+}
+
+/// An RDF term bound to a variable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Term {
+    Iri(String),
+    BlankNode(String),
+    Literal {
+        value: String,
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+}
+
+impl Term {
+    /// The term in N-Triples syntax, with IRIs abbreviated by the `prefixes` where possible.
+    pub(crate) fn abbreviated(&self, prefixes: &[(String, String)]) -> String {
+        table::cell(self, prefixes)
+    }
+}
+
+impl fmt::Display for Term {
+    /// Writes the term in N-Triples syntax.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Iri(iri) => write!(f, "<{}>", iri),
+            Term::BlankNode(id) => write!(f, "_:{}", id),
+            Term::Literal {
+                value,
+                datatype,
+                language,
+            } => {
+                write!(f, "{}", quoted(value))?;
+                match (language, datatype) {
+                    (Some(language), _) => write!(f, "@{}", language),
+                    (None, Some(datatype)) => write!(f, "^^<{}>", datatype),
+                    (None, None) => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// The lexical form of a literal as N-Triples string.
+fn quoted(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The results of a `SELECT` or `ASK` query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryResults {
+    /// The solutions of a `SELECT` query, each binds the variables in the same order.
+    Solutions {
+        variables: Vec<String>,
+        solutions: Vec<Vec<Option<Term>>>,
+    },
+    /// The answer of an `ASK` query.
+    Boolean(bool),
+}
+
+/// Results that could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultsError(pub String);
+
+impl fmt::Display for ResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query results: {}", self.0)
+    }
+}
+
+impl std::error::Error for ResultsError {}
+
+impl QueryResults {
+    /// Reads results in the given format.
+    /// CSV does not tell IRIs from literals, values with a scheme like `http:` are read as IRIs.
+    pub fn parse(text: &str, format: ResultsFormat) -> Result<Self, ResultsError> {
+        match format {
+            ResultsFormat::Json => json::parse(text),
+            ResultsFormat::Xml => xml::parse(text),
+            ResultsFormat::Csv => csv::parse(text),
+            ResultsFormat::Tsv => tsv::parse(text),
+        }
+    }
+
+    /// Writes the results in the given format.
+    /// CSV only keeps the values of the terms, without datatypes and languages.
+    pub fn serialize(&self, format: ResultsFormat) -> String {
+        match format {
+            ResultsFormat::Json => json::serialize(self),
+            ResultsFormat::Xml => xml::serialize(self),
+            ResultsFormat::Csv => csv::serialize(self),
+            ResultsFormat::Tsv => tsv::serialize(self),
+        }
+    }
+
+    /// Renders the results as a table with aligned columns and one row per solution.
+    /// IRIs are abbreviated with the `prefixes`, pairs of prefix and namespace IRI.
+    pub fn table(&self, prefixes: &[(String, String)]) -> String {
+        table::render(self, prefixes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryResults, ResultsFormat, Term};
+
+    #[test]
+    fn display_terms() {
+        assert_eq!(Term::Iri("http://a".to_string()).to_string(), "<http://a>");
+        assert_eq!(Term::BlankNode("b0".to_string()).to_string(), "_:b0");
+        assert_eq!(
+            Term::Literal {
+                value: "say \"hi\"\n".to_string(),
+                datatype: None,
+                language: Some("en".to_string())
+            }
+            .to_string(),
+            r#""say \"hi\"\n"@en"#
+        );
+        assert_eq!(
+            Term::Literal {
+                value: "1".to_string(),
+                datatype: Some("http://www.w3.org/2001/XMLSchema#integer".to_string()),
+                language: None
+            }
+            .to_string(),
+            "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+        );
+    }
+
+    #[test]
+    fn convert() {
+        let results = QueryResults::Solutions {
+            variables: vec!["s".to_string(), "o".to_string(), "n".to_string()],
+            solutions: vec![
+                vec![
+                    Some(Term::Iri("http://example.org/a".to_string())),
+                    Some(Term::Literal {
+                        value: "tab\tand \"quotes\"".to_string(),
+                        datatype: None,
+                        language: Some("en-GB".to_string()),
+                    }),
+                    Some(Term::Literal {
+                        value: "42".to_string(),
+                        datatype: Some("http://www.w3.org/2001/XMLSchema#integer".to_string()),
+                        language: None,
+                    }),
+                ],
+                vec![Some(Term::BlankNode("b1".to_string())), None, None],
+            ],
+        };
+        for format in [ResultsFormat::Json, ResultsFormat::Xml, ResultsFormat::Tsv] {
+            let serialized = results.serialize(format);
+            assert_eq!(
+                QueryResults::parse(&serialized, format).unwrap(),
+                results,
+                "{:?}",
+                format
+            );
+        }
+        let csv = results.serialize(ResultsFormat::Csv);
+        assert_eq!(
+            csv,
+            "s,o,n\r\nhttp://example.org/a,\"tab\tand \"\"quotes\"\"\",42\r\n_:b1,,\r\n"
+        );
+        for format in [
+            ResultsFormat::Json,
+            ResultsFormat::Xml,
+            ResultsFormat::Csv,
+            ResultsFormat::Tsv,
+        ] {
+            let boolean = QueryResults::Boolean(true).serialize(format);
+            assert_eq!(
+                QueryResults::parse(&boolean, format).unwrap(),
+                QueryResults::Boolean(true)
+            );
+        }
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("csv".parse::<ResultsFormat>(), Ok(ResultsFormat::Csv));
+        assert!("table".parse::<ResultsFormat>().is_err());
+        assert_eq!(
+            ResultsFormat::from_extension("SRJ"),
+            Some(ResultsFormat::Json)
+        );
+        assert_eq!(
+            ResultsFormat::from_extension("srx"),
+            Some(ResultsFormat::Xml)
+        );
+        assert_eq!(ResultsFormat::from_extension("rq"), None);
+    }
+}
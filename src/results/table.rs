@@ -0,0 +1,163 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::analysis::abbreviate;
+
+use super::{quoted, QueryResults, Term};
+
+pub(super) fn render(results: &QueryResults, prefixes: &[(String, String)]) -> String {
+    let (variables, solutions) = match results {
+        QueryResults::Boolean(boolean) => return format!("{}\n", boolean),
+        QueryResults::Solutions {
+            variables,
+            solutions,
+        } => (variables, solutions),
+    };
+    let header: Vec<String> = variables
+        .iter()
+        .map(|variable| format!("?{}", variable))
+        .collect();
+    let rows: Vec<Vec<String>> = solutions
+        .iter()
+        .map(|solution| {
+            solution
+                .iter()
+                .map(|term| {
+                    term.as_ref()
+                        .map_or(String::new(), |term| cell(term, prefixes))
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            // NOTE: `{:width$}` pads by chars, wide characters take two columns in a terminal.
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.width())))
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    let mut table = line(&header);
+    table.push_str(&format!("|-{}-|\n", rule.join("-|-")));
+    for row in &rows {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+/// The term in N-Triples syntax, with IRIs abbreviated where possible.
+pub(super) fn cell(term: &Term, prefixes: &[(String, String)]) -> String {
+    match term {
+        Term::Iri(iri) => abbreviate(iri, prefixes).unwrap_or_else(|| term.to_string()),
+        Term::Literal {
+            value,
+            datatype: Some(datatype),
+            language: None,
+        } => match abbreviate(datatype, prefixes) {
+            Some(datatype) => format!("{}^^{}", quoted(value), datatype),
+            None => term.to_string(),
+        },
+        term => term.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::results::{QueryResults, Term};
+
+    use super::render;
+
+    fn prefixes(prefixes: &[(&str, &str)]) -> Vec<(String, String)> {
+        prefixes
+            .iter()
+            .map(|(prefix, namespace)| (prefix.to_string(), namespace.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn table() {
+        let results = QueryResults::Solutions {
+            variables: vec!["s".to_string(), "label".to_string()],
+            solutions: vec![
+                vec![
+                    Some(Term::Iri("http://example.org/ä".to_string())),
+                    Some(Term::Literal {
+                        value: "A".to_string(),
+                        datatype: None,
+                        language: None,
+                    }),
+                ],
+                vec![Some(Term::BlankNode("b".to_string())), None],
+            ],
+        };
+        assert_eq!(
+            render(&results, &[]),
+            indoc! {r#"
+                | ?s                     | ?label |
+                |------------------------|--------|
+                | <http://example.org/ä> | "A"    |
+                | _:b                    |        |
+            "#}
+        );
+        assert_eq!(render(&QueryResults::Boolean(true), &[]), "true\n");
+    }
+
+    #[test]
+    fn wide_characters() {
+        let results = QueryResults::Solutions {
+            variables: vec!["s".to_string()],
+            solutions: vec![
+                vec![Some(Term::Iri("http://example.org/日本語".to_string()))],
+                vec![Some(Term::Iri("http://example.org/abc".to_string()))],
+            ],
+        };
+        assert_eq!(
+            render(&results, &[]),
+            indoc! {r#"
+                | ?s                          |
+                |-----------------------------|
+                | <http://example.org/日本語> |
+                | <http://example.org/abc>    |
+            "#}
+        );
+    }
+
+    #[test]
+    fn abbreviated_table() {
+        let results = QueryResults::Solutions {
+            variables: vec!["item".to_string(), "count".to_string()],
+            solutions: vec![vec![
+                Some(Term::Iri("http://www.wikidata.org/entity/Q42".to_string())),
+                Some(Term::Literal {
+                    value: "7".to_string(),
+                    datatype: Some("http://www.w3.org/2001/XMLSchema#integer".to_string()),
+                    language: None,
+                }),
+            ]],
+        };
+        let prefixes = prefixes(&[
+            ("wd", "http://www.wikidata.org/entity/"),
+            ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+        ]);
+        assert_eq!(
+            render(&results, &prefixes),
+            indoc! {r#"
+                | ?item  | ?count           |
+                |--------|------------------|
+                | wd:Q42 | "7"^^xsd:integer |
+            "#}
+        );
+    }
+}
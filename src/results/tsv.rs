@@ -0,0 +1,291 @@
+use super::{QueryResults, ResultsError, Term};
+
+// https://www.w3.org/TR/sparql11-results-csv-tsv/#tsv
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// NOTE: There is no TSV for `ASK` results, they are written as a single variable like other tools do.
+pub(super) const ASK_VARIABLE: &str = "_askResult";
+
+pub(super) fn parse(text: &str) -> Result<QueryResults, ResultsError> {
+    let mut lines = text.lines();
+    let header = lines
+        .next()
+        .ok_or_else(|| ResultsError("the header is missing".to_string()))?;
+    let variables = header
+        .split('\t')
+        .filter(|variable| !variable.is_empty())
+        .map(|variable| {
+            variable
+                .strip_prefix(['?', '$'])
+                .map(str::to_string)
+                .ok_or_else(|| ResultsError(format!("invalid variable \"{}\"", variable)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let solutions = lines
+        .enumerate()
+        // NOTE: The last line break is optional, an empty line only binds nothing if there are no variables.
+        .filter(|(_number, line)| !line.is_empty() || variables.is_empty())
+        .map(|(number, line)| {
+            let values: Vec<&str> = line.split('\t').collect();
+            if values.len() != variables.len().max(1) {
+                return Err(ResultsError(format!(
+                    "line {} has {} values, but there are {} variables",
+                    number + 2,
+                    values.len(),
+                    variables.len()
+                )));
+            }
+            values
+                .into_iter()
+                .take(variables.len())
+                .map(|value| match value.trim() {
+                    "" => Ok(None),
+                    value => parse_term(value).map(Some),
+                })
+                .collect()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ask_result(variables, solutions))
+}
+
+/// Reads a single variable `_askResult` with a boolean as `ASK` result.
+pub(super) fn ask_result(
+    variables: Vec<String>,
+    solutions: Vec<Vec<Option<Term>>>,
+) -> QueryResults {
+    match (variables.as_slice(), solutions.as_slice()) {
+        ([variable], [solution]) if variable == ASK_VARIABLE => match solution.as_slice() {
+            [Some(Term::Literal { value, .. })] if value == "true" => QueryResults::Boolean(true),
+            [Some(Term::Literal { value, .. })] if value == "false" => QueryResults::Boolean(false),
+            _ => QueryResults::Solutions {
+                variables,
+                solutions,
+            },
+        },
+        _ => QueryResults::Solutions {
+            variables,
+            solutions,
+        },
+    }
+}
+
+/// Reads a term in the syntax of SPARQL, as TSV results contain them.
+fn parse_term(text: &str) -> Result<Term, ResultsError> {
+    let invalid = || ResultsError(format!("invalid term {}", text));
+    if let Some(iri) = text.strip_prefix('<') {
+        return Ok(Term::Iri(
+            iri.strip_suffix('>').ok_or_else(invalid)?.to_string(),
+        ));
+    }
+    if let Some(id) = text.strip_prefix("_:") {
+        return Ok(Term::BlankNode(id.to_string()));
+    }
+    if let Some(rest) = text.strip_prefix('"') {
+        let (value, suffix) = unescape(rest).ok_or_else(invalid)?;
+        let (datatype, language) = if let Some(language) = suffix.strip_prefix('@') {
+            (None, Some(language.to_string()))
+        } else if let Some(datatype) = suffix.strip_prefix("^^<") {
+            let datatype = datatype.strip_suffix('>').ok_or_else(invalid)?;
+            (Some(datatype.to_string()), None)
+        } else if suffix.is_empty() {
+            (None, None)
+        } else {
+            return Err(invalid());
+        };
+        return Ok(Term::Literal {
+            value,
+            datatype,
+            language,
+        });
+    }
+    let number = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let is_digits = |text: &str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+    let datatype = match number.split_once('.') {
+        _ if text == "true" || text == "false" => "boolean",
+        _ if number.contains(['e', 'E']) && text.parse::<f64>().is_ok() => "double",
+        None if is_digits(number) => "integer",
+        Some((integer, fraction))
+            if (integer.is_empty() || is_digits(integer)) && is_digits(fraction) =>
+        {
+            "decimal"
+        }
+        _ => return Err(invalid()),
+    };
+    Ok(Term::Literal {
+        value: text.to_string(),
+        datatype: Some(format!("{}{}", XSD, datatype)),
+        language: None,
+    })
+}
+
+/// Reads the rest of a string literal after the opening quote.
+/// Returns the value and the text after the closing quote.
+fn unescape(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((offset, char)) = chars.next() {
+        match char {
+            '"' => return Some((value, &text[offset + 1..])),
+            '\\' => {
+                let escaped = match chars.next()?.1 {
+                    't' => '\t',
+                    'b' => '\u{8}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    'f' => '\u{c}',
+                    '"' => '"',
+                    '\'' => '\'',
+                    '\\' => '\\',
+                    kind @ ('u' | 'U') => {
+                        let length = if kind == 'u' { 4 } else { 8 };
+                        let hex: String = (0..length)
+                            .map(|_| chars.next().map(|(_offset, char)| char))
+                            .collect::<Option<_>>()?;
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    _ => return None,
+                };
+                value.push(escaped);
+            }
+            char => value.push(char),
+        }
+    }
+    None
+}
+
+pub(super) fn serialize(results: &QueryResults) -> String {
+    match results {
+        QueryResults::Boolean(boolean) => format!("?{}\n{}\n", ASK_VARIABLE, boolean),
+        QueryResults::Solutions {
+            variables,
+            solutions,
+        } => {
+            let header: Vec<String> = variables
+                .iter()
+                .map(|variable| format!("?{}", variable))
+                .collect();
+            let mut text = header.join("\t");
+            text.push('\n');
+            for solution in solutions {
+                let values: Vec<String> = solution
+                    .iter()
+                    .map(|term| term.as_ref().map_or(String::new(), Term::to_string))
+                    .collect();
+                text.push_str(&values.join("\t"));
+                text.push('\n');
+            }
+            text
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::results::{QueryResults, Term};
+
+    use super::{parse, parse_term, serialize};
+
+    fn literal(value: &str, datatype: Option<&str>, language: Option<&str>) -> Term {
+        Term::Literal {
+            value: value.to_string(),
+            datatype: datatype.map(str::to_string),
+            language: language.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parse_terms() {
+        assert_eq!(
+            parse_term("<http://a>").unwrap(),
+            Term::Iri("http://a".to_string())
+        );
+        assert_eq!(
+            parse_term("_:b0").unwrap(),
+            Term::BlankNode("b0".to_string())
+        );
+        assert_eq!(
+            parse_term(r#""a\"bä\n"@en"#).unwrap(),
+            literal("a\"bä\n", None, Some("en"))
+        );
+        assert_eq!(
+            parse_term(r#""1"^^<http://www.w3.org/2001/XMLSchema#int>"#).unwrap(),
+            literal("1", Some("http://www.w3.org/2001/XMLSchema#int"), None)
+        );
+        assert_eq!(
+            parse_term("-42").unwrap(),
+            literal(
+                "-42",
+                Some("http://www.w3.org/2001/XMLSchema#integer"),
+                None
+            )
+        );
+        assert_eq!(
+            parse_term("1.5").unwrap(),
+            literal(
+                "1.5",
+                Some("http://www.w3.org/2001/XMLSchema#decimal"),
+                None
+            )
+        );
+        assert_eq!(
+            parse_term("1e3").unwrap(),
+            literal("1e3", Some("http://www.w3.org/2001/XMLSchema#double"), None)
+        );
+        assert_eq!(
+            parse_term("true").unwrap(),
+            literal(
+                "true",
+                Some("http://www.w3.org/2001/XMLSchema#boolean"),
+                None
+            )
+        );
+        assert!(parse_term("<http://a").is_err());
+        assert!(parse_term(r#""open"#).is_err());
+        assert!(parse_term(r#""a"b"#).is_err());
+        assert!(parse_term("ex:a").is_err());
+    }
+
+    #[test]
+    fn parse_solutions() {
+        let results = parse("?s\t?o\n<http://a>\t\"x\"\n_:b\t\n").unwrap();
+        assert_eq!(
+            results,
+            QueryResults::Solutions {
+                variables: vec!["s".to_string(), "o".to_string()],
+                solutions: vec![
+                    vec![
+                        Some(Term::Iri("http://a".to_string())),
+                        Some(literal("x", None, None))
+                    ],
+                    vec![Some(Term::BlankNode("b".to_string())), None]
+                ]
+            }
+        );
+        assert_eq!(serialize(&results), "?s\t?o\n<http://a>\t\"x\"\n_:b\t\n");
+        assert!(parse("?s\t?o\n<http://a>\n").is_err());
+        assert!(parse("s\n").is_err());
+    }
+
+    #[test]
+    fn ask_results() {
+        assert_eq!(
+            serialize(&QueryResults::Boolean(true)),
+            "?_askResult\ntrue\n"
+        );
+        assert_eq!(
+            parse("?_askResult\nfalse\n").unwrap(),
+            QueryResults::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn no_variables() {
+        assert_eq!(
+            parse("\n\n").unwrap(),
+            QueryResults::Solutions {
+                variables: vec![],
+                solutions: vec![vec![]]
+            }
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use super::{QueryResults, ResultsError, Term};
+
+// https://www.w3.org/TR/rdf-sparql-XMLres/
+const NAMESPACE: &str = "http://www.w3.org/2005/sparql-results#";
+
+/// The term that is read, with its attributes.
+enum TermKind {
+    Iri,
+    BlankNode,
+    Literal {
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+}
+
+pub(super) fn parse(text: &str) -> Result<QueryResults, ResultsError> {
+    let invalid = |error: quick_xml::Error| ResultsError(error.to_string());
+    let mut reader = Reader::from_str(text);
+    let mut variables: Vec<String> = vec![];
+    let mut solutions: Vec<Vec<Option<Term>>> = vec![];
+    let mut boolean: Option<String> = None;
+    let mut solution: Option<Vec<Option<Term>>> = None;
+    let mut binding: Option<usize> = None;
+    let mut term: Option<(TermKind, String)> = None;
+    let mut has_results = false;
+    // The number of open elements.
+    let mut depth = 0;
+    loop {
+        let event = reader.read_event().map_err(invalid)?;
+        let is_empty = matches!(event, Event::Empty(_));
+        match &event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            _ => {}
+        }
+        match event {
+            Event::Start(element) | Event::Empty(element) => {
+                match element.local_name().as_ref() {
+                    b"variable" => variables.push(
+                        attribute(&element, "name")?
+                            .ok_or_else(|| ResultsError("a variable has no name".to_string()))?,
+                    ),
+                    b"results" => has_results = true,
+                    b"result" => {
+                        let empty = vec![None; variables.len()];
+                        match is_empty {
+                            true => solutions.push(empty),
+                            false => solution = Some(empty),
+                        }
+                    }
+                    b"binding" => {
+                        let name = attribute(&element, "name")?.unwrap_or_default();
+                        binding = Some(
+                            variables
+                                .iter()
+                                .position(|variable| *variable == name)
+                                .ok_or_else(|| {
+                                    ResultsError(format!("unknown variable \"{}\"", name))
+                                })?,
+                        );
+                    }
+                    b"uri" => term = Some((TermKind::Iri, String::new())),
+                    b"bnode" => term = Some((TermKind::BlankNode, String::new())),
+                    b"literal" => {
+                        let kind = TermKind::Literal {
+                            datatype: attribute(&element, "datatype")?,
+                            language: attribute(&element, "xml:lang")?,
+                        };
+                        term = Some((kind, String::new()));
+                    }
+                    b"boolean" => boolean = Some(String::new()),
+                    _ => {}
+                }
+                if is_empty && term.is_some() {
+                    bind(&mut solution, binding, term.take())?;
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(invalid)?;
+                match (&mut term, &mut boolean) {
+                    (Some((_kind, value)), _) => value.push_str(&text),
+                    (None, Some(boolean)) => boolean.push_str(&text),
+                    (None, None) => {}
+                }
+            }
+            Event::CData(text) => {
+                if let Some((_kind, value)) = &mut term {
+                    value.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"uri" | b"bnode" | b"literal" => bind(&mut solution, binding, term.take())?,
+                b"binding" => binding = None,
+                b"result" => solutions.extend(solution.take()),
+                b"boolean" => {
+                    return match boolean.as_deref().map(str::trim) {
+                        Some("true") => Ok(QueryResults::Boolean(true)),
+                        Some("false") => Ok(QueryResults::Boolean(false)),
+                        _ => Err(ResultsError("invalid boolean".to_string())),
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof if depth > 0 => {
+                return Err(ResultsError("the document ends too early".to_string()))
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    match has_results {
+        true => Ok(QueryResults::Solutions {
+            variables,
+            solutions,
+        }),
+        false => Err(ResultsError(
+            "neither <results> nor <boolean> is present".to_string(),
+        )),
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, ResultsError> {
+    match element
+        .try_get_attribute(name)
+        .map_err(|error| ResultsError(error.to_string()))?
+    {
+        Some(attribute) => Ok(Some(
+            attribute
+                .unescape_value()
+                .map_err(|error| ResultsError(error.to_string()))?
+                .into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Binds the term that was read to the variable of the current `<binding>`.
+fn bind(
+    solution: &mut Option<Vec<Option<Term>>>,
+    binding: Option<usize>,
+    term: Option<(TermKind, String)>,
+) -> Result<(), ResultsError> {
+    let (Some(solution), Some(binding), Some((kind, value))) = (solution, binding, term) else {
+        return Err(ResultsError("a term is outside of a binding".to_string()));
+    };
+    solution[binding] = Some(match kind {
+        TermKind::Iri => Term::Iri(value),
+        TermKind::BlankNode => Term::BlankNode(value),
+        TermKind::Literal { datatype, language } => Term::Literal {
+            value,
+            datatype,
+            language,
+        },
+    });
+    Ok(())
+}
+
+pub(super) fn serialize(results: &QueryResults) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\"?>\n<sparql xmlns=\"{}\">\n  <head>\n",
+        NAMESPACE
+    );
+    match results {
+        QueryResults::Boolean(boolean) => {
+            xml.push_str("  </head>\n");
+            xml.push_str(&format!("  <boolean>{}</boolean>\n", boolean));
+        }
+        QueryResults::Solutions {
+            variables,
+            solutions,
+        } => {
+            for variable in variables {
+                xml.push_str(&format!("    <variable name=\"{}\"/>\n", escape(variable)));
+            }
+            xml.push_str("  </head>\n  <results>\n");
+            for solution in solutions {
+                xml.push_str("    <result>\n");
+                for (variable, term) in variables.iter().zip(solution) {
+                    let Some(term) = term else {
+                        continue;
+                    };
+                    let term = match term {
+                        Term::Iri(iri) => format!("<uri>{}</uri>", escape(iri)),
+                        Term::BlankNode(id) => format!("<bnode>{}</bnode>", escape(id)),
+                        Term::Literal {
+                            value,
+                            language: Some(language),
+                            ..
+                        } => format!(
+                            "<literal xml:lang=\"{}\">{}</literal>",
+                            escape(language),
+                            escape(value)
+                        ),
+                        Term::Literal {
+                            value,
+                            datatype: Some(datatype),
+                            ..
+                        } => format!(
+                            "<literal datatype=\"{}\">{}</literal>",
+                            escape(datatype),
+                            escape(value)
+                        ),
+                        Term::Literal { value, .. } => {
+                            format!("<literal>{}</literal>", escape(value))
+                        }
+                    };
+                    xml.push_str(&format!(
+                        "      <binding name=\"{}\">{}</binding>\n",
+                        escape(variable),
+                        term
+                    ));
+                }
+                xml.push_str("    </result>\n");
+            }
+            xml.push_str("  </results>\n");
+        }
+    }
+    xml.push_str("</sparql>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::results::{QueryResults, Term};
+
+    use super::{parse, serialize};
+
+    #[test]
+    fn parse_solutions() {
+        let text = indoc! {r#"
+            <?xml version="1.0"?>
+            <sparql xmlns="http://www.w3.org/2005/sparql-results#">
+              <head>
+                <variable name="s"/>
+                <variable name="o"/>
+                <link href="metadata.rdf"/>
+              </head>
+              <results>
+                <result>
+                  <binding name="s"><uri>http://example.org/a?b=1&amp;c=2</uri></binding>
+                  <binding name="o"><literal xml:lang="en"> Hello &lt;World&gt; </literal></binding>
+                </result>
+                <result>
+                  <binding name="o"><literal datatype="http://www.w3.org/2001/XMLSchema#string"/></binding>
+                </result>
+                <result/>
+              </results>
+            </sparql>
+        "#};
+        assert_eq!(
+            parse(text).unwrap(),
+            QueryResults::Solutions {
+                variables: vec!["s".to_string(), "o".to_string()],
+                solutions: vec![
+                    vec![
+                        Some(Term::Iri("http://example.org/a?b=1&c=2".to_string())),
+                        Some(Term::Literal {
+                            value: " Hello <World> ".to_string(),
+                            datatype: None,
+                            language: Some("en".to_string())
+                        })
+                    ],
+                    vec![
+                        None,
+                        Some(Term::Literal {
+                            value: String::new(),
+                            datatype: Some("http://www.w3.org/2001/XMLSchema#string".to_string()),
+                            language: None
+                        })
+                    ],
+                    vec![None, None]
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn boolean() {
+        let text = r#"<sparql xmlns="http://www.w3.org/2005/sparql-results#"><head/><boolean>true</boolean></sparql>"#;
+        assert_eq!(parse(text).unwrap(), QueryResults::Boolean(true));
+        assert_eq!(
+            parse(&serialize(&QueryResults::Boolean(false))).unwrap(),
+            QueryResults::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn invalid_results() {
+        assert!(parse("<sparql><head/></sparql>").is_err());
+        assert!(parse(r#"<sparql><head><variable name="s"/></head><results><result><binding name="x"><uri>a</uri></binding></result></results></sparql>"#).is_err());
+        assert!(parse("<sparql><boolean>maybe</boolean></sparql>").is_err());
+        assert!(parse("<sparql><results>").is_err());
+    }
+
+    #[test]
+    fn serialize_solutions() {
+        let results = QueryResults::Solutions {
+            variables: vec!["s".to_string(), "o".to_string()],
+            solutions: vec![vec![
+                Some(Term::BlankNode("b0".to_string())),
+                Some(Term::Literal {
+                    value: "1 < 2".to_string(),
+                    datatype: Some("http://www.w3.org/2001/XMLSchema#string".to_string()),
+                    language: None,
+                }),
+            ]],
+        };
+        assert_eq!(
+            serialize(&results),
+            indoc! {r#"
+                <?xml version="1.0"?>
+                <sparql xmlns="http://www.w3.org/2005/sparql-results#">
+                  <head>
+                    <variable name="s"/>
+                    <variable name="o"/>
+                  </head>
+                  <results>
+                    <result>
+                      <binding name="s"><bnode>b0</bnode></binding>
+                      <binding name="o"><literal datatype="http://www.w3.org/2001/XMLSchema#string">1 &lt; 2</literal></binding>
+                    </result>
+                  </results>
+                </sparql>
+            "#}
+        );
+        assert_eq!(parse(&serialize(&results)).unwrap(), results);
+    }
+}
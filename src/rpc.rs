@@ -1,45 +1,129 @@
 use nom::{
-    bytes::complete::{tag, take_while},
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::space0,
     IResult,
 };
 use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::{
+    fmt,
+    io::{self, BufRead, Read, Write},
+};
 
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Header {
     pub content_length: usize,
 }
 
+/// The id of a request, a number or a string.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(untagged)]
+pub enum RequestId {
+    Integer(i64),
+    String(String),
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RequestId::Integer(id) => write!(f, "{}", id),
+            RequestId::String(id) => write!(f, "\"{}\"", id),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct RequestMessage {
     #[serde(flatten)]
     pub base: BaseMessage,
-    // WARNING: This is not to Spec! It could also be a string, or null
-    pub id: u32,
+    pub id: RequestId,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct ResponseMessage {
     pub jsonrpc: String,
-    // WARNING: This is not to Spec! It could also be a string, or null
-    // TODO: Is there a serde way to "omitempty" this field?
-    pub id: u32,
+    /// `None` (serialized as `null`) if the id of the request could not be determined.
+    pub id: Option<RequestId>,
 }
 
 impl ResponseMessage {
-    pub fn new(id: u32) -> Self {
+    pub fn new(id: RequestId) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
-            id,
+            id: Some(id),
+        }
+    }
+}
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#errorCodes
+#[derive(Serialize_repr, Deserialize_repr, Debug, PartialEq, Clone, Copy)]
+#[repr(i32)]
+pub enum ErrorCode {
+    ParseError = -32700,
+    InvalidRequest = -32600,
+    MethodNotFound = -32601,
+    InvalidParams = -32602,
+    ServerNotInitialized = -32002,
+    RequestCancelled = -32800,
+    ContentModified = -32801,
+    RequestFailed = -32803,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ResponseError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ResponseError {
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        Self {
+            code,
+            message,
+            data: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ErrorResponse {
+    #[serde(flatten)]
+    pub base: ResponseMessage,
+    pub error: ResponseError,
+}
+
+impl ErrorResponse {
+    pub fn new(id: Option<RequestId>, error: ResponseError) -> Self {
+        Self {
+            base: ResponseMessage {
+                jsonrpc: "2.0".to_string(),
+                id,
+            },
+            error,
         }
     }
 }
 
+/// The `$/cancelRequest` notification.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CancelNotification {
+    pub params: CancelParams,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct ResponseError {
-    code: u32,
-    message: String,
-    // WARNING: This is not to Spec! It could also be a number, bool, object, ...
-    data: Option<String>,
+pub struct CancelParams {
+    pub id: RequestId,
+}
+
+/// The fields every incoming request or notification has.
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Message {
+    pub method: String,
+    /// `None` for notifications.
+    #[serde(default)]
+    pub id: Option<RequestId>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -57,30 +141,141 @@ impl BaseMessage {
     }
 }
 
-fn parse_header(input: &str) -> IResult<&str, usize> {
-    let (input, _) = tag("Content-Length: ")(input)?;
-    let (input, number) = take_while(|c: char| c.is_digit(10))(input)?;
-    Ok((input, number.parse().unwrap()))
+/// Parses a header field like `Content-Length: 42`.
+fn parse_header_field(input: &str) -> IResult<&str, (&str, &str)> {
+    let (input, name) = take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-')(input)?;
+    let (input, _) = tag(":")(input)?;
+    let (input, _) = space0(input)?;
+    let (input, value) = take_while(|c: char| c != '\r' && c != '\n')(input)?;
+    let (input, _) = tag("\r\n")(input)?;
+    Ok((input, (name, value.trim_end())))
 }
 
 impl Header {
+    /// Parses the header part of a message, including the terminating empty line.
+    /// The fields may come in any order, unknown fields like `Content-Type` are ignored.
     pub fn from_string(string: String) -> Result<Header, String> {
-        let (_rest, content_length) = parse_header(string.as_str()).expect("Expected valid header");
-        Ok(Header { content_length })
+        let mut input = string.as_str();
+        let mut content_length = None;
+        while !input.starts_with("\r\n") {
+            let (rest, (name, value)) = parse_header_field(input)
+                .map_err(|_| format!("Invalid header field: {:?}", input.lines().next()))?;
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid Content-Length: {:?}", value))?,
+                );
+            }
+            input = rest;
+        }
+        if input != "\r\n" {
+            return Err("Unexpected content after the header".to_string());
+        }
+        match content_length {
+            Some(content_length) => Ok(Header { content_length }),
+            None => Err("Missing Content-Length header field".to_string()),
+        }
+    }
+}
+
+/// The answer of the client to a request of the server.
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ClientResponse {
+    pub id: Option<RequestId>,
+    #[serde(default)]
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Incoming {
+    /// A request or notification.
+    Message(Message),
+    Response(ClientResponse),
+}
+
+pub fn decode_message(msg: &[u8]) -> Result<Incoming, ResponseError> {
+    let value: serde_json::Value = serde_json::from_slice(msg).map_err(|error| {
+        ResponseError::new(
+            ErrorCode::ParseError,
+            format!("Could not parse message: {}", error),
+        )
+    })?;
+    let is_response = value.get("method").is_none()
+        && (value.get("result").is_some() || value.get("error").is_some());
+    match is_response {
+        true => serde_json::from_value(value).map(Incoming::Response),
+        false => serde_json::from_value(value).map(Incoming::Message),
+    }
+    .map_err(|error| {
+        ResponseError::new(
+            ErrorCode::InvalidRequest,
+            format!("Invalid message: {}", error),
+        )
+    })
+}
+
+/// A message read from a byte stream.
+#[derive(Debug, PartialEq)]
+pub enum Frame {
+    Message(Vec<u8>),
+    /// A message with an invalid header, its content was skipped if possible.
+    Malformed(String),
+    EndOfStream,
+}
+
+/// Reads the next message from `reader`.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Frame> {
+    let mut header = Vec::new();
+    loop {
+        let line_start = header.len();
+        if reader.read_until(b'\n', &mut header)? == 0 {
+            return Ok(Frame::EndOfStream);
+        }
+        if &header[line_start..] == b"\r\n" {
+            break;
+        }
+    }
+    let header = match String::from_utf8(header) {
+        Ok(header) => Header::from_string(header),
+        Err(_) => Err("The header is not valid UTF-8".to_string()),
+    };
+    let content_length = match header {
+        Ok(header) => header.content_length,
+        Err(error) => return Ok(Frame::Malformed(error)),
+    };
+    // Not allocating `content_length` bytes up front, the header could lie.
+    let mut content = Vec::new();
+    reader
+        .take(content_length as u64)
+        .read_to_end(&mut content)?;
+    if content.len() < content_length {
+        return Ok(Frame::EndOfStream);
     }
+    Ok(Frame::Message(content))
 }
 
-pub fn decode_message(msg: &Vec<u8>) -> Result<BaseMessage, String> {
-    let msg_string = String::from_utf8(msg.to_vec()).unwrap();
-    let request: BaseMessage = serde_json::from_str(&msg_string).expect("A valid Message");
-    return Ok(request);
+pub fn write_message(writer: &mut impl Write, content: &str) -> io::Result<()> {
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::rpc::{BaseMessage, Header};
+    use std::io::Cursor;
 
-    use super::decode_message;
+    use proptest::prelude::*;
+
+    use crate::rpc::{ClientResponse, Header, Incoming, Message, RequestId};
+
+    use super::{
+        decode_message, read_message, write_message, ErrorCode, ErrorResponse, Frame, ResponseError,
+    };
 
     #[test]
     fn header_parses() {
@@ -93,18 +288,159 @@ mod tests {
         );
     }
 
+    #[test]
+    fn header_fields() {
+        let header =
+            "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length:42\r\n\r\n";
+        assert_eq!(
+            Header::from_string(header.to_string()),
+            Ok(Header { content_length: 42 })
+        );
+        assert!(Header::from_string("Content-Type: text/plain\r\n\r\n".to_string()).is_err());
+        assert!(Header::from_string("Content-Length: -1\r\n\r\n".to_string()).is_err());
+        assert!(Header::from_string("Content-Length 12\r\n\r\n".to_string()).is_err());
+        assert!(Header::from_string("Content-Length: 12\r\n".to_string()).is_err());
+        assert!(Header::from_string("".to_string()).is_err());
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            decode_message(b"{\"method\": ").unwrap_err().code,
+            ErrorCode::ParseError
+        );
+        assert_eq!(
+            decode_message(&[0xff, 0xfe]).unwrap_err().code,
+            ErrorCode::ParseError
+        );
+        assert_eq!(
+            decode_message(br#"{"jsonrpc":"2.0","id":1}"#)
+                .unwrap_err()
+                .code,
+            ErrorCode::InvalidRequest
+        );
+        assert_eq!(
+            decode_message(br#"{"jsonrpc":"2.0","id":1.5,"method":"shutdown"}"#)
+                .unwrap_err()
+                .code,
+            ErrorCode::InvalidRequest
+        );
+    }
+
+    #[test]
+    fn read_messages() {
+        let mut stream = Vec::new();
+        write_message(&mut stream, "{}").unwrap();
+        stream.extend_from_slice(b"Content-Type: x\r\n\r\n");
+        stream.extend_from_slice(b"Content-Type: x\r\nContent-Length: 2\r\n\r\n[]");
+        stream.extend_from_slice(b"Content-Length: 10\r\n\r\n{}");
+        let mut reader = Cursor::new(stream);
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Frame::Message(b"{}".to_vec())
+        );
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Frame::Malformed(_)
+        ));
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Frame::Message(b"[]".to_vec())
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), Frame::EndOfStream);
+        assert_eq!(read_message(&mut reader).unwrap(), Frame::EndOfStream);
+    }
+
+    proptest! {
+        #[test]
+        fn header_never_panics(header in "(?s).{0,64}") {
+            let _ = Header::from_string(header);
+        }
+
+        #[test]
+        fn decode_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..128)) {
+            let _ = decode_message(&bytes);
+        }
+
+        #[test]
+        fn read_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            let mut reader = Cursor::new(bytes);
+            while !matches!(read_message(&mut reader), Ok(Frame::EndOfStream) | Err(_)) {}
+        }
+
+        #[test]
+        fn read_roundtrip(contents in prop::collection::vec("(?s).{0,32}", 0..8)) {
+            let mut stream = Vec::new();
+            for content in &contents {
+                write_message(&mut stream, content).unwrap();
+            }
+            let mut reader = Cursor::new(stream);
+            for content in &contents {
+                prop_assert_eq!(
+                    read_message(&mut reader).unwrap(),
+                    Frame::Message(content.as_bytes().to_vec())
+                );
+            }
+            prop_assert_eq!(read_message(&mut reader).unwrap(), Frame::EndOfStream);
+        }
+    }
+
     #[test]
     fn test_decode() {
         let maybe_request = decode_message(
-            &b"{\"jsonrpc\": \"2.0\",\"id\": 1, \"method\": \"initialize\", \"params\": {}}"
-                .to_vec(),
+            b"{\"jsonrpc\": \"2.0\",\"id\": 1, \"method\": \"initialize\", \"params\": {}}"
+                .as_ref(),
         );
         assert_eq!(
             maybe_request,
-            Ok(BaseMessage {
-                jsonrpc: "2.0".to_owned(),
+            Ok(Incoming::Message(Message {
                 method: "initialize".to_owned(),
-            })
+                id: Some(RequestId::Integer(1)),
+            }))
+        );
+        let notification = decode_message(br#"{"jsonrpc":"2.0","method":"initialized"}"#);
+        assert_eq!(
+            notification,
+            Ok(Incoming::Message(Message {
+                method: "initialized".to_owned(),
+                id: None,
+            }))
+        );
+        let string_id = decode_message(br#"{"jsonrpc":"2.0","id":"a1","method":"shutdown"}"#);
+        assert_eq!(
+            string_id,
+            Ok(Incoming::Message(Message {
+                method: "shutdown".to_owned(),
+                id: Some(RequestId::String("a1".to_string())),
+            }))
+        );
+        let response = decode_message(br#"{"jsonrpc":"2.0","id":7,"result":null}"#);
+        assert_eq!(
+            response,
+            Ok(Incoming::Response(ClientResponse {
+                id: Some(RequestId::Integer(7)),
+                error: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn error_response() {
+        let response = ErrorResponse::new(
+            Some(RequestId::String("a1".to_string())),
+            ResponseError::new(ErrorCode::MethodNotFound, "Unknown method".to_string()),
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":"a1","error":{"code":-32601,"message":"Unknown method"}}"#
+        );
+        let response = ErrorResponse::new(
+            None,
+            ResponseError::new(ErrorCode::InvalidParams, "Invalid".to_string()),
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32602,"message":"Invalid"}}"#
         );
     }
 }
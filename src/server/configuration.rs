@@ -1,15 +1,43 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::lsp::DiagnosticSeverity;
+
+/// How queries are formatted.
+/// New settings may be added in minor releases, so outside of this crate
+/// they are created with [`FormatSettings::default`] or [`FormatSettings::builder`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct FormatSettings {
+    /// Align the objects of the predicates of a subject.
     pub align_predicates: bool,
+    /// Align the IRIs of the prefix declarations.
     pub align_prefixes: bool,
+    /// Separate the prologue from the query by an empty line.
     pub separate_prolouge: bool,
+    /// Write keywords in upper case.
     pub capitalize_keywords: bool,
+    /// Indent with spaces instead of tabs.
     pub insert_spaces: bool,
+    /// The number of spaces of one indentation level.
     pub tab_size: u8,
+    /// Put `WHERE` on a new line.
     pub where_new_line: bool,
+    /// What to do with queries that have syntax errors.
+    pub on_syntax_error: SyntaxErrorPolicy,
+}
+
+/// How queries with syntax errors are formatted.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyntaxErrorPolicy {
+    /// Don't format the query at all.
+    #[default]
+    Refuse,
+    /// Format the top-level parts without errors, copy the others verbatim.
+    Partial,
 }
 
 impl Default for FormatSettings {
@@ -22,20 +50,306 @@ impl Default for FormatSettings {
             insert_spaces: true,
             tab_size: 2,
             where_new_line: false,
+            on_syntax_error: SyntaxErrorPolicy::default(),
+        }
+    }
+}
+
+impl FormatSettings {
+    /// Starts from the default settings.
+    ///
+    /// ```
+    /// use fichu::FormatSettings;
+    ///
+    /// let settings = FormatSettings::builder()
+    ///     .tab_size(4)
+    ///     .capitalize_keywords(false)
+    ///     .build();
+    /// assert_eq!(settings.tab_size, 4);
+    /// assert!(settings.insert_spaces);
+    /// ```
+    pub fn builder() -> FormatSettingsBuilder {
+        FormatSettingsBuilder {
+            settings: FormatSettings::default(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Builds [`FormatSettings`], see [`FormatSettings::builder`].
+#[derive(Debug, Clone)]
+pub struct FormatSettingsBuilder {
+    settings: FormatSettings,
+}
+
+impl FormatSettingsBuilder {
+    pub fn align_predicates(mut self, align_predicates: bool) -> Self {
+        self.settings.align_predicates = align_predicates;
+        self
+    }
+
+    pub fn align_prefixes(mut self, align_prefixes: bool) -> Self {
+        self.settings.align_prefixes = align_prefixes;
+        self
+    }
+
+    pub fn separate_prolouge(mut self, separate_prolouge: bool) -> Self {
+        self.settings.separate_prolouge = separate_prolouge;
+        self
+    }
+
+    pub fn capitalize_keywords(mut self, capitalize_keywords: bool) -> Self {
+        self.settings.capitalize_keywords = capitalize_keywords;
+        self
+    }
+
+    pub fn insert_spaces(mut self, insert_spaces: bool) -> Self {
+        self.settings.insert_spaces = insert_spaces;
+        self
+    }
+
+    pub fn tab_size(mut self, tab_size: u8) -> Self {
+        self.settings.tab_size = tab_size;
+        self
+    }
+
+    pub fn where_new_line(mut self, where_new_line: bool) -> Self {
+        self.settings.where_new_line = where_new_line;
+        self
+    }
+
+    pub fn on_syntax_error(mut self, on_syntax_error: SyntaxErrorPolicy) -> Self {
+        self.settings.on_syntax_error = on_syntax_error;
+        self
+    }
+
+    pub fn build(self) -> FormatSettings {
+        self.settings
+    }
+}
+
+/// The level a lint rule is reported with.
+/// `Off` disables the rule, all other levels map to a `DiagnosticSeverity`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Off,
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintLevel {
+    pub fn severity(&self) -> Option<DiagnosticSeverity> {
+        match self {
+            LintLevel::Off => None,
+            LintLevel::Hint => Some(DiagnosticSeverity::Hint),
+            LintLevel::Info => Some(DiagnosticSeverity::Information),
+            LintLevel::Warning => Some(DiagnosticSeverity::Warning),
+            LintLevel::Error => Some(DiagnosticSeverity::Error),
+        }
+    }
+}
+
+/// Maps lint rule names to the level they should be reported with.
+/// Rules that are not listed use their default level.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct LintSettings {
+    #[serde(flatten)]
+    pub rules: HashMap<String, LintLevel>,
+}
+
+impl LintSettings {
+    /// Reports the `rules` with the given levels.
+    ///
+    /// ```
+    /// use fichu::{LintLevel, LintSettings};
+    ///
+    /// let lint = LintSettings::new([("unused_prefix".to_string(), LintLevel::Off)].into());
+    /// assert_eq!(lint.rules["unused_prefix"], LintLevel::Off);
+    /// ```
+    pub fn new(rules: HashMap<String, LintLevel>) -> Self {
+        Self { rules }
+    }
+}
+
+/// All settings, as read from `fichu.toml` or sent by the client.
+/// Outside of this crate they are created with [`Settings::default`] or [`Settings::builder`].
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct Settings {
     pub format: FormatSettings,
+    pub lint: LintSettings,
+    /// The endpoint queries are executed on.
+    pub endpoint: Option<EndpointSettings>,
 }
 
-impl Default for Settings {
-    fn default() -> Self {
+/// Builds [`Settings`], see [`Settings::builder`].
+#[derive(Debug, Clone)]
+pub struct SettingsBuilder {
+    settings: Settings,
+}
+
+impl SettingsBuilder {
+    pub fn format(mut self, format: FormatSettings) -> Self {
+        self.settings.format = format;
+        self
+    }
+
+    pub fn lint(mut self, lint: LintSettings) -> Self {
+        self.settings.lint = lint;
+        self
+    }
+
+    pub fn endpoint(mut self, endpoint: EndpointSettings) -> Self {
+        self.settings.endpoint = Some(endpoint);
+        self
+    }
+
+    pub fn build(self) -> Settings {
+        self.settings
+    }
+}
+
+/// A SPARQL endpoint, that queries are sent to over the SPARQL 1.1 Protocol.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct EndpointSettings {
+    pub url: String,
+    /// How many seconds to wait for the results.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+fn default_timeout() -> u64 {
+    60
+}
+
+impl EndpointSettings {
+    /// An endpoint with the default timeout.
+    pub fn new(url: &str) -> Self {
         Self {
-            format: Default::default(),
+            url: url.to_string(),
+            timeout: default_timeout(),
         }
     }
+
+    /// Waits `timeout` seconds for the results.
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Settings {
+    /// Starts from the default settings.
+    ///
+    /// ```
+    /// use fichu::{EndpointSettings, FormatSettings, Settings};
+    ///
+    /// let settings = Settings::builder()
+    ///     .format(FormatSettings::builder().tab_size(4).build())
+    ///     .endpoint(EndpointSettings::new("https://query.wikidata.org/sparql").with_timeout(10))
+    ///     .build();
+    /// assert_eq!(settings.format.tab_size, 4);
+    /// assert_eq!(settings.endpoint.unwrap().timeout, 10);
+    /// ```
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder {
+            settings: Settings::default(),
+        }
+    }
+
+    /// Loads the settings from a `fichu.toml` or `fichu.yml` in the working directory.
+    /// Missing files fall back to the default settings.
+    pub fn load() -> Result<Self, config::ConfigError> {
+        builder().build()?.try_deserialize()
+    }
+
+    /// Loads the settings like [`Settings::load`], the `overrides` of the client take precedence.
+    pub fn load_with_overrides(overrides: &serde_json::Value) -> Result<Self, config::ConfigError> {
+        builder()
+            .add_source(config::File::from_str(
+                &overrides.to_string(),
+                config::FileFormat::Json,
+            ))
+            .build()?
+            .try_deserialize()
+    }
+}
+
+fn builder() -> config::ConfigBuilder<config::builder::DefaultState> {
+    let builder = config::Config::builder();
+    // NOTE: There is no file system in the browser.
+    #[cfg(not(target_family = "wasm"))]
+    let builder = builder.add_source(config::File::with_name("fichu").required(false));
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    use super::{EndpointSettings, LintLevel, Settings};
+
+    #[test]
+    fn overrides() {
+        let settings = Settings::load_with_overrides(&serde_json::json!({
+            "format": {"tab_size": 4},
+            "lint": {"unused_prefix": "off"}
+        }))
+        .unwrap();
+        assert_eq!(settings.format.tab_size, 4);
+        assert!(settings.format.capitalize_keywords);
+        assert_eq!(
+            settings.lint.rules.get("unused_prefix"),
+            Some(&LintLevel::Off)
+        );
+        assert!(
+            Settings::load_with_overrides(&serde_json::json!({"format": {"tab_size": "x"}}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn lint_section() {
+        let settings: Settings = Config::builder()
+            .add_source(File::from_str(
+                "[lint]\nunused_prefix = \"error\"\nundeclared_prefix = \"off\"",
+                FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(
+            settings.lint.rules.get("unused_prefix"),
+            Some(&LintLevel::Error)
+        );
+        assert_eq!(
+            settings.lint.rules.get("undeclared_prefix"),
+            Some(&LintLevel::Off)
+        );
+    }
+
+    #[test]
+    fn endpoint_section() {
+        let settings: Settings = Config::builder()
+            .add_source(File::from_str(
+                "[endpoint]\nurl = \"https://query.wikidata.org/sparql\"",
+                FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(
+            settings.endpoint,
+            Some(EndpointSettings::new("https://query.wikidata.org/sparql"))
+        );
+        assert_eq!(Settings::default().endpoint, None);
+    }
 }
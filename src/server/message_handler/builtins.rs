@@ -0,0 +1,720 @@
+//! Signatures and documentation of the SPARQL 1.1 builtin functions and aggregates,
+//! taken from https://www.w3.org/TR/sparql11-query/.
+
+const SPEC: &str = "https://www.w3.org/TR/sparql11-query/";
+
+pub(super) struct Builtin {
+    pub name: &'static str,
+    pub parameters: &'static [Parameter],
+    /// Whether the last parameter may be repeated.
+    pub variadic: bool,
+    pub return_type: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+    /// The anchor of the function in the SPARQL 1.1 Query Language specification.
+    pub anchor: &'static str,
+}
+
+pub(super) struct Parameter {
+    /// The type and name of the parameter, as written in the specification.
+    pub label: &'static str,
+    pub documentation: &'static str,
+    pub optional: bool,
+}
+
+const fn parameter(label: &'static str, documentation: &'static str) -> Parameter {
+    Parameter {
+        label,
+        documentation,
+        optional: false,
+    }
+}
+
+const fn optional(label: &'static str, documentation: &'static str) -> Parameter {
+    Parameter {
+        label,
+        documentation,
+        optional: true,
+    }
+}
+
+const fn builtin(
+    name: &'static str,
+    parameters: &'static [Parameter],
+    return_type: &'static str,
+    description: &'static str,
+    example: &'static str,
+    anchor: &'static str,
+) -> Builtin {
+    Builtin {
+        name,
+        parameters,
+        variadic: false,
+        return_type,
+        description,
+        example,
+        anchor,
+    }
+}
+
+const fn variadic(builtin: Builtin) -> Builtin {
+    Builtin {
+        variadic: true,
+        ..builtin
+    }
+}
+
+const TERM: Parameter = parameter("RDF term term", "The RDF term to test or convert.");
+const STRING: Parameter = parameter("string literal str", "A string literal.");
+const ARG1: Parameter = parameter(
+    "string literal arg1",
+    "The string that is searched in. Must be argument compatible with `arg2`.",
+);
+const ARG2: Parameter = parameter("string literal arg2", "The string that is searched for.");
+const NUMBER: Parameter = parameter("numeric term", "A numeric value.");
+const DATE_TIME: Parameter = parameter("xsd:dateTime arg", "A date and time.");
+const HASHED: Parameter = parameter(
+    "simple literal arg",
+    "The string to hash, a simple literal or an `xsd:string`.",
+);
+const EXPRESSION: Parameter = parameter(
+    "expression",
+    "The expression that is evaluated for each solution of the group. May be preceded by `DISTINCT`.",
+);
+
+pub(super) const BUILTINS: &[Builtin] = &[
+    // Functional forms
+    builtin(
+        "BOUND",
+        &[parameter("variable var", "The variable to test.")],
+        "xsd:boolean",
+        "Returns `true` if `var` is bound to a value, `false` otherwise.",
+        "FILTER (!BOUND(?date))",
+        "func-bound",
+    ),
+    builtin(
+        "IF",
+        &[
+            parameter("expression1", "The condition, evaluated as effective boolean value."),
+            parameter("expression2", "The result if the condition is `true`."),
+            parameter("expression3", "The result if the condition is `false`."),
+        ],
+        "RDF term",
+        "Evaluates the first argument and returns the value of the second argument if it is `true`, or the value of the third argument if it is `false`.",
+        "BIND (IF(?age >= 18, \"adult\", \"minor\") AS ?group)",
+        "func-if",
+    ),
+    variadic(builtin(
+        "COALESCE",
+        &[parameter("expression", "An expression, returned if it evaluates without error.")],
+        "RDF term",
+        "Returns the value of the first expression that evaluates without error. Unbound variables are errors.",
+        "BIND (COALESCE(?nickname, ?name, \"unknown\") AS ?label)",
+        "func-coalesce",
+    )),
+    builtin(
+        "sameTerm",
+        &[
+            parameter("RDF term term1", "The first term."),
+            parameter("RDF term term2", "The second term."),
+        ],
+        "xsd:boolean",
+        "Returns `true` if `term1` and `term2` are the same RDF term.",
+        "FILTER (!sameTerm(?person, ?friend))",
+        "func-sameTerm",
+    ),
+    // Functions on RDF terms
+    builtin(
+        "isIRI",
+        &[TERM],
+        "xsd:boolean",
+        "Returns `true` if `term` is an IRI.",
+        "FILTER (isIRI(?object))",
+        "func-isIRI",
+    ),
+    builtin(
+        "isURI",
+        &[TERM],
+        "xsd:boolean",
+        "Returns `true` if `term` is an IRI. Synonym of `isIRI`.",
+        "FILTER (isURI(?object))",
+        "func-isIRI",
+    ),
+    builtin(
+        "isBLANK",
+        &[TERM],
+        "xsd:boolean",
+        "Returns `true` if `term` is a blank node.",
+        "FILTER (isBLANK(?object))",
+        "func-isBlank",
+    ),
+    builtin(
+        "isLITERAL",
+        &[TERM],
+        "xsd:boolean",
+        "Returns `true` if `term` is a literal.",
+        "FILTER (isLITERAL(?object))",
+        "func-isLiteral",
+    ),
+    builtin(
+        "isNUMERIC",
+        &[TERM],
+        "xsd:boolean",
+        "Returns `true` if `term` is a numeric value.",
+        "FILTER (isNUMERIC(?value))",
+        "func-isNumeric",
+    ),
+    builtin(
+        "STR",
+        &[parameter("literal or IRI ltrl", "The literal or IRI to convert.")],
+        "simple literal",
+        "Returns the lexical form of a literal or the codepoint representation of an IRI.",
+        "FILTER (STRSTARTS(STR(?page), \"https://\"))",
+        "func-str",
+    ),
+    builtin(
+        "LANG",
+        &[parameter("literal ltrl", "The literal whose language tag is returned.")],
+        "simple literal",
+        "Returns the language tag of `ltrl`, or `\"\"` if it has none.",
+        "FILTER (LANG(?label) = \"en\")",
+        "func-lang",
+    ),
+    builtin(
+        "DATATYPE",
+        &[parameter("literal literal", "The literal whose datatype is returned.")],
+        "iri",
+        "Returns the datatype IRI of `literal`.",
+        "FILTER (DATATYPE(?value) = xsd:integer)",
+        "func-datatype",
+    ),
+    builtin(
+        "IRI",
+        &[parameter(
+            "simple literal or IRI str",
+            "The IRI string, resolved against the base IRI.",
+        )],
+        "iri",
+        "Returns an IRI, resolved against the base IRI of the query.",
+        "BIND (IRI(CONCAT(\"http://example.org/\", ?id)) AS ?item)",
+        "func-iri",
+    ),
+    builtin(
+        "URI",
+        &[parameter(
+            "simple literal or IRI str",
+            "The IRI string, resolved against the base IRI.",
+        )],
+        "iri",
+        "Returns an IRI, resolved against the base IRI of the query. Synonym of `IRI`.",
+        "BIND (URI(CONCAT(\"http://example.org/\", ?id)) AS ?item)",
+        "func-iri",
+    ),
+    builtin(
+        "BNODE",
+        &[optional(
+            "simple literal str",
+            "Calls with the same string return the same blank node within one solution.",
+        )],
+        "blank node",
+        "Constructs a blank node that is distinct from all blank nodes in the dataset.",
+        "BIND (BNODE(?id) AS ?node)",
+        "func-bnode",
+    ),
+    builtin(
+        "STRDT",
+        &[
+            parameter("simple literal lexicalForm", "The lexical form of the literal."),
+            parameter("IRI datatypeIRI", "The datatype of the literal."),
+        ],
+        "literal",
+        "Constructs a literal with the given lexical form and datatype.",
+        "BIND (STRDT(\"42\", xsd:integer) AS ?answer)",
+        "func-strdt",
+    ),
+    builtin(
+        "STRLANG",
+        &[
+            parameter("simple literal lexicalForm", "The lexical form of the literal."),
+            parameter("simple literal langTag", "The language tag of the literal."),
+        ],
+        "literal",
+        "Constructs a literal with the given lexical form and language tag.",
+        "BIND (STRLANG(\"chat\", \"fr\") AS ?word)",
+        "func-strlang",
+    ),
+    builtin(
+        "UUID",
+        &[],
+        "iri",
+        "Returns a fresh IRI from the UUID URN scheme.",
+        "BIND (UUID() AS ?id)",
+        "func-uuid",
+    ),
+    builtin(
+        "STRUUID",
+        &[],
+        "simple literal",
+        "Returns a string that is the scheme specific part of a fresh UUID.",
+        "BIND (STRUUID() AS ?id)",
+        "func-struuid",
+    ),
+    // Functions on strings
+    builtin(
+        "STRLEN",
+        &[STRING],
+        "xsd:integer",
+        "Returns the number of characters in `str`.",
+        "FILTER (STRLEN(?name) > 3)",
+        "func-strlen",
+    ),
+    builtin(
+        "SUBSTR",
+        &[
+            parameter("string literal source", "The string to take the substring from."),
+            parameter("xsd:integer startingLoc", "The position of the first character, starting at 1."),
+            optional("xsd:integer length", "The number of characters. Defaults to the rest of the string."),
+        ],
+        "string literal",
+        "Returns the part of `source` that starts at `startingLoc` and has the given `length`.",
+        "BIND (SUBSTR(\"foobar\", 4) AS ?bar)",
+        "func-substr",
+    ),
+    builtin(
+        "UCASE",
+        &[STRING],
+        "string literal",
+        "Converts `str` to upper case.",
+        "BIND (UCASE(?name) AS ?upper)",
+        "func-ucase",
+    ),
+    builtin(
+        "LCASE",
+        &[STRING],
+        "string literal",
+        "Converts `str` to lower case.",
+        "BIND (LCASE(?name) AS ?lower)",
+        "func-lcase",
+    ),
+    builtin(
+        "STRSTARTS",
+        &[ARG1, ARG2],
+        "xsd:boolean",
+        "Returns `true` if `arg1` starts with `arg2`.",
+        "FILTER (STRSTARTS(?title, \"SPARQL\"))",
+        "func-strstarts",
+    ),
+    builtin(
+        "STRENDS",
+        &[ARG1, ARG2],
+        "xsd:boolean",
+        "Returns `true` if `arg1` ends with `arg2`.",
+        "FILTER (STRENDS(?file, \".ttl\"))",
+        "func-strends",
+    ),
+    builtin(
+        "CONTAINS",
+        &[ARG1, ARG2],
+        "xsd:boolean",
+        "Returns `true` if `arg2` is a substring of `arg1`.",
+        "FILTER (CONTAINS(?title, \"query\"))",
+        "func-contains",
+    ),
+    builtin(
+        "STRBEFORE",
+        &[ARG1, ARG2],
+        "string literal",
+        "Returns the part of `arg1` before the first occurrence of `arg2`.",
+        "BIND (STRBEFORE(\"abc\", \"b\") AS ?a)",
+        "func-strbefore",
+    ),
+    builtin(
+        "STRAFTER",
+        &[ARG1, ARG2],
+        "string literal",
+        "Returns the part of `arg1` after the first occurrence of `arg2`.",
+        "BIND (STRAFTER(\"abc\", \"b\") AS ?c)",
+        "func-strafter",
+    ),
+    builtin(
+        "ENCODE_FOR_URI",
+        &[STRING],
+        "simple literal",
+        "Percent-encodes all characters of `str` that are not unreserved in URIs.",
+        "BIND (ENCODE_FOR_URI(\"Los Angeles\") AS ?encoded)",
+        "func-encode",
+    ),
+    variadic(builtin(
+        "CONCAT",
+        &[parameter("string literal ltrl", "A string to append.")],
+        "string literal",
+        "Returns the concatenation of all arguments.",
+        "BIND (CONCAT(?first, \" \", ?last) AS ?name)",
+        "func-concat",
+    )),
+    builtin(
+        "LANGMATCHES",
+        &[
+            parameter("simple literal language-tag", "The language tag, e.g. the result of `LANG`."),
+            parameter("simple literal language-range", "The language range, e.g. `\"en\"` or `\"*\"`."),
+        ],
+        "xsd:boolean",
+        "Returns `true` if `language-tag` matches `language-range` as defined in RFC 4647.",
+        "FILTER (LANGMATCHES(LANG(?label), \"en\"))",
+        "func-langMatches",
+    ),
+    builtin(
+        "REGEX",
+        &[
+            parameter("string literal text", "The string to match."),
+            parameter("simple literal pattern", "The XPath regular expression."),
+            optional("simple literal flags", "Matching flags, e.g. `\"i\"` for case insensitive matching."),
+        ],
+        "xsd:boolean",
+        "Returns `true` if `text` matches the regular expression `pattern`.",
+        "FILTER (REGEX(?name, \"^ali\", \"i\"))",
+        "func-regex",
+    ),
+    builtin(
+        "REPLACE",
+        &[
+            parameter("string literal arg", "The string to replace in."),
+            parameter("simple literal pattern", "The XPath regular expression."),
+            parameter("simple literal replacement", "The replacement, may refer to groups with `$1`."),
+            optional("simple literal flags", "Matching flags, e.g. `\"i\"` for case insensitive matching."),
+        ],
+        "string literal",
+        "Replaces each non-overlapping match of `pattern` in `arg` by `replacement`.",
+        "BIND (REPLACE(?phone, \"[^0-9]\", \"\") AS ?digits)",
+        "func-replace",
+    ),
+    // Functions on numerics
+    builtin(
+        "ABS",
+        &[NUMBER],
+        "numeric",
+        "Returns the absolute value of `term`.",
+        "BIND (ABS(?delta) AS ?distance)",
+        "func-abs",
+    ),
+    builtin(
+        "ROUND",
+        &[NUMBER],
+        "numeric",
+        "Rounds `term` to the nearest integer, rounding halves towards positive infinity.",
+        "BIND (ROUND(?price) AS ?rounded)",
+        "func-round",
+    ),
+    builtin(
+        "CEIL",
+        &[NUMBER],
+        "numeric",
+        "Returns the smallest integer that is not smaller than `term`.",
+        "BIND (CEIL(?price) AS ?ceiling)",
+        "func-ceil",
+    ),
+    builtin(
+        "FLOOR",
+        &[NUMBER],
+        "numeric",
+        "Returns the largest integer that is not larger than `term`.",
+        "BIND (FLOOR(?price) AS ?floor)",
+        "func-floor",
+    ),
+    builtin(
+        "RAND",
+        &[],
+        "xsd:double",
+        "Returns a pseudo-random number between 0 (inclusive) and 1 (exclusive).",
+        "SELECT * WHERE { ?s ?p ?o } ORDER BY RAND() LIMIT 10",
+        "func-numerics",
+    ),
+    // Functions on dates and times
+    builtin(
+        "NOW",
+        &[],
+        "xsd:dateTime",
+        "Returns the time of the query evaluation. All calls within one query return the same value.",
+        "FILTER (?deadline > NOW())",
+        "func-now",
+    ),
+    builtin(
+        "YEAR",
+        &[DATE_TIME],
+        "xsd:integer",
+        "Returns the year of `arg`.",
+        "FILTER (YEAR(?birthday) = 1990)",
+        "func-year",
+    ),
+    builtin(
+        "MONTH",
+        &[DATE_TIME],
+        "xsd:integer",
+        "Returns the month of `arg`.",
+        "BIND (MONTH(?date) AS ?month)",
+        "func-month",
+    ),
+    builtin(
+        "DAY",
+        &[DATE_TIME],
+        "xsd:integer",
+        "Returns the day of `arg`.",
+        "BIND (DAY(?date) AS ?day)",
+        "func-day",
+    ),
+    builtin(
+        "HOURS",
+        &[DATE_TIME],
+        "xsd:integer",
+        "Returns the hours of `arg`.",
+        "BIND (HOURS(?time) AS ?hours)",
+        "func-hours",
+    ),
+    builtin(
+        "MINUTES",
+        &[DATE_TIME],
+        "xsd:integer",
+        "Returns the minutes of `arg`.",
+        "BIND (MINUTES(?time) AS ?minutes)",
+        "func-minutes",
+    ),
+    builtin(
+        "SECONDS",
+        &[DATE_TIME],
+        "xsd:decimal",
+        "Returns the seconds of `arg`.",
+        "BIND (SECONDS(?time) AS ?seconds)",
+        "func-seconds",
+    ),
+    builtin(
+        "TIMEZONE",
+        &[DATE_TIME],
+        "xsd:dayTimeDuration",
+        "Returns the timezone of `arg` as a duration. Raises an error if there is no timezone.",
+        "BIND (TIMEZONE(?time) AS ?offset)",
+        "func-timezone",
+    ),
+    builtin(
+        "TZ",
+        &[DATE_TIME],
+        "simple literal",
+        "Returns the timezone of `arg` as a string, or `\"\"` if there is none.",
+        "BIND (TZ(?time) AS ?zone)",
+        "func-tz",
+    ),
+    // Hash functions
+    builtin(
+        "MD5",
+        &[HASHED],
+        "simple literal",
+        "Returns the MD5 checksum of `arg` as a hex string.",
+        "BIND (MD5(?email) AS ?hash)",
+        "func-md5",
+    ),
+    builtin(
+        "SHA1",
+        &[HASHED],
+        "simple literal",
+        "Returns the SHA1 checksum of `arg` as a hex string.",
+        "BIND (SHA1(?email) AS ?hash)",
+        "func-sha1",
+    ),
+    builtin(
+        "SHA256",
+        &[HASHED],
+        "simple literal",
+        "Returns the SHA256 checksum of `arg` as a hex string.",
+        "BIND (SHA256(?email) AS ?hash)",
+        "func-sha256",
+    ),
+    builtin(
+        "SHA384",
+        &[HASHED],
+        "simple literal",
+        "Returns the SHA384 checksum of `arg` as a hex string.",
+        "BIND (SHA384(?email) AS ?hash)",
+        "func-sha384",
+    ),
+    builtin(
+        "SHA512",
+        &[HASHED],
+        "simple literal",
+        "Returns the SHA512 checksum of `arg` as a hex string.",
+        "BIND (SHA512(?email) AS ?hash)",
+        "func-sha512",
+    ),
+    // Aggregates
+    builtin(
+        "COUNT",
+        &[parameter(
+            "expression",
+            "The expression whose bound values are counted, or `*` to count the solutions. May be preceded by `DISTINCT`.",
+        )],
+        "xsd:integer",
+        "Counts the solutions of each group.",
+        "SELECT (COUNT(?person) AS ?count) WHERE { ?person a foaf:Person }",
+        "defn_aggCount",
+    ),
+    builtin(
+        "SUM",
+        &[EXPRESSION],
+        "numeric",
+        "Returns the sum of the values of each group.",
+        "SELECT ?order (SUM(?price) AS ?total) WHERE { ?order ex:item/ex:price ?price } GROUP BY ?order",
+        "defn_aggSum",
+    ),
+    builtin(
+        "MIN",
+        &[EXPRESSION],
+        "RDF term",
+        "Returns the smallest value of each group.",
+        "SELECT (MIN(?age) AS ?youngest) WHERE { ?person foaf:age ?age }",
+        "defn_aggMin",
+    ),
+    builtin(
+        "MAX",
+        &[EXPRESSION],
+        "RDF term",
+        "Returns the largest value of each group.",
+        "SELECT (MAX(?age) AS ?oldest) WHERE { ?person foaf:age ?age }",
+        "defn_aggMax",
+    ),
+    builtin(
+        "AVG",
+        &[EXPRESSION],
+        "numeric",
+        "Returns the average of the values of each group.",
+        "SELECT (AVG(?age) AS ?average) WHERE { ?person foaf:age ?age }",
+        "defn_aggAvg",
+    ),
+    builtin(
+        "SAMPLE",
+        &[EXPRESSION],
+        "RDF term",
+        "Returns an arbitrary value of each group.",
+        "SELECT ?person (SAMPLE(?name) AS ?label) WHERE { ?person foaf:name ?name } GROUP BY ?person",
+        "defn_aggSample",
+    ),
+    builtin(
+        "GROUP_CONCAT",
+        &[
+            EXPRESSION,
+            optional(
+                "SEPARATOR = string literal",
+                "The string between the values, a single space by default.",
+            ),
+        ],
+        "string literal",
+        "Concatenates the string values of each group.",
+        "SELECT ?book (GROUP_CONCAT(?author; SEPARATOR=\", \") AS ?authors) WHERE { ?book dc:creator ?author } GROUP BY ?book",
+        "defn_aggGroupConcat",
+    ),
+];
+
+/// The XSD constructor functions, called like `xsd:integer(?x)`.
+pub(super) const CASTS: &[Builtin] = &[
+    cast("xsd:boolean"),
+    cast("xsd:double"),
+    cast("xsd:float"),
+    cast("xsd:decimal"),
+    cast("xsd:integer"),
+    cast("xsd:dateTime"),
+    cast("xsd:string"),
+];
+
+const CAST_PARAMETERS: &[Parameter] = &[parameter("RDF term term", "The literal or IRI to cast.")];
+
+const fn cast(name: &'static str) -> Builtin {
+    builtin(
+        name,
+        CAST_PARAMETERS,
+        name,
+        "Casts `term` to the datatype. Raises an error if the value cannot be cast.",
+        "BIND (xsd:integer(\"42\") AS ?answer)",
+        "FunctionMapping",
+    )
+}
+
+/// Finds a builtin function or aggregate by its (case insensitive) name.
+pub(super) fn find(name: &str) -> Option<&'static Builtin> {
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name.eq_ignore_ascii_case(name))
+}
+
+/// Finds an XSD constructor function by the IRI it is called with.
+pub(super) fn find_cast(iri: &str) -> Option<&'static Builtin> {
+    let local_name = iri.strip_prefix("xsd:").or(iri
+        .strip_prefix("<http://www.w3.org/2001/XMLSchema#")
+        .and_then(|iri| iri.strip_suffix('>')))?;
+    CASTS
+        .iter()
+        .find(|cast| cast.name.strip_prefix("xsd:") == Some(local_name))
+}
+
+impl Builtin {
+    /// The link to the function in the specification.
+    pub fn link(&self) -> String {
+        format!("{}#{}", SPEC, self.anchor)
+    }
+
+    /// The signature in the notation of the specification, e.g. `xsd:boolean STRSTARTS(string literal arg1, string literal arg2)`.
+    /// Also returns the range of each parameter label in the signature, in UTF-16 code units.
+    pub fn signature(&self) -> (String, Vec<[u32; 2]>) {
+        let mut signature = format!("{} {}(", self.return_type, self.name);
+        let mut offsets = vec![];
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            if index > 0 {
+                signature += if self.name == "GROUP_CONCAT" {
+                    "; "
+                } else {
+                    ", "
+                };
+            }
+            if parameter.optional {
+                signature += "[";
+            }
+            let start = signature.encode_utf16().count() as u32;
+            signature += parameter.label;
+            offsets.push([start, signature.encode_utf16().count() as u32]);
+            if parameter.optional {
+                signature += "]";
+            }
+        }
+        if self.variadic {
+            signature += ", ...";
+        }
+        signature += ")";
+        (signature, offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, find_cast};
+
+    #[test]
+    fn signature() {
+        let (signature, offsets) = find("regex").unwrap().signature();
+        assert_eq!(
+            signature,
+            "xsd:boolean REGEX(string literal text, simple literal pattern, [simple literal flags])"
+        );
+        assert_eq!(
+            &signature[offsets[1][0] as usize..offsets[1][1] as usize],
+            "simple literal pattern"
+        );
+        assert_eq!(
+            find("CONCAT").unwrap().signature().0,
+            "string literal CONCAT(string literal ltrl, ...)"
+        );
+        assert_eq!(find("NOW").unwrap().signature().0, "xsd:dateTime NOW()");
+    }
+
+    #[test]
+    fn casts() {
+        assert_eq!(find_cast("xsd:integer").unwrap().name, "xsd:integer");
+        assert!(find_cast("<http://www.w3.org/2001/XMLSchema#dateTime>").is_some());
+        assert!(find_cast("ex:integer").is_none());
+    }
+}
@@ -0,0 +1,741 @@
+use std::{collections::HashMap, ops::Range as ByteRange};
+
+use log::error;
+use tree_sitter::{Node, Parser, Tree};
+
+use crate::{
+    analysis::{abbreviate, find_all, prefix_declarations, variable_name},
+    lsp::{
+        textdocument::{Range, TextDocumentItem, TextEdit},
+        CodeAction, CodeActionRequest, CodeActionResponse, WorkspaceEdit,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::{configuration::FormatSettings, Snapshot},
+};
+
+use super::{formatting::format_node, selection_range::token_at};
+
+const TRIPLES: [&str; 2] = ["TriplesSameSubjectPath", "TriplesSameSubject"];
+const PROPERTY_LISTS: [&str; 2] = ["PropertyListPathNotEmpty", "PropertyListNotEmpty"];
+/// The blocks that contain triples.
+const GROUPS: [&str; 2] = ["GroupGraphPattern", "ConstructTemplate"];
+
+/// A rewrite of the query, as replacements of byte ranges of the text.
+#[derive(Debug)]
+struct Refactoring {
+    title: String,
+    kind: &'static str,
+    /// The byte range and kind of the node that contains the edits.
+    /// Only this node is formatted after the rewrite, `None` if the edits need no formatting.
+    scope: Option<(ByteRange<usize>, &'static str)>,
+    edits: Vec<(ByteRange<usize>, String)>,
+}
+
+impl Refactoring {
+    fn rewrite(
+        title: impl Into<String>,
+        scope: Option<Node>,
+        edits: Vec<(ByteRange<usize>, String)>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            kind: "refactor.rewrite",
+            scope: scope.map(|node| (node.byte_range(), node.kind())),
+            edits,
+        }
+    }
+}
+
+pub fn handle_code_action_request(
+    request: CodeActionRequest,
+    state: &Snapshot,
+) -> Result<CodeActionResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let code_actions = match state.analysis_state.get_state(uri) {
+        // NOTE: The refactorings only work on valid queries, the formatter could mangle the others.
+        Some((document, Some(tree))) if !tree.root_node().has_error() => {
+            let range = request.get_range();
+            let (start, end) = (document.offset(&range.start), document.offset(&range.end));
+            refactorings(document, tree, start.min(end), end.max(start))
+                .into_iter()
+                .filter(|refactoring| {
+                    request.get_only().is_none_or(|only| {
+                        only.iter().any(|kind| {
+                            refactoring.kind == kind
+                                || refactoring.kind.starts_with(&format!("{}.", kind))
+                        })
+                    })
+                })
+                .filter_map(|refactoring| {
+                    let text = rewrite(&document.text, &refactoring, &state.settings.format)?;
+                    Some(CodeAction {
+                        title: refactoring.title,
+                        kind: refactoring.kind.to_string(),
+                        edit: WorkspaceEdit {
+                            changes: HashMap::from([(
+                                uri.clone(),
+                                vec![text_edit(document, &text)],
+                            )]),
+                        },
+                    })
+                })
+                .collect()
+        }
+        Some(_) => vec![],
+        None => {
+            error!("Requested code actions for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(CodeActionResponse::new(request.get_id(), code_actions))
+}
+
+/// The refactorings that apply to the selection from byte `start` to `end`.
+fn refactorings(
+    document: &TextDocumentItem,
+    tree: &Tree,
+    start: usize,
+    end: usize,
+) -> Vec<Refactoring> {
+    let text = &document.text;
+    let root = tree.root_node();
+    let prefixes = prefix_declarations(root, text);
+    let mut refactorings = vec![];
+    if let Some(token) = token_at(tree, document.point_at(start)) {
+        refactorings.extend(expand_prefixed_name(token, text, &prefixes));
+        refactorings.extend(compact_iri(token, text, &prefixes));
+        refactorings.extend(merge_triples(token, text));
+        refactorings.extend(split_property_list(token, text));
+        refactorings.extend(extract_group(token, text));
+    }
+    if start < end {
+        refactorings.extend(wrap_selection(root, text, start, end));
+    }
+    refactorings
+}
+
+/// Applies the refactoring and formats the rewritten node, `None` if the result is not valid.
+/// The rest of the text is left as it is.
+fn rewrite(text: &str, refactoring: &Refactoring, settings: &FormatSettings) -> Option<String> {
+    let mut edits: Vec<&(ByteRange<usize>, String)> = refactoring.edits.iter().collect();
+    edits.sort_by_key(|(range, _replacement)| std::cmp::Reverse(range.start));
+    let mut text = text.to_string();
+    for (range, replacement) in edits {
+        text.replace_range(range.clone(), replacement);
+    }
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_sparql::language())
+        .expect("The SPARQL grammar is compatible");
+    let tree = parser.parse(&text, None)?;
+    if tree.root_node().has_error() {
+        error!("Refactoring \"{}\" caused syntax errors", refactoring.title);
+        return None;
+    }
+    let Some((scope, kind)) = &refactoring.scope else {
+        return Some(text);
+    };
+    // NOTE: The node still starts where it did, its end moved by the edits.
+    let end = refactoring
+        .edits
+        .iter()
+        .fold(scope.end, |end, (range, replacement)| {
+            end - range.len() + replacement.len()
+        });
+    let node = node_at(tree.root_node(), scope.start..end, kind)?;
+    let formatted = format_node(&text, node, settings);
+    text.replace_range(node.byte_range(), &formatted);
+    if parser.parse(&text, None)?.root_node().has_error() {
+        error!(
+            "Formatting the result of refactoring \"{}\" caused syntax errors",
+            refactoring.title
+        );
+        return None;
+    }
+    Some(text)
+}
+
+/// The node of `kind` that spans exactly the byte `range`.
+fn node_at<'a>(root: Node<'a>, range: ByteRange<usize>, kind: &str) -> Option<Node<'a>> {
+    let mut current = root.descendant_for_byte_range(range.start, range.end);
+    while let Some(node) = current {
+        if node.byte_range() == range && node.kind() == kind {
+            return Some(node);
+        }
+        current = node.parent().filter(|parent| parent.byte_range() == range);
+    }
+    None
+}
+
+/// The edit that turns the document into `text`, it only spans the lines that changed.
+fn text_edit(document: &TextDocumentItem, text: &str) -> TextEdit {
+    let old = document.text.as_str();
+    let mut prefix = old
+        .bytes()
+        .zip(text.bytes())
+        .take_while(|(old, new)| old == new)
+        .count();
+    prefix = old[..prefix]
+        .rfind('\n')
+        .map_or(0, |line_break| line_break + 1);
+    let mut suffix = old[prefix..]
+        .bytes()
+        .rev()
+        .zip(text[prefix..].bytes().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    suffix = old[old.len() - suffix..]
+        .find('\n')
+        .map_or(0, |line_break| suffix - line_break - 1);
+    let range = Range {
+        start: document.position(document.point_at(prefix)),
+        end: document.position(document.point_at(old.len() - suffix)),
+    };
+    TextEdit::new(range, text[prefix..text.len() - suffix].to_string())
+}
+
+/// The closest ancestor of `node` of one of the `kinds`, or `node` itself.
+fn ancestor<'a>(node: Node<'a>, kinds: &[&str]) -> Option<Node<'a>> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if kinds.contains(&node.kind()) {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+fn node_text<'a>(node: Node, text: &'a str) -> &'a str {
+    &text[node.byte_range()]
+}
+
+/// `ex:a\.b` to `<http://example.org/a.b>`, with the namespace of the last declaration of the prefix.
+fn expand_prefixed_name(
+    token: Node,
+    text: &str,
+    prefixes: &[(String, String)],
+) -> Option<Refactoring> {
+    let name = ancestor(token, &["PrefixedName"])?;
+    let (prefix, local_name) = node_text(name, text).split_once(':')?;
+    let (_prefix, namespace) = prefixes
+        .iter()
+        .rev()
+        .find(|(declared, _namespace)| declared == prefix)?;
+    let mut iri = namespace.clone();
+    let mut escaped = false;
+    for char in local_name.chars() {
+        match char {
+            '\\' if !escaped => escaped = true,
+            char => {
+                iri.push(char);
+                escaped = false;
+            }
+        }
+    }
+    Some(Refactoring::rewrite(
+        "Expand to full IRI",
+        ancestor(name, &TRIPLES),
+        vec![(name.byte_range(), format!("<{}>", iri))],
+    ))
+}
+
+/// `<http://example.org/a>` to `ex:a`, with the longest declared namespace.
+fn compact_iri(token: Node, text: &str, prefixes: &[(String, String)]) -> Option<Refactoring> {
+    if token.kind() != "IRIREF"
+        || token
+            .parent()
+            .is_some_and(|parent| matches!(parent.kind(), "PrefixDecl" | "BaseDecl"))
+    {
+        return None;
+    }
+    let iri = node_text(token, text)
+        .strip_prefix('<')?
+        .strip_suffix('>')?;
+    let prefixed_name = abbreviate(iri, prefixes)?;
+    Some(Refactoring::rewrite(
+        format!("Compact to {}", prefixed_name),
+        ancestor(token, &TRIPLES),
+        vec![(token.byte_range(), prefixed_name)],
+    ))
+}
+
+/// The subject of a triple that can be repeated, it must not be `[]` or `()`.
+fn subject(triple: Node) -> Option<Node> {
+    triple
+        .child_by_field_name("subject")
+        .filter(|subject| !matches!(subject.kind(), "ANON" | "NIL"))
+}
+
+fn property_list(triple: Node) -> Option<Node> {
+    triple
+        .named_children(&mut triple.walk())
+        .find(|child| PROPERTY_LISTS.contains(&child.kind()))
+}
+
+/// `?s p1 o1 . ?s p2 o2` to `?s p1 o1 ; p2 o2`, for all triples of the block with the subject.
+fn merge_triples(token: Node, text: &str) -> Option<Refactoring> {
+    let triple = ancestor(token, &TRIPLES)?;
+    let subject_text = node_text(subject(triple)?, text);
+    let block = triple.parent()?;
+    let triples: Vec<Node> = block
+        .named_children(&mut block.walk())
+        .filter(|sibling| {
+            TRIPLES.contains(&sibling.kind())
+                && subject(*sibling).is_some_and(|subject| node_text(subject, text) == subject_text)
+                && property_list(*sibling).is_some()
+        })
+        .collect();
+    let (first, rest) = triples.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+    let property_lists: Vec<&str> = triples
+        .iter()
+        .filter_map(|triple| Some(node_text(property_list(*triple)?, text)))
+        .collect();
+    let mut edits = vec![(
+        first.byte_range(),
+        format!("{} {}", subject_text, property_lists.join(" ;\n")),
+    )];
+    for triple in rest {
+        let end = match triple.next_sibling() {
+            Some(dot) if dot.kind() == "." => dot.end_byte(),
+            _ => triple.end_byte(),
+        };
+        edits.push((triple.start_byte()..end, String::new()));
+    }
+    Some(Refactoring::rewrite(
+        format!("Merge the triples of {}", subject_text),
+        ancestor(block, &GROUPS),
+        edits,
+    ))
+}
+
+/// `?s p1 o1 ; p2 o2` to `?s p1 o1 . ?s p2 o2`.
+fn split_property_list(token: Node, text: &str) -> Option<Refactoring> {
+    let triple = ancestor(token, &TRIPLES)?;
+    let subject_text = node_text(subject(triple)?, text);
+    let property_list = property_list(triple)?;
+    let mut segments: Vec<Vec<Node>> = vec![vec![]];
+    for child in property_list.children(&mut property_list.walk()) {
+        match child.kind() {
+            ";" => segments.push(vec![]),
+            "comment" => {}
+            _ => segments.last_mut()?.push(child),
+        }
+    }
+    let triples: Vec<String> = segments
+        .iter()
+        .filter_map(|segment| {
+            let (first, last) = (segment.first()?, segment.last()?);
+            Some(format!(
+                "{} {}",
+                subject_text,
+                &text[first.start_byte()..last.end_byte()]
+            ))
+        })
+        .collect();
+    if triples.len() < 2 {
+        return None;
+    }
+    Some(Refactoring::rewrite(
+        "Split the property list into triples",
+        ancestor(triple, &GROUPS),
+        vec![(triple.byte_range(), triples.join(" .\n"))],
+    ))
+}
+
+/// The projection of a sub-select of the `range`: the variables that are also used outside of it.
+fn projection(group: Node, text: &str, range: ByteRange<usize>) -> String {
+    let root = {
+        let mut root = group;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        root
+    };
+    // NOTE: With `SELECT *` all variables of the range are visible outside of it.
+    let star =
+        ancestor(group, &["SubSelect", "SelectQuery", "DescribeQuery"]).is_some_and(|query| {
+            let clause = match query.kind() {
+                "DescribeQuery" => Some(query),
+                _ => query
+                    .children(&mut query.walk())
+                    .find(|child| child.kind() == "SelectClause"),
+            };
+            clause.is_some_and(|clause| {
+                clause
+                    .children(&mut clause.walk())
+                    .any(|child| child.kind() == "*")
+            })
+        });
+    let (inside, outside): (Vec<Node>, Vec<Node>) = find_all(root, "VAR")
+        .into_iter()
+        .partition(|var| range.start <= var.start_byte() && var.end_byte() <= range.end);
+    let outside: Vec<String> = outside
+        .into_iter()
+        .map(|var| variable_name(var, text))
+        .collect();
+    let mut projected: Vec<String> = vec![];
+    for var in inside {
+        let name = variable_name(var, text);
+        if (star || outside.contains(&name)) && !projected.contains(&name) {
+            projected.push(name);
+        }
+    }
+    match projected.is_empty() {
+        true => "*".to_string(),
+        false => projected
+            .iter()
+            .map(|name| format!("?{}", name))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Wraps the graph patterns of the selection in `OPTIONAL`, `FILTER NOT EXISTS` or a sub-select.
+fn wrap_selection(root: Node, text: &str, start: usize, end: usize) -> Vec<Refactoring> {
+    let Some(group) = root
+        .descendant_for_byte_range(start, end)
+        .and_then(|node| ancestor(node, &["GroupGraphPatternSub"]))
+    else {
+        return vec![];
+    };
+    let mut elements = vec![];
+    for child in group.named_children(&mut group.walk()) {
+        match child.kind() {
+            "TriplesBlock" => elements.extend(
+                child
+                    .named_children(&mut child.walk())
+                    .filter(|triple| TRIPLES.contains(&triple.kind())),
+            ),
+            "comment" => {}
+            _ => elements.push(child),
+        }
+    }
+    let selected: Vec<Node> = elements
+        .into_iter()
+        .filter(|element| element.start_byte() < end && start < element.end_byte())
+        .collect();
+    let (Some(first), Some(last)) = (selected.first(), selected.last()) else {
+        return vec![];
+    };
+    // NOTE: The `.` after the last triple moves into the new group.
+    let end = match last.next_sibling() {
+        Some(dot) if dot.kind() == "." && TRIPLES.contains(&last.kind()) => dot.end_byte(),
+        _ => last.end_byte(),
+    };
+    let range = first.start_byte()..end;
+    let selection = &text[range.clone()];
+    let projection = projection(group, text, range.clone());
+    let scope = group.parent();
+    vec![
+        Refactoring::rewrite(
+            "Wrap in OPTIONAL",
+            scope,
+            vec![(range.clone(), format!("OPTIONAL {{ {} }}", selection))],
+        ),
+        Refactoring::rewrite(
+            "Wrap in FILTER NOT EXISTS",
+            scope,
+            vec![(
+                range.clone(),
+                format!("FILTER NOT EXISTS {{ {} }}", selection),
+            )],
+        ),
+        Refactoring::rewrite(
+            "Wrap in sub-select",
+            scope,
+            vec![(
+                range,
+                format!("{{ SELECT {} WHERE {{ {} }} }}", projection, selection),
+            )],
+        ),
+    ]
+}
+
+/// Turns the group around the token into a sub-select, that projects the variables used outside of it.
+fn extract_group(token: Node, text: &str) -> Option<Refactoring> {
+    let group = ancestor(token, &["GroupGraphPattern"])?;
+    if group
+        .parent()
+        .is_none_or(|parent| parent.kind() == "WhereClause")
+    {
+        return None;
+    }
+    let content = group
+        .named_children(&mut group.walk())
+        .find(|child| child.kind() != "comment")?;
+    if content.kind() != "GroupGraphPatternSub" {
+        return None;
+    }
+    let projection = projection(group, text, content.byte_range());
+    Some(Refactoring {
+        kind: "refactor.extract",
+        ..Refactoring::rewrite(
+            "Extract the group into a sub-select",
+            Some(group),
+            vec![(
+                content.byte_range(),
+                format!(
+                    "SELECT {} WHERE {{ {} }}",
+                    projection,
+                    node_text(content, text)
+                ),
+            )],
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use tree_sitter::Parser;
+
+    use crate::{
+        lsp::{textdocument::TextDocumentItem, PositionEncodingKind},
+        server::configuration::FormatSettings,
+    };
+
+    use super::{refactorings, rewrite, text_edit};
+
+    /// The formatted results of the refactorings at the selection between the `|` markers,
+    /// or at the single `|` marker.
+    fn refactor(marked: &str) -> Vec<(String, String)> {
+        let start = marked.find('|').unwrap();
+        let end = marked.rfind('|').unwrap();
+        let text = marked.replace('|', "");
+        let end = if end == start { start } else { end - 1 };
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(&text, None).unwrap();
+        let mut document = TextDocumentItem::new("uri", &text);
+        document.position_encoding = PositionEncodingKind::Utf8;
+        refactorings(&document, &tree, start, end)
+            .into_iter()
+            .filter_map(|refactoring| {
+                let text = rewrite(&text, &refactoring, &FormatSettings::default())?;
+                Some((refactoring.title, text))
+            })
+            .collect()
+    }
+
+    fn refactored(marked: &str, title: &str) -> String {
+        refactor(marked)
+            .into_iter()
+            .find(|(refactoring, _text)| refactoring == title)
+            .unwrap_or_else(|| panic!("{} is not offered", title))
+            .1
+    }
+
+    #[test]
+    fn prefixed_names() {
+        let query =
+            "PREFIX ex: <http://example.org/>\nSELECT * { ?s ex:a|\\.b <http://example.org/c> }";
+        assert_eq!(
+            refactored(query, "Expand to full IRI"),
+            "PREFIX ex: <http://example.org/>\nSELECT * { ?s <http://example.org/a.b> <http://example.org/c> }"
+        );
+        let query =
+            "PREFIX ex: <http://example.org/>\nSELECT * { ?s ex:a <http://|example.org/c> }";
+        assert_eq!(
+            refactored(query, "Compact to ex:c"),
+            "PREFIX ex: <http://example.org/>\nSELECT * { ?s ex:a ex:c }"
+        );
+        let query = "PREFIX ex: <http://example.org/>\nSELECT * { FILTER(?o = ex:|c) }";
+        assert_eq!(
+            refactored(query, "Expand to full IRI"),
+            "PREFIX ex: <http://example.org/>\nSELECT * { FILTER(?o = <http://example.org/c>) }"
+        );
+        assert!(refactor("PREFIX ex: <http://ex|ample.org/>\nSELECT * {}").is_empty());
+        assert!(refactor("SELECT * { ?s un:decl|ared ?o }").is_empty());
+    }
+
+    #[test]
+    fn property_lists() {
+        let query = indoc! {"
+            SELECT * {
+              ?s <p> ?o .
+              ?x <q> ?y .
+              |?s <r> ?z ; <t> ?u .
+            }"
+        };
+        assert_eq!(
+            refactored(query, "Merge the triples of ?s"),
+            indoc! {"
+                SELECT * {
+                  ?s <p> ?o ;
+                     <r> ?z ;
+                     <t> ?u .
+                  ?x <q> ?y .
+                }"
+            }
+        );
+        assert_eq!(
+            refactored(query, "Split the property list into triples"),
+            indoc! {"
+                SELECT * {
+                  ?s <p> ?o .
+                  ?x <q> ?y .
+                  ?s <r> ?z .
+                  ?s <t> ?u .
+                }"
+            }
+        );
+        assert_eq!(
+            refactored(
+                "CONSTRUCT { ?s <p> ?o . ?s <q> ?|x } WHERE {}",
+                "Merge the triples of ?s"
+            ),
+            "CONSTRUCT {\n  ?s <p> ?o ; <q> ?x .\n} WHERE {}"
+        );
+        let titles: Vec<String> = refactor("SELECT * { [] <p> ?o ; <q> ?|x . [] <p> ?y }")
+            .into_iter()
+            .map(|(title, _text)| title)
+            .collect();
+        assert!(titles.is_empty());
+    }
+
+    #[test]
+    fn wrap_selection() {
+        let query = indoc! {"
+            SELECT ?s ?name {
+              ?s a <Person> .
+              |?s <name> ?name .
+              ?s <age> ?age .|
+              FILTER (?age > 18)
+            }"
+        };
+        assert_eq!(
+            refactored(query, "Wrap in OPTIONAL"),
+            indoc! {"
+                SELECT ?s ?name {
+                  ?s a <Person> .
+                  OPTIONAL {
+                    ?s <name> ?name .
+                    ?s <age> ?age .
+                  }
+                  FILTER (?age > 18)
+                }"
+            }
+        );
+        assert_eq!(
+            refactored(query, "Wrap in FILTER NOT EXISTS"),
+            indoc! {"
+                SELECT ?s ?name {
+                  ?s a <Person> .
+                  FILTER NOT EXISTS {
+                    ?s <name> ?name .
+                    ?s <age> ?age .
+                  }
+                  FILTER (?age > 18)
+                }"
+            }
+        );
+        assert_eq!(
+            refactored(query, "Wrap in sub-select"),
+            indoc! {"
+                SELECT ?s ?name {
+                  ?s a <Person> .
+                  {
+                    SELECT ?s ?name ?age WHERE {
+                      ?s <name> ?name .
+                      ?s <age> ?age .
+                    }
+                  }
+                  FILTER (?age > 18)
+                }"
+            }
+        );
+        assert!(refactor("SELECT * {\n  ?s ?p ?o .|\n|}").is_empty());
+    }
+
+    #[test]
+    fn extract_group() {
+        let query = indoc! {"
+            SELECT ?s {
+              ?s a <Person> .
+              OPTIONAL {
+                ?s <knows> ?friend .
+                ?friend <name> |?name .
+              }
+            }"
+        };
+        assert_eq!(
+            refactored(query, "Extract the group into a sub-select"),
+            indoc! {"
+                SELECT ?s {
+                  ?s a <Person> .
+                  OPTIONAL {
+                    SELECT ?s WHERE {
+                      ?s <knows> ?friend .
+                      ?friend <name> ?name .
+                    }
+                  }
+                }"
+            }
+        );
+        let titles: Vec<String> = refactor("SELECT * { ?s ?|p ?o }")
+            .into_iter()
+            .map(|(title, _text)| title)
+            .collect();
+        assert!(!titles.contains(&"Extract the group into a sub-select".to_string()));
+    }
+
+    #[test]
+    fn only_the_rewritten_node_is_formatted() {
+        let query = indoc! {"
+            prefix ex:   <http://example.org/>
+            select * where {
+                ?x   ex:q   ?y .
+              ?s   ex:|p   ?o   ;   ex:r ?z
+                OPTIONAL {   ?y    ex:s    ?z }
+            }
+            limit   1"
+        };
+        assert_eq!(
+            refactored(query, "Expand to full IRI"),
+            indoc! {"
+                prefix ex:   <http://example.org/>
+                select * where {
+                    ?x   ex:q   ?y .
+                  ?s <http://example.org/p> ?o ;
+                     ex:r ?z
+                    OPTIONAL {   ?y    ex:s    ?z }
+                }
+                limit   1"
+            }
+        );
+        assert_eq!(
+            refactored(query, "Split the property list into triples"),
+            indoc! {"
+                prefix ex:   <http://example.org/>
+                select * where {
+                  ?x ex:q ?y .
+                  ?s ex:p ?o .
+                  ?s ex:r ?z
+                  OPTIONAL {
+                    ?y ex:s ?z
+                  }
+                }
+                limit   1"
+            }
+        );
+    }
+
+    #[test]
+    fn minimal_text_edit() {
+        let document = TextDocumentItem::new("uri", "SELECT * {\n  ?s ?p ?o\n}\nLIMIT 1");
+        let edit = text_edit(&document, "SELECT * {\n  ?s ?p ?x\n}\nLIMIT 1");
+        assert_eq!(
+            serde_json::to_string(&edit).unwrap(),
+            r#"{"range":{"start":{"line":1,"character":0},"end":{"line":2,"character":0}},"newText":"  ?s ?p ?x\n"}"#
+        );
+    }
+}
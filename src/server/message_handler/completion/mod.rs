@@ -1,19 +1,25 @@
-use log::{error, info};
+use log::error;
 
 use crate::{
-    analysis::get_all_variables,
-    lsp::{CompletionRequest, CompletionResponse, CompletionTriggerKind},
-    server::ServerState,
+    analysis::{get_all_variables, AnalysisState},
+    lsp::{
+        textdocument::{Position, TextDocumentItem},
+        CompletionItem, CompletionRequest, CompletionResponse, CompletionTriggerKind,
+    },
+    server::Snapshot,
 };
 
 pub fn handel_completion_request(
     request: CompletionRequest,
-    state: &mut ServerState,
+    state: &Snapshot,
 ) -> CompletionResponse {
     match request.get_completion_context().trigger_kind {
         // Completion was triggered by typing an identifier (24x7 code complete),
         // manual invocation (e.g Ctrl+Space) or via API.
-        CompletionTriggerKind::Invoked => CompletionResponse::new(request.get_id()),
+        CompletionTriggerKind::Invoked => CompletionResponse::new(
+            request.get_id(),
+            state.client_capabilities.snippet_support(),
+        ),
         // Completion was triggered by a trigger character specified by
         // the `triggerCharacters` properties of the `CompletionRegistrationOptions`.
         // i.e. "?"
@@ -27,3 +33,48 @@ pub fn handel_completion_request(
         }
     }
 }
+
+/// The completions at `position` of `text`:
+/// The variables of the query right after a `?` or `$`, the keyword snippets otherwise.
+pub(crate) fn completions(text: &str, position: &Position) -> Vec<CompletionItem> {
+    let mut state = AnalysisState::new();
+    state.add_document(TextDocumentItem::new("query", text));
+    let point = match state.get_state("query") {
+        Some((document, _tree)) => document.point(position),
+        None => return vec![],
+    };
+    let before = text
+        .lines()
+        .nth(point.row)
+        .and_then(|line| line.get(..point.column))
+        .unwrap_or("");
+    match before.ends_with(['?', '$']) {
+        true => CompletionItem::variables(get_all_variables(&state, "query")),
+        false => CompletionItem::keywords(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lsp::textdocument::Position;
+
+    use super::completions;
+
+    fn labels(text: &str, character: u32) -> Vec<String> {
+        completions(text, &Position::new(0, character))
+            .into_iter()
+            .map(|item| serde_json::to_value(item).unwrap()["label"].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn keywords_or_variables() {
+        assert_eq!(
+            labels("S", 1),
+            vec![r#""SELECT""#, r#""PREFIX""#, r#""FILTER""#, r#""ORDER BY""#]
+        );
+        let mut variables = labels("SELECT * { ?名前 ?b ? }", 19);
+        variables.sort();
+        assert_eq!(variables, vec![r#""b""#, r#""名前""#]);
+    }
+}
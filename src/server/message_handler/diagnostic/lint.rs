@@ -0,0 +1,85 @@
+use crate::{
+    analysis::AnalysisState,
+    lsp::{textdocument::Range, Diagnostic, DiagnosticSeverity},
+    server::configuration::{LintLevel, LintSettings},
+};
+
+/// A single problem found by a `LintRule`.
+#[derive(Debug, PartialEq)]
+pub struct LintViolation {
+    pub range: Range,
+    pub message: String,
+}
+
+impl LintViolation {
+    pub fn new(range: Range, message: String) -> Self {
+        Self { range, message }
+    }
+
+    pub(super) fn into_diagnostic(self, rule: &str, severity: DiagnosticSeverity) -> Diagnostic {
+        Diagnostic {
+            range: self.range,
+            severity,
+            code: Some(rule.to_string()),
+            source: "fichu".to_string(),
+            message: self.message,
+        }
+    }
+}
+
+/// A family of lint rules.
+/// A whole family can be configured at once by using its name in the `[lint]` section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintCategory {
+    General,
+    /// Patterns that are known to be expensive to evaluate. These rules are opt-in.
+    Performance,
+}
+
+impl LintCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintCategory::General => "general",
+            LintCategory::Performance => "performance",
+        }
+    }
+}
+
+/// A check that inspects a single document and reports `LintViolation`s.
+///
+/// Every rule has a unique name. The name is used to configure the rule in the `[lint]` section
+/// of `fichu.toml` and to suppress it with `# fichu-ignore: <name>` comments.
+pub trait LintRule {
+    fn name(&self) -> &'static str;
+
+    fn category(&self) -> LintCategory {
+        LintCategory::General
+    }
+
+    /// The level that is used when neither the rule nor its category is configured.
+    fn default_level(&self) -> LintLevel {
+        match self.category() {
+            LintCategory::General => LintLevel::Warning,
+            LintCategory::Performance => LintLevel::Off,
+        }
+    }
+
+    fn level(&self, settings: &LintSettings) -> LintLevel {
+        settings
+            .rules
+            .get(self.name())
+            .or(settings.rules.get(self.category().name()))
+            .copied()
+            .unwrap_or(self.default_level())
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation>;
+}
+
+/// Runs a single rule against a query string.
+#[cfg(test)]
+pub(super) fn check_rule(rule: &dyn LintRule, text: &str) -> Vec<LintViolation> {
+    let mut state = AnalysisState::new();
+    state.add_document(crate::lsp::textdocument::TextDocumentItem::new("uri", text));
+    rule.check(&state, "uri")
+}
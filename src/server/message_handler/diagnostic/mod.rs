@@ -0,0 +1,179 @@
+mod lint;
+mod rules;
+mod suppression;
+mod syntax;
+
+pub use lint::*;
+
+use log::warn;
+
+use crate::{
+    analysis::AnalysisState,
+    lsp::{textdocument::TextDocumentItem, Diagnostic, PositionEncodingKind},
+    server::configuration::LintSettings,
+};
+
+use suppression::Suppressions;
+pub(crate) use syntax::syntax_errors;
+
+/// Collects the syntax errors of the document and runs every enabled lint rule on it.
+/// Lint diagnostics that are suppressed by a `# fichu-ignore` comment are dropped.
+pub fn collect_diagnostics(
+    state: &AnalysisState,
+    uri: &str,
+    settings: &LintSettings,
+) -> impl Iterator<Item = Diagnostic> {
+    for name in settings.rules.keys() {
+        if rules::find(name).is_none() && rules::find_category(name).is_none() {
+            warn!("Unknown lint rule in configuration: \"{}\"", name);
+        }
+    }
+    let suppressions = match state.get_state(uri) {
+        Some((document, Some(tree))) => Suppressions::from_tree(tree, &document.text),
+        _ => Suppressions::default(),
+    };
+    let lints = rules::all()
+        .into_iter()
+        .filter_map(|rule| {
+            rule.level(settings)
+                .severity()
+                .map(|severity| (rule, severity))
+        })
+        .flat_map(|(rule, severity)| {
+            rule.check(state, uri)
+                .into_iter()
+                .map(move |violation| violation.into_diagnostic(rule.name(), severity))
+        })
+        .filter(|diagnostic| !suppressions.is_suppressed(diagnostic));
+    syntax_errors(state, uri)
+        .into_iter()
+        .chain(lints)
+        .collect::<Vec<Diagnostic>>()
+        .into_iter()
+}
+
+/// Parses the given query and returns its syntax errors and lint diagnostics.
+pub fn check(text: &str, settings: &LintSettings) -> Vec<Diagnostic> {
+    check_with_encoding(text, settings, PositionEncodingKind::default())
+}
+
+/// Like [`check`], with the positions of the diagnostics in `position_encoding`.
+pub(crate) fn check_with_encoding(
+    text: &str,
+    settings: &LintSettings,
+    position_encoding: PositionEncodingKind,
+) -> Vec<Diagnostic> {
+    let mut state = AnalysisState::new();
+    state.position_encoding = position_encoding;
+    state.add_document(TextDocumentItem::new("query", text));
+    collect_diagnostics(&state, "query", settings).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{
+        lsp::{Diagnostic, DiagnosticSeverity},
+        server::configuration::{LintLevel, LintSettings},
+    };
+
+    use super::check;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn default_rules() {
+        let diagnostics = check(
+            "PREFIX wd: <>\nSELECT * {?a wdt:P31 ?b}",
+            &LintSettings::default(),
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            vec!["unused_prefix", "undeclared_prefix"]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning));
+    }
+
+    #[test]
+    fn configured_levels() {
+        let mut settings = LintSettings::default();
+        settings
+            .rules
+            .insert("unused_prefix".to_string(), LintLevel::Off);
+        settings
+            .rules
+            .insert("undeclared_prefix".to_string(), LintLevel::Error);
+        let diagnostics = check("PREFIX wd: <>\nSELECT * {?a wdt:P31 ?b}", &settings);
+        assert_eq!(codes(&diagnostics), vec!["undeclared_prefix"]);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+    }
+
+    #[test]
+    fn configured_category() {
+        let text = "SELECT * {?s ?p ?o}";
+        assert!(check(text, &LintSettings::default()).is_empty());
+        let mut settings = LintSettings::default();
+        settings
+            .rules
+            .insert("performance".to_string(), LintLevel::Warning);
+        assert_eq!(codes(&check(text, &settings)), vec!["unbound_triple"]);
+        settings
+            .rules
+            .insert("unbound_triple".to_string(), LintLevel::Off);
+        assert!(check(text, &settings).is_empty());
+    }
+
+    #[test]
+    fn suppress_next_line() {
+        let diagnostics = check(
+            indoc!(
+                "# fichu-ignore: unused_prefix
+                 PREFIX wd: <>
+                 PREFIX p: <>
+                 SELECT * {
+                   # fichu-ignore: unused_prefix
+                   ?a wdt:P31 ?b
+                 }"
+            ),
+            &LintSettings::default(),
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            vec!["unused_prefix", "undeclared_prefix"]
+        );
+        assert_eq!(diagnostics[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn syntax_errors_first() {
+        let diagnostics = check("PREFIX wd: <>\nSELECT * {", &LintSettings::default());
+        assert_eq!(codes(&diagnostics), vec!["syntax_error", "unused_prefix"]);
+    }
+
+    #[test]
+    fn suppress_file() {
+        let text = indoc!(
+            "PREFIX wd: <>
+             # fichu-ignore-file
+             SELECT * {?a wdt:P31 ?b}"
+        );
+        assert!(check(text, &LintSettings::default()).is_empty());
+        let text = indoc!(
+            "# fichu-ignore-file: undeclared_prefix
+             PREFIX wd: <>
+             SELECT * {?a wdt:P31 ?b}"
+        );
+        assert_eq!(
+            codes(&check(text, &LintSettings::default())),
+            vec!["unused_prefix"]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use crate::{
+    analysis::{get_declared_namspaces, AnalysisState},
+    server::message_handler::diagnostic::{LintRule, LintViolation},
+};
+
+/// Reports prefixes that are declared more than once.
+pub(super) struct DuplicatePrefix;
+
+impl LintRule for DuplicatePrefix {
+    fn name(&self) -> &'static str {
+        "duplicate_prefix"
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let mut seen = HashSet::new();
+        get_declared_namspaces(state, uri)
+            .into_iter()
+            .filter(|(prefix, _range)| !seen.insert(prefix.clone()))
+            .map(|(prefix, range)| {
+                LintViolation::new(range, format!("'{}' is already declared", prefix))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::DuplicatePrefix;
+
+    #[test]
+    fn duplicate_prefix() {
+        let violations = check_rule(
+            &DuplicatePrefix,
+            indoc!(
+                "PREFIX wdt: <>
+                 PREFIX wd: <>
+                 PREFIX wdt: <>
+                 SELECT * {}"
+            ),
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "'wdt:' is already declared");
+        assert_eq!(violations[0].range.start.line, 2);
+    }
+}
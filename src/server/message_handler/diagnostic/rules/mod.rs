@@ -0,0 +1,31 @@
+mod duplicate_prefix;
+mod performance;
+mod undeclared_prefix;
+mod unused_prefix;
+
+use super::{LintCategory, LintRule};
+
+/// Returns every available lint rule.
+pub(super) fn all() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(unused_prefix::UnusedPrefix),
+        Box::new(undeclared_prefix::UndeclaredPrefix),
+        Box::new(duplicate_prefix::DuplicatePrefix),
+        Box::new(performance::UnboundTriple),
+        Box::new(performance::RegexFilter),
+        Box::new(performance::CartesianProduct),
+        Box::new(performance::DisconnectedOptional),
+        Box::new(performance::DistinctStar),
+    ]
+}
+
+pub(super) fn find(name: &str) -> Option<Box<dyn LintRule>> {
+    all().into_iter().find(|rule| rule.name() == name)
+}
+
+pub(super) fn find_category(name: &str) -> Option<LintCategory> {
+    all()
+        .into_iter()
+        .map(|rule| rule.category())
+        .find(|category| category.name() == name)
+}
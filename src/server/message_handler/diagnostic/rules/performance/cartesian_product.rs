@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use crate::{
+    analysis::{find_all, variables_in, AnalysisState},
+    server::message_handler::diagnostic::{LintCategory, LintRule, LintViolation},
+};
+
+/// Reports triple patterns of a group that share no variables with the other triple patterns
+/// of that group. Evaluating such a group computes the cartesian product of the disconnected parts.
+pub(in super::super) struct CartesianProduct;
+
+impl LintRule for CartesianProduct {
+    fn name(&self) -> &'static str {
+        "cartesian_product"
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Performance
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let (document, tree) = match state.get_state(uri) {
+            Some((document, Some(tree))) => (document, tree),
+            _ => return vec![],
+        };
+        let text = &document.text;
+        let mut violations = vec![];
+        for group in find_all(tree.root_node(), "GroupGraphPatternSub") {
+            // NOTE: Each component is a list of triple patterns and the variables they use.
+            let mut components: Vec<(Vec<_>, HashSet<String>)> = vec![];
+            let triples = group
+                .children(&mut group.walk())
+                .filter(|child| child.kind() == "TriplesBlock")
+                .flat_map(|block| find_all(block, "TriplesSameSubjectPath"))
+                .collect::<Vec<_>>();
+            for triple in triples {
+                let variables = variables_in(triple, text);
+                if variables.is_empty() {
+                    continue;
+                }
+                let (connected, mut rest): (Vec<_>, Vec<_>) =
+                    components
+                        .into_iter()
+                        .partition(|(_, component_variables)| {
+                            !component_variables.is_disjoint(&variables)
+                        });
+                let merged = connected.into_iter().fold(
+                    (vec![], variables),
+                    |(mut nodes, mut variables), (component_nodes, component_variables)| {
+                        nodes.extend(component_nodes);
+                        variables.extend(component_variables);
+                        (nodes, variables)
+                    },
+                );
+                let (mut nodes, variables) = merged;
+                nodes.push(triple);
+                nodes.sort_by_key(|node| node.start_byte());
+                rest.push((nodes, variables));
+                rest.sort_by_key(|(nodes, _)| nodes[0].start_byte());
+                components = rest;
+            }
+            for (nodes, _variables) in components.iter().skip(1) {
+                violations.push(LintViolation::new(
+                    document.range(nodes[0]),
+                    "This triple pattern shares no variables with the preceding triple patterns of this group, which results in a cartesian product".to_string(),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::CartesianProduct;
+
+    #[test]
+    fn cartesian_product() {
+        let violations = check_rule(
+            &CartesianProduct,
+            indoc!(
+                "SELECT * {
+                   ?a <p> ?b .
+                   ?c <p> ?d .
+                   ?b <q> ?e .
+                 }"
+            ),
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn connected() {
+        let text = indoc!(
+            "SELECT * {
+               ?a <p> ?b .
+               ?c <p> ?d .
+               ?b <q> ?c .
+               <x> <y> <z> .
+               OPTIONAL { ?f <p> ?g }
+             }"
+        );
+        assert!(check_rule(&CartesianProduct, text).is_empty());
+    }
+}
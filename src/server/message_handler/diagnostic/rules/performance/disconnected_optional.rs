@@ -0,0 +1,72 @@
+use crate::{
+    analysis::{find_all, variable_name, variables_in, AnalysisState},
+    server::message_handler::diagnostic::{LintCategory, LintRule, LintViolation},
+};
+
+use super::{contains, enclosing_pattern};
+
+/// Reports `OPTIONAL` blocks that share no variable with the pattern around them.
+/// Every solution of such a block is combined with every solution of the outer pattern.
+pub(in super::super) struct DisconnectedOptional;
+
+impl LintRule for DisconnectedOptional {
+    fn name(&self) -> &'static str {
+        "disconnected_optional"
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Performance
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let (document, tree) = match state.get_state(uri) {
+            Some((document, Some(tree))) => (document, tree),
+            _ => return vec![],
+        };
+        let text = &document.text;
+        find_all(tree.root_node(), "OptionalGraphPattern")
+            .into_iter()
+            .filter(|optional| {
+                let Some(pattern) = enclosing_pattern(*optional) else {
+                    return false;
+                };
+                let variables = variables_in(*optional, text);
+                !variables.is_empty()
+                    && !find_all(pattern, "VAR").into_iter().any(|var| {
+                        !contains(*optional, var) && variables.contains(&variable_name(var, text))
+                    })
+            })
+            .map(|optional| {
+                LintViolation::new(
+                    document.range(optional),
+                    "This OPTIONAL shares no variables with the surrounding pattern, which results in a cartesian product".to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::DisconnectedOptional;
+
+    #[test]
+    fn disconnected() {
+        let violations = check_rule(
+            &DisconnectedOptional,
+            "SELECT * {?a <p> ?b OPTIONAL {?c <q> ?d}}",
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].range.start.character, 20);
+    }
+
+    #[test]
+    fn connected() {
+        let text = "SELECT * {?a <p> ?b OPTIONAL {?b <q> ?d}}";
+        assert!(check_rule(&DisconnectedOptional, text).is_empty());
+        let text = "SELECT * {?a <p> ?b {OPTIONAL {?b <q> ?d}}}";
+        assert!(check_rule(&DisconnectedOptional, text).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    analysis::{find_all, find_all_until, AnalysisState},
+    server::message_handler::diagnostic::{LintCategory, LintRule, LintViolation},
+};
+
+/// The number of triple patterns from which on a join is considered large.
+const LARGE_JOIN: usize = 3;
+
+/// Nested queries, their triples do not belong to the join of the outer query.
+const NESTED_QUERIES: [&str; 3] = ["SubSelect", "ExistsFunc", "NotExistsFunc"];
+
+/// Reports `SELECT DISTINCT *` over large joins.
+/// Removing duplicates over all variables of a large join requires to compare entire rows.
+pub(in super::super) struct DistinctStar;
+
+impl LintRule for DistinctStar {
+    fn name(&self) -> &'static str {
+        "distinct_star"
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Performance
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let Some((document, Some(tree))) = state.get_state(uri) else {
+            return vec![];
+        };
+        find_all(tree.root_node(), "SelectClause")
+            .into_iter()
+            .filter(|select_clause| {
+                let kinds: Vec<&str> = select_clause
+                    .children(&mut select_clause.walk())
+                    .map(|child| child.kind())
+                    .collect();
+                kinds.contains(&"DISTINCT") && kinds.contains(&"*")
+            })
+            .filter(|select_clause| {
+                select_clause
+                    .next_sibling()
+                    .into_iter()
+                    .flat_map(|sibling| find_all_until(sibling, "ObjectList", &NESTED_QUERIES))
+                    .map(|object_list| {
+                        object_list
+                            .children_by_field_name("object", &mut object_list.walk())
+                            .count()
+                    })
+                    .sum::<usize>()
+                    >= LARGE_JOIN
+            })
+            .map(|select_clause| {
+                LintViolation::new(
+                    document.range(select_clause),
+                    "DISTINCT over all variables of a large join is expensive, consider projecting only the variables you need".to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::DistinctStar;
+
+    #[test]
+    fn distinct_star() {
+        let text = "SELECT DISTINCT * {?a <p> ?b, ?c . ?b <q> ?d}";
+        assert_eq!(check_rule(&DistinctStar, text).len(), 1);
+    }
+
+    #[test]
+    fn small_or_projected() {
+        let text = "SELECT DISTINCT * {?a <p> ?b}";
+        assert!(check_rule(&DistinctStar, text).is_empty());
+        let text = "SELECT DISTINCT ?a {?a <p> ?b, ?c . ?b <q> ?d}";
+        assert!(check_rule(&DistinctStar, text).is_empty());
+        let text = "SELECT * {?a <p> ?b, ?c . ?b <q> ?d}";
+        assert!(check_rule(&DistinctStar, text).is_empty());
+    }
+
+    #[test]
+    fn large_nested_queries() {
+        let text = "SELECT DISTINCT * {?a <p> ?b { SELECT ?a {?a <p> ?b, ?c . ?b <q> ?d} }}";
+        assert!(check_rule(&DistinctStar, text).is_empty());
+        let text = "SELECT DISTINCT * {?a <p> ?b FILTER NOT EXISTS {?a <p> ?b, ?c . ?b <q> ?d}}";
+        assert!(check_rule(&DistinctStar, text).is_empty());
+        // The sub-select itself is checked on its own.
+        let text = "SELECT * {?a <p> ?b { SELECT DISTINCT * {?a <p> ?b, ?c . ?b <q> ?d} }}";
+        assert_eq!(check_rule(&DistinctStar, text).len(), 1);
+    }
+}
@@ -0,0 +1,29 @@
+mod cartesian_product;
+mod disconnected_optional;
+mod distinct_star;
+mod regex_filter;
+mod unbound_triple;
+
+pub(super) use cartesian_product::CartesianProduct;
+pub(super) use disconnected_optional::DisconnectedOptional;
+pub(super) use distinct_star::DistinctStar;
+pub(super) use regex_filter::RegexFilter;
+pub(super) use unbound_triple::UnboundTriple;
+
+use tree_sitter::Node;
+
+/// Returns the graph pattern of the `WHERE` clause (or `Modify` operation) that contains `node`.
+fn enclosing_pattern(node: Node) -> Option<Node> {
+    let mut current = node.parent()?;
+    loop {
+        match current.kind() {
+            "WhereClause" | "Modify" => return Some(current),
+            _ => current = current.parent()?,
+        }
+    }
+}
+
+/// Returns `true` if `inner` lies inside of `outer`.
+fn contains(outer: Node, inner: Node) -> bool {
+    outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+}
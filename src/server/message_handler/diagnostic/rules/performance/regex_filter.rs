@@ -0,0 +1,130 @@
+use tree_sitter::Node;
+
+use crate::{
+    analysis::{find_all, AnalysisState},
+    server::message_handler::diagnostic::{LintCategory, LintRule, LintViolation},
+};
+
+const REGEX_META_CHARACTERS: &[char] = &[
+    '\\', '.', '^', '$', '|', '?', '*', '+', '(', ')', '[', ']', '{', '}',
+];
+
+/// Reports `FILTER(REGEX(...))` calls whose pattern contains no regular expression syntax.
+/// These can be replaced by the much cheaper `STRSTARTS`, `STRENDS` or `CONTAINS`.
+pub(in super::super) struct RegexFilter;
+
+impl LintRule for RegexFilter {
+    fn name(&self) -> &'static str {
+        "regex_filter"
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Performance
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let (document, tree) = match state.get_state(uri) {
+            Some((document, Some(tree))) => (document, tree),
+            _ => return vec![],
+        };
+        let text = &document.text;
+        find_all(tree.root_node(), "Filter")
+            .into_iter()
+            .flat_map(|filter| find_all(filter, "RegexExpression"))
+            .filter_map(|regex| {
+                let replacement = replacement(regex, text)?;
+                Some(LintViolation::new(
+                    document.range(regex),
+                    format!(
+                        "This REGEX uses no regular expression syntax, use `{}` instead",
+                        replacement
+                    ),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Computes the cheaper string function call that is equivalent to the `RegexExpression`.
+fn replacement(regex: Node, text: &str) -> Option<String> {
+    if regex.child_by_field_name("flag").is_some() {
+        return None;
+    }
+    let argument = regex
+        .child_by_field_name("text")?
+        .utf8_text(text.as_bytes())
+        .ok()?;
+    let pattern = regex.child_by_field_name("pattern")?;
+    // NOTE: Only plain literals without language tag or datatype are considered.
+    if pattern.kind() != "RdfLiteral" || pattern.child_count() != 1 {
+        return None;
+    }
+    let literal = pattern.utf8_text(text.as_bytes()).ok()?;
+    let quote_length = match literal.starts_with("\"\"\"") || literal.starts_with("'''") {
+        true => 3,
+        false => 1,
+    };
+    let content = literal.get(quote_length..literal.len().checked_sub(quote_length)?)?;
+    let (anchored_start, content) = match content.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, content),
+    };
+    let (anchored_end, content) = match content.strip_suffix('$') {
+        Some(rest) => (true, rest),
+        None => (false, content),
+    };
+    if content.is_empty() || content.contains(REGEX_META_CHARACTERS) {
+        return None;
+    }
+    let quote = &literal[..1];
+    Some(match (anchored_start, anchored_end) {
+        (true, true) => format!("STR({}) = {}{}{}", argument, quote, content, quote),
+        (true, false) => format!("STRSTARTS({}, {}{}{})", argument, quote, content, quote),
+        (false, true) => format!("STRENDS({}, {}{}{})", argument, quote, content, quote),
+        (false, false) => format!("CONTAINS({}, {}{}{})", argument, quote, content, quote),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::RegexFilter;
+
+    fn messages(text: &str) -> Vec<String> {
+        check_rule(&RegexFilter, text)
+            .into_iter()
+            .map(|violation| violation.message)
+            .collect()
+    }
+
+    #[test]
+    fn replaceable() {
+        assert_eq!(
+            messages(r#"SELECT * {?s ?p ?o FILTER(REGEX(?o, "^Berlin"))}"#),
+            vec![
+                r#"This REGEX uses no regular expression syntax, use `STRSTARTS(?o, "Berlin")` instead"#
+            ]
+        );
+        assert_eq!(
+            messages(r#"SELECT * {?s ?p ?o FILTER(regex(str(?o), 'Berlin'))}"#),
+            vec![
+                r#"This REGEX uses no regular expression syntax, use `CONTAINS(str(?o), 'Berlin')` instead"#
+            ]
+        );
+        assert_eq!(
+            messages(r#"SELECT * {?s ?p ?o FILTER(REGEX(?o, "Berlin$"))}"#),
+            vec![
+                r#"This REGEX uses no regular expression syntax, use `STRENDS(?o, "Berlin")` instead"#
+            ]
+        );
+    }
+
+    #[test]
+    fn not_replaceable() {
+        assert!(messages(r#"SELECT * {?s ?p ?o FILTER(REGEX(?o, "^Ber.in"))}"#).is_empty());
+        assert!(messages(r#"SELECT * {?s ?p ?o FILTER(REGEX(?o, "berlin", "i"))}"#).is_empty());
+        assert!(messages(r#"SELECT * {?s ?p ?o FILTER(REGEX(?o, ?pattern))}"#).is_empty());
+        assert!(messages(r#"SELECT (REGEX(?o, "Berlin") AS ?b) {?s ?p ?o}"#).is_empty());
+    }
+}
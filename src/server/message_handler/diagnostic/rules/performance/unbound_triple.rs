@@ -0,0 +1,94 @@
+use crate::{
+    analysis::{find_all, variable_name, AnalysisState},
+    server::message_handler::diagnostic::{LintCategory, LintRule, LintViolation},
+};
+
+use super::enclosing_pattern;
+
+/// Reports triple patterns like `?s ?p ?o` whose variables are not bound anywhere else.
+/// Such a pattern matches every triple in the dataset.
+pub(in super::super) struct UnboundTriple;
+
+impl LintRule for UnboundTriple {
+    fn name(&self) -> &'static str {
+        "unbound_triple"
+    }
+
+    fn category(&self) -> LintCategory {
+        LintCategory::Performance
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        let (document, tree) = match state.get_state(uri) {
+            Some((document, Some(tree))) => (document, tree),
+            _ => return vec![],
+        };
+        let text = &document.text;
+        let mut violations = vec![];
+        for triples in find_all(tree.root_node(), "TriplesSameSubjectPath") {
+            let subject = match triples.child_by_field_name("subject") {
+                Some(subject) if subject.kind() == "VAR" => subject,
+                _ => continue,
+            };
+            let property_list = match triples.child(1) {
+                Some(node) if node.kind() == "PropertyListPathNotEmpty" => node,
+                _ => continue,
+            };
+            // NOTE: Further predicates of the same subject restrict it.
+            let predicates: Vec<_> = property_list
+                .children_by_field_name("predicate", &mut property_list.walk())
+                .collect();
+            let predicate = match predicates.as_slice() {
+                [predicate] if predicate.kind() == "VAR" => *predicate,
+                _ => continue,
+            };
+            let Some(object_list) = predicate.next_sibling() else {
+                continue;
+            };
+            let Some(pattern) = enclosing_pattern(triples) else {
+                continue;
+            };
+            for object in object_list.children_by_field_name("object", &mut object_list.walk()) {
+                if object.kind() != "VAR" {
+                    continue;
+                }
+                let own = [subject, predicate, object];
+                let names: Vec<String> = own.iter().map(|var| variable_name(*var, text)).collect();
+                let bound_elsewhere = find_all(pattern, "VAR").into_iter().any(|var| {
+                    !own.iter().any(|own_var| own_var.id() == var.id())
+                        && names.contains(&variable_name(var, text))
+                });
+                if !bound_elsewhere {
+                    violations.push(LintViolation::new(
+                        document.range_between(subject, object),
+                        "This triple pattern matches every triple in the dataset, because none of its variables is bound elsewhere".to_string(),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::UnboundTriple;
+
+    #[test]
+    fn unbound_triple() {
+        let violations = check_rule(&UnboundTriple, "SELECT * {?s ?p ?o}");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].range.start.character, 10);
+        assert_eq!(violations[0].range.end.character, 18);
+    }
+
+    #[test]
+    fn bound_elsewhere() {
+        assert!(check_rule(&UnboundTriple, "SELECT * {?s ?p ?o . ?s a <Human>}").is_empty());
+        assert!(check_rule(&UnboundTriple, "SELECT * {?s ?p ?o ; a <Human>}").is_empty());
+        assert!(check_rule(&UnboundTriple, "SELECT * {?s ?p ?o VALUES ?p {<name>}}").is_empty());
+        assert!(check_rule(&UnboundTriple, "SELECT * {?s <p> ?o}").is_empty());
+    }
+}
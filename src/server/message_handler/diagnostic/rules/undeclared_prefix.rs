@@ -0,0 +1,45 @@
+use crate::{
+    analysis::{get_undeclared_prefixes, AnalysisState},
+    server::message_handler::diagnostic::{LintRule, LintViolation},
+};
+
+/// Reports prefixed names whose prefix was never declared.
+pub(super) struct UndeclaredPrefix;
+
+impl LintRule for UndeclaredPrefix {
+    fn name(&self) -> &'static str {
+        "undeclared_prefix"
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        get_undeclared_prefixes(state, uri)
+            .map(|(prefix, range)| {
+                LintViolation::new(
+                    range,
+                    format!("'{}' is used here, but was never declared", prefix),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::UndeclaredPrefix;
+
+    #[test]
+    fn undeclared_prefix() {
+        let violations = check_rule(
+            &UndeclaredPrefix,
+            "PREFIX wdt: <>\nSELECT * {?a wdt:P31 wd:Q5}",
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "'wd:' is used here, but was never declared"
+        );
+        assert_eq!(violations[0].range.start.line, 1);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    analysis::{get_unused_prefixes, AnalysisState},
+    server::message_handler::diagnostic::{LintRule, LintViolation},
+};
+
+/// Reports prefixes that are declared but never used.
+pub(super) struct UnusedPrefix;
+
+impl LintRule for UnusedPrefix {
+    fn name(&self) -> &'static str {
+        "unused_prefix"
+    }
+
+    fn check(&self, state: &AnalysisState, uri: &str) -> Vec<LintViolation> {
+        get_unused_prefixes(state, uri)
+            .map(|(prefix, range)| {
+                LintViolation::new(
+                    range,
+                    format!("'{}' is declared here, but was never used", prefix),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::server::message_handler::diagnostic::check_rule;
+
+    use super::UnusedPrefix;
+
+    #[test]
+    fn unused_prefix() {
+        let violations = check_rule(
+            &UnusedPrefix,
+            indoc!(
+                "PREFIX wd: <>
+                 PREFIX wdt: <>
+                 SELECT * {?a wdt:P31 ?b}"
+            ),
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "'wd:' is declared here, but was never used"
+        );
+        assert_eq!(violations[0].range.start.line, 0);
+    }
+}
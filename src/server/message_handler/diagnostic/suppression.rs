@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use tree_sitter::{Node, Tree};
+
+use crate::lsp::Diagnostic;
+
+const IGNORE: &str = "fichu-ignore";
+const IGNORE_FILE: &str = "fichu-ignore-file";
+
+/// The rules a suppression comment applies to.
+#[derive(Debug, Clone, PartialEq)]
+enum Scope {
+    All,
+    Rules(Vec<String>),
+}
+
+impl Scope {
+    fn parse(rest: &str) -> Option<Self> {
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Some(Scope::All);
+        }
+        let rules = rest.strip_prefix(':')?;
+        Some(Scope::Rules(
+            rules
+                .split(',')
+                .map(|rule| rule.trim().to_string())
+                .filter(|rule| !rule.is_empty())
+                .collect(),
+        ))
+    }
+
+    fn contains(&self, rule: &str) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Rules(rules) => rules.iter().any(|r| r == rule),
+        }
+    }
+}
+
+/// The suppression comments of a document.
+///
+/// `# fichu-ignore: rule_a, rule_b` suppresses the listed rules on the following line,
+/// `# fichu-ignore-file: rule_a` suppresses them in the whole document.
+/// Without a list of rules, all rules are suppressed.
+#[derive(Debug, Default)]
+pub(super) struct Suppressions {
+    file: Vec<Scope>,
+    lines: HashMap<usize, Vec<Scope>>,
+}
+
+impl Suppressions {
+    pub(super) fn from_tree(tree: &Tree, text: &str) -> Self {
+        let mut suppressions = Suppressions::default();
+        collect_comments(tree.root_node(), &mut |node| {
+            let comment = node
+                .utf8_text(text.as_bytes())
+                .unwrap_or("")
+                .trim_start_matches('#')
+                .trim();
+            if let Some(rest) = comment.strip_prefix(IGNORE_FILE) {
+                if let Some(scope) = Scope::parse(rest) {
+                    suppressions.file.push(scope);
+                }
+            } else if let Some(rest) = comment.strip_prefix(IGNORE) {
+                if let Some(scope) = Scope::parse(rest) {
+                    suppressions
+                        .lines
+                        .entry(node.end_position().row + 1)
+                        .or_default()
+                        .push(scope);
+                }
+            }
+        });
+        suppressions
+    }
+
+    pub(super) fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        let rule = diagnostic.code.as_deref().unwrap_or("");
+        let line = diagnostic.range.start.line as usize;
+        self.file.iter().any(|scope| scope.contains(rule))
+            || self
+                .lines
+                .get(&line)
+                .is_some_and(|scopes| scopes.iter().any(|scope| scope.contains(rule)))
+    }
+}
+
+fn collect_comments<'a>(node: Node<'a>, callback: &mut impl FnMut(Node<'a>)) {
+    if node.kind() == "comment" {
+        callback(node);
+    }
+    for child in node.children(&mut node.walk()) {
+        collect_comments(child, callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+
+    #[test]
+    fn parse_scope() {
+        assert_eq!(Scope::parse(""), Some(Scope::All));
+        assert_eq!(
+            Scope::parse(": unused_prefix,undeclared_prefix "),
+            Some(Scope::Rules(vec![
+                "unused_prefix".to_string(),
+                "undeclared_prefix".to_string()
+            ]))
+        );
+        assert_eq!(Scope::parse("-something-else"), None);
+    }
+}
@@ -0,0 +1,72 @@
+use tree_sitter::Node;
+
+use crate::{
+    analysis::AnalysisState,
+    lsp::{textdocument::TextDocumentItem, Diagnostic, DiagnosticSeverity},
+};
+
+pub(super) const SYNTAX_ERROR: &str = "syntax_error";
+
+/// Reports every `ERROR` and `MISSING` node of the document's syntax tree.
+pub(crate) fn syntax_errors(state: &AnalysisState, uri: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    if let Some((document, Some(tree))) = state.get_state(uri) {
+        collect_errors(tree.root_node(), document, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn collect_errors(node: Node, document: &TextDocumentItem, diagnostics: &mut Vec<Diagnostic>) {
+    if node.is_error() {
+        let snippet = node.utf8_text(document.text.as_bytes()).unwrap_or("");
+        diagnostics.push(syntax_error(
+            document,
+            node,
+            match snippet.lines().next() {
+                Some(line) if !line.trim().is_empty() => {
+                    format!("Syntax error: unexpected '{}'", line.trim())
+                }
+                _ => "Syntax error".to_string(),
+            },
+        ));
+    } else if node.is_missing() {
+        diagnostics.push(syntax_error(
+            document,
+            node,
+            format!("Syntax error: missing '{}'", node.kind()),
+        ));
+    } else if node.has_error() {
+        for child in node.children(&mut node.walk()) {
+            collect_errors(child, document, diagnostics);
+        }
+    }
+}
+
+fn syntax_error(document: &TextDocumentItem, node: Node, message: String) -> Diagnostic {
+    Diagnostic {
+        range: document.range(node),
+        severity: DiagnosticSeverity::Error,
+        code: Some(SYNTAX_ERROR.to_string()),
+        source: "fichu".to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::AnalysisState, lsp::textdocument::TextDocumentItem};
+
+    use super::syntax_errors;
+
+    #[test]
+    fn errors() {
+        let mut state = AnalysisState::new();
+        state.add_document(TextDocumentItem::new("uri", "SELECT * {?a ?b ?c"));
+        let diagnostics = syntax_errors(&state, "uri");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Syntax error: missing '}'");
+
+        state.add_document(TextDocumentItem::new("uri", "SELECT * {?a ?b ?c}"));
+        assert!(syntax_errors(&state, "uri").is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+use log::error;
+use tree_sitter::Node;
+
+use crate::{
+    lsp::{
+        textdocument::TextDocumentItem, DocumentSymbol, DocumentSymbolRequest,
+        DocumentSymbolResponse, SymbolKind,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+/// The maximal number of characters of a symbol name.
+const MAX_NAME_LENGTH: usize = 50;
+
+pub fn handle_document_symbol_request(
+    request: DocumentSymbolRequest,
+    state: &Snapshot,
+) -> Result<DocumentSymbolResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let symbols = match state.analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => document_symbols(tree.root_node(), document),
+        _ => {
+            error!("Requested document symbols for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(DocumentSymbolResponse::new(request.get_id(), symbols))
+}
+
+/// Builds the symbol tree of `node`.
+/// Nodes without a symbol of their own contribute the symbols of their children.
+pub(super) fn document_symbols(node: Node, document: &TextDocumentItem) -> Vec<DocumentSymbol> {
+    let text = &document.text;
+    let children = || {
+        node.children(&mut node.walk())
+            .flat_map(|child| document_symbols(child, document))
+            .collect::<Vec<DocumentSymbol>>()
+    };
+    let (name, detail, kind, selection_node) = match node.kind() {
+        "PrefixDecl" => (
+            text_of(node.child(1), text),
+            Some(text_of(node.child(2), text)),
+            SymbolKind::Namespace,
+            node.child(1),
+        ),
+        "BaseDecl" => (
+            "BASE".to_string(),
+            Some(text_of(node.child(1), text)),
+            SymbolKind::Namespace,
+            node.child(0),
+        ),
+        "SelectQuery" | "ConstructQuery" | "DescribeQuery" | "AskQuery" => (
+            format!(
+                "{} query",
+                text_of(Some(first_token(node)), text).to_uppercase()
+            ),
+            None,
+            SymbolKind::Class,
+            Some(first_token(node)),
+        ),
+        "SubSelect" => (
+            "sub-select".to_string(),
+            None,
+            SymbolKind::Class,
+            Some(first_token(node)),
+        ),
+        "Load" | "Clear" | "Drop" | "Add" | "Move" | "Copy" | "Create" | "InsertData"
+        | "DeleteData" | "DeleteWhere" => (
+            summary(node, text),
+            None,
+            SymbolKind::Function,
+            node.child(0),
+        ),
+        "Modify" => (
+            "DELETE/INSERT".to_string(),
+            None,
+            SymbolKind::Function,
+            node.child(0),
+        ),
+        "SelectClause" => (
+            summary(node, text),
+            Some("projection".to_string()),
+            SymbolKind::Array,
+            node.child(0),
+        ),
+        "VAR"
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "SelectClause") =>
+        {
+            (
+                text_of(Some(node), text),
+                None,
+                SymbolKind::Variable,
+                Some(node),
+            )
+        }
+        "assignment"
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "SelectClause") =>
+        {
+            let variable = node.child_by_field_name("bound_variable");
+            (
+                text_of(variable, text),
+                Some(summary(node, text)),
+                SymbolKind::Variable,
+                variable,
+            )
+        }
+        "WhereClause" => ("WHERE".to_string(), None, SymbolKind::Struct, node.child(0)),
+        "GroupGraphPattern"
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "Modify") =>
+        {
+            (
+                "WHERE".to_string(),
+                None,
+                SymbolKind::Struct,
+                node.prev_sibling(),
+            )
+        }
+        "DatasetClause" | "GroupClause" | "HavingClause" | "OrderClause" | "LimitClause"
+        | "OffsetClause" | "DeleteClause" | "InsertClause" | "UsingClause" => (
+            summary(node, text),
+            None,
+            SymbolKind::Property,
+            node.child(0),
+        ),
+        "OptionalGraphPattern"
+        | "MinusGraphPattern"
+        | "ServiceGraphPattern"
+        | "GraphGraphPattern" => (summary(node, text), None, SymbolKind::Struct, node.child(0)),
+        "GroupOrUnionGraphPattern" if node.child_count() > 1 => {
+            let branches = node
+                .children(&mut node.walk())
+                .filter(|child| child.kind() == "GroupGraphPattern")
+                .map(|branch| DocumentSymbol {
+                    name: "{ }".to_string(),
+                    detail: None,
+                    kind: SymbolKind::Object,
+                    range: document.range(branch),
+                    selection_range: document.range(branch),
+                    children: document_symbols(branch, document),
+                })
+                .collect();
+            let union = node
+                .children(&mut node.walk())
+                .find(|child| child.kind() == "UNION");
+            return vec![DocumentSymbol {
+                name: "UNION".to_string(),
+                detail: None,
+                kind: SymbolKind::Struct,
+                range: document.range(node),
+                selection_range: document.range(union.unwrap_or(node)),
+                children: branches,
+            }];
+        }
+        "Filter" | "Bind" | "InlineData" | "ValuesClause" => (
+            summary(node, text),
+            None,
+            SymbolKind::Operator,
+            node.child(0),
+        ),
+        _ => return children(),
+    };
+    vec![DocumentSymbol {
+        name,
+        detail,
+        kind,
+        range: document.range(node),
+        selection_range: document.range(selection_node.unwrap_or(node)),
+        children: children(),
+    }]
+}
+
+fn text_of(node: Option<Node>, text: &str) -> String {
+    node.and_then(|node| node.utf8_text(text.as_bytes()).ok())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// The first leaf of the node, i.e. the keyword a query starts with.
+fn first_token(node: Node) -> Node {
+    match node.child(0) {
+        Some(child) => first_token(child),
+        None => node,
+    }
+}
+
+/// The text of the node up to the first `{`, on a single line.
+fn summary(node: Node, text: &str) -> String {
+    let node_text = text_of(Some(node), text);
+    let head = node_text.split('{').next().unwrap_or("");
+    let summary = head.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match summary.chars().count() > MAX_NAME_LENGTH {
+        true => summary.chars().take(MAX_NAME_LENGTH).collect::<String>() + "…",
+        false if summary.is_empty() => node.kind().to_string(),
+        false => summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use tree_sitter::Parser;
+
+    use crate::lsp::{textdocument::TextDocumentItem, DocumentSymbol, SymbolKind};
+
+    use super::document_symbols;
+
+    fn outline(symbols: &[DocumentSymbol], depth: usize) -> String {
+        symbols
+            .iter()
+            .map(|symbol| {
+                format!("{}{}\n", "  ".repeat(depth), symbol.name)
+                    + &outline(&symbol.children, depth + 1)
+            })
+            .collect()
+    }
+
+    fn symbols(text: &str) -> Vec<DocumentSymbol> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        document_symbols(tree.root_node(), &TextDocumentItem::new("uri", text))
+    }
+
+    #[test]
+    fn select_query() {
+        let symbols = symbols(indoc!(
+            "PREFIX wd: <http://www.wikidata.org/entity/>
+             SELECT ?item (COUNT(?x) AS ?count) WHERE {
+               ?item ?p ?x .
+               OPTIONAL { ?x ?p ?y }
+               { ?a ?b ?c } UNION { SELECT * WHERE { ?a ?b ?c } }
+               SERVICE wikibase:label { ?a ?b ?c }
+               FILTER (?x > 1)
+             }
+             GROUP BY ?item
+             LIMIT 10"
+        ));
+        assert_eq!(
+            outline(&symbols, 0),
+            indoc!(
+                "wd:
+                 SELECT query
+                   SELECT ?item (COUNT(?x) AS ?count)
+                     ?item
+                     ?count
+                   WHERE
+                     OPTIONAL
+                     UNION
+                       { }
+                       { }
+                         sub-select
+                           SELECT *
+                           WHERE
+                     SERVICE wikibase:label
+                     FILTER (?x > 1)
+                   GROUP BY ?item
+                   LIMIT 10
+                 "
+            )
+        );
+        assert_eq!(symbols[0].kind, SymbolKind::Namespace);
+        assert_eq!(
+            symbols[0].detail.as_deref(),
+            Some("<http://www.wikidata.org/entity/>")
+        );
+        assert_eq!(symbols[1].range.start.line, 1);
+        assert_eq!(symbols[1].range.end.line, 9);
+    }
+
+    #[test]
+    fn update() {
+        let symbols = symbols(indoc!(
+            "INSERT DATA { <a> <b> <c> } ;
+             DELETE { ?a ?b ?c } WHERE { ?a ?b ?c }"
+        ));
+        assert_eq!(
+            outline(&symbols, 0),
+            indoc!(
+                "INSERT DATA
+                 DELETE/INSERT
+                   DELETE
+                   WHERE
+                 "
+            )
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use log::info;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    algebra::Query,
+    analysis::{is_update, query_prefixes},
+    endpoint::execute_query,
+    lsp::{textdocument::Range, ExecuteCommandRequest, ExecuteCommandResponse},
+    results::ResultsFormat,
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+/// The arguments of `fichu.executeQuery`.
+#[derive(Debug, Deserialize)]
+struct ExecuteQueryArguments {
+    uri: String,
+    /// Only the selected sub-query is executed.
+    range: Option<Range>,
+    #[serde(default)]
+    format: ResultsFormat,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteQueryResult {
+    endpoint: String,
+    content_type: String,
+    results: String,
+    /// How long the endpoint took, in milliseconds.
+    duration: u128,
+}
+
+/// The arguments of `fichu.showAlgebra`.
+#[derive(Debug, Deserialize)]
+struct ShowAlgebraArguments {
+    uri: String,
+    /// Only the algebra of the selected sub-query is shown.
+    range: Option<Range>,
+}
+
+#[derive(Debug, Serialize)]
+struct ShowAlgebraResult {
+    /// The algebra as S-expressions.
+    algebra: String,
+}
+
+pub(super) fn handle_execute_command_request(
+    request: ExecuteCommandRequest,
+    snapshot: &Snapshot,
+) -> Result<ExecuteCommandResponse, ResponseError> {
+    let result = match request.get_command() {
+        "fichu.executeQuery" => execute_query_command(request.get_arguments(), snapshot)?,
+        "fichu.showAlgebra" => show_algebra_command(request.get_arguments(), snapshot)?,
+        command => {
+            return Err(ResponseError::new(
+                ErrorCode::InvalidParams,
+                format!("Unknown command: {}", command),
+            ))
+        }
+    };
+    Ok(ExecuteCommandResponse::new(request.get_id(), result))
+}
+
+/// Deserializes the first argument of the `command`.
+fn parse_arguments<T: DeserializeOwned>(
+    arguments: &[Value],
+    command: &str,
+) -> Result<T, ResponseError> {
+    arguments
+        .first()
+        .cloned()
+        .ok_or_else(|| "missing arguments".to_string())
+        .and_then(|arguments| serde_json::from_value(arguments).map_err(|error| error.to_string()))
+        .map_err(|error| {
+            ResponseError::new(
+                ErrorCode::InvalidParams,
+                format!("Invalid arguments for {}: {}", command, error),
+            )
+        })
+}
+
+fn execute_query_command(arguments: &[Value], snapshot: &Snapshot) -> Result<Value, ResponseError> {
+    let arguments: ExecuteQueryArguments = parse_arguments(arguments, "fichu.executeQuery")?;
+    let endpoint = snapshot.settings.endpoint.as_ref().ok_or_else(|| {
+        ResponseError::new(
+            ErrorCode::RequestFailed,
+            "No endpoint is configured, add an [endpoint] section with an url to fichu.toml"
+                .to_string(),
+        )
+    })?;
+    let query = query_text(snapshot, &arguments.uri, arguments.range.as_ref())?;
+    // NOTE: Updates change the data of the endpoint, they are not run by accident.
+    if is_update(&query) {
+        return Err(ResponseError::new(
+            ErrorCode::InvalidParams,
+            format!(
+                "{} is an update, only queries can be executed",
+                arguments.uri
+            ),
+        ));
+    }
+    info!("Executing query of {} on {}", arguments.uri, endpoint.url);
+    let response = execute_query(
+        &endpoint.url,
+        &query,
+        arguments.format,
+        Duration::from_secs(endpoint.timeout),
+    )
+    .map_err(|error| ResponseError::new(ErrorCode::RequestFailed, error.to_string()))?;
+    Ok(serde_json::to_value(ExecuteQueryResult {
+        endpoint: endpoint.url.clone(),
+        content_type: response.content_type,
+        results: response.body,
+        duration: response.duration.as_millis(),
+    })
+    .expect("Results are serializable"))
+}
+
+fn show_algebra_command(arguments: &[Value], snapshot: &Snapshot) -> Result<Value, ResponseError> {
+    let arguments: ShowAlgebraArguments = parse_arguments(arguments, "fichu.showAlgebra")?;
+    let query = query_text(snapshot, &arguments.uri, arguments.range.as_ref())?;
+    let algebra = Query::parse(&query, Some(&arguments.uri))
+        .map_err(|error| ResponseError::new(ErrorCode::RequestFailed, error.to_string()))?;
+    Ok(serde_json::to_value(ShowAlgebraResult {
+        algebra: algebra.to_sse(&query_prefixes(&query)),
+    })
+    .expect("The algebra is serializable"))
+}
+
+/// The text of the document, or of the selected range.
+/// A selection after the prologue gets the prologue prepended, so its prefixes are declared.
+fn query_text(
+    snapshot: &Snapshot,
+    uri: &str,
+    range: Option<&Range>,
+) -> Result<String, ResponseError> {
+    let (document, tree) = snapshot.analysis_state.get_state(uri).ok_or_else(|| {
+        ResponseError::new(
+            ErrorCode::RequestFailed,
+            format!("Unknown document: {}", uri),
+        )
+    })?;
+    let range = match range {
+        Some(range) if range.start != range.end => range,
+        _ => return Ok(document.text.clone()),
+    };
+    let (start, end) = (document.offset(&range.start), document.offset(&range.end));
+    let selection = &document.text[start.min(end)..end.max(start)];
+    let prologue = tree.as_ref().and_then(|tree| {
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let prologue = root
+            .children(&mut cursor)
+            .find(|child| child.kind() == "Prologue" && child.end_byte() <= start.min(end));
+        prologue.map(|prologue| &document.text[prologue.byte_range()])
+    });
+    Ok(match prologue {
+        Some(prologue) => format!("{}\n{}", prologue, selection),
+        None => selection.to_string(),
+    })
+}
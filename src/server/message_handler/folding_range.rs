@@ -0,0 +1,153 @@
+use log::error;
+use tree_sitter::Node;
+
+use crate::{
+    analysis::find_all,
+    lsp::{
+        textdocument::TextDocumentItem, FoldingRange, FoldingRangeKind, FoldingRangeRequest,
+        FoldingRangeResponse,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+/// The kinds of nodes that are delimited by braces and can be folded.
+const FOLDABLE_KINDS: [&str; 4] = [
+    "GroupGraphPattern",
+    "ConstructTemplate",
+    "QuadData",
+    "DataBlock",
+];
+
+pub fn handle_folding_range_request(
+    request: FoldingRangeRequest,
+    state: &Snapshot,
+) -> Result<FoldingRangeResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let folding_ranges = match state.analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => folding_ranges(tree.root_node(), document),
+        _ => {
+            error!("Requested folding ranges for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(FoldingRangeResponse::new(request.get_id(), folding_ranges))
+}
+
+pub(super) fn folding_ranges(root: Node, document: &TextDocumentItem) -> Vec<FoldingRange> {
+    let mut folding_ranges: Vec<FoldingRange> = find_all(root, "Prologue")
+        .into_iter()
+        .filter_map(|prologue| folding_range(document, prologue, Some(FoldingRangeKind::Imports)))
+        .collect();
+    folding_ranges.extend(
+        FOLDABLE_KINDS
+            .iter()
+            .flat_map(|kind| find_all(root, kind))
+            .filter_map(|node| folding_range(document, node, None)),
+    );
+    folding_ranges.extend(comment_blocks(root, document));
+    folding_ranges.sort_by_key(|folding_range| (folding_range.start_line, folding_range.end_line));
+    folding_ranges
+}
+
+/// Returns a folding range for the node, if it spans multiple lines.
+fn folding_range(
+    document: &TextDocumentItem,
+    node: Node,
+    kind: Option<FoldingRangeKind>,
+) -> Option<FoldingRange> {
+    let range = document.range(node);
+    (range.start.line < range.end.line).then_some(FoldingRange {
+        start_line: range.start.line,
+        start_character: range.start.character,
+        end_line: range.end.line,
+        end_character: range.end.character,
+        kind,
+    })
+}
+
+/// Returns a folding range for each block of comments on consecutive lines.
+/// Only comments that are the first thing on their line are considered.
+fn comment_blocks(root: Node, document: &TextDocumentItem) -> Vec<FoldingRange> {
+    let lines: Vec<&str> = document.text.lines().collect();
+    let comments = find_all(root, "comment").into_iter().filter(|comment| {
+        let position = comment.start_position();
+        lines
+            .get(position.row)
+            .and_then(|line| line.get(..position.column))
+            .is_some_and(|before| before.trim().is_empty())
+    });
+    let mut blocks: Vec<(Node, Node)> = vec![];
+    for comment in comments {
+        match blocks.last_mut() {
+            Some((_first, last)) if last.end_position().row + 1 == comment.start_position().row => {
+                *last = comment
+            }
+            _ => blocks.push((comment, comment)),
+        }
+    }
+    blocks
+        .into_iter()
+        .filter(|(first, last)| first.start_position().row < last.end_position().row)
+        .map(|(first, last)| {
+            let range = document.range_between(first, last);
+            FoldingRange {
+                start_line: range.start.line,
+                start_character: range.start.character,
+                end_line: range.end.line,
+                end_character: range.end.character,
+                kind: Some(FoldingRangeKind::Comment),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use tree_sitter::Parser;
+
+    use crate::lsp::{textdocument::TextDocumentItem, FoldingRangeKind};
+
+    use super::folding_ranges;
+
+    #[test]
+    fn folding_ranges_of_query() {
+        let text = indoc!(
+            "PREFIX wd: <>
+             PREFIX wdt: <>
+             # This query
+             # does nothing
+             SELECT * WHERE {
+               ?a ?b ?c # trailing
+               # comment
+               OPTIONAL { ?a ?b ?c }
+               VALUES ?a {
+                 1 2
+               }
+             }"
+        );
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        let folding_ranges: Vec<(u32, u32, Option<FoldingRangeKind>)> =
+            folding_ranges(tree.root_node(), &TextDocumentItem::new("uri", text))
+                .into_iter()
+                .map(|range| (range.start_line, range.end_line, range.kind))
+                .collect();
+        assert_eq!(
+            folding_ranges,
+            vec![
+                (0, 1, Some(FoldingRangeKind::Imports)),
+                (2, 3, Some(FoldingRangeKind::Comment)),
+                (4, 11, None),
+                (8, 10, None),
+            ]
+        );
+    }
+}
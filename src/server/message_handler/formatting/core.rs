@@ -1,5 +1,4 @@
-use core::alloc;
-use std::{collections::HashSet, usize};
+use std::collections::HashSet;
 
 use log::{error, warn};
 use tree_sitter::{Node, Query, QueryCursor, Tree, TreeCursor};
@@ -9,7 +8,7 @@ use crate::{
         textdocument::{TextDocumentItem, TextEdit},
         FormattingOptions,
     },
-    server::configuration::FormatSettings,
+    server::configuration::{FormatSettings, SyntaxErrorPolicy},
 };
 
 use super::utils::KEYWORDS;
@@ -18,22 +17,62 @@ pub(super) fn format_textdoument(
     document: &TextDocumentItem,
     tree: &Tree,
     settings: &FormatSettings,
-    options: &FormattingOptions,
+    _options: &FormattingOptions,
 ) -> Vec<TextEdit> {
     let range = document.get_full_range();
-    let indent_string = match settings.insert_spaces {
-        true => " ".repeat(settings.tab_size as usize),
-        false => "\t".to_string(),
-    };
-    let text = format_helper(
-        &document.text,
+    let text = format_tree(&document.text, tree, settings);
+    vec![TextEdit::new(range, text)]
+}
+
+/// The formatted text of the whole `tree`.
+pub(super) fn format_tree(text: &String, tree: &Tree, settings: &FormatSettings) -> String {
+    format_helper(
+        text,
         &mut tree.walk(),
         0,
-        &indent_string,
+        &indent_base(settings),
         "",
         settings,
-    );
-    vec![TextEdit::new(range, text)]
+    )
+}
+
+/// The nodes whose children are indented one level deeper.
+const INDENTING_KINDS: [&str; 5] = [
+    "GroupGraphPattern",
+    "BrackettedExpression",
+    "ConstructTemplate",
+    "QuadData",
+    "QuadsNotTriples",
+];
+
+/// The formatted text of `node`, indented as deep as in the formatted document.
+pub(crate) fn format_node(text: &String, node: Node, settings: &FormatSettings) -> String {
+    let mut indentation = 0;
+    let mut ancestor = node.parent();
+    while let Some(node) = ancestor {
+        if INDENTING_KINDS.contains(&node.kind()) {
+            indentation += 1;
+        }
+        ancestor = node.parent();
+    }
+    format_helper(
+        text,
+        &mut node.walk(),
+        indentation,
+        &indent_base(settings),
+        "",
+        settings,
+    )
+    .replace("→", "")
+    .replace("←", "")
+}
+
+/// The string of one level of indentation.
+fn indent_base(settings: &FormatSettings) -> String {
+    match settings.insert_spaces {
+        true => " ".repeat(settings.tab_size as usize),
+        false => "\t".to_string(),
+    }
 }
 
 pub(super) fn format_helper(
@@ -47,15 +86,13 @@ pub(super) fn format_helper(
     let indent_str = &indent_base.repeat(indentation);
     let indent_str_small = match indentation {
         0 => String::new(),
-        i => (&indent_base).repeat(i - 1),
+        i => indent_base.repeat(i - 1),
     };
-    let line_break = "\n".to_string() + &indent_str;
+    let line_break = "\n".to_string() + indent_str;
     let line_break_small = "\n".to_string() + &indent_str_small;
 
     match cursor.node().kind() {
-        "unit" => separate_children_by(text, &cursor.node(), &line_break, 0, indent_base, settings)
-            .replace("→", "")
-            .replace("←", ""),
+        "unit" => format_top_level(text, &cursor.node(), indent_base, settings),
         "Update" => separate_children_by(text, &cursor.node(), " ", 0, indent_base, settings)
             .replace("; ", ";\n"),
         "Prologue" => {
@@ -340,20 +377,16 @@ pub(super) fn format_helper(
             .utf8_text(text.as_bytes())
             .unwrap()
             .to_string(),
-        // "ERROR" => {
-        //     cursor.node().child
-        //     if let Some(child) = cursor.node().child(0) {
-        //         format_helper(
-        //             text,
-        //             &mut child.walk(),
-        //             indentation,
-        //             indent_base,
-        //             extra_indent,
-        //         )
-        //     } else {
-        //         String::new()
-        //     }
-        // }
+        // NOTE: If nothing could be parsed, the root itself is an error.
+        "ERROR" if cursor.node().parent().is_none() => {
+            format_top_level(text, &cursor.node(), indent_base, settings)
+        }
+        // NOTE: Text the parser could not make sense of is kept as it is.
+        "ERROR" => cursor
+            .node()
+            .utf8_text(text.as_bytes())
+            .unwrap()
+            .to_string(),
         other => {
             warn!("found unknown node kind while formatting: {}", other);
             cursor
@@ -365,6 +398,52 @@ pub(super) fn format_helper(
     }
 }
 
+/// The nodes a query or update consists of.
+const TOP_LEVEL_KINDS: [&str; 8] = [
+    "Prologue",
+    "SelectQuery",
+    "ConstructQuery",
+    "DescribeQuery",
+    "AskQuery",
+    "ValuesClause",
+    "Update",
+    "comment",
+];
+
+/// Formats the top-level parts of `root`, one per line.
+/// Everything else is copied verbatim, formatting it could mangle it.
+/// With [`SyntaxErrorPolicy::Partial`] this includes the parts with syntax errors.
+fn format_top_level(
+    text: &String,
+    root: &Node,
+    indent_base: &str,
+    settings: &FormatSettings,
+) -> String {
+    let mut parts = vec![];
+    let mut verbatim: Option<(usize, usize)> = None;
+    for child in root.children(&mut root.walk()) {
+        if !TOP_LEVEL_KINDS.contains(&child.kind())
+            || (child.has_error() && settings.on_syntax_error == SyntaxErrorPolicy::Partial)
+        {
+            let start = verbatim.map_or(child.start_byte(), |(start, _end)| start);
+            verbatim = Some((start, child.end_byte()));
+            continue;
+        }
+        if let Some((start, end)) = verbatim.take() {
+            parts.push(text[start..end].to_string());
+        }
+        parts.push(
+            format_helper(text, &mut child.walk(), 0, indent_base, "", settings)
+                .replace("→", "")
+                .replace("←", ""),
+        );
+    }
+    if let Some((start, end)) = verbatim {
+        parts.push(text[start..end].to_string());
+    }
+    parts.join("\n")
+}
+
 fn separate_children_by(
     text: &String,
     node: &Node,
@@ -423,5 +502,5 @@ fn align_prefixes(mut formatted_string: String, node: &Node, text: &String) -> S
             "Query string to retrieve prefixes in invalid!\nIndentation of Prefixes was aborted."
         );
     }
-    return formatted_string;
+    formatted_string
 }
@@ -1,54 +1,146 @@
 mod core;
-mod utils;
+pub(super) mod utils;
+pub(super) use core::format_node;
 use core::*;
 use log::{error, info};
 
 use tree_sitter::Parser;
 use wasm_bindgen::prelude::wasm_bindgen;
 
+use std::fmt;
+
 use crate::{
-    lsp::{FormattingRequest, FormattingResponse},
+    analysis::AnalysisState,
+    lsp::{
+        textdocument::TextDocumentItem, Diagnostic, FormattingRequest, FormattingResponse,
+        PositionEncodingKind,
+    },
+    rpc::{ErrorCode, ResponseError},
     server::{
-        configuration::{FormatSettings, Settings},
-        ServerState,
+        configuration::{FormatSettings, SyntaxErrorPolicy},
+        Snapshot,
     },
 };
 
+use super::diagnostic::syntax_errors;
+
 pub fn handle_format_request(
     request: FormattingRequest,
-    state: &mut ServerState,
-    settings: &Settings,
-) -> FormattingResponse {
+    state: &Snapshot,
+) -> Result<FormattingResponse, ResponseError> {
     let uri = request.get_document_uri();
     info!("Received formatting request for: {}", uri);
     match state.analysis_state.get_state(uri) {
+        Some((_document, Some(tree)))
+            if tree.root_node().has_error()
+                && state.settings.format.on_syntax_error == SyntaxErrorPolicy::Refuse =>
+        {
+            let error = FormatError::SyntaxErrors(syntax_errors(&state.analysis_state, uri));
+            Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("The document was not formatted: {}", error),
+            ))
+        }
         Some((document, Some(tree))) => {
             let options = request.get_options();
-            let text_edits = format_textdoument(document, tree, &settings.format, options);
-            FormattingResponse::new(request.get_id(), text_edits)
+            let text_edits = format_textdoument(document, tree, &state.settings.format, options);
+            Ok(FormattingResponse::new(request.get_id(), text_edits))
         }
         _ => {
             error!("Requested formatting for unknown document: {}", uri);
-            todo!()
+            Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ))
         }
     }
 }
 
-#[wasm_bindgen]
-pub fn format_raw(text: String) -> String {
+/// Formats `text` with the given settings.
+pub(crate) fn format_text(text: &str, settings: &FormatSettings) -> String {
     let mut parser = Parser::new();
-    // TODO: look for user configuration
-    let format_settings = FormatSettings::default();
     match parser.set_language(&tree_sitter_sparql::language()) {
         Ok(()) => {
-            let tree = parser.parse(text.clone(), None).expect("could not parse");
-            let formatted_text =
-                format_helper(&text, &mut tree.walk(), 0, "  ", "", &format_settings);
-            return formatted_text;
+            let text = text.to_string();
+            let tree = parser.parse(&text, None).expect("could not parse");
+            format_tree(&text, &tree, settings)
         }
         Err(_) => panic!("Could not setup parser"),
     }
 }
 
+/// Formats `text` with the default settings, even if it has syntax errors.
+#[wasm_bindgen]
+pub fn format_raw(text: String) -> String {
+    format_text(&text, &FormatSettings::default())
+}
+
+/// Why a query was not formatted.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum FormatError {
+    /// The query is not valid SPARQL, formatting it could mangle it.
+    /// The columns of the errors count bytes.
+    SyntaxErrors(Vec<Diagnostic>),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::SyntaxErrors(errors) => match errors.first() {
+                Some(error) => write!(
+                    f,
+                    "{} at {}:{}",
+                    error.message,
+                    error.range.start.line + 1,
+                    error.range.start.character + 1
+                ),
+                None => write!(f, "Syntax error"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Formats the query.
+/// Queries with syntax errors are only formatted with [`SyntaxErrorPolicy::Partial`].
+///
+/// ```
+/// use fichu::{format, FormatError, FormatSettings, SyntaxErrorPolicy};
+///
+/// let settings = FormatSettings::builder().tab_size(4).build();
+/// assert_eq!(
+///     format("select * { ?s ?p ?o }", &settings).unwrap(),
+///     "SELECT * {\n    ?s ?p ?o\n}"
+/// );
+///
+/// let error = format("SELECT * {", &settings).unwrap_err();
+/// assert_eq!(error.to_string(), "Syntax error: missing '}' at 1:11");
+/// assert!(matches!(error, FormatError::SyntaxErrors(errors) if errors.len() == 1));
+///
+/// let settings = FormatSettings::builder()
+///     .on_syntax_error(SyntaxErrorPolicy::Partial)
+///     .build();
+/// assert_eq!(
+///     format("prefix a: <a>\nselect * {", &settings).unwrap(),
+///     "PREFIX a: <a>\nselect * {"
+/// );
+/// ```
+pub fn format(text: &str, settings: &FormatSettings) -> Result<String, FormatError> {
+    let mut state = AnalysisState::new();
+    state.position_encoding = PositionEncodingKind::Utf8;
+    state.add_document(TextDocumentItem::new("query", text));
+    match state.get_state("query") {
+        Some((document, Some(tree)))
+            if !tree.root_node().has_error()
+                || settings.on_syntax_error == SyntaxErrorPolicy::Partial =>
+        {
+            Ok(format_tree(&document.text, tree, settings))
+        }
+        _ => Err(FormatError::SyntaxErrors(syntax_errors(&state, "query"))),
+    }
+}
+
 #[cfg(test)]
 mod tests;
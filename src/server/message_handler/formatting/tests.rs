@@ -1,7 +1,10 @@
 use indoc::indoc;
 use tree_sitter::Parser;
 
-use crate::server::{configuration::FormatSettings, message_handler::formatting::format_helper};
+use crate::server::{
+    configuration::{FormatSettings, SyntaxErrorPolicy},
+    message_handler::formatting::{format, format_helper, format_text, FormatError},
+};
 
 fn format_and_compare(ugly_query: &str, pretty_query: &str) {
     let format_settings = FormatSettings::default();
@@ -554,3 +557,53 @@ fn function_like_keywords() {
     );
     format_and_compare(ugly_query, pretty_query)
 }
+
+#[test]
+fn format_with_settings() {
+    let settings = FormatSettings {
+        capitalize_keywords: false,
+        insert_spaces: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        format_text("select * where { ?a ?b ?c }", &settings),
+        "select * where {\n\t?a ?b ?c\n}"
+    );
+}
+
+#[test]
+fn refuse_syntax_errors() {
+    let error = format("SELECT * {", &FormatSettings::default()).unwrap_err();
+    let FormatError::SyntaxErrors(errors) = error;
+    assert_eq!(errors[0].message, "Syntax error: missing '}'");
+    assert_eq!(errors[0].range.start.character, 10);
+}
+
+#[test]
+fn format_partially() {
+    let settings = FormatSettings::builder()
+        .on_syntax_error(SyntaxErrorPolicy::Partial)
+        .build();
+    // The erroneous prefix declaration is copied verbatim, the query around it is formatted.
+    assert_eq!(
+        format(
+            "prefix a: <a> prefix b <b>\nselect * where {?a ?b ?c}",
+            &settings
+        )
+        .unwrap(),
+        "PREFIX a: <a>\nprefix b <b>\nSELECT * WHERE {\n  ?a ?b ?c\n}"
+    );
+    assert_eq!(
+        format(
+            "select * where {?a ?b ?c  FILTER( }\nvalues ?a {1}",
+            &settings
+        )
+        .unwrap(),
+        "select * where {?a ?b ?c  FILTER( }\nVALUES ?a { 1 }"
+    );
+    // If nothing could be parsed, the fragments keep their layout.
+    assert_eq!(
+        format("prefix a: <a>\nselect * {?a   ?b", &settings).unwrap(),
+        "PREFIX a: <a>\nselect * {?a   ?b"
+    );
+}
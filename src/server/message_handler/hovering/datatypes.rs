@@ -0,0 +1,219 @@
+//! Documentation of the XSD datatypes that are used in RDF literals.
+
+pub(super) const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+pub(super) struct Datatype {
+    /// The local name in the XSD namespace.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+}
+
+impl Datatype {
+    pub fn link(&self) -> String {
+        format!("https://www.w3.org/TR/xmlschema11-2/#{}", self.name)
+    }
+}
+
+const fn datatype(
+    name: &'static str,
+    description: &'static str,
+    example: &'static str,
+) -> Datatype {
+    Datatype {
+        name,
+        description,
+        example,
+    }
+}
+
+pub(super) const DATATYPES: &[Datatype] = &[
+    datatype("string", "A character string.", "\"hello\"^^xsd:string"),
+    datatype(
+        "boolean",
+        "`true` or `false` (also `1` or `0`).",
+        "\"true\"^^xsd:boolean",
+    ),
+    datatype(
+        "decimal",
+        "An arbitrary precision decimal number.",
+        "\"3.14\"^^xsd:decimal",
+    ),
+    datatype(
+        "integer",
+        "An arbitrary size integer.",
+        "\"42\"^^xsd:integer",
+    ),
+    datatype(
+        "double",
+        "A 64 bit IEEE 754 floating point number.",
+        "\"1.0e6\"^^xsd:double",
+    ),
+    datatype(
+        "float",
+        "A 32 bit IEEE 754 floating point number.",
+        "\"1.5\"^^xsd:float",
+    ),
+    datatype(
+        "date",
+        "A calendar date, with an optional timezone.",
+        "\"2024-02-29\"^^xsd:date",
+    ),
+    datatype(
+        "time",
+        "A time of day, with an optional timezone.",
+        "\"13:37:00\"^^xsd:time",
+    ),
+    datatype(
+        "dateTime",
+        "A date and time of day, with an optional timezone.",
+        "\"2024-02-29T13:37:00Z\"^^xsd:dateTime",
+    ),
+    datatype(
+        "dateTimeStamp",
+        "A date and time of day with a required timezone.",
+        "\"2024-02-29T13:37:00+01:00\"^^xsd:dateTimeStamp",
+    ),
+    datatype(
+        "duration",
+        "A duration of years, months, days, hours, minutes and seconds.",
+        "\"P1Y2M3DT4H\"^^xsd:duration",
+    ),
+    datatype(
+        "dayTimeDuration",
+        "A duration of days, hours, minutes and seconds.",
+        "\"PT2H30M\"^^xsd:dayTimeDuration",
+    ),
+    datatype(
+        "yearMonthDuration",
+        "A duration of years and months.",
+        "\"P1Y6M\"^^xsd:yearMonthDuration",
+    ),
+    datatype(
+        "gYear",
+        "A year of the Gregorian calendar.",
+        "\"2024\"^^xsd:gYear",
+    ),
+    datatype(
+        "gYearMonth",
+        "A month of a year of the Gregorian calendar.",
+        "\"2024-02\"^^xsd:gYearMonth",
+    ),
+    datatype(
+        "gMonth",
+        "A month of the Gregorian calendar that recurs every year.",
+        "\"--02\"^^xsd:gMonth",
+    ),
+    datatype(
+        "gMonthDay",
+        "A day of the Gregorian calendar that recurs every year.",
+        "\"--02-29\"^^xsd:gMonthDay",
+    ),
+    datatype(
+        "gDay",
+        "A day of the month that recurs every month.",
+        "\"---29\"^^xsd:gDay",
+    ),
+    datatype(
+        "hexBinary",
+        "Hex encoded binary data.",
+        "\"CAFE\"^^xsd:hexBinary",
+    ),
+    datatype(
+        "base64Binary",
+        "Base64 encoded binary data.",
+        "\"SGVsbG8=\"^^xsd:base64Binary",
+    ),
+    datatype(
+        "anyURI",
+        "A URI reference.",
+        "\"http://example.org/\"^^xsd:anyURI",
+    ),
+    datatype(
+        "language",
+        "A BCP 47 language tag.",
+        "\"en-US\"^^xsd:language",
+    ),
+    datatype(
+        "normalizedString",
+        "A string without carriage returns, line feeds and tabs.",
+        "\"hello world\"^^xsd:normalizedString",
+    ),
+    datatype(
+        "token",
+        "A normalized string without leading, trailing or double spaces.",
+        "\"hello world\"^^xsd:token",
+    ),
+    datatype("NMTOKEN", "An XML name token.", "\"name-1\"^^xsd:NMTOKEN"),
+    datatype("Name", "An XML name.", "\"name\"^^xsd:Name"),
+    datatype(
+        "NCName",
+        "An XML name without colons.",
+        "\"name\"^^xsd:NCName",
+    ),
+    datatype(
+        "long",
+        "An integer between -2^63 and 2^63 - 1.",
+        "\"9000000000\"^^xsd:long",
+    ),
+    datatype(
+        "int",
+        "An integer between -2^31 and 2^31 - 1.",
+        "\"42\"^^xsd:int",
+    ),
+    datatype(
+        "short",
+        "An integer between -32768 and 32767.",
+        "\"42\"^^xsd:short",
+    ),
+    datatype(
+        "byte",
+        "An integer between -128 and 127.",
+        "\"42\"^^xsd:byte",
+    ),
+    datatype(
+        "nonNegativeInteger",
+        "An integer that is 0 or larger.",
+        "\"0\"^^xsd:nonNegativeInteger",
+    ),
+    datatype(
+        "positiveInteger",
+        "An integer that is 1 or larger.",
+        "\"1\"^^xsd:positiveInteger",
+    ),
+    datatype(
+        "nonPositiveInteger",
+        "An integer that is 0 or smaller.",
+        "\"0\"^^xsd:nonPositiveInteger",
+    ),
+    datatype(
+        "negativeInteger",
+        "An integer that is -1 or smaller.",
+        "\"-1\"^^xsd:negativeInteger",
+    ),
+    datatype(
+        "unsignedLong",
+        "An integer between 0 and 2^64 - 1.",
+        "\"42\"^^xsd:unsignedLong",
+    ),
+    datatype(
+        "unsignedInt",
+        "An integer between 0 and 2^32 - 1.",
+        "\"42\"^^xsd:unsignedInt",
+    ),
+    datatype(
+        "unsignedShort",
+        "An integer between 0 and 65535.",
+        "\"42\"^^xsd:unsignedShort",
+    ),
+    datatype(
+        "unsignedByte",
+        "An integer between 0 and 255.",
+        "\"42\"^^xsd:unsignedByte",
+    ),
+];
+
+/// Finds a datatype by its local name in the XSD namespace.
+pub(super) fn find(name: &str) -> Option<&'static Datatype> {
+    DATATYPES.iter().find(|datatype| datatype.name == name)
+}
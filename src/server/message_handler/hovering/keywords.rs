@@ -0,0 +1,443 @@
+//! Documentation of the SPARQL 1.1 keywords that are not builtin functions.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Specification {
+    Query,
+    Update,
+    FederatedQuery,
+}
+
+impl Specification {
+    fn url(&self) -> &'static str {
+        match self {
+            Specification::Query => "https://www.w3.org/TR/sparql11-query/",
+            Specification::Update => "https://www.w3.org/TR/sparql11-update/",
+            Specification::FederatedQuery => "https://www.w3.org/TR/sparql11-federated-query/",
+        }
+    }
+}
+
+pub(super) struct Keyword {
+    pub name: &'static str,
+    /// The syntax of the construct the keyword introduces.
+    pub syntax: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+    specification: Specification,
+    anchor: &'static str,
+}
+
+impl Keyword {
+    pub fn link(&self) -> String {
+        format!("{}#{}", self.specification.url(), self.anchor)
+    }
+}
+
+const fn query(
+    name: &'static str,
+    syntax: &'static str,
+    description: &'static str,
+    example: &'static str,
+    anchor: &'static str,
+) -> Keyword {
+    Keyword {
+        name,
+        syntax,
+        description,
+        example,
+        specification: Specification::Query,
+        anchor,
+    }
+}
+
+const fn update(
+    name: &'static str,
+    syntax: &'static str,
+    description: &'static str,
+    example: &'static str,
+    anchor: &'static str,
+) -> Keyword {
+    Keyword {
+        specification: Specification::Update,
+        ..query(name, syntax, description, example, anchor)
+    }
+}
+
+const fn federated(
+    name: &'static str,
+    syntax: &'static str,
+    description: &'static str,
+    example: &'static str,
+    anchor: &'static str,
+) -> Keyword {
+    Keyword {
+        specification: Specification::FederatedQuery,
+        ..query(name, syntax, description, example, anchor)
+    }
+}
+
+pub(super) const KEYWORDS: &[Keyword] = &[
+    // Prologue
+    query(
+        "BASE",
+        "BASE <iri>",
+        "Sets the base IRI against which relative IRIs in the query are resolved.",
+        "BASE <http://example.org/>\nSELECT * WHERE { <alice> ?p ?o }",
+        "relIRIs",
+    ),
+    query(
+        "PREFIX",
+        "PREFIX prefix: <iri>",
+        "Defines a namespace prefix to simplify the use of IRIs in the query.",
+        "PREFIX foaf: <http://xmlns.com/foaf/0.1/>\nSELECT ?name WHERE { ?person foaf:name ?name }",
+        "prefNames",
+    ),
+    // Query forms
+    query(
+        "SELECT",
+        "SELECT [DISTINCT | REDUCED] (?var | (expression AS ?var))+ | *",
+        "Returns the bindings of the projected variables, as a table of solutions.",
+        "SELECT ?name WHERE { ?person foaf:name ?name }",
+        "select",
+    ),
+    query(
+        "CONSTRUCT",
+        "CONSTRUCT { template } WHERE { pattern }",
+        "Returns an RDF graph that is built by instantiating the template with each solution.",
+        "CONSTRUCT { ?person foaf:name ?name } WHERE { ?person vcard:FN ?name }",
+        "construct",
+    ),
+    query(
+        "DESCRIBE",
+        "DESCRIBE (?var | iri)+ | * [WHERE { pattern }]",
+        "Returns an RDF graph that describes the resources. The description is chosen by the endpoint.",
+        "DESCRIBE ?person WHERE { ?person foaf:name \"Alice\" }",
+        "describe",
+    ),
+    query(
+        "ASK",
+        "ASK { pattern }",
+        "Returns `true` if the pattern has at least one solution, `false` otherwise.",
+        "ASK { ?person foaf:name \"Alice\" }",
+        "ask",
+    ),
+    query(
+        "DISTINCT",
+        "SELECT DISTINCT ...",
+        "Removes duplicate solutions from the result.",
+        "SELECT DISTINCT ?type WHERE { ?s a ?type }",
+        "modDistinct",
+    ),
+    query(
+        "REDUCED",
+        "SELECT REDUCED ...",
+        "Permits, but does not require, the elimination of duplicate solutions.",
+        "SELECT REDUCED ?type WHERE { ?s a ?type }",
+        "modReduced",
+    ),
+    query(
+        "AS",
+        "(expression AS ?var)",
+        "Binds the value of an expression to a new variable, in a projection or a `BIND`.",
+        "SELECT (COUNT(?s) AS ?count) WHERE { ?s ?p ?o }",
+        "selectExpressions",
+    ),
+    // Dataset
+    query(
+        "FROM",
+        "FROM [NAMED] <iri>",
+        "Adds a graph to the default graph of the dataset, or a named graph with `FROM NAMED`.",
+        "SELECT * FROM <http://example.org/graph> WHERE { ?s ?p ?o }",
+        "specifyingDataset",
+    ),
+    query(
+        "NAMED",
+        "FROM NAMED <iri> | USING NAMED <iri> | CLEAR NAMED",
+        "Refers to named graphs, as opposed to the default graph.",
+        "SELECT * FROM NAMED <http://example.org/graph> WHERE { GRAPH ?g { ?s ?p ?o } }",
+        "specifyingDataset",
+    ),
+    // Graph patterns
+    query(
+        "WHERE",
+        "WHERE { pattern }",
+        "Introduces the graph pattern that is matched against the dataset.",
+        "SELECT * WHERE { ?s ?p ?o }",
+        "GraphPattern",
+    ),
+    query(
+        "a",
+        "?subject a ?class",
+        "Abbreviation of the IRI `rdf:type`.",
+        "SELECT ?person WHERE { ?person a foaf:Person }",
+        "abbrevRdfType",
+    ),
+    query(
+        "OPTIONAL",
+        "OPTIONAL { pattern }",
+        "Extends the solutions with the pattern where possible, but keeps the solutions where it does not match.",
+        "SELECT * WHERE { ?person foaf:name ?name OPTIONAL { ?person foaf:mbox ?mbox } }",
+        "optionals",
+    ),
+    query(
+        "UNION",
+        "{ pattern } UNION { pattern }",
+        "Combines the solutions of alternative graph patterns.",
+        "SELECT * WHERE { { ?s dc10:title ?title } UNION { ?s dc11:title ?title } }",
+        "alternatives",
+    ),
+    query(
+        "MINUS",
+        "MINUS { pattern }",
+        "Removes the solutions that are compatible with a solution of the pattern.",
+        "SELECT * WHERE { ?s a foaf:Person MINUS { ?s foaf:givenName \"Bob\" } }",
+        "neg-minus",
+    ),
+    query(
+        "GRAPH",
+        "GRAPH (?var | <iri>) { pattern }",
+        "Matches the pattern against a named graph of the dataset.",
+        "SELECT ?g ?name WHERE { GRAPH ?g { ?person foaf:name ?name } }",
+        "queryDataset",
+    ),
+    federated(
+        "SERVICE",
+        "SERVICE [SILENT] (?var | <iri>) { pattern }",
+        "Evaluates the pattern at a remote SPARQL endpoint.",
+        "SELECT * WHERE { SERVICE <https://query.wikidata.org/sparql> { ?s ?p ?o } }",
+        "introduction",
+    ),
+    federated(
+        "SILENT",
+        "SERVICE SILENT ... | LOAD SILENT ... | DROP SILENT ...",
+        "Ignores errors of a remote service or an update operation.",
+        "SELECT * WHERE { SERVICE SILENT <http://example.org/sparql> { ?s ?p ?o } }",
+        "serviceFailure",
+    ),
+    query(
+        "FILTER",
+        "FILTER (expression)",
+        "Restricts the solutions of the group to those for which the expression is `true`.",
+        "SELECT ?name WHERE { ?person foaf:name ?name ; foaf:age ?age FILTER (?age > 20) }",
+        "expressions",
+    ),
+    query(
+        "BIND",
+        "BIND (expression AS ?var)",
+        "Assigns the value of an expression to a variable that is not bound yet.",
+        "SELECT * WHERE { ?book ex:price ?price BIND (?price * 1.19 AS ?gross) }",
+        "bind",
+    ),
+    query(
+        "VALUES",
+        "VALUES ?var { value* } | VALUES (?var*) { (value*)* }",
+        "Provides inline data that is joined with the solutions.",
+        "SELECT * WHERE { VALUES ?city { \"Berlin\" \"Paris\" } ?place ex:city ?city }",
+        "inline-data",
+    ),
+    query(
+        "EXISTS",
+        "EXISTS { pattern }",
+        "Returns `true` if the pattern matches with the current solution.",
+        "SELECT * WHERE { ?person a foaf:Person FILTER EXISTS { ?person foaf:name ?name } }",
+        "func-filter-exists",
+    ),
+    query(
+        "NOT",
+        "NOT EXISTS { pattern } | expression NOT IN (expression, ...)",
+        "Negates `EXISTS` or `IN`.",
+        "SELECT * WHERE { ?person a foaf:Person FILTER NOT EXISTS { ?person foaf:name ?name } }",
+        "func-filter-exists",
+    ),
+    query(
+        "IN",
+        "expression [NOT] IN (expression, ...)",
+        "Returns `true` if the value is equal to one of the listed values.",
+        "SELECT * WHERE { ?s ex:color ?color FILTER (?color IN (\"red\", \"blue\")) }",
+        "func-in",
+    ),
+    query(
+        "SEPARATOR",
+        "GROUP_CONCAT(expression; SEPARATOR = \"string\")",
+        "Sets the string that `GROUP_CONCAT` puts between the values.",
+        "SELECT (GROUP_CONCAT(?name; SEPARATOR = \", \") AS ?names) WHERE { ?s foaf:name ?name }",
+        "defn_aggGroupConcat",
+    ),
+    // Solution modifiers
+    query(
+        "GROUP",
+        "GROUP BY (?var | expression)+",
+        "Partitions the solutions into groups, which can be aggregated.",
+        "SELECT ?type (COUNT(?s) AS ?count) WHERE { ?s a ?type } GROUP BY ?type",
+        "groupby",
+    ),
+    query(
+        "BY",
+        "GROUP BY ... | ORDER BY ...",
+        "Introduces the conditions of a `GROUP BY` or an `ORDER BY` clause.",
+        "SELECT * WHERE { ?s foaf:name ?name } ORDER BY ?name",
+        "groupby",
+    ),
+    query(
+        "HAVING",
+        "HAVING (expression)",
+        "Filters groups, after the aggregates are computed.",
+        "SELECT ?type WHERE { ?s a ?type } GROUP BY ?type HAVING (COUNT(?s) > 10)",
+        "having",
+    ),
+    query(
+        "ORDER",
+        "ORDER BY (ASC(expression) | DESC(expression) | expression)+",
+        "Sorts the solutions.",
+        "SELECT * WHERE { ?s foaf:name ?name } ORDER BY DESC(?name)",
+        "modOrderBy",
+    ),
+    query(
+        "ASC",
+        "ORDER BY ASC(expression)",
+        "Sorts the solutions in ascending order, which is the default.",
+        "SELECT * WHERE { ?s foaf:age ?age } ORDER BY ASC(?age)",
+        "modOrderBy",
+    ),
+    query(
+        "DESC",
+        "ORDER BY DESC(expression)",
+        "Sorts the solutions in descending order.",
+        "SELECT * WHERE { ?s foaf:age ?age } ORDER BY DESC(?age)",
+        "modOrderBy",
+    ),
+    query(
+        "LIMIT",
+        "LIMIT integer",
+        "Restricts the number of returned solutions.",
+        "SELECT * WHERE { ?s ?p ?o } LIMIT 10",
+        "modResultLimit",
+    ),
+    query(
+        "OFFSET",
+        "OFFSET integer",
+        "Skips the given number of solutions. Usually combined with `ORDER BY`.",
+        "SELECT * WHERE { ?s ?p ?o } ORDER BY ?s LIMIT 10 OFFSET 20",
+        "modOffset",
+    ),
+    // Update
+    update(
+        "LOAD",
+        "LOAD [SILENT] <document> [INTO GRAPH <graph>]",
+        "Reads an RDF document and inserts its triples into a graph.",
+        "LOAD <http://example.org/data.ttl> INTO GRAPH <http://example.org/graph>",
+        "load",
+    ),
+    update(
+        "INTO",
+        "LOAD <document> INTO GRAPH <graph>",
+        "Sets the graph that `LOAD` inserts into.",
+        "LOAD <http://example.org/data.ttl> INTO GRAPH <http://example.org/graph>",
+        "load",
+    ),
+    update(
+        "CLEAR",
+        "CLEAR [SILENT] (GRAPH <graph> | DEFAULT | NAMED | ALL)",
+        "Removes all triples from the graphs, but keeps the graphs.",
+        "CLEAR GRAPH <http://example.org/graph>",
+        "clear",
+    ),
+    update(
+        "DROP",
+        "DROP [SILENT] (GRAPH <graph> | DEFAULT | NAMED | ALL)",
+        "Removes the graphs and their triples.",
+        "DROP GRAPH <http://example.org/graph>",
+        "drop",
+    ),
+    update(
+        "CREATE",
+        "CREATE [SILENT] GRAPH <graph>",
+        "Creates a new, empty graph.",
+        "CREATE GRAPH <http://example.org/graph>",
+        "create",
+    ),
+    update(
+        "ADD",
+        "ADD [SILENT] (GRAPH <from> | DEFAULT) TO (GRAPH <to> | DEFAULT)",
+        "Inserts all triples of one graph into another graph.",
+        "ADD GRAPH <http://example.org/a> TO DEFAULT",
+        "add",
+    ),
+    update(
+        "MOVE",
+        "MOVE [SILENT] (GRAPH <from> | DEFAULT) TO (GRAPH <to> | DEFAULT)",
+        "Moves all triples of one graph into another graph, replacing its content, and removes the source graph.",
+        "MOVE GRAPH <http://example.org/a> TO GRAPH <http://example.org/b>",
+        "move",
+    ),
+    update(
+        "COPY",
+        "COPY [SILENT] (GRAPH <from> | DEFAULT) TO (GRAPH <to> | DEFAULT)",
+        "Copies all triples of one graph into another graph, replacing its content.",
+        "COPY DEFAULT TO GRAPH <http://example.org/backup>",
+        "copy",
+    ),
+    update(
+        "TO",
+        "ADD ... TO ... | MOVE ... TO ... | COPY ... TO ...",
+        "Sets the target graph of `ADD`, `MOVE` and `COPY`.",
+        "COPY DEFAULT TO GRAPH <http://example.org/backup>",
+        "add",
+    ),
+    update(
+        "DEFAULT",
+        "CLEAR DEFAULT | DROP DEFAULT | ... TO DEFAULT",
+        "Refers to the default graph.",
+        "CLEAR DEFAULT",
+        "graphManagement",
+    ),
+    update(
+        "ALL",
+        "CLEAR ALL | DROP ALL",
+        "Refers to the default graph and all named graphs.",
+        "DROP ALL",
+        "graphManagement",
+    ),
+    update(
+        "INSERT",
+        "INSERT DATA { triples } | [DELETE { template }] INSERT { template } WHERE { pattern }",
+        "Adds triples to the graph store, either given directly or built from the solutions of a pattern.",
+        "INSERT { ?person foaf:knows ?friend } WHERE { ?friend foaf:knows ?person }",
+        "deleteInsert",
+    ),
+    update(
+        "DELETE",
+        "DELETE DATA { triples } | DELETE WHERE { pattern } | DELETE { template } [INSERT { template }] WHERE { pattern }",
+        "Removes triples from the graph store, either given directly or built from the solutions of a pattern.",
+        "DELETE { ?person foaf:age ?age } WHERE { ?person foaf:age ?age FILTER (?age < 0) }",
+        "deleteInsert",
+    ),
+    update(
+        "DATA",
+        "INSERT DATA { triples } | DELETE DATA { triples }",
+        "Inserts or deletes concrete triples, without a `WHERE` clause. Variables are not allowed.",
+        "INSERT DATA { <http://example.org/alice> foaf:name \"Alice\" }",
+        "insertData",
+    ),
+    update(
+        "WITH",
+        "WITH <graph> DELETE ... INSERT ... WHERE ...",
+        "Sets the graph that the templates and the pattern of an update refer to by default.",
+        "WITH <http://example.org/graph> DELETE { ?s ?p ?o } WHERE { ?s ?p ?o }",
+        "deleteInsert",
+    ),
+    update(
+        "USING",
+        "USING [NAMED] <graph>",
+        "Sets the dataset that the `WHERE` clause of an update is matched against.",
+        "DELETE { ?s ?p ?o } USING <http://example.org/graph> WHERE { ?s ?p ?o }",
+        "deleteInsert",
+    ),
+];
+
+/// Finds a keyword by its (case insensitive) name.
+pub(super) fn find(name: &str) -> Option<&'static Keyword> {
+    KEYWORDS
+        .iter()
+        .find(|keyword| keyword.name.eq_ignore_ascii_case(name))
+}
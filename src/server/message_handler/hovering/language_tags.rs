@@ -0,0 +1,76 @@
+//! Descriptions of BCP 47 language tags, as used in `"chat"@fr`.
+
+/// Names of common ISO 639-1 language codes.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("bg", "Bulgarian"),
+    ("ca", "Catalan"),
+    ("cs", "Czech"),
+    ("cy", "Welsh"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("eo", "Esperanto"),
+    ("es", "Spanish"),
+    ("et", "Estonian"),
+    ("eu", "Basque"),
+    ("fa", "Persian"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("ga", "Irish"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hr", "Croatian"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("is", "Icelandic"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("la", "Latin"),
+    ("lt", "Lithuanian"),
+    ("lv", "Latvian"),
+    ("nb", "Norwegian Bokmål"),
+    ("nl", "Dutch"),
+    ("nn", "Norwegian Nynorsk"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sk", "Slovak"),
+    ("sl", "Slovenian"),
+    ("sr", "Serbian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+/// Describes the subtags of a language tag like `@en-US` or `@zh-Hant`.
+pub(super) fn describe(tag: &str) -> String {
+    let tag = tag.trim_start_matches('@');
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or("");
+    let mut description = format!(
+        "### Language tag `{}`\n\n- **Language:** {}",
+        tag,
+        LANGUAGES
+            .iter()
+            .find(|(code, _name)| code.eq_ignore_ascii_case(language))
+            .map_or(format!("`{}`", language), |(_code, name)| name.to_string())
+    );
+    for subtag in subtags {
+        let kind = match subtag.len() {
+            4 if subtag.chars().all(|char| char.is_ascii_alphabetic()) => "Script",
+            2 if subtag.chars().all(|char| char.is_ascii_alphabetic()) => "Region",
+            3 if subtag.chars().all(|char| char.is_ascii_digit()) => "Region",
+            _ => "Variant",
+        };
+        description += &format!("\n- **{}:** `{}`", kind, subtag);
+    }
+    description + "\n\n[BCP 47](https://www.rfc-editor.org/info/bcp47)"
+}
@@ -0,0 +1,282 @@
+mod datatypes;
+mod keywords;
+mod language_tags;
+
+use log::error;
+use tree_sitter::{Node, Tree};
+
+use crate::{
+    analysis::{prefix_declarations, AnalysisState},
+    lsp::{
+        textdocument::{Position, TextDocumentItem},
+        HoverRequest, HoverResponse, Markupkind,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+use super::builtins::{self, Builtin};
+use datatypes::{Datatype, XSD};
+use keywords::Keyword;
+
+pub fn handle_hover_request(
+    request: &HoverRequest,
+    state: &Snapshot,
+) -> Result<HoverResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let documentation = match state.analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => documentation_at(document, tree, request.get_position()),
+        _ => {
+            error!("Requested hover for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(match documentation {
+        Some(documentation) if state.client_capabilities.markdown_support() => {
+            HoverResponse::new(request.get_id(), documentation, Markupkind::Markdown)
+        }
+        Some(documentation) => HoverResponse::new(
+            request.get_id(),
+            plain_text(&documentation),
+            Markupkind::Plaintext,
+        ),
+        None => HoverResponse::empty(request.get_id()),
+    })
+}
+
+/// The markdown documentation of the token at `position` of `text`, if there is any.
+pub(crate) fn hover(text: &str, position: &Position) -> Option<String> {
+    let mut state = AnalysisState::new();
+    state.add_document(TextDocumentItem::new("query", text));
+    match state.get_state("query")? {
+        (document, Some(tree)) => documentation_at(document, tree, position),
+        (_document, None) => None,
+    }
+}
+
+fn documentation_at(
+    document: &TextDocumentItem,
+    tree: &Tree,
+    position: &Position,
+) -> Option<String> {
+    let point = document.point(position);
+    tree.root_node()
+        .descendant_for_point_range(point, point)
+        .and_then(|node| documentation(node, &document.text))
+}
+
+/// Strips the markdown syntax of the documentation for clients without markdown support.
+fn plain_text(markdown: &str) -> String {
+    markdown
+        .lines()
+        .filter(|line| !line.starts_with("```"))
+        .map(|line| {
+            let line = line
+                .trim_start_matches("### ")
+                .replace('`', "")
+                .replace("**", "");
+            // Turn links like `[Specification](https://...)` into `Specification: https://...`.
+            match (line.strip_prefix('['), line.find("](")) {
+                (Some(_), Some(middle)) if line.ends_with(')') => {
+                    format!(
+                        "{}: {}",
+                        &line[1..middle],
+                        &line[middle + 2..line.len() - 1]
+                    )
+                }
+                _ => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The markdown documentation of the token `node`, if there is any.
+fn documentation(node: Node, text: &str) -> Option<String> {
+    match node.kind() {
+        "LANGTAG" => Some(language_tags::describe(
+            node.utf8_text(text.as_bytes()).ok()?,
+        )),
+        "PN_PREFIX" | ":" | "PNAME_NS" | "PN_LOCAL" | "PrefixedName" | "IRIREF" => {
+            let iri = match node.kind() {
+                "IRIREF" | "PrefixedName" => node,
+                _ => ancestor(node, "PrefixedName")?,
+            };
+            let iri_text = iri.utf8_text(text.as_bytes()).ok()?;
+            match iri.parent()?.kind() {
+                "RdfLiteral" => datatypes::find(xsd_local_name(iri, text)?)
+                    .map(|datatype| datatype_documentation(datatype, iri_text)),
+                "FunctionCall" => builtins::find_cast(iri_text).map(builtin_documentation),
+                _ => None,
+            }
+        }
+        kind if !node.is_named() => builtins::find(kind)
+            .map(builtin_documentation)
+            .or_else(|| keywords::find(kind).map(keyword_documentation)),
+        _ => None,
+    }
+}
+
+fn ancestor<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == kind {
+            return Some(parent);
+        }
+        current = parent.parent();
+    }
+    None
+}
+
+/// The local name of an IRI in the XSD namespace, e.g. `integer` for `xsd:integer`.
+/// Prefixes are resolved with the prefix declarations, `xsd:` may be used without declaration.
+fn xsd_local_name<'a>(iri: Node, text: &'a str) -> Option<&'a str> {
+    let iri_text = iri.utf8_text(text.as_bytes()).ok()?;
+    if iri.kind() == "IRIREF" {
+        return iri_text
+            .strip_prefix(&format!("<{}", XSD))?
+            .strip_suffix('>');
+    }
+    let (prefix, local_name) = iri_text.split_once(':')?;
+    let mut root = iri;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+    let namespace = prefix_declarations(root, text)
+        .into_iter()
+        .find(|(declared_prefix, _namespace)| declared_prefix == prefix)
+        .map(|(_prefix, namespace)| namespace);
+    match namespace {
+        Some(namespace) if namespace == XSD => Some(local_name),
+        None if prefix == "xsd" => Some(local_name),
+        _ => None,
+    }
+}
+
+fn builtin_documentation(builtin: &Builtin) -> String {
+    let (signature, _offsets) = builtin.signature();
+    let parameters: String = builtin
+        .parameters
+        .iter()
+        .map(|parameter| format!("- `{}`: {}\n", parameter.label, parameter.documentation))
+        .collect();
+    format!(
+        "### `{}`\n\n```sparql\n{}\n```\n\n{}\n\n{}**Returns:** `{}`\n\n**Example:**\n\n```sparql\n{}\n```\n\n[SPARQL 1.1 Query Language]({})",
+        builtin.name,
+        signature,
+        builtin.description,
+        match parameters.is_empty() {
+            true => String::new(),
+            false => format!("**Parameters:**\n\n{}\n", parameters),
+        },
+        builtin.return_type,
+        builtin.example,
+        builtin.link()
+    )
+}
+
+fn keyword_documentation(keyword: &Keyword) -> String {
+    format!(
+        "### `{}`\n\n```sparql\n{}\n```\n\n{}\n\n**Example:**\n\n```sparql\n{}\n```\n\n[Specification]({})",
+        keyword.name,
+        keyword.syntax,
+        keyword.description,
+        keyword.example,
+        keyword.link()
+    )
+}
+
+fn datatype_documentation(datatype: &Datatype, iri: &str) -> String {
+    format!(
+        "### `{}`\n\n`{}{}`\n\n{}\n\n**Example:**\n\n```sparql\n{}\n```\n\n[XML Schema Datatypes]({})",
+        iri,
+        XSD,
+        datatype.name,
+        datatype.description,
+        datatype.example,
+        datatype.link()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lsp::textdocument::Position, server::message_handler::formatting::utils::KEYWORDS,
+    };
+
+    use super::{builtins, keywords, plain_text};
+
+    /// The documentation of the token at the `|` in `text`.
+    fn hover(text: &str) -> Option<String> {
+        let (before, after) = text.split_once('|').unwrap();
+        let column = before.encode_utf16().count() as u32;
+        super::hover(&format!("{}{}", before, after), &Position::new(0, column))
+    }
+
+    #[test]
+    fn every_keyword_is_documented() {
+        for keyword in KEYWORDS {
+            assert!(
+                builtins::find(keyword).is_some() || keywords::find(keyword).is_some(),
+                "{} is not documented",
+                keyword
+            );
+        }
+    }
+
+    #[test]
+    fn keywords() {
+        let filter = hover("SELECT * { ?a ?b ?c FI|LTER (?c > 1) }").unwrap();
+        assert!(filter.starts_with("### `FILTER`"));
+        assert!(filter.contains("https://www.w3.org/TR/sparql11-query/#expressions"));
+        let regex = hover("SELECT * { FILTER (re|gex(?a, \"x\")) }").unwrap();
+        assert!(regex.contains("**Returns:** `xsd:boolean`"));
+        assert!(regex.contains("- `simple literal pattern`: "));
+        assert!(hover("SELECT * { ?a |a ?c }").unwrap().contains("rdf:type"));
+        assert_eq!(hover("SELECT * { ?a ?b |?c }"), None);
+    }
+
+    #[test]
+    fn datatypes() {
+        let integer = hover("SELECT * { ?a ?b \"1\"^^xsd:int|eger }").unwrap();
+        assert!(integer.contains("http://www.w3.org/2001/XMLSchema#integer"));
+        assert!(hover(
+            "PREFIX x: <http://www.w3.org/2001/XMLSchema#> SELECT * { ?a ?b \"1\"^^x:|int }"
+        )
+        .is_some());
+        assert!(
+            hover("PREFIX xsd: <http://example.org/> SELECT * { ?a ?b \"1\"^^xsd:|int }").is_none()
+        );
+        assert!(
+            hover("SELECT * { ?a ?b \"1\"^^<http://www.w3.org/2001/XMLSchema#|date> }").is_some()
+        );
+        assert!(hover("SELECT * { BIND (xsd:int|eger(?a) AS ?b) }")
+            .unwrap()
+            .contains("Casts `term`"));
+    }
+
+    #[test]
+    fn utf16_columns() {
+        let filter = hover("SELECT * { ?名前 ?b ?c FI|LTER (?c > 1) }").unwrap();
+        assert!(filter.starts_with("### `FILTER`"));
+    }
+
+    #[test]
+    fn plain_text_documentation() {
+        let filter = plain_text(&hover("SELECT * { FI|LTER (?c > 1) }").unwrap());
+        assert!(filter.starts_with("FILTER\n\nFILTER (expression)\n\n"));
+        assert!(filter
+            .ends_with("\n\nSpecification: https://www.w3.org/TR/sparql11-query/#expressions"));
+        assert!(!filter.contains('`'));
+    }
+
+    #[test]
+    fn language_tags() {
+        let tag = hover("SELECT * { ?a ?b \"colour\"@en-|GB }").unwrap();
+        assert!(tag.contains("**Language:** English"));
+        assert!(tag.contains("**Region:** `GB`"));
+    }
+}
@@ -1,191 +1,1081 @@
+mod builtins;
+mod code_action;
 mod completion;
 mod diagnostic;
+mod document_symbol;
+mod execute_command;
+mod folding_range;
 mod formatting;
 mod hovering;
+mod selection_range;
+mod semantic_tokens;
+mod signature_help;
 use std::process::exit;
 
+use code_action::handle_code_action_request;
 use completion::handel_completion_request;
+use document_symbol::handle_document_symbol_request;
+use execute_command::handle_execute_command_request;
+use folding_range::handle_folding_range_request;
 use hovering::handle_hover_request;
 use log::{debug, error, info, warn};
+use selection_range::handle_selection_range_request;
+use semantic_tokens::{
+    handle_semantic_tokens_delta_request, handle_semantic_tokens_full_request,
+    handle_semantic_tokens_range_request,
+};
+use serde::{Deserialize, Serialize};
+use signature_help::handle_signature_help_request;
 
+pub(crate) use completion::completions;
 pub use diagnostic::*;
-pub use formatting::format_raw;
+pub use formatting::{format, format_raw, FormatError};
+pub(crate) use hovering::hover;
 
 use crate::{
     lsp::{
-        textdocument::TextDocumentItem, CompletionRequest, Diagnostic, DiagnosticRequest,
-        DiagnosticResponse, DidChangeTextDocumentNotification, DidOpenTextDocumentNotification,
-        FormattingRequest, HoverRequest, InitializeRequest, InitializeResonse, ShutdownResponse,
+        textdocument::TextDocumentItem, CodeActionRequest, CompletionRequest, Diagnostic,
+        DiagnosticRequest, DiagnosticResponse, DidChangeConfigurationNotification,
+        DidChangeTextDocumentNotification, DidCloseTextDocumentNotification,
+        DidOpenTextDocumentNotification, DidSaveTextDocumentNotification, DocumentSymbolRequest,
+        ExecuteCommandRequest, FoldingRangeRequest, FormattingRequest, HoverRequest,
+        InitializeRequest, InitializeResonse, PublishDiagnosticsNotification,
+        PublishDiagnosticsPrarams, Registration, RegistrationRequest, SelectionRangeRequest,
+        SemanticTokensDeltaRequest, SemanticTokensFullRequest, SemanticTokensRangeRequest,
+        ServerCapabilities, ShutdownResponse, SignatureHelpRequest,
+    },
+    rpc::{
+        self, BaseMessage, CancelNotification, ErrorCode, ErrorResponse, Incoming, Message,
+        RequestId, RequestMessage, ResponseError,
+    },
+    server::{
+        worker::{Finished, Job},
+        ServerState, ServerStatus, Snapshot,
     },
-    rpc::{self, RequestMessage},
-    server::{ServerState, ServerStatus},
 };
 
 use self::formatting::handle_format_request;
 
 use super::configuration::Settings;
 
-pub fn dispatch(bytes: &Vec<u8>, state: &mut ServerState, settings: &Settings) -> Option<String> {
-    if let Ok(message) = rpc::decode_message(bytes) {
-        match message.method.as_str() {
-            "initialize" => match serde_json::from_slice::<InitializeRequest>(bytes) {
-                Ok(initialize_request) => {
-                    info!(
-                        "Connected to: {} {}",
-                        initialize_request.params.client_info.name,
-                        initialize_request
-                            .params
-                            .client_info
-                            .version
-                            .unwrap_or("no version specified".to_string())
-                    );
-                    let initialize_response = InitializeResonse::new(initialize_request.base.id);
-                    return Some(serde_json::to_string(&initialize_response).unwrap());
-                }
-                Err(error) => {
-                    error!("Could not parse initialize request: {:?}", error);
-                    return None;
-                }
-            },
-            "initialized" => {
-                info!("initialization completed");
-                state.status = ServerStatus::Running;
-                return None;
-            }
-            "shutdown" => match serde_json::from_slice::<RequestMessage>(bytes) {
-                Ok(shutdown_request) => {
-                    info!("recieved shutdown request, preparing to shut down");
-                    let response = ShutdownResponse::new(shutdown_request.id);
-                    state.status = ServerStatus::ShuttingDown;
-                    return Some(serde_json::to_string(&response).unwrap());
-                }
-                Err(error) => {
-                    error!("Could not parse shutdown request: {:?}", error);
-                    return None;
-                }
-            },
-            "exit" => {
-                info!("recieved exit notification, shutting down!");
-                exit(0);
+/// The outcome of handling a message on the main loop.
+pub(crate) enum Handled {
+    /// Messages to send right away.
+    Done(Vec<String>),
+    /// A request that is answered from a snapshot, possibly on another thread.
+    Deferred(Box<Job>),
+}
+
+/// Handles an incoming message and returns the outgoing messages.
+/// Deferred requests are answered right away, on the current thread.
+pub fn dispatch(bytes: &[u8], state: &mut ServerState, settings: &mut Settings) -> Vec<String> {
+    match handle(bytes, state, settings) {
+        Handled::Done(messages) => messages,
+        Handled::Deferred(job) => complete(job.run(), state),
+    }
+}
+
+/// Handles an incoming message. Requests that only read the documents are deferred.
+pub(crate) fn handle(bytes: &[u8], state: &mut ServerState, settings: &mut Settings) -> Handled {
+    let message = match rpc::decode_message(bytes) {
+        Ok(Incoming::Message(message)) => message,
+        Ok(Incoming::Response(response)) => {
+            match response.error {
+                Some(error) => warn!(
+                    "Client answered request {:?} with an error: {}",
+                    response.id, error
+                ),
+                None => debug!("Client answered request {:?}", response.id),
             }
-            "textDocument/didOpen" => {
-                match serde_json::from_slice::<DidOpenTextDocumentNotification>(bytes) {
-                    Ok(did_open_notification) => {
-                        debug!(
-                            "opened text document: \"{}\"\n{}",
-                            did_open_notification.params.text_document.uri,
-                            did_open_notification.params.text_document.text
-                        );
-                        let text_document: TextDocumentItem =
-                            did_open_notification.get_text_document();
-                        state.add_document(text_document);
-                        return None;
-                    }
-                    Err(error) => {
-                        error!("Could not parse textDocument/didOpen request: {:?}", error);
-                        return None;
-                    }
-                }
+            return Handled::Done(vec![]);
+        }
+        Err(error) => {
+            error!(
+                "An error occured while parsing the request content: {}",
+                error.message
+            );
+            return Handled::Done(vec![serialize(&ErrorResponse::new(None, error))]);
+        }
+    };
+    let result = match defer(&message, bytes, state, settings) {
+        Ok(Some(job)) => {
+            if let Some(id) = &message.id {
+                state.pending_requests.insert(id.clone());
             }
-            "textDocument/didChange" => {
-                match serde_json::from_slice::<DidChangeTextDocumentNotification>(bytes) {
-                    Ok(did_change_notification) => {
-                        debug!(
-                            "text document changed: {}",
-                            did_change_notification.params.text_document.base.uri
-                        );
-                        state.change_document(
-                            did_change_notification.params.text_document.base.uri,
-                            did_change_notification.params.content_changes,
-                        );
-
-                        return None;
-                    }
-                    Err(error) => {
-                        error!(
-                            "Could not parse textDocument/didChange notification: {:?}",
-                            error
-                        );
-                        return None;
-                    }
-                }
+            return Handled::Deferred(Box::new(job));
+        }
+        Ok(None) => route(&message, bytes, state, settings),
+        Err(error) => Err(error),
+    };
+    Handled::Done(match result {
+        Ok(response) => response,
+        Err(error) => match message.id {
+            Some(id) => vec![serialize(&ErrorResponse::new(Some(id), error))],
+            None => {
+                error!(
+                    "Could not handle {} notification: {}",
+                    message.method, error.message
+                );
+                vec![]
             }
-            "textDocument/hover" => match serde_json::from_slice::<HoverRequest>(bytes) {
-                Ok(hover_request) => {
-                    debug!(
-                        "recieved hover request for {} {}",
-                        hover_request.get_document_uri(),
-                        hover_request.get_position()
-                    );
-                    let response = handle_hover_request(&hover_request, state);
+        },
+    })
+}
 
-                    return Some(serde_json::to_string(&response).unwrap());
-                }
-                Err(error) => {
-                    error!("Could not parse textDocument/hover request: {:?}", error);
-                    return None;
-                }
-            },
-            "textDocument/completion" => match serde_json::from_slice::<CompletionRequest>(bytes) {
-                Ok(completion_request) => {
+/// Filters the messages of a finished job that are no longer wanted:
+/// Cancelled requests are answered with `RequestCancelled` and results that were computed
+/// for a superseded version of the document with `ContentModified`.
+pub(crate) fn complete(finished: Finished, state: &mut ServerState) -> Vec<String> {
+    if let Some(id) = &finished.id {
+        state.pending_requests.remove(id);
+        if state.cancelled_requests.remove(id) {
+            return vec![cancelled(id)];
+        }
+    }
+    match (finished.document, finished.id) {
+        (Some((uri, version)), id) if state.analysis_state.get_version(&uri) != Some(version) => {
+            match id {
+                Some(id) => vec![serialize(&ErrorResponse::new(
+                    Some(id),
+                    ResponseError::new(
+                        ErrorCode::ContentModified,
+                        format!("{} changed while the request was handled", uri),
+                    ),
+                ))],
+                None => {
                     debug!(
-                        "Received completion request for {} {}",
-                        completion_request.get_document_uri(),
-                        completion_request.get_position()
-                    );
-                    let response = handel_completion_request(completion_request, state);
-                    return Some(serde_json::to_string(&response).unwrap());
-                }
-                Err(error) => {
-                    error!(
-                        "Could not parse textDocument/completion request: {:?}",
-                        error
-                    );
-                    return None;
-                }
-            },
-            "textDocument/formatting" => match serde_json::from_slice::<FormattingRequest>(bytes) {
-                Ok(formatting_request) => {
-                    let response = handle_format_request(formatting_request, state, settings);
-                    return Some(serde_json::to_string(&response).unwrap());
-                }
-                Err(error) => {
-                    error!(
-                        "Could not parse textDocument/formatting request: {:?}",
-                        error
-                    );
-                    return None;
-                }
-            },
-            "textDocument/diagnostic" => match serde_json::from_slice::<DiagnosticRequest>(bytes) {
-                Ok(diagnostic_request) => {
-                    let diagnostics: Vec<Diagnostic> = collect_diagnostics(
-                        &state.analysis_state,
-                        &diagnostic_request.params.text_document.uri,
-                    )
-                    .collect();
-                    let resonse = DiagnosticResponse::new(diagnostic_request.base.id, diagnostics);
-                    return Some(serde_json::to_string(&resonse).unwrap());
-                }
-                Err(error) => {
-                    error!(
-                        "Could not parse textDocument/diagnostic request: {:?}",
-                        error
+                        "Dropped outdated notification for version {} of {}",
+                        version, uri
                     );
-                    return None;
+                    vec![]
                 }
-            },
-            unknown_method => {
-                warn!(
-                    "Received message with unknown method \"{}\": {:?}",
-                    unknown_method,
-                    String::from_utf8(bytes.to_vec()).unwrap()
-                );
-                return None;
+            }
+        }
+        _ => finished.messages,
+    }
+}
+
+fn cancelled(id: &RequestId) -> String {
+    debug!("request {} was cancelled", id);
+    serialize(&ErrorResponse::new(
+        Some(id.clone()),
+        ResponseError::new(
+            ErrorCode::RequestCancelled,
+            format!("Request {} was cancelled", id),
+        ),
+    ))
+}
+
+/// Requests that only read the documents are answered from a [`Snapshot`] by a [`Job`],
+/// so that they don't hold up the messages after them.
+fn defer(
+    message: &Message,
+    bytes: &[u8],
+    state: &ServerState,
+    settings: &Settings,
+) -> Result<Option<Job>, ResponseError> {
+    if matches!(state.status, ServerStatus::Initializing) {
+        return Ok(None);
+    }
+    let method = message.method.as_str();
+    let job = match method {
+        "textDocument/hover" => {
+            let hover_request: HoverRequest = parse(bytes, method)?;
+            debug!(
+                "recieved hover request for {} {}",
+                hover_request.get_document_uri(),
+                hover_request.get_position()
+            );
+            let uri = hover_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(hover_request.get_id()),
+                uri,
+                move |snapshot| Ok(serialize(&handle_hover_request(&hover_request, snapshot)?)),
+            )
+        }
+        "textDocument/completion" => {
+            let completion_request: CompletionRequest = parse(bytes, method)?;
+            debug!(
+                "Received completion request for {} {}",
+                completion_request.get_document_uri(),
+                completion_request.get_position()
+            );
+            let uri = completion_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(completion_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handel_completion_request(
+                        completion_request,
+                        snapshot,
+                    )))
+                },
+            )
+        }
+        "textDocument/formatting" => {
+            let formatting_request: FormattingRequest = parse(bytes, method)?;
+            let uri = formatting_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(formatting_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_format_request(
+                        formatting_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/diagnostic" => {
+            let diagnostic_request: DiagnosticRequest = parse(bytes, method)?;
+            let uri = diagnostic_request.params.text_document.uri.clone();
+            Job::new(
+                state.snapshot(settings),
+                Some(diagnostic_request.base.id.clone()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_diagnostic_request(
+                        diagnostic_request,
+                        snapshot,
+                    )))
+                },
+            )
+        }
+        "textDocument/documentSymbol" => {
+            let document_symbol_request: DocumentSymbolRequest = parse(bytes, method)?;
+            let uri = document_symbol_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(document_symbol_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_document_symbol_request(
+                        document_symbol_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/foldingRange" => {
+            let folding_range_request: FoldingRangeRequest = parse(bytes, method)?;
+            let uri = folding_range_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(folding_range_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_folding_range_request(
+                        folding_range_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/selectionRange" => {
+            let selection_range_request: SelectionRangeRequest = parse(bytes, method)?;
+            let uri = selection_range_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(selection_range_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_selection_range_request(
+                        selection_range_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/semanticTokens/range" => {
+            let semantic_tokens_request: SemanticTokensRangeRequest = parse(bytes, method)?;
+            let uri = semantic_tokens_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(semantic_tokens_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_semantic_tokens_range_request(
+                        semantic_tokens_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/signatureHelp" => {
+            let signature_help_request: SignatureHelpRequest = parse(bytes, method)?;
+            let uri = signature_help_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(signature_help_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_signature_help_request(
+                        signature_help_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "textDocument/codeAction" => {
+            let code_action_request: CodeActionRequest = parse(bytes, method)?;
+            let uri = code_action_request.get_document_uri().to_string();
+            Job::new(
+                state.snapshot(settings),
+                Some(code_action_request.get_id()),
+                uri,
+                move |snapshot| {
+                    Ok(serialize(&handle_code_action_request(
+                        code_action_request,
+                        snapshot,
+                    )?))
+                },
+            )
+        }
+        "workspace/executeCommand" => {
+            let execute_command_request: ExecuteCommandRequest = parse(bytes, method)?;
+            debug!("Received {} command", execute_command_request.get_command());
+            // NOTE: Queries can take until the timeout of the endpoint.
+            let is_query = execute_command_request.get_command() == "fichu.executeQuery";
+            let job = Job::independent(
+                state.snapshot(settings),
+                execute_command_request.get_id(),
+                move |snapshot| {
+                    Ok(serialize(&handle_execute_command_request(
+                        execute_command_request,
+                        snapshot,
+                    )?))
+                },
+            );
+            match is_query {
+                true => job.on_own_thread(),
+                false => job,
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(job))
+}
+
+fn route(
+    message: &Message,
+    bytes: &[u8],
+    state: &mut ServerState,
+    settings: &mut Settings,
+) -> Result<Vec<String>, ResponseError> {
+    let method = message.method.as_str();
+    if matches!(state.status, ServerStatus::Initializing)
+        && !matches!(method, "initialize" | "initialized" | "exit")
+    {
+        return match message.id {
+            Some(_) => Err(ResponseError::new(
+                ErrorCode::ServerNotInitialized,
+                format!("Received {} request before initialize", method),
+            )),
+            None => {
+                warn!("Dropped {} notification before initialize", method);
+                Ok(vec![])
             }
         };
-    } else {
-        error!("An error occured while parsing the request content");
-        return None;
+    }
+    match method {
+        "initialize" => {
+            let initialize_request: InitializeRequest = parse(bytes, method)?;
+            let params = initialize_request.params;
+            match params.client_info {
+                Some(client_info) => info!(
+                    "Connected to: {} {}",
+                    client_info.name,
+                    client_info
+                        .version
+                        .unwrap_or("no version specified".to_string())
+                ),
+                None => info!("Connected to an unnamed client"),
+            }
+            if let Some(options) = params.initialization_options {
+                update_settings(settings, &options);
+            }
+            state.status = ServerStatus::Running;
+            let initialize_response = InitializeResonse::new(
+                initialize_request.base.id,
+                ServerCapabilities::new(&params.capabilities),
+            );
+            state.analysis_state.position_encoding = params.capabilities.position_encoding();
+            state.client_capabilities = params.capabilities;
+            Ok(vec![serialize(&initialize_response)])
+        }
+        "initialized" => {
+            info!("initialization completed");
+            match state.client_capabilities.configuration_registration() {
+                true => Ok(vec![serialize(&RegistrationRequest::new(
+                    state.next_request_id(),
+                    vec![Registration::new("workspace/didChangeConfiguration")],
+                ))]),
+                false => Ok(vec![]),
+            }
+        }
+        "workspace/didChangeConfiguration" => {
+            let did_change_configuration_notification: DidChangeConfigurationNotification =
+                parse(bytes, method)?;
+            update_settings(
+                settings,
+                &did_change_configuration_notification.params.settings,
+            );
+            Ok(vec![])
+        }
+        "shutdown" => {
+            let shutdown_request: RequestMessage = parse(bytes, method)?;
+            info!("recieved shutdown request, preparing to shut down");
+            let response = ShutdownResponse::new(shutdown_request.id);
+            state.status = ServerStatus::ShuttingDown;
+            Ok(vec![serialize(&response)])
+        }
+        "exit" => {
+            info!("recieved exit notification, shutting down!");
+            exit(0);
+        }
+        "$/cancelRequest" => {
+            let cancel_notification: CancelNotification = parse(bytes, method)?;
+            let id = cancel_notification.params.id;
+            debug!("recieved cancel notification for request {}", id);
+            // NOTE: Requests that are handled on the main loop are already answered.
+            if state.pending_requests.contains(&id) {
+                state.cancelled_requests.insert(id);
+            }
+            Ok(vec![])
+        }
+        "textDocument/didOpen" => {
+            let did_open_notification: DidOpenTextDocumentNotification = parse(bytes, method)?;
+            debug!(
+                "opened text document: \"{}\"\n{}",
+                did_open_notification.params.text_document.uri,
+                did_open_notification.params.text_document.text
+            );
+            let text_document: TextDocumentItem = did_open_notification.get_text_document();
+            state.add_document(text_document);
+            Ok(vec![])
+        }
+        "textDocument/didChange" => {
+            let did_change_notification: DidChangeTextDocumentNotification = parse(bytes, method)?;
+            debug!(
+                "text document changed: {}",
+                did_change_notification.params.text_document.base.uri
+            );
+            state.change_document(
+                did_change_notification.params.text_document.base.uri,
+                did_change_notification.params.text_document.version,
+                did_change_notification.params.content_changes,
+            );
+            Ok(vec![])
+        }
+        "textDocument/didSave" => {
+            let did_save_notification: DidSaveTextDocumentNotification = parse(bytes, method)?;
+            debug!(
+                "text document saved: {}",
+                did_save_notification.params.text_document.uri
+            );
+            Ok(vec![])
+        }
+        "textDocument/didClose" => {
+            let did_close_notification: DidCloseTextDocumentNotification = parse(bytes, method)?;
+            let uri = did_close_notification.params.text_document.uri;
+            debug!("text document closed: {}", uri);
+            state.close_document(&uri);
+            if state.client_capabilities.pull_diagnostics() {
+                return Ok(vec![]);
+            }
+            // Clear the published diagnostics of the closed document.
+            Ok(vec![serialize(&PublishDiagnosticsNotification {
+                base: BaseMessage::new("textDocument/publishDiagnostics".to_string()),
+                params: PublishDiagnosticsPrarams {
+                    uri,
+                    version: None,
+                    diagnostics: vec![],
+                },
+            })])
+        }
+        "textDocument/semanticTokens/full" => {
+            let semantic_tokens_request: SemanticTokensFullRequest = parse(bytes, method)?;
+            Ok(vec![serialize(&handle_semantic_tokens_full_request(
+                semantic_tokens_request,
+                state,
+            )?)])
+        }
+        "textDocument/semanticTokens/full/delta" => {
+            let semantic_tokens_request: SemanticTokensDeltaRequest = parse(bytes, method)?;
+            Ok(vec![serialize(&handle_semantic_tokens_delta_request(
+                semantic_tokens_request,
+                state,
+            )?)])
+        }
+        // Protocol specific notifications may be ignored, requests must be answered.
+        method if method.starts_with("$/") && message.id.is_none() => {
+            debug!("Ignored {} notification", method);
+            Ok(vec![])
+        }
+        unknown_method => {
+            warn!(
+                "Received message with unknown method \"{}\": {:?}",
+                unknown_method,
+                String::from_utf8_lossy(bytes)
+            );
+            match message.id {
+                Some(_) => Err(ResponseError::new(
+                    ErrorCode::MethodNotFound,
+                    format!("Unknown method \"{}\"", unknown_method),
+                )),
+                None => Ok(vec![]),
+            }
+        }
+    }
+}
+
+/// Answers a pull diagnostics request.
+/// The diagnostics only depend on the document, so its version identifies them.
+fn handle_diagnostic_request(request: DiagnosticRequest, state: &Snapshot) -> DiagnosticResponse {
+    let uri = &request.params.text_document.uri;
+    let result_id = state
+        .analysis_state
+        .get_state(uri)
+        .map(|(document, _tree)| document.version().to_string());
+    if let (Some(result_id), Some(previous_result_id)) =
+        (&result_id, &request.params.previous_result_id)
+    {
+        if result_id == previous_result_id {
+            return DiagnosticResponse::unchanged(request.base.id, result_id.clone());
+        }
+    }
+    let diagnostics: Vec<Diagnostic> =
+        collect_diagnostics(&state.analysis_state, uri, &state.settings.lint).collect();
+    DiagnosticResponse::new(request.base.id, result_id, diagnostics)
+}
+
+/// The `textDocument/publishDiagnostics` notification with the current diagnostics of the document.
+pub(crate) fn publish_diagnostics(state: &Snapshot, uri: &str) -> String {
+    serialize(&PublishDiagnosticsNotification {
+        base: BaseMessage::new("textDocument/publishDiagnostics".to_string()),
+        params: PublishDiagnosticsPrarams {
+            uri: uri.to_string(),
+            version: state.analysis_state.get_version(uri),
+            diagnostics: collect_diagnostics(&state.analysis_state, uri, &state.settings.lint)
+                .collect(),
+        },
+    })
+}
+
+/// Replaces the settings with the ones from the settings file, overriden by `overrides`.
+fn update_settings(settings: &mut Settings, overrides: &serde_json::Value) {
+    if overrides.is_null() {
+        return;
+    }
+    match Settings::load_with_overrides(overrides) {
+        Ok(new_settings) => {
+            info!("Updated settings: {:?}", new_settings);
+            *settings = new_settings;
+        }
+        Err(error) => error!("Could not apply the settings of the client: {}", error),
+    }
+}
+
+fn parse<'a, T: Deserialize<'a>>(bytes: &'a [u8], method: &str) -> Result<T, ResponseError> {
+    serde_json::from_slice(bytes).map_err(|error| {
+        error!("Could not parse {} message: {:?}", method, error);
+        ResponseError::new(
+            ErrorCode::InvalidParams,
+            format!("Could not parse {} message: {}", method, error),
+        )
+    })
+}
+
+fn serialize<T: Serialize>(message: &T) -> String {
+    serde_json::to_string(message).expect("Messages are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::{
+        configuration::{Settings, SyntaxErrorPolicy},
+        ServerState, ServerStatus,
+    };
+
+    use super::{complete, dispatch, handle, Handled};
+
+    fn running_state() -> ServerState {
+        let mut state = ServerState::new();
+        state.status = ServerStatus::Running;
+        state
+    }
+
+    #[test]
+    fn not_initialized() {
+        let mut state = ServerState::new();
+        let response = dispatch(
+            br#"{"jsonrpc":"2.0","id":1,"method":"textDocument/hover","params":{}}"#,
+            &mut state,
+            &mut Settings::default(),
+        );
+        assert_eq!(
+            response[0],
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32002,"message":"Received textDocument/hover request before initialize"}}"#
+        );
+    }
+
+    #[test]
+    fn unknown_method() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        let response = dispatch(
+            br#"{"jsonrpc":"2.0","id":"a","method":"textDocument/dings"}"#,
+            &mut state,
+            &mut settings,
+        );
+        assert_eq!(
+            response[0],
+            r#"{"jsonrpc":"2.0","id":"a","error":{"code":-32601,"message":"Unknown method \"textDocument/dings\""}}"#
+        );
+        let notification = br#"{"jsonrpc":"2.0","method":"$/setTrace","params":{"value":"off"}}"#;
+        assert_eq!(
+            dispatch(notification, &mut state, &mut settings),
+            Vec::<String>::new()
+        );
+        let request = br#"{"jsonrpc":"2.0","id":2,"method":"$/dings"}"#;
+        assert!(dispatch(request, &mut state, &mut settings)[0].contains(r#""code":-32601"#));
+    }
+
+    #[test]
+    fn invalid_params() {
+        let mut state = running_state();
+        let response = dispatch(
+            br#"{"jsonrpc":"2.0","id":3,"method":"textDocument/hover","params":{}}"#,
+            &mut state,
+            &mut Settings::default(),
+        );
+        assert!(response[0].contains(r#""code":-32602"#));
+    }
+
+    #[test]
+    fn format_unknown_document() {
+        let mut state = running_state();
+        let response = dispatch(
+            br#"{"jsonrpc":"2.0","id":4,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///dings"},"options":{"tabSize":2,"insertSpaces":true}}}"#,
+            &mut state,
+            &mut Settings::default(),
+        );
+        assert_eq!(
+            response[0],
+            r#"{"jsonrpc":"2.0","id":4,"error":{"code":-32803,"message":"Unknown document: file:///dings"}}"#
+        );
+    }
+
+    #[test]
+    fn requests_for_unknown_documents() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        let document = r#""textDocument":{"uri":"file:///dings"}"#;
+        let position = r#""position":{"line":0,"character":0}"#;
+        let range = r#""range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}}"#;
+        for (method, params) in [
+            ("textDocument/hover", format!("{},{}", document, position)),
+            ("textDocument/documentSymbol", document.to_string()),
+            ("textDocument/foldingRange", document.to_string()),
+            (
+                "textDocument/selectionRange",
+                format!(r#"{},"positions":[{{"line":0,"character":0}}]"#, document),
+            ),
+            ("textDocument/semanticTokens/full", document.to_string()),
+            (
+                "textDocument/semanticTokens/full/delta",
+                format!(r#"{},"previousResultId":"1""#, document),
+            ),
+            (
+                "textDocument/semanticTokens/range",
+                format!("{},{}", document, range),
+            ),
+            (
+                "textDocument/signatureHelp",
+                format!("{},{}", document, position),
+            ),
+            (
+                "textDocument/codeAction",
+                format!(r#"{},{},"context":{{"diagnostics":[]}}"#, document, range),
+            ),
+        ] {
+            let request = format!(
+                r#"{{"jsonrpc":"2.0","id":7,"method":"{}","params":{{{}}}}}"#,
+                method, params
+            );
+            assert_eq!(
+                dispatch(request.as_bytes(), &mut state, &mut settings),
+                vec![
+                    r#"{"jsonrpc":"2.0","id":7,"error":{"code":-32803,"message":"Unknown document: file:///dings"}}"#
+                ],
+                "{}",
+                method
+            );
+        }
+        assert!(state.semantic_tokens.is_empty());
+    }
+
+    #[test]
+    fn refuse_to_format_syntax_errors() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"select * {"}}}"#,
+            &mut state,
+            &mut settings,
+        );
+        let request = br#"{"jsonrpc":"2.0","id":5,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.rq"},"options":{"tabSize":2,"insertSpaces":true}}}"#;
+        assert_eq!(
+            dispatch(request, &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":5,"error":{"code":-32803,"message":"The document was not formatted: Syntax error: missing '}' at 1:11"}}"#
+        );
+        settings.format.on_syntax_error = SyntaxErrorPolicy::Partial;
+        assert!(dispatch(request, &mut state, &mut settings)[0].contains(r#""result":"#));
+    }
+
+    #[test]
+    fn cancel_request() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        let cancel = br#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":"five"}}"#;
+        assert_eq!(
+            dispatch(cancel, &mut state, &mut settings),
+            Vec::<String>::new()
+        );
+        // Unknown requests are not cancelled, the id could be reused.
+        assert!(state.cancelled_requests.is_empty());
+        let request = br#"{"jsonrpc":"2.0","id":"five","method":"textDocument/dings"}"#;
+        assert!(dispatch(request, &mut state, &mut settings)[0].contains(r#""code":-32601"#));
+    }
+
+    #[test]
+    fn cancel_answered_request() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        let formatting = br#"{"jsonrpc":"2.0","id":6,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.rq"},"options":{"tabSize":2,"insertSpaces":true}}}"#;
+        assert!(dispatch(formatting, &mut state, &mut settings)[0].contains(r#""result":"#));
+        let cancel = br#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":6}}"#;
+        dispatch(cancel, &mut state, &mut settings);
+        assert!(state.pending_requests.is_empty());
+        assert!(state.cancelled_requests.is_empty());
+    }
+
+    fn did_change(version: u32, text: &str) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didChange","params":{{"textDocument":{{"uri":"file:///a.rq","version":{}}},"contentChanges":[{{"text":"{}"}}]}}}}"#,
+            version, text
+        )
+    }
+
+    const DID_OPEN: &[u8] = br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT * {}"}}}"#;
+
+    #[test]
+    fn document_versions() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        dispatch(
+            did_change(3, "SELECT ?a {}").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        dispatch(
+            did_change(2, "ASK {}").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        dispatch(
+            did_change(3, "ASK {}").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        let (document, _tree) = state.analysis_state.get_state("file:///a.rq").unwrap();
+        assert_eq!(document.text, "SELECT ?a {}");
+        assert_eq!(document.version(), 3);
+    }
+
+    #[test]
+    fn pull_diagnostics_result_id() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        let request = |previous_result_id: &str| {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"textDocument/diagnostic","params":{{"textDocument":{{"uri":"file:///a.rq"}},"previousResultId":"{}"}}}}"#,
+                previous_result_id
+            )
+        };
+        assert_eq!(
+            dispatch(request("0").as_bytes(), &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":1,"result":{"kind":"full","resultId":"1","items":[]}}"#
+        );
+        assert_eq!(
+            dispatch(request("1").as_bytes(), &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":1,"result":{"kind":"unchanged","resultId":"1"}}"#
+        );
+        dispatch(
+            did_change(2, "SELECT * {").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        assert!(
+            dispatch(request("1").as_bytes(), &mut state, &mut settings)[0]
+                .contains(r#""kind":"full","resultId":"2","items":[{"#)
+        );
+    }
+
+    #[test]
+    fn close_document() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        let did_save = br#"{"jsonrpc":"2.0","method":"textDocument/didSave","params":{"textDocument":{"uri":"file:///a.rq"}}}"#;
+        assert_eq!(
+            dispatch(did_save, &mut state, &mut settings),
+            Vec::<String>::new()
+        );
+        let did_close = br#"{"jsonrpc":"2.0","method":"textDocument/didClose","params":{"textDocument":{"uri":"file:///a.rq"}}}"#;
+        assert_eq!(
+            dispatch(did_close, &mut state, &mut settings),
+            vec![
+                r#"{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"file:///a.rq","diagnostics":[]}}"#
+            ]
+        );
+        assert!(state.analysis_state.get_state("file:///a.rq").is_none());
+        assert!(state.take_changed_documents().is_empty());
+    }
+
+    #[test]
+    fn negotiate() {
+        let mut state = ServerState::new();
+        let mut settings = Settings::default();
+        let initialize = br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"processId":null,"capabilities":{"workspace":{"didChangeConfiguration":{"dynamicRegistration":true}},"textDocument":{"diagnostic":{}}},"initializationOptions":{"format":{"tab_size":8}}}}"#;
+        let response = dispatch(initialize, &mut state, &mut settings);
+        assert!(response[0].contains(r#""diagnosticProvider":{"#));
+        assert_eq!(settings.format.tab_size, 8);
+        let initialized = br#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#;
+        assert_eq!(
+            dispatch(initialized, &mut state, &mut settings),
+            vec![
+                r#"{"jsonrpc":"2.0","method":"client/registerCapability","id":1,"params":{"registrations":[{"id":"workspace/didChangeConfiguration","method":"workspace/didChangeConfiguration"}]}}"#
+            ]
+        );
+        let registered = br#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+        assert_eq!(
+            dispatch(registered, &mut state, &mut settings),
+            Vec::<String>::new()
+        );
+        let did_change_configuration = br#"{"jsonrpc":"2.0","method":"workspace/didChangeConfiguration","params":{"settings":{"format":{"capitalize_keywords":false}}}}"#;
+        dispatch(did_change_configuration, &mut state, &mut settings);
+        assert_eq!(settings.format.tab_size, 2);
+        assert!(!settings.format.capitalize_keywords);
+        // Pull diagnostics are not mixed with published ones.
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        let did_close = br#"{"jsonrpc":"2.0","method":"textDocument/didClose","params":{"textDocument":{"uri":"file:///a.rq"}}}"#;
+        assert_eq!(
+            dispatch(did_close, &mut state, &mut settings),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn negotiated_position_encoding() {
+        let mut state = ServerState::new();
+        let mut settings = Settings::default();
+        let initialize = br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{"general":{"positionEncodings":["utf-8","utf-16"]}}}}"#;
+        let response = dispatch(initialize, &mut state, &mut settings);
+        assert!(response[0].contains(r#""positionEncoding":"utf-8""#));
+        let did_open = r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT ?名前 {}"}}}"#;
+        dispatch(did_open.as_bytes(), &mut state, &mut settings);
+        let selection_range = br#"{"jsonrpc":"2.0","id":2,"method":"textDocument/selectionRange","params":{"textDocument":{"uri":"file:///a.rq"},"positions":[{"line":0,"character":9}]}}"#;
+        let response = dispatch(selection_range, &mut state, &mut settings);
+        assert!(response[0].contains(
+            r#"{"range":{"start":{"line":0,"character":7},"end":{"line":0,"character":14}}"#
+        ));
+    }
+
+    #[test]
+    fn deferred_requests() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(DID_OPEN, &mut state, &mut settings);
+        let formatting = br#"{"jsonrpc":"2.0","id":7,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.rq"},"options":{"tabSize":2,"insertSpaces":true}}}"#;
+        let Handled::Deferred(job) = handle(formatting, &mut state, &mut settings) else {
+            panic!("formatting requests are deferred");
+        };
+        // The document changes before the result is sent.
+        dispatch(
+            did_change(2, "SELECT ?a {}").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        let response = complete(job.run(), &mut state);
+        assert!(response[0].contains(r#""id":7,"error":{"code":-32801"#));
+
+        let Handled::Deferred(job) = handle(formatting, &mut state, &mut settings) else {
+            panic!("formatting requests are deferred");
+        };
+        let cancel = br#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":7}}"#;
+        dispatch(cancel, &mut state, &mut settings);
+        let response = complete(job.run(), &mut state);
+        assert!(response[0].contains(r#""id":7,"error":{"code":-32800"#));
+
+        let Handled::Deferred(job) = handle(formatting, &mut state, &mut settings) else {
+            panic!("formatting requests are deferred");
+        };
+        let response = complete(job.run(), &mut state);
+        assert!(response[0].contains(r#""newText":"SELECT ?a {}"#));
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn execute_query() {
+        use crate::{endpoint::mock::serve_once, server::configuration::EndpointSettings};
+
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"PREFIX ex: <http://example.org/>\nSELECT * {\n  { SELECT ?s { ?s ex:p ?o } }\n}"}}}"#,
+            &mut state,
+            &mut settings,
+        );
+        let request = br#"{"jsonrpc":"2.0","id":8,"method":"workspace/executeCommand","params":{"command":"fichu.executeQuery","arguments":[{"uri":"file:///a.rq","range":{"start":{"line":2,"character":4},"end":{"line":2,"character":28}},"format":"csv"}]}}"#;
+        assert!(dispatch(request, &mut state, &mut settings)[0]
+            .contains(r#""error":{"code":-32803,"message":"No endpoint is configured"#));
+
+        let (url, endpoint) = serve_once(200, "text/csv", "s\r\nhttp://example.org/a\r\n");
+        settings.endpoint = Some(EndpointSettings::new(&url));
+        let Handled::Deferred(job) = handle(request, &mut state, &mut settings) else {
+            panic!("commands are deferred");
+        };
+        // The results stay valid, even if the document changes in the meantime.
+        dispatch(
+            did_change(2, "SELECT ?a {}").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        let response: serde_json::Value =
+            serde_json::from_str(&complete(job.run(), &mut state)[0]).unwrap();
+        assert_eq!(response["id"], 8);
+        assert_eq!(response["result"]["endpoint"], url);
+        assert_eq!(response["result"]["contentType"], "text/csv");
+        assert_eq!(
+            response["result"]["results"],
+            "s\r\nhttp://example.org/a\r\n"
+        );
+        assert!(response["result"]["duration"].is_u64());
+        let received = endpoint.join().unwrap();
+        assert_eq!(received.accept, "text/csv");
+        assert_eq!(
+            received.body,
+            "PREFIX ex: <http://example.org/>\nSELECT ?s { ?s ex:p ?o }"
+        );
+
+        let unknown = br#"{"jsonrpc":"2.0","id":9,"method":"workspace/executeCommand","params":{"command":"fichu.dings"}}"#;
+        assert_eq!(
+            dispatch(unknown, &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":9,"error":{"code":-32602,"message":"Unknown command: fichu.dings"}}"#
+        );
+    }
+
+    #[test]
+    fn refuse_to_execute_updates() {
+        use crate::{endpoint::mock::serve_once, server::configuration::EndpointSettings};
+
+        let mut state = running_state();
+        let (url, endpoint) = serve_once(200, "text/csv", "s\r\n");
+        let mut settings = Settings::builder()
+            .endpoint(EndpointSettings::new(&url))
+            .build();
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.ru","languageId":"sparql","version":1,"text":"DELETE WHERE { ?s ?p ?o }"}}}"#,
+            &mut state,
+            &mut settings,
+        );
+        let request = br#"{"jsonrpc":"2.0","id":10,"method":"workspace/executeCommand","params":{"command":"fichu.executeQuery","arguments":[{"uri":"file:///a.ru"}]}}"#;
+        assert_eq!(
+            dispatch(request, &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":10,"error":{"code":-32602,"message":"file:///a.ru is an update, only queries can be executed"}}"#
+        );
+        // The endpoint only receives the query that follows.
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":{"uri":"file:///a.ru","version":2},"contentChanges":[{"text":"SELECT * {}"}]}}"#,
+            &mut state,
+            &mut settings,
+        );
+        assert!(dispatch(request, &mut state, &mut settings)[0].contains(r#""result":"#));
+        let received = endpoint.join().unwrap();
+        assert_eq!(received.content_type, "application/sparql-query");
+        assert_eq!(received.body, "SELECT * {}");
+    }
+
+    #[test]
+    fn code_actions() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"PREFIX ex: <http://example.org/>\nSELECT * {\n  ?s ex:p ?o .\n  ?s ex:q ?x .\n}"}}}"#,
+            &mut state,
+            &mut settings,
+        );
+        let request = br#"{"jsonrpc":"2.0","id":4,"method":"textDocument/codeAction","params":{"textDocument":{"uri":"file:///a.rq"},"range":{"start":{"line":2,"character":6},"end":{"line":2,"character":6}},"context":{"diagnostics":[]}}}"#;
+        let response: serde_json::Value =
+            serde_json::from_str(&dispatch(request, &mut state, &mut settings)[0]).unwrap();
+        let titles: Vec<&str> = response["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            titles,
+            vec!["Expand to full IRI", "Merge the triples of ?s"]
+        );
+        assert_eq!(
+            response["result"][1]["edit"]["changes"]["file:///a.rq"][0],
+            serde_json::json!({
+                "range": {"start": {"line": 2, "character": 0}, "end": {"line": 4, "character": 0}},
+                "newText": "  ?s ex:p ?o ;\n     ex:q ?x .\n"
+            })
+        );
+
+        let only = br#"{"jsonrpc":"2.0","id":5,"method":"textDocument/codeAction","params":{"textDocument":{"uri":"file:///a.rq"},"range":{"start":{"line":2,"character":6},"end":{"line":2,"character":6}},"context":{"diagnostics":[],"only":["refactor.extract"]}}}"#;
+        assert_eq!(
+            dispatch(only, &mut state, &mut settings)[0],
+            r#"{"jsonrpc":"2.0","id":5,"result":[]}"#
+        );
+    }
+
+    #[test]
+    fn show_algebra() {
+        let mut state = running_state();
+        let mut settings = Settings::default();
+        dispatch(
+            br#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"PREFIX ex: <http://example.org/>\nSELECT * {\n  ?s ex:p ?o OPTIONAL { ?o ex:q ?x }\n}"}}}"#,
+            &mut state,
+            &mut settings,
+        );
+        let request = br#"{"jsonrpc":"2.0","id":3,"method":"workspace/executeCommand","params":{"command":"fichu.showAlgebra","arguments":[{"uri":"file:///a.rq"}]}}"#;
+        let response: serde_json::Value =
+            serde_json::from_str(&dispatch(request, &mut state, &mut settings)[0]).unwrap();
+        assert_eq!(
+            response["result"]["algebra"],
+            "(prefix ((ex: <http://example.org/>))\n  (project (?s ?o ?x)\n    (leftjoin\n      (bgp (triple ?s ex:p ?o))\n      (bgp (triple ?o ex:q ?x)))))\n"
+        );
+
+        dispatch(
+            did_change(2, "SELECT * {").as_bytes(),
+            &mut state,
+            &mut settings,
+        );
+        assert!(dispatch(request, &mut state, &mut settings)[0]
+            .contains(r#""error":{"code":-32803,"message":"Syntax error at 1:11"}"#));
+        let invalid = br#"{"jsonrpc":"2.0","id":4,"method":"workspace/executeCommand","params":{"command":"fichu.showAlgebra","arguments":[]}}"#;
+        assert!(dispatch(invalid, &mut state, &mut settings)[0]
+            .contains(r#""message":"Invalid arguments for fichu.showAlgebra: missing arguments""#));
     }
 }
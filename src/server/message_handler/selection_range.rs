@@ -0,0 +1,142 @@
+use log::error;
+use tree_sitter::{Node, Point, Tree};
+
+use crate::{
+    lsp::{
+        textdocument::{Position, Range, TextDocumentItem},
+        SelectionRange, SelectionRangeRequest, SelectionRangeResponse,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+pub fn handle_selection_range_request(
+    request: SelectionRangeRequest,
+    state: &Snapshot,
+) -> Result<SelectionRangeResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let selection_ranges = match state.analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => request
+            .get_positions()
+            .iter()
+            .map(|position| selection_range(document, tree, position))
+            .collect(),
+        _ => {
+            error!("Requested selection ranges for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(SelectionRangeResponse::new(
+        request.get_id(),
+        selection_ranges,
+    ))
+}
+
+/// Builds the chain of selection ranges from the token at `position` up to the root,
+/// by walking up the parents in the syntax tree.
+pub(super) fn selection_range(
+    document: &TextDocumentItem,
+    tree: &Tree,
+    position: &Position,
+) -> SelectionRange {
+    let mut nodes: Vec<Node> = vec![];
+    let mut current = token_at(tree, document.point(position));
+    while let Some(node) = current {
+        // NOTE: Nodes that span the same range as their child would not expand the selection.
+        if nodes
+            .last()
+            .is_none_or(|last| last.byte_range() != node.byte_range())
+        {
+            nodes.push(node);
+        }
+        current = node.parent();
+    }
+    nodes
+        .into_iter()
+        .rev()
+        .fold(None, |parent, node| {
+            Some(SelectionRange {
+                range: document.range(node),
+                parent: parent.map(Box::new),
+            })
+        })
+        .unwrap_or(SelectionRange {
+            range: Range::new(
+                position.line,
+                position.character,
+                position.line,
+                position.character,
+            ),
+            parent: None,
+        })
+}
+
+/// The token at `point`. If the point is right behind a token, that token is returned.
+pub(super) fn token_at(tree: &Tree, point: Point) -> Option<Node<'_>> {
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    if node.child_count() == 0 || point.column == 0 {
+        return Some(node);
+    }
+    let before = Point::new(point.row, point.column - 1);
+    tree.root_node()
+        .descendant_for_point_range(before, before)
+        .filter(|token| token.child_count() == 0 && token.end_position() == point)
+        .or(Some(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use crate::lsp::{
+        textdocument::{Position, TextDocumentItem},
+        SelectionRange,
+    };
+
+    use super::selection_range;
+
+    #[test]
+    fn expand_selection() {
+        let text = "SELECT * WHERE {\n  ?a <p> ?b ;\n     <q> ?c\n}";
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        let mut selection = Some(selection_range(
+            &TextDocumentItem::new("uri", text),
+            &tree,
+            &Position::new(2, 11),
+        ));
+        let mut ranges = vec![];
+        while let Some(SelectionRange { range, parent }) = selection {
+            ranges.push((
+                range.start.line,
+                range.start.character,
+                range.end.line,
+                range.end.character,
+            ));
+            selection = parent.map(|parent| *parent);
+        }
+        assert_eq!(
+            ranges,
+            vec![
+                // token
+                (2, 9, 2, 11),
+                // property list
+                (1, 5, 2, 11),
+                // triple
+                (1, 2, 2, 11),
+                // group
+                (0, 15, 3, 1),
+                // where clause
+                (0, 9, 3, 1),
+                // query
+                (0, 0, 3, 1),
+            ]
+        );
+    }
+}
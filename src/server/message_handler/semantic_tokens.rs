@@ -0,0 +1,430 @@
+use log::error;
+use tree_sitter::{Node, Point};
+
+use crate::{
+    analysis::{variable_name, AnalysisState},
+    lsp::{
+        textdocument::{Range, TextDocumentItem},
+        SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+        SemanticTokensDeltaRequest, SemanticTokensDeltaResponse, SemanticTokensDeltaResult,
+        SemanticTokensEdit, SemanticTokensFullRequest, SemanticTokensRangeRequest,
+        SemanticTokensResponse,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::{ServerState, Snapshot},
+};
+
+/// Keywords that are the name of a builtin function when they start one of these nodes.
+const FUNCTION_KINDS: [&str; 5] = [
+    "BuildInCall",
+    "Aggregate",
+    "RegexExpression",
+    "SubstringExpression",
+    "String_replace_expression",
+];
+
+/// A single semantic token. Columns are in the negotiated position encoding.
+#[derive(Debug, PartialEq)]
+struct SemanticToken {
+    line: u32,
+    start: u32,
+    length: u32,
+    token_type: SemanticTokenType,
+    modifiers: u32,
+}
+
+pub fn handle_semantic_tokens_full_request(
+    request: SemanticTokensFullRequest,
+    state: &mut ServerState,
+) -> Result<SemanticTokensResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let data = encode(&document_tokens(&state.analysis_state, uri)?);
+    let semantic_tokens = remember(state, uri, data);
+    Ok(SemanticTokensResponse::new(
+        request.get_id(),
+        semantic_tokens,
+    ))
+}
+
+pub fn handle_semantic_tokens_range_request(
+    request: SemanticTokensRangeRequest,
+    state: &Snapshot,
+) -> Result<SemanticTokensResponse, ResponseError> {
+    let range = request.get_range();
+    let tokens: Vec<SemanticToken> =
+        document_tokens(&state.analysis_state, request.get_document_uri())?
+            .into_iter()
+            .filter(|token| is_in_range(token, range))
+            .collect();
+    Ok(SemanticTokensResponse::new(
+        request.get_id(),
+        SemanticTokens {
+            result_id: None,
+            data: encode(&tokens),
+        },
+    ))
+}
+
+pub fn handle_semantic_tokens_delta_request(
+    request: SemanticTokensDeltaRequest,
+    state: &mut ServerState,
+) -> Result<SemanticTokensDeltaResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let data = encode(&document_tokens(&state.analysis_state, uri)?);
+    let previous = state
+        .semantic_tokens
+        .get(uri)
+        .filter(|previous| previous.result_id.as_ref() == Some(request.get_previous_result_id()))
+        .map(|previous| edits(&previous.data, &data));
+    let semantic_tokens = remember(state, uri, data);
+    Ok(SemanticTokensDeltaResponse::new(
+        request.get_id(),
+        match (previous, semantic_tokens.result_id) {
+            (Some(edits), Some(result_id)) => {
+                SemanticTokensDeltaResult::Delta(SemanticTokensDelta { result_id, edits })
+            }
+            (_, result_id) => SemanticTokensDeltaResult::Full(SemanticTokens {
+                result_id,
+                data: semantic_tokens.data,
+            }),
+        },
+    ))
+}
+
+fn document_tokens(
+    analysis_state: &AnalysisState,
+    uri: &str,
+) -> Result<Vec<SemanticToken>, ResponseError> {
+    match analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => Ok(semantic_tokens(tree.root_node(), document)),
+        _ => {
+            error!("Requested semantic tokens for unknown document: {}", uri);
+            Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ))
+        }
+    }
+}
+
+/// Stores the tokens under a new result id, so that the next delta request can refer to them.
+fn remember(state: &mut ServerState, uri: &str, data: Vec<u32>) -> SemanticTokens {
+    state.semantic_tokens_result_id += 1;
+    let semantic_tokens = SemanticTokens {
+        result_id: Some(state.semantic_tokens_result_id.to_string()),
+        data,
+    };
+    state
+        .semantic_tokens
+        .insert(uri.to_string(), semantic_tokens.clone());
+    semantic_tokens
+}
+
+fn is_in_range(token: &SemanticToken, range: &Range) -> bool {
+    (token.line, token.start + token.length) > (range.start.line, range.start.character)
+        && (token.line, token.start) < (range.end.line, range.end.character)
+}
+
+/// Collects the semantic tokens of the whole tree, in document order.
+fn semantic_tokens(root: Node, document: &TextDocumentItem) -> Vec<SemanticToken> {
+    let mut tokens = vec![];
+    collect_tokens(root, document, &mut tokens);
+    tokens
+}
+
+fn collect_tokens(node: Node, document: &TextDocumentItem, tokens: &mut Vec<SemanticToken>) {
+    match classify(node, &document.text) {
+        Some((token_type, modifiers)) => push_token(node, token_type, modifiers, document, tokens),
+        None => {
+            for child in node.children(&mut node.walk()) {
+                collect_tokens(child, document, tokens);
+            }
+        }
+    }
+}
+
+/// The token type and modifiers of a node, `None` if the children should be classified instead.
+fn classify(node: Node, text: &str) -> Option<(SemanticTokenType, u32)> {
+    let token_type = match node.kind() {
+        "comment" => SemanticTokenType::Comment,
+        "VAR" => {
+            let modifier = match is_projected(node, text) {
+                true => SemanticTokenModifier::Projected,
+                false => SemanticTokenModifier::Local,
+            };
+            return Some((SemanticTokenType::Variable, modifier.bit()));
+        }
+        "IRIREF" | "PrefixedName" if is_datatype(node) => SemanticTokenType::Type,
+        "IRIREF" | "PrefixedName" if is_function_name(node) => SemanticTokenType::Function,
+        "IRIREF" => SemanticTokenType::Iri,
+        "PNAME_NS" => SemanticTokenType::Namespace,
+        "PN_LOCAL" => SemanticTokenType::Property,
+        "STRING_LITERAL" | "STRING_LITERAL_LONG" => SemanticTokenType::String,
+        "LANGTAG" => SemanticTokenType::Decorator,
+        "INTEGER" | "DECIMAL" | "DOUBLE" => SemanticTokenType::Number,
+        "BLANK_NODE_LABEL" | "ANON" => SemanticTokenType::BlankNode,
+        "boolean_literal" => SemanticTokenType::Keyword,
+        kind if !node.is_named() && is_keyword(kind) => {
+            match node.prev_sibling().is_none()
+                && node
+                    .parent()
+                    .is_some_and(|parent| FUNCTION_KINDS.contains(&parent.kind()))
+            {
+                true => SemanticTokenType::Function,
+                false => SemanticTokenType::Keyword,
+            }
+        }
+        _ => return None,
+    };
+    Some((token_type, 0))
+}
+
+fn is_keyword(kind: &str) -> bool {
+    kind.chars().any(|char| char.is_ascii_alphabetic())
+        && kind
+            .chars()
+            .all(|char| char.is_ascii_alphabetic() || char == '_')
+}
+
+fn is_datatype(node: Node) -> bool {
+    node.prev_sibling()
+        .is_some_and(|sibling| sibling.kind() == "^^")
+}
+
+fn is_function_name(node: Node) -> bool {
+    node.parent()
+        .is_some_and(|parent| parent.kind() == "FunctionCall")
+}
+
+/// Whether the variable is projected by the closest enclosing (sub-)select.
+/// Variables outside of select queries are never projected.
+fn is_projected(var: Node, text: &str) -> bool {
+    let mut ancestor = var.parent();
+    while let Some(node) = ancestor {
+        if matches!(node.kind(), "SelectQuery" | "SubSelect") {
+            let Some(select_clause) = node
+                .children(&mut node.walk())
+                .find(|child| child.kind() == "SelectClause")
+            else {
+                return false;
+            };
+            let name = variable_name(var, text);
+            let projected = select_clause
+                .children(&mut select_clause.walk())
+                .any(|child| match child.kind() {
+                    "*" => true,
+                    "VAR" => variable_name(child, text) == name,
+                    "assignment" => child
+                        .child_by_field_name("bound_variable")
+                        .is_some_and(|bound| variable_name(bound, text) == name),
+                    _ => false,
+                });
+            return projected;
+        }
+        ancestor = node.parent();
+    }
+    false
+}
+
+/// Adds a token for the node. Tokens that span multiple lines are split into one token per line.
+fn push_token(
+    node: Node,
+    token_type: SemanticTokenType,
+    modifiers: u32,
+    document: &TextDocumentItem,
+    tokens: &mut Vec<SemanticToken>,
+) {
+    let start = node.start_position();
+    let end = node.end_position();
+    for row in start.row..=end.row {
+        let from = if row == start.row { start.column } else { 0 };
+        // NOTE: Columns behind the end of the line are clamped to the end of the line.
+        let to = if row == end.row {
+            end.column
+        } else {
+            usize::MAX
+        };
+        let from = document.position(Point::new(row, from)).character;
+        let to = document.position(Point::new(row, to)).character;
+        if to > from {
+            tokens.push(SemanticToken {
+                line: row as u32,
+                start: from,
+                length: to - from,
+                token_type,
+                modifiers,
+            });
+        }
+    }
+}
+
+/// Encodes the tokens relative to their predecessor, as specified by the LSP.
+fn encode(tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let (mut line, mut start) = (0, 0);
+    for token in tokens {
+        if token.line != line {
+            start = 0;
+        }
+        data.extend([
+            token.line - line,
+            token.start - start,
+            token.length,
+            SemanticTokenType::ALL
+                .iter()
+                .position(|token_type| *token_type == token.token_type)
+                .expect("every token type should be in the legend") as u32,
+            token.modifiers,
+        ]);
+        line = token.line;
+        start = token.start;
+    }
+    data
+}
+
+/// A single edit that replaces everything between the common prefix and suffix of both token sets.
+fn edits(previous: &[u32], current: &[u32]) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    if prefix == previous.len() && prefix == current.len() {
+        return vec![];
+    }
+    vec![SemanticTokensEdit {
+        start: prefix as u32,
+        delete_count: (previous.len() - prefix - suffix) as u32,
+        data: current[prefix..current.len() - suffix].to_vec(),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use crate::lsp::{
+        textdocument::TextDocumentItem, PositionEncodingKind, SemanticTokenModifier,
+        SemanticTokenType,
+    };
+
+    use super::{edits, encode, semantic_tokens, SemanticToken};
+
+    fn tokens(text: &str) -> Vec<SemanticToken> {
+        tokens_in(text, PositionEncodingKind::Utf16)
+    }
+
+    fn tokens_in(text: &str, position_encoding: PositionEncodingKind) -> Vec<SemanticToken> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        let mut document = TextDocumentItem::new("uri", text);
+        document.position_encoding = position_encoding;
+        semantic_tokens(tree.root_node(), &document)
+    }
+
+    fn types(text: &str) -> Vec<(u32, u32, u32, SemanticTokenType)> {
+        tokens(text)
+            .into_iter()
+            .map(|token| (token.line, token.start, token.length, token.token_type))
+            .collect()
+    }
+
+    #[test]
+    fn token_types() {
+        use SemanticTokenType::*;
+        assert_eq!(
+            types("PREFIX wd: <x>\nSELECT * { ?a wd:Q5 \"x\"@en, \"1\"^^<i>, 2, _:b } # c"),
+            vec![
+                (0, 0, 6, Keyword),
+                (0, 7, 3, Namespace),
+                (0, 11, 3, Iri),
+                (1, 0, 6, Keyword),
+                (1, 11, 2, Variable),
+                (1, 14, 3, Namespace),
+                (1, 17, 2, Property),
+                (1, 20, 3, String),
+                (1, 23, 3, Decorator),
+                (1, 28, 3, String),
+                (1, 33, 3, Type),
+                (1, 38, 1, Number),
+                (1, 41, 3, BlankNode),
+                (1, 47, 3, Comment),
+            ]
+        );
+        assert_eq!(
+            types("SELECT (COUNT(?a) AS ?b) {}")[1..3]
+                .iter()
+                .map(|token| token.3)
+                .collect::<Vec<_>>(),
+            vec![Function, Variable]
+        );
+    }
+
+    #[test]
+    fn projected_variables() {
+        let modifiers: Vec<u32> = tokens("SELECT ?a { ?a ?b ?c { SELECT ?b {} } }")
+            .into_iter()
+            .filter(|token| token.token_type == SemanticTokenType::Variable)
+            .map(|token| token.modifiers)
+            .collect();
+        let projected = SemanticTokenModifier::Projected.bit();
+        let local = SemanticTokenModifier::Local.bit();
+        assert_eq!(
+            modifiers,
+            vec![projected, projected, local, local, projected]
+        );
+    }
+
+    #[test]
+    fn utf16_columns() {
+        let tokens = types("SELECT * { ?b ?c \"🦀\", ?ä }");
+        assert_eq!(tokens[3], (0, 17, 4, SemanticTokenType::String));
+        assert_eq!(tokens[4], (0, 23, 2, SemanticTokenType::Variable));
+    }
+
+    #[test]
+    fn utf8_columns() {
+        let tokens: Vec<(u32, u32)> =
+            tokens_in("SELECT * { ?b ?c \"🦀\", ?ä }", PositionEncodingKind::Utf8)
+                .into_iter()
+                .map(|token| (token.start, token.length))
+                .collect();
+        assert_eq!(tokens[3..], [(17, 6), (25, 3)]);
+    }
+
+    #[test]
+    fn multiline_tokens() {
+        let tokens = types("SELECT * {\n?a ?b \"\"\"one\ntwo\"\"\" }");
+        assert_eq!(tokens[3], (1, 6, 6, SemanticTokenType::String));
+        assert_eq!(tokens[4], (2, 0, 6, SemanticTokenType::String));
+    }
+
+    #[test]
+    fn relative_encoding() {
+        assert_eq!(
+            encode(&tokens("SELECT ?a\n{ ?a }")),
+            vec![0, 0, 6, 0, 0, 0, 7, 2, 2, 1, 1, 2, 2, 2, 1]
+        );
+    }
+
+    #[test]
+    fn delta() {
+        let previous = vec![0, 0, 6, 0, 0, 0, 7, 2, 2, 1];
+        let current = vec![0, 0, 6, 0, 0, 1, 7, 2, 2, 1];
+        let edits = edits(&previous, &current);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].start, 5);
+        assert_eq!(edits[0].delete_count, 1);
+        assert_eq!(edits[0].data, vec![1]);
+        assert!(super::edits(&current, &current).is_empty());
+    }
+}
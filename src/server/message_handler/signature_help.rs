@@ -0,0 +1,184 @@
+use log::error;
+use tree_sitter::{Node, Point};
+
+use crate::{
+    lsp::{
+        ParameterInformation, SignatureHelp, SignatureHelpRequest, SignatureHelpResponse,
+        SignatureInformation,
+    },
+    rpc::{ErrorCode, ResponseError},
+    server::Snapshot,
+};
+
+use super::builtins::{self, Builtin};
+
+/// Nodes whose text is a single token for the purpose of finding the call at the cursor.
+const ATOMIC_KINDS: [&str; 5] = ["PrefixedName", "IRIREF", "String", "VAR", "NIL"];
+
+pub fn handle_signature_help_request(
+    request: SignatureHelpRequest,
+    state: &Snapshot,
+) -> Result<SignatureHelpResponse, ResponseError> {
+    let uri = request.get_document_uri();
+    let signature_help = match state.analysis_state.get_state(uri) {
+        Some((document, Some(tree))) => signature_help(
+            tree.root_node(),
+            &document.text,
+            document.point(request.get_position()),
+        ),
+        _ => {
+            error!("Requested signature help for unknown document: {}", uri);
+            return Err(ResponseError::new(
+                ErrorCode::RequestFailed,
+                format!("Unknown document: {}", uri),
+            ));
+        }
+    };
+    Ok(SignatureHelpResponse::new(request.get_id(), signature_help))
+}
+
+/// Finds the call of a builtin, aggregate or XSD cast around `point`.
+///
+/// While typing, the call is usually not complete and the tree contains errors.
+/// So instead of searching for `BuildInCall`, `Aggregate` or `FunctionCall` nodes,
+/// the tokens before the cursor are scanned backwards for an unclosed `(`.
+pub(super) fn signature_help(root: Node, text: &str, point: Point) -> Option<SignatureHelp> {
+    let mut tokens = vec![];
+    collect_tokens(root, point, &mut tokens);
+    let mut depth = 0;
+    let mut active_parameter = 0;
+    let mut open_parenthesis = None;
+    for (index, token) in tokens.iter().enumerate().rev() {
+        match token.kind() {
+            // NOTE: The cursor is between the parentheses of an empty argument list.
+            "NIL" if token.start_position() < point && point < token.end_position() => {
+                open_parenthesis = Some(index);
+                break;
+            }
+            ")" => depth += 1,
+            "(" if depth == 0 => {
+                open_parenthesis = Some(index);
+                break;
+            }
+            "(" => depth -= 1,
+            "," | ";" if depth == 0 => active_parameter += 1,
+            "{" | "}" | "." => return None,
+            _ => {}
+        }
+    }
+    let name = tokens.get(open_parenthesis?.checked_sub(1)?)?;
+    let name_text = name.utf8_text(text.as_bytes()).ok()?;
+    let builtin = match name.kind() {
+        "PrefixedName" | "IRIREF" => builtins::find_cast(name_text),
+        _ if !name.is_named() => builtins::find(name_text),
+        _ => None,
+    }?;
+    Some(SignatureHelp {
+        active_parameter: match builtin.variadic {
+            true => active_parameter.min(builtin.parameters.len().saturating_sub(1) as u32),
+            false => active_parameter,
+        },
+        signatures: vec![signature_information(builtin)],
+        active_signature: 0,
+    })
+}
+
+/// Collects the tokens that start before `point`, in document order.
+fn collect_tokens<'a>(node: Node<'a>, point: Point, tokens: &mut Vec<Node<'a>>) {
+    if node.start_position() >= point || node.is_missing() || node.kind() == "comment" {
+        return;
+    }
+    if node.child_count() == 0 || ATOMIC_KINDS.contains(&node.kind()) {
+        tokens.push(node);
+        return;
+    }
+    for child in node.children(&mut node.walk()) {
+        collect_tokens(child, point, tokens);
+    }
+}
+
+fn signature_information(builtin: &Builtin) -> SignatureInformation {
+    let (label, offsets) = builtin.signature();
+    SignatureInformation {
+        label,
+        documentation: format!("{}\n\n{}", builtin.description, builtin.link()),
+        parameters: builtin
+            .parameters
+            .iter()
+            .zip(offsets)
+            .map(|(parameter, label)| ParameterInformation {
+                label,
+                documentation: parameter.documentation.to_string(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::{Parser, Point};
+
+    use super::signature_help;
+
+    /// The signature label and active parameter at the `|` in `text`.
+    fn help(text: &str) -> Option<(String, u32)> {
+        let (line, column) = text
+            .lines()
+            .enumerate()
+            .find_map(|(line, content)| content.find('|').map(|column| (line, column)))
+            .unwrap();
+        let text = text.replace('|', "");
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_sparql::language())
+            .unwrap();
+        let tree = parser.parse(&text, None).unwrap();
+        signature_help(tree.root_node(), &text, Point::new(line, column))
+            .map(|help| (help.signatures[0].label.clone(), help.active_parameter))
+    }
+
+    #[test]
+    fn while_typing() {
+        let (label, active) = help("SELECT * { FILTER(REGEX(|").unwrap();
+        assert!(label.starts_with("xsd:boolean REGEX("));
+        assert_eq!(active, 0);
+        assert_eq!(help("SELECT * { FILTER(regex(?a, \"x\", |").unwrap().1, 2);
+        assert_eq!(help("SELECT (GROUP_CONCAT(?a; |").unwrap().1, 1);
+    }
+
+    #[test]
+    fn complete_calls() {
+        assert_eq!(
+            help("SELECT * {\n  BIND(SUBSTR(?a, STRLEN(?b), |2) AS ?c)\n}").unwrap(),
+            (
+                "string literal SUBSTR(string literal source, xsd:integer startingLoc, [xsd:integer length])"
+                    .to_string(),
+                2
+            )
+        );
+        assert_eq!(help("SELECT * { BIND(STRLEN(|?b) AS ?c) }").unwrap().1, 0);
+        assert_eq!(help("SELECT * { BIND(CONCAT(|) AS ?d) }").unwrap().1, 0);
+        assert_eq!(
+            help("SELECT * { BIND(CONCAT(?a, ?b, |?c) AS ?d) }")
+                .unwrap()
+                .1,
+            0
+        );
+        assert_eq!(
+            help("SELECT (COUNT(DISTINCT |?a) AS ?b) {}").unwrap().0,
+            "xsd:integer COUNT(expression)"
+        );
+        assert_eq!(
+            help("SELECT * { BIND(xsd:integer(|?a) AS ?b) }").unwrap().0,
+            "xsd:integer xsd:integer(RDF term term)"
+        );
+    }
+
+    #[test]
+    fn outside_of_calls() {
+        assert_eq!(help("SELECT * { FILTER(|"), None);
+        assert_eq!(help("SELECT * { BIND(STR(?a)|"), None);
+        assert_eq!(help("SELECT * { BIND(ex:f(|?a) AS ?b) }"), None);
+        assert_eq!(help("SELECT * { ?a ?b |?c }"), None);
+    }
+}
@@ -1,23 +1,32 @@
-mod configuration;
+pub(crate) mod configuration;
 mod message_handler;
+mod worker;
 
+use crate::wasm::{from_js, JsSettings};
 use crate::{
     analysis::AnalysisState,
     lsp::{
-        textdocument::TextDocumentItem, PublishDiagnosticsNotification, PublishDiagnosticsPrarams,
+        textdocument::TextDocumentItem, ClientCapabilities, SemanticTokens,
         TextDocumentContentChangeEvent,
     },
-    rpc::{BaseMessage, Header},
+    rpc::{self, ErrorCode, ErrorResponse, Frame, RequestId, ResponseError},
 };
 use configuration::Settings;
 use log::{error, info};
-use message_handler::{collect_diagnostics, dispatch};
+use message_handler::{complete, dispatch, handle, publish_diagnostics, Handled};
+use worker::{Finished, Job, WorkerPool};
 
-pub use message_handler::format_raw;
+pub use message_handler::{check, format, format_raw, FormatError};
+pub(crate) use message_handler::{check_with_encoding, completions, hover};
 
 use std::{
-    io::{self, BufReader, Read, Write},
+    collections::{HashMap, HashSet},
+    io::{self, BufRead, BufReader, Write},
+    num::NonZeroUsize,
     process::exit,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
 };
 
 use wasm_bindgen::prelude::*;
@@ -31,12 +40,7 @@ pub struct Server {
 #[wasm_bindgen]
 impl Server {
     pub fn new() -> Self {
-        // Load configuration
-        let config = config::Config::builder()
-            .add_source(config::File::with_name("fichu").required(false))
-            .build()
-            .unwrap();
-        let settings: Settings = config.try_deserialize().expect("could not load Settings");
+        let settings = Settings::load().expect("could not load Settings");
         info!("{:?}", settings);
         info!("Started LSP Server!!");
         Self {
@@ -45,90 +49,162 @@ impl Server {
         }
     }
 
-    pub fn handle_message(&mut self, message: Vec<u8>) -> Option<String> {
-        dispatch(&message, &mut self.state, &self.settings)
+    /// Replaces the settings. In the browser there is no settings file to load them from.
+    pub fn set_settings(&mut self, settings: JsSettings) -> Result<(), JsError> {
+        self.settings = from_js(Some(settings))?;
+        info!("Updated settings: {:?}", self.settings);
+        Ok(())
     }
 
-    pub fn publish_diagnostic(&self, uri: String) -> String {
-        let notification: PublishDiagnosticsNotification = PublishDiagnosticsNotification {
-            base: BaseMessage::new("textDocument/publishDiagnostics".to_string()),
-            params: PublishDiagnosticsPrarams {
-                uri: uri.clone(),
-                diagnostics: collect_diagnostics(&self.state.analysis_state, &uri).collect(),
-            },
-        };
+    /// Handles a single message on the calling thread, requests are answered right away.
+    /// This is how the wasm build, that has no threads, is driven.
+    pub fn handle_message(&mut self, message: Vec<u8>) -> Vec<String> {
+        dispatch(&message, &mut self.state, &mut self.settings)
+    }
 
-        serde_json::to_string(&notification)
-            .expect("Could not parse PublishDiagnosticsNotification")
+    pub fn publish_diagnostic(&self, uri: String) -> String {
+        publish_diagnostics(&self.state.snapshot(&self.settings), &uri)
     }
 
     pub fn listen_stdio(&mut self) {
-        let stdin = io::stdin();
-        let reader = BufReader::new(stdin);
+        let stdin = BufReader::new(io::stdin());
+        let stdout = io::stdout();
+        if let Err(error) = self.listen(stdin, &mut stdout.lock()) {
+            error!("IO error while communicating with the client: {}", error);
+            exit(1);
+        }
+        error!("Stream ended unexpected while waiting for header, shutting down");
+        exit(1);
+    }
+}
+
+/// How long the document has to stay unchanged before its diagnostics are published.
+pub(crate) const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The maximal number of threads that answer requests.
+const MAX_WORKERS: usize = 4;
+
+/// What the main loop waits for.
+enum Event {
+    Frame(io::Result<Frame>),
+    Finished(Finished),
+}
+
+impl From<Finished> for Event {
+    fn from(finished: Finished) -> Self {
+        Event::Finished(finished)
+    }
+}
 
-        let mut bytes = reader.bytes();
-        let mut buffer = vec![];
+impl Server {
+    /// The documents whose published diagnostics are outdated, since they changed.
+    /// Always empty if the client pulls the diagnostics.
+    pub(crate) fn take_outdated_diagnostics(&mut self) -> HashSet<String> {
+        let changed_documents = self.state.take_changed_documents();
+        match self.state.client_capabilities.pull_diagnostics() {
+            true => HashSet::new(),
+            false => changed_documents,
+        }
+    }
 
-        loop {
-            match bytes.next() {
-                Some(Ok(byte)) => {
-                    buffer.push(byte);
+    /// Handles the messages from `reader` until the stream ends.
+    /// The messages are read in a separate thread, requests that only read the documents
+    /// are answered by a pool of workers and diagnostics are published
+    /// once the documents did not change for [`DIAGNOSTICS_DEBOUNCE`].
+    /// All changes of the state happen on the calling thread, in the order of the messages.
+    fn listen(
+        &mut self,
+        reader: impl BufRead + Send + 'static,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let reader_sender = sender.clone();
+        thread::spawn(move || {
+            let mut reader = reader;
+            loop {
+                let frame = rpc::read_message(&mut reader);
+                let end = matches!(frame, Ok(Frame::EndOfStream) | Err(_));
+                if reader_sender.send(Event::Frame(frame)).is_err() || end {
+                    break;
                 }
-                Some(Err(error)) => {
-                    error!("Error while reading byte: {}", error);
-                    panic!("{}", error);
+            }
+        });
+        let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let pool = WorkerPool::new(workers.min(MAX_WORKERS), sender);
+        // The number of jobs that were not answered yet.
+        let mut running = 0;
+        let mut end_of_stream = false;
+        let mut outdated_diagnostics: HashSet<String> = HashSet::new();
+        let mut deadline: Option<Instant> = None;
+        while !end_of_stream || running > 0 {
+            let event = match deadline {
+                Some(deadline) if !end_of_stream => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
                 }
-                None => {
-                    error!("Stream ended unexpected while waiting for header, shutting down");
-                    exit(1);
+                _ => receiver.recv().map_err(RecvTimeoutError::from),
+            };
+            match event {
+                Err(RecvTimeoutError::Timeout) => {
+                    let snapshot = self.state.snapshot(&self.settings);
+                    for uri in outdated_diagnostics.drain() {
+                        running += 1;
+                        pool.execute(Job::new(
+                            snapshot.clone(),
+                            None,
+                            uri.clone(),
+                            move |snapshot| Ok(publish_diagnostics(snapshot, &uri)),
+                        ));
+                    }
+                    deadline = None;
                 }
-            }
-            if buffer.ends_with(b"\r\n\r\n") {
-                let header = match Header::from_string(
-                    String::from_utf8(buffer.clone()).expect("valid utf8 bytes"),
-                ) {
-                    Ok(header) => header,
-                    Err(err) => {
-                        error!("Received error while parsing header: {err}, clearing buffer");
-                        buffer.clear();
-                        continue;
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                Ok(Event::Finished(finished)) => {
+                    running -= 1;
+                    for message in complete(finished, &mut self.state) {
+                        rpc::write_message(writer, &message)?;
                     }
-                };
-                buffer.clear();
-                for ele in 0..header.content_length {
-                    match bytes.next() {
-                        Some(Ok(byte)) => {
-                            buffer.push(byte);
-                        }
-                        Some(Err(err)) => {
-                            error!(
-                                "Error {} occured while reading byte {} of {}, clearing buffer",
-                                err, ele, header.content_length
-                            );
-                            buffer.clear();
-                            break;
-                        }
-                        None => {
-                            error!(
-                                "Byte stream endet after {} of {} bytes, clearing message buffer",
-                                ele, header.content_length
-                            );
-                            buffer.clear();
-                            break;
+                }
+                Ok(Event::Frame(frame)) => match frame? {
+                    Frame::Message(message) => {
+                        match handle(&message, &mut self.state, &mut self.settings) {
+                            Handled::Done(messages) => {
+                                for message in messages {
+                                    rpc::write_message(writer, &message)?;
+                                }
+                            }
+                            Handled::Deferred(job) => {
+                                running += 1;
+                                pool.execute(*job);
+                            }
                         }
                     }
-                }
-                match self.handle_message(buffer.clone()) {
-                    Some(response) => {
-                        print!("Content-Length: {}\r\n\r\n{}", response.len(), response);
-                        io::stdout().flush().expect("No IO errors or EOFs");
+                    Frame::Malformed(error) => {
+                        error!("Received malformed message: {}", error);
+                        let response = ErrorResponse::new(
+                            None,
+                            ResponseError::new(ErrorCode::ParseError, error),
+                        );
+                        rpc::write_message(
+                            writer,
+                            &serde_json::to_string(&response).expect("Responses are serializable"),
+                        )?;
                     }
-                    _ => {}
-                }
-
-                buffer.clear();
+                    Frame::EndOfStream => end_of_stream = true,
+                },
+            }
+            let changed_documents = self.take_outdated_diagnostics();
+            if !changed_documents.is_empty() {
+                outdated_diagnostics.extend(changed_documents);
+                deadline = Some(Instant::now() + DIAGNOSTICS_DEBOUNCE);
             }
         }
+        Ok(())
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -142,6 +218,27 @@ pub enum ServerStatus {
 pub struct ServerState {
     pub status: ServerStatus,
     pub analysis_state: AnalysisState,
+    /// The semantic tokens last sent for each document, to answer delta requests.
+    semantic_tokens: HashMap<String, SemanticTokens>,
+    semantic_tokens_result_id: u32,
+    /// Requests that are answered by a [`Job`] that did not finish yet.
+    pending_requests: HashSet<RequestId>,
+    /// Pending requests the client cancelled with `$/cancelRequest`.
+    cancelled_requests: HashSet<RequestId>,
+    pub(crate) client_capabilities: ClientCapabilities,
+    /// The id of the next request of the server to the client.
+    next_request_id: i64,
+    /// Documents that were opened or changed since the last [`ServerState::take_changed_documents`].
+    changed_documents: HashSet<String>,
+}
+
+/// An immutable copy of the state requests are answered from, that can be sent to worker threads.
+/// The documents are shared with the [`ServerState`], so taking a snapshot is cheap.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub analysis_state: AnalysisState,
+    pub(crate) client_capabilities: ClientCapabilities,
+    pub(crate) settings: Settings,
 }
 
 impl ServerState {
@@ -149,19 +246,297 @@ impl ServerState {
         ServerState {
             status: ServerStatus::Initializing,
             analysis_state: AnalysisState::new(),
+            semantic_tokens: HashMap::new(),
+            semantic_tokens_result_id: 0,
+            pending_requests: HashSet::new(),
+            cancelled_requests: HashSet::new(),
+            client_capabilities: ClientCapabilities::default(),
+            next_request_id: 0,
+            changed_documents: HashSet::new(),
         }
     }
 
+    pub(crate) fn snapshot(&self, settings: &Settings) -> Snapshot {
+        Snapshot {
+            analysis_state: self.analysis_state.clone(),
+            client_capabilities: self.client_capabilities.clone(),
+            settings: settings.clone(),
+        }
+    }
+
+    pub(crate) fn next_request_id(&mut self) -> RequestId {
+        self.next_request_id += 1;
+        RequestId::Integer(self.next_request_id)
+    }
+
     pub fn add_document(&mut self, document: TextDocumentItem) {
+        self.changed_documents.insert(document.uri.clone());
         self.analysis_state.add_document(document);
     }
 
     pub(crate) fn change_document(
         &mut self,
         document_uri: String,
+        version: u32,
         content_changes: Vec<TextDocumentContentChangeEvent>,
     ) {
+        self.changed_documents.insert(document_uri.clone());
         self.analysis_state
-            .change_document(document_uri, content_changes)
+            .change_document(document_uri, version, content_changes)
+    }
+
+    pub(crate) fn close_document(&mut self, document_uri: &str) {
+        self.changed_documents.remove(document_uri);
+        self.semantic_tokens.remove(document_uri);
+        self.analysis_state.remove_document(document_uri);
+    }
+
+    /// The documents whose diagnostics are outdated, since they changed.
+    pub(crate) fn take_changed_documents(&mut self) -> HashSet<String> {
+        std::mem::take(&mut self.changed_documents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        io::{BufReader, Cursor, Read},
+        thread,
+        time::Duration,
+    };
+
+    use proptest::prelude::*;
+
+    use crate::rpc::write_message;
+
+    use super::{Server, DIAGNOSTICS_DEBOUNCE, MAX_WORKERS};
+
+    fn listen(input: Vec<u8>) -> String {
+        let mut server = Server::new();
+        let mut output = Vec::new();
+        server.listen(Cursor::new(input), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// A stream of messages with a pause before each chunk.
+    struct Chunks(VecDeque<(Duration, Vec<u8>)>);
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.pop_front() {
+                Some((pause, chunk)) => {
+                    thread::sleep(pause);
+                    assert!(chunk.len() <= buf.len());
+                    buf[..chunk.len()].copy_from_slice(&chunk);
+                    Ok(chunk.len())
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    fn frame(message: &str) -> Vec<u8> {
+        let mut frame = Vec::new();
+        write_message(&mut frame, message).unwrap();
+        frame
+    }
+
+    fn did_change(version: u32, text: &str) -> Vec<u8> {
+        frame(&format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/didChange","params":{{"textDocument":{{"uri":"file:///a.rq","version":{}}},"contentChanges":[{{"text":"{}"}}]}}}}"#,
+            version, text
+        ))
+    }
+
+    #[test]
+    fn publish_diagnostics_debounced() {
+        let short = Duration::from_millis(10);
+        let long = DIAGNOSTICS_DEBOUNCE * 2;
+        let chunks = Chunks(VecDeque::from([
+            (
+                short,
+                frame(
+                    r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"processId":null,"clientInfo":{"name":"test"},"capabilities":{}}}"#,
+                ),
+            ),
+            (
+                short,
+                frame(
+                    r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT * {}"}}}"#,
+                ),
+            ),
+            (short, did_change(2, "SELECT * {")),
+            (short, did_change(3, "SELECT * {}}")),
+            (long, did_change(4, "SELECT * {")),
+            (long, Vec::new()),
+        ]));
+        let mut server = Server::new();
+        let mut output = Vec::new();
+        server.listen(BufReader::new(chunks), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let published: Vec<&str> = output
+            .split("Content-Length")
+            .filter(|message| message.contains("textDocument/publishDiagnostics"))
+            .collect();
+        assert_eq!(published.len(), 2);
+        assert!(published[0].contains(r#""uri":"file:///a.rq""#));
+        assert!(published[0].contains(r#""range":{"start":{"line":0,"character":11}"#));
+        assert!(published[1].contains(r#""diagnostics":[{"#));
+    }
+
+    #[test]
+    fn malformed_messages() {
+        let output = listen(
+            b"Content-Length: 3\r\n\r\n{]}Content-Type: x\r\n\r\nContent-Length: 24\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":1}"
+                .to_vec(),
+        );
+        assert_eq!(
+            output
+                .matches(r#""id":null,"error":{"code":-32700"#)
+                .count(),
+            2
+        );
+        assert!(output.contains(r#""id":null,"error":{"code":-32600"#));
+    }
+
+    #[test]
+    fn answer_deferred_requests() {
+        let mut input = frame(
+            r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"capabilities":{"textDocument":{"diagnostic":{}}}}}"#,
+        );
+        input.extend(frame(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT * {}"}}}"#,
+        ));
+        for id in 1..=20 {
+            input.extend(frame(&format!(
+                r#"{{"jsonrpc":"2.0","id":{},"method":"textDocument/formatting","params":{{"textDocument":{{"uri":"file:///a.rq"}},"options":{{"tabSize":2,"insertSpaces":true}}}}}}"#,
+                id
+            )));
+        }
+        let output = listen(input);
+        // All requests are answered before the server stops at the end of the stream.
+        assert_eq!(output.matches(r#""newText":"#).count(), 20);
+        for id in 1..=20 {
+            assert!(output.contains(&format!(r#""id":{},"result":["#, id)));
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn answer_requests_while_querying() {
+        use crate::{endpoint::mock::serve_delayed, server::configuration::EndpointSettings};
+
+        // NOTE: There are more queries than workers, each waits for the endpoint.
+        let url = serve_delayed(
+            MAX_WORKERS + 1,
+            Duration::from_millis(500),
+            "text/csv",
+            "s\r\n",
+        );
+        let mut input = frame(
+            r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"capabilities":{"textDocument":{"diagnostic":{}}}}}"#,
+        );
+        input.extend(frame(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT * {}"}}}"#,
+        ));
+        for id in 1..=MAX_WORKERS + 1 {
+            input.extend(frame(&format!(
+                r#"{{"jsonrpc":"2.0","id":{},"method":"workspace/executeCommand","params":{{"command":"fichu.executeQuery","arguments":[{{"uri":"file:///a.rq"}}]}}}}"#,
+                id
+            )));
+        }
+        input.extend(frame(
+            r#"{"jsonrpc":"2.0","id":"format","method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.rq"},"options":{"tabSize":2,"insertSpaces":true}}}"#,
+        ));
+        let mut server = Server::new();
+        server.settings.endpoint = Some(EndpointSettings::new(&url));
+        let mut output = Vec::new();
+        server.listen(Cursor::new(input), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let formatted = output.find(r#""id":"format","result":["#).unwrap();
+        for id in 1..=MAX_WORKERS + 1 {
+            let results = output.find(&format!(r#""id":{},"result":{{"#, id)).unwrap();
+            assert!(formatted < results);
+        }
+    }
+
+    const METHODS: &[&str] = &[
+        "initialize",
+        "initialized",
+        "shutdown",
+        "$/cancelRequest",
+        "textDocument/didOpen",
+        "textDocument/didChange",
+        "textDocument/hover",
+        "textDocument/completion",
+        "textDocument/formatting",
+        "textDocument/diagnostic",
+        "textDocument/documentSymbol",
+        "textDocument/foldingRange",
+        "textDocument/selectionRange",
+        "textDocument/semanticTokens/full",
+        "textDocument/semanticTokens/full/delta",
+        "textDocument/semanticTokens/range",
+        "textDocument/signatureHelp",
+    ];
+
+    fn position() -> impl Strategy<Value = serde_json::Value> {
+        (0u32..4, 0u32..40)
+            .prop_map(|(line, character)| serde_json::json!({"line": line, "character": character}))
+    }
+
+    /// Messages with plausible but arbitrary parameters.
+    fn message() -> impl Strategy<Value = String> {
+        (
+            prop::sample::select(METHODS),
+            prop::option::of(0i64..4),
+            "[SELECT*WHERE{}?a:<>\"( ).\n;,éðŸ¦€]{0,24}",
+            position(),
+            position(),
+            any::<bool>(),
+        )
+            .prop_map(|(method, id, text, start, end, full_change)| {
+                let change = match full_change {
+                    true => serde_json::json!({"text": text}),
+                    false => serde_json::json!({"range": {"start": start, "end": end}, "text": text}),
+                };
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": {
+                        "processId": null,
+                        "clientInfo": {"name": "fuzzer"},
+                        "capabilities": {},
+                        "id": id,
+                        "textDocument": {"uri": "file:///fuzz.rq", "languageId": "sparql", "version": 1, "text": text},
+                        "contentChanges": [change],
+                        "position": start,
+                        "positions": [start, end],
+                        "range": {"start": start, "end": end},
+                        "previousResultId": "1",
+                        "options": {"tabSize": 2, "insertSpaces": true},
+                        "context": {"triggerKind": 1}
+                    }
+                })
+                .to_string()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+            listen(bytes);
+        }
+
+        #[test]
+        fn arbitrary_messages(messages in prop::collection::vec(message(), 0..16)) {
+            let mut input = Vec::new();
+            for message in messages {
+                crate::rpc::write_message(&mut input, &message).unwrap();
+            }
+            listen(input);
+        }
     }
 }
@@ -0,0 +1,230 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use log::error;
+
+use crate::rpc::{ErrorCode, ErrorResponse, RequestId, ResponseError};
+
+use super::Snapshot;
+
+type Work = Box<dyn FnOnce(&Snapshot) -> Result<String, ResponseError> + Send>;
+
+/// A message that is computed from a [`Snapshot`], possibly on another thread.
+pub(crate) struct Job {
+    id: Option<RequestId>,
+    /// The document the message is about and its version in the snapshot.
+    document: Option<(String, u32)>,
+    snapshot: Snapshot,
+    work: Work,
+    /// Whether the job runs on its own thread, instead of on a worker of the pool.
+    own_thread: bool,
+}
+
+impl Job {
+    /// A job that answers request `id`, or sends a notification if there is no `id`.
+    pub(crate) fn new(
+        snapshot: Snapshot,
+        id: Option<RequestId>,
+        uri: String,
+        work: impl FnOnce(&Snapshot) -> Result<String, ResponseError> + Send + 'static,
+    ) -> Self {
+        Self {
+            id,
+            document: snapshot
+                .analysis_state
+                .get_version(&uri)
+                .map(|version| (uri, version)),
+            snapshot,
+            work: Box::new(work),
+            own_thread: false,
+        }
+    }
+
+    /// A job that answers request `id`, even if the documents change in the meantime.
+    pub(crate) fn independent(
+        snapshot: Snapshot,
+        id: RequestId,
+        work: impl FnOnce(&Snapshot) -> Result<String, ResponseError> + Send + 'static,
+    ) -> Self {
+        Self {
+            id: Some(id),
+            document: None,
+            snapshot,
+            work: Box::new(work),
+            own_thread: false,
+        }
+    }
+
+    /// Runs the job on its own thread, e.g. since it waits for the network,
+    /// so that it does not keep the workers from answering other requests.
+    pub(crate) fn on_own_thread(self) -> Self {
+        Self {
+            own_thread: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn run(self) -> Finished {
+        let (work, snapshot) = (self.work, self.snapshot);
+        // A panicking handler must not take the worker down, the request is answered anyway.
+        let result =
+            panic::catch_unwind(AssertUnwindSafe(move || work(&snapshot))).unwrap_or_else(|_| {
+                Err(ResponseError::new(
+                    ErrorCode::RequestFailed,
+                    "Internal error while handling the message".to_string(),
+                ))
+            });
+        let messages = match (result, &self.id) {
+            (Ok(message), _) => vec![message],
+            (Err(error), Some(id)) => {
+                vec![
+                    serde_json::to_string(&ErrorResponse::new(Some(id.clone()), error))
+                        .expect("Responses are serializable"),
+                ]
+            }
+            (Err(error), None) => {
+                error!("Could not compute notification: {}", error.message);
+                vec![]
+            }
+        };
+        Finished {
+            id: self.id,
+            document: self.document,
+            messages,
+        }
+    }
+}
+
+/// The outgoing messages of a [`Job`], that are only sent if they are still wanted.
+pub(crate) struct Finished {
+    pub(crate) id: Option<RequestId>,
+    pub(crate) document: Option<(String, u32)>,
+    pub(crate) messages: Vec<String>,
+}
+
+/// A fixed number of threads that run [`Job`]s and send back what they [`Finished`].
+pub(crate) struct WorkerPool<T> {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    /// Where the jobs that run on their own thread send what they finished.
+    finished: mpsc::Sender<T>,
+}
+
+impl<T: From<Finished> + Send + 'static> WorkerPool<T> {
+    pub(crate) fn new(size: usize, finished: mpsc::Sender<T>) -> Self {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|_| {
+                let receiver = receiver.clone();
+                let finished = finished.clone();
+                thread::spawn(move || loop {
+                    // NOTE: The lock is released before the job runs.
+                    let job = receiver.lock().expect("Workers don't panic").recv();
+                    match job {
+                        Ok(job) => {
+                            if finished.send(job.run().into()).is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            jobs: Some(jobs),
+            workers,
+            finished,
+        }
+    }
+
+    pub(crate) fn execute(&self, job: Job) {
+        if job.own_thread {
+            let finished = self.finished.clone();
+            // NOTE: The thread is not joined, the results are dropped if the pool is gone.
+            thread::spawn(move || finished.send(job.run().into()));
+            return;
+        }
+        if let Some(jobs) = &self.jobs {
+            jobs.send(job).expect("Workers run as long as the pool");
+        }
+    }
+}
+
+impl<T> Drop for WorkerPool<T> {
+    fn drop(&mut self) {
+        // Closing the channel ends the workers once they are idle.
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                error!("A worker thread panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use crate::{
+        rpc::{ErrorCode, RequestId, ResponseError},
+        server::{configuration::Settings, ServerState},
+    };
+
+    use super::{Finished, Job, WorkerPool};
+
+    #[test]
+    fn run_jobs() {
+        let snapshot = ServerState::new().snapshot(&Settings::default());
+        let (sender, receiver) = mpsc::channel::<Finished>();
+        let pool = WorkerPool::new(2, sender);
+        pool.execute(Job::new(
+            snapshot.clone(),
+            Some(RequestId::Integer(1)),
+            "file:///a.rq".to_string(),
+            |_snapshot| Ok("answer".to_string()),
+        ));
+        pool.execute(Job::new(
+            snapshot.clone(),
+            Some(RequestId::Integer(2)),
+            "file:///a.rq".to_string(),
+            |_snapshot| {
+                Err(ResponseError::new(
+                    ErrorCode::RequestFailed,
+                    "failed".to_string(),
+                ))
+            },
+        ));
+        pool.execute(Job::new(
+            snapshot,
+            Some(RequestId::Integer(3)),
+            "file:///a.rq".to_string(),
+            |_snapshot| panic!("bug in a handler"),
+        ));
+        let mut finished: Vec<(Option<RequestId>, Vec<String>)> = receiver
+            .iter()
+            .take(3)
+            .map(|finished| (finished.id, finished.messages))
+            .collect();
+        finished.sort_by_key(|(id, _messages)| id.as_ref().map(ToString::to_string));
+        assert_eq!(
+            finished,
+            vec![
+                (Some(RequestId::Integer(1)), vec!["answer".to_string()]),
+                (
+                    Some(RequestId::Integer(2)),
+                    vec![r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32803,"message":"failed"}}"#.to_string()]
+                ),
+                (
+                    Some(RequestId::Integer(3)),
+                    vec![r#"{"jsonrpc":"2.0","id":3,"error":{"code":-32803,"message":"Internal error while handling the message"}}"#.to_string()]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    analysis::query_prefixes,
+    lsp::textdocument::Position,
+    results::{QueryResults, ResultsFormat},
+    server::{
+        check, completions as completions_at,
+        configuration::{FormatSettings, LintSettings},
+        hover as hover_at,
+    },
+};
+
+mod worker;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = r#"
+/** Settings that are not given keep their default. */
+export interface FormatSettings {
+  align_predicates?: boolean;
+  align_prefixes?: boolean;
+  separate_prolouge?: boolean;
+  capitalize_keywords?: boolean;
+  insert_spaces?: boolean;
+  tab_size?: number;
+  where_new_line?: boolean;
+  /** "refuse" (default) leaves queries with syntax errors alone, "partial" formats the parts without errors. */
+  on_syntax_error?: "refuse" | "partial";
+}
+
+export type LintLevel = "off" | "hint" | "info" | "warning" | "error";
+
+/** Maps lint rules and rule categories like "performance" to the level they are reported with. */
+export type LintSettings = Record<string, LintLevel>;
+
+export interface Settings {
+  format?: FormatSettings;
+  lint?: LintSettings;
+}
+
+/** `character` counts UTF-16 code units, like the indices of JavaScript strings. */
+export interface Position {
+  line: number;
+  character: number;
+}
+
+export interface Range {
+  start: Position;
+  end: Position;
+}
+
+export interface Diagnostic {
+  range: Range;
+  /** 1: error, 2: warning, 3: information, 4: hint */
+  severity: 1 | 2 | 3 | 4;
+  /** The name of the lint rule, or "syntax_error". */
+  code?: string;
+  source: string;
+  message: string;
+}
+
+export interface CompletionItem {
+  label: string;
+  /** The `CompletionItemKind` of the language server protocol, e.g. 6 for variables and 15 for snippets. */
+  kind: number;
+  detail: string;
+  insertText: string;
+  /** 1: plain text, 2: snippet */
+  insertTextFormat: 1 | 2;
+}
+
+/** The SPARQL 1.1 Query Results formats. */
+export type ResultsFormat = "json" | "xml" | "csv" | "tsv";
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "FormatSettings")]
+    pub type JsFormatSettings;
+
+    #[wasm_bindgen(typescript_type = "LintSettings")]
+    pub type JsLintSettings;
+
+    #[wasm_bindgen(typescript_type = "Settings")]
+    pub type JsSettings;
+
+    #[wasm_bindgen(typescript_type = "Diagnostic[]")]
+    pub type JsDiagnostics;
+
+    #[wasm_bindgen(typescript_type = "CompletionItem[]")]
+    pub type JsCompletionItems;
+
+    #[wasm_bindgen(typescript_type = "ResultsFormat")]
+    pub type JsResultsFormat;
+}
+
+/// Reads a settings object, `undefined` gives the default settings.
+pub(crate) fn from_js<T: DeserializeOwned + Default>(
+    value: Option<impl Into<JsValue>>,
+) -> Result<T, JsError> {
+    match value.map(Into::into) {
+        Some(value) if !value.is_undefined() && !value.is_null() => {
+            Ok(serde_wasm_bindgen::from_value(value)?)
+        }
+        _ => Ok(T::default()),
+    }
+}
+
+fn to_js<T: JsCast>(value: &impl Serialize) -> Result<T, JsError> {
+    Ok(value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())?
+        .unchecked_into())
+}
+
+/// Formats the query. Throws if the query has syntax errors, unless `on_syntax_error` is `"partial"`.
+#[wasm_bindgen]
+pub fn format(text: &str, settings: Option<JsFormatSettings>) -> Result<String, JsError> {
+    let settings: FormatSettings = from_js(settings)?;
+    Ok(crate::format(text, &settings)?)
+}
+
+/// The syntax errors and lint diagnostics of the query.
+#[wasm_bindgen]
+pub fn diagnostics(text: &str, settings: Option<JsLintSettings>) -> Result<JsDiagnostics, JsError> {
+    let settings: LintSettings = from_js(settings)?;
+    to_js(&check(text, &settings))
+}
+
+/// The completions at the position, `character` counts UTF-16 code units.
+#[wasm_bindgen]
+pub fn completions(text: &str, line: u32, character: u32) -> Result<JsCompletionItems, JsError> {
+    to_js(&completions_at(text, &Position::new(line, character)))
+}
+
+/// The markdown documentation of the token at the position, `character` counts UTF-16 code units.
+#[wasm_bindgen]
+pub fn hover(text: &str, line: u32, character: u32) -> Option<String> {
+    hover_at(text, &Position::new(line, character))
+}
+
+fn results_format(format: JsResultsFormat) -> Result<ResultsFormat, JsError> {
+    let format: JsValue = format.into();
+    Ok(format.as_string().unwrap_or_default().parse()?)
+}
+
+/// Converts query results between formats. Throws if the results are invalid.
+#[wasm_bindgen]
+pub fn convert_results(
+    results: &str,
+    from: JsResultsFormat,
+    to: JsResultsFormat,
+) -> Result<String, JsError> {
+    let results = QueryResults::parse(results, results_format(from)?)?;
+    Ok(results.serialize(results_format(to)?))
+}
+
+/// Renders query results as a table with aligned columns.
+/// IRIs are abbreviated with the prefixes the `query` declares.
+#[wasm_bindgen]
+pub fn results_table(
+    results: &str,
+    format: JsResultsFormat,
+    query: Option<String>,
+) -> Result<String, JsError> {
+    let results = QueryResults::parse(results, results_format(format)?)?;
+    let prefixes = query.as_deref().map(query_prefixes).unwrap_or_default();
+    Ok(results.table(&prefixes))
+}
@@ -0,0 +1,247 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use js_sys::{Function, Reflect, JSON};
+use log::{error, info};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    init_language_server,
+    server::{Server, DIAGNOSTICS_DEBOUNCE},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Anything messages can be exchanged with through `postMessage` and `onmessage`.
+    #[wasm_bindgen(typescript_type = "MessagePort | Worker | DedicatedWorkerGlobalScope")]
+    pub type Port;
+
+    #[wasm_bindgen(method, catch, js_name = postMessage)]
+    fn post_message(this: &Port, message: &JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, setter)]
+    fn set_onmessage(this: &Port, handler: Option<&Function>);
+
+    // NOTE: Browsers return a number, Node.js a `Timeout` object.
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &Function, timeout: u32) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(timer: &JsValue);
+}
+
+/// Runs the language server inside a Web Worker, on the messages posted to the worker.
+/// Call this from the worker script, after the wasm module was initialized.
+#[wasm_bindgen]
+pub fn listen_worker() {
+    listen_port(js_sys::global().unchecked_into(), init_language_server());
+}
+
+/// Runs `server` on the messages posted to `port`.
+/// The messages are JSON-RPC objects, as `BrowserMessageReader` and `BrowserMessageWriter`
+/// of `vscode-languageserver-protocol/browser` exchange them. Serialized messages are accepted as well.
+/// Diagnostics are published once the documents did not change for a moment.
+#[wasm_bindgen]
+pub fn listen_port(port: Port, server: Server) {
+    let connection = Rc::new(RefCell::new(Connection {
+        server,
+        port,
+        outdated_diagnostics: HashSet::new(),
+        timer: None,
+    }));
+    let on_timeout = Closure::<dyn FnMut()>::new({
+        let connection = connection.clone();
+        move || connection.borrow_mut().publish_diagnostics()
+    });
+    let on_message = Closure::<dyn FnMut(JsValue)>::new({
+        let connection = connection.clone();
+        let on_timeout: Function = on_timeout.as_ref().clone().unchecked_into();
+        move |event: JsValue| {
+            let data = Reflect::get(&event, &"data".into()).unwrap_or_default();
+            connection.borrow_mut().receive(data, &on_timeout)
+        }
+    });
+    connection
+        .borrow()
+        .port
+        .set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // NOTE: The server runs as long as the port, so the handlers are never dropped.
+    on_message.forget();
+    on_timeout.forget();
+}
+
+struct Connection {
+    server: Server,
+    port: Port,
+    /// Documents whose diagnostics are published when the timer fires.
+    outdated_diagnostics: HashSet<String>,
+    timer: Option<JsValue>,
+}
+
+impl Connection {
+    fn receive(&mut self, data: JsValue, on_timeout: &Function) {
+        let method = Reflect::get(&data, &"method".into())
+            .ok()
+            .and_then(|method| method.as_string());
+        if method.as_deref() == Some("exit") {
+            info!("recieved exit notification, shutting down!");
+            self.close();
+            return;
+        }
+        let message = match data.as_string() {
+            Some(message) => message,
+            None => match JSON::stringify(&data) {
+                Ok(message) => String::from(message),
+                Err(error) => {
+                    error!("Could not serialize posted message: {:?}", error);
+                    return;
+                }
+            },
+        };
+        for message in self.server.handle_message(message.into_bytes()) {
+            self.send(&message);
+        }
+        let outdated_diagnostics = self.server.take_outdated_diagnostics();
+        if !outdated_diagnostics.is_empty() {
+            self.outdated_diagnostics.extend(outdated_diagnostics);
+            if let Some(timer) = self.timer.take() {
+                clear_timeout(&timer);
+            }
+            self.timer = Some(set_timeout(
+                on_timeout,
+                DIAGNOSTICS_DEBOUNCE.as_millis() as u32,
+            ));
+        }
+    }
+
+    fn publish_diagnostics(&mut self) {
+        self.timer = None;
+        for uri in std::mem::take(&mut self.outdated_diagnostics) {
+            let message = self.server.publish_diagnostic(uri);
+            self.send(&message);
+        }
+    }
+
+    fn send(&self, message: &str) {
+        if let Err(error) =
+            JSON::parse(message).and_then(|message| self.port.post_message(&message))
+        {
+            error!("Could not post message: {:?}", error);
+        }
+    }
+
+    /// Stops listening and closes the port, or the worker if the port is its global scope.
+    fn close(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            clear_timeout(&timer);
+        }
+        self.port.set_onmessage(None);
+        match Reflect::get(&self.port, &"close".into()).map(JsCast::dyn_into::<Function>) {
+            Ok(Ok(close)) => {
+                if let Err(error) = close.call0(&self.port) {
+                    error!("Could not close the port: {:?}", error);
+                }
+            }
+            _ => info!("The port can not be closed"),
+        }
+    }
+}
+
+#[cfg(all(test, target_family = "wasm"))]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use js_sys::{Promise, Reflect, JSON};
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::init_language_server;
+
+    use super::{listen_port, set_timeout, Port};
+
+    #[wasm_bindgen]
+    extern "C" {
+        type MessageChannel;
+
+        #[wasm_bindgen(constructor)]
+        fn new() -> MessageChannel;
+
+        #[wasm_bindgen(method, getter)]
+        fn port1(this: &MessageChannel) -> Port;
+
+        #[wasm_bindgen(method, getter)]
+        fn port2(this: &MessageChannel) -> Port;
+    }
+
+    async fn sleep(milliseconds: u32) {
+        let promise = Promise::new(&mut |resolve, _reject| {
+            set_timeout(&resolve, milliseconds);
+        });
+        JsFuture::from(promise).await.unwrap();
+    }
+
+    fn post(port: &Port, message: &str) {
+        port.post_message(&JSON::parse(message).unwrap()).unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn serve_message_port() {
+        let channel = MessageChannel::new();
+        listen_port(channel.port1(), init_language_server());
+        let client = channel.port2();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let on_message = Closure::<dyn FnMut(JsValue)>::new({
+            let received = received.clone();
+            move |event: JsValue| {
+                let data = Reflect::get(&event, &"data".into()).unwrap();
+                received
+                    .borrow_mut()
+                    .push(String::from(JSON::stringify(&data).unwrap()));
+            }
+        });
+        client.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        post(
+            &client,
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+        );
+        post(
+            &client,
+            r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+        );
+        post(
+            &client,
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rq","languageId":"sparql","version":1,"text":"SELECT * WHERE { ?s ?p }"}}}"#,
+        );
+        // Serialized messages are accepted as well.
+        client
+            .post_message(&JsValue::from_str(
+                r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///a.rq"},"position":{"line":0,"character":2}}}"#,
+            ))
+            .unwrap();
+        sleep(2 * super::DIAGNOSTICS_DEBOUNCE.as_millis() as u32).await;
+
+        let messages: Vec<serde_json::Value> = received
+            .borrow()
+            .iter()
+            .map(|message| serde_json::from_str(message).unwrap())
+            .collect();
+        assert!(messages[0]["result"]["capabilities"].is_object());
+        assert_eq!(messages[0]["id"], 1);
+        assert_eq!(messages[1]["id"], 2);
+        assert!(messages[1]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("SELECT"));
+        assert_eq!(messages[2]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(messages[2]["params"]["uri"], "file:///a.rq");
+        assert!(!messages[2]["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        assert_eq!(messages.len(), 3);
+
+        post(&client, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+        client.set_onmessage(None);
+    }
+}